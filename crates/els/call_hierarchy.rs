@@ -0,0 +1,328 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use erg_common::traits::Locational;
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::hir::{Accessor, Args, Array, Call, Def, Dict, Expr, Identifier, Set, Tuple};
+use erg_compiler::ty::HasType;
+use erg_compiler::varinfo::AbsLocation;
+
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams, Range,
+    SymbolKind, Url,
+};
+
+use crate::server::{send, send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+/// `callee` is called in `caller` (`None` means the top level of the module).
+struct CallSite<'h> {
+    caller: Option<&'h Def>,
+    callee: &'h Identifier,
+}
+
+fn callee_ident(call: &Call) -> Option<&Identifier> {
+    if let Some(attr_name) = call.attr_name.as_ref() {
+        return Some(attr_name);
+    }
+    match call.obj.as_ref() {
+        Expr::Accessor(Accessor::Ident(ident)) => Some(ident),
+        Expr::Accessor(Accessor::Attr(attr)) => Some(&attr.ident),
+        _ => None,
+    }
+}
+
+fn collect_calls_from_args<'h>(
+    args: &'h Args,
+    caller: Option<&'h Def>,
+    sites: &mut Vec<CallSite<'h>>,
+) {
+    for arg in args.pos_args.iter() {
+        collect_calls(&arg.expr, caller, sites);
+    }
+    if let Some(var_args) = args.var_args.as_ref() {
+        collect_calls(&var_args.expr, caller, sites);
+    }
+    for arg in args.kw_args.iter() {
+        collect_calls(&arg.expr, caller, sites);
+    }
+    if let Some(kw_var_args) = args.kw_var_args.as_ref() {
+        collect_calls(&kw_var_args.expr, caller, sites);
+    }
+}
+
+fn collect_calls_from_exprs<'h>(
+    exprs: impl Iterator<Item = &'h Expr>,
+    caller: Option<&'h Def>,
+    sites: &mut Vec<CallSite<'h>>,
+) {
+    for expr in exprs {
+        collect_calls(expr, caller, sites);
+    }
+}
+
+fn collect_calls<'h>(expr: &'h Expr, caller: Option<&'h Def>, sites: &mut Vec<CallSite<'h>>) {
+    match expr {
+        Expr::Call(call) => {
+            if let Some(callee) = callee_ident(call) {
+                sites.push(CallSite { caller, callee });
+            }
+            collect_calls(&call.obj, caller, sites);
+            collect_calls_from_args(&call.args, caller, sites);
+        }
+        Expr::Accessor(Accessor::Attr(attr)) => collect_calls(&attr.obj, caller, sites),
+        Expr::BinOp(bin) => {
+            collect_calls(&bin.lhs, caller, sites);
+            collect_calls(&bin.rhs, caller, sites);
+        }
+        Expr::UnaryOp(unary) => collect_calls(&unary.expr, caller, sites),
+        Expr::Def(def) => {
+            // the calls in a nested subroutine belong to it
+            let caller = if def.sig.is_subr() { Some(def) } else { caller };
+            collect_calls_from_exprs(def.body.block.iter(), caller, sites);
+        }
+        Expr::ClassDef(class_def) => {
+            if let Some(req_sup) = class_def.require_or_sup.as_ref() {
+                collect_calls(req_sup, caller, sites);
+            }
+            collect_calls_from_exprs(class_def.methods.iter(), caller, sites);
+        }
+        Expr::PatchDef(patch_def) => {
+            collect_calls_from_exprs(patch_def.methods.iter(), caller, sites);
+        }
+        Expr::ReDef(redef) => collect_calls_from_exprs(redef.block.iter(), caller, sites),
+        Expr::Lambda(lambda) => collect_calls_from_exprs(lambda.body.iter(), caller, sites),
+        Expr::TypeAsc(type_asc) => collect_calls(&type_asc.expr, caller, sites),
+        Expr::Array(Array::Normal(arr)) => collect_calls_from_args(&arr.elems, caller, sites),
+        Expr::Array(Array::WithLength(arr)) => {
+            collect_calls(&arr.elem, caller, sites);
+            collect_calls(&arr.len, caller, sites);
+        }
+        Expr::Array(Array::Comprehension(arr)) => {
+            collect_calls(&arr.elem, caller, sites);
+            collect_calls(&arr.guard, caller, sites);
+        }
+        Expr::Tuple(Tuple::Normal(tuple)) => collect_calls_from_args(&tuple.elems, caller, sites),
+        Expr::Set(Set::Normal(set)) => collect_calls_from_args(&set.elems, caller, sites),
+        Expr::Set(Set::WithLength(set)) => {
+            collect_calls(&set.elem, caller, sites);
+            collect_calls(&set.len, caller, sites);
+        }
+        Expr::Dict(Dict::Normal(dict)) => {
+            for spread in dict.spreads.iter() {
+                collect_calls(spread, caller, sites);
+            }
+            for kv in dict.kvs.iter() {
+                collect_calls(&kv.key, caller, sites);
+                collect_calls(&kv.value, caller, sites);
+            }
+        }
+        Expr::Dict(Dict::Comprehension(dict)) => {
+            collect_calls(&dict.key, caller, sites);
+            collect_calls(&dict.value, caller, sites);
+            collect_calls(&dict.guard, caller, sites);
+        }
+        Expr::Record(record) => {
+            for attr in record.attrs.iter() {
+                collect_calls_from_exprs(attr.body.block.iter(), caller, sites);
+            }
+        }
+        Expr::Compound(block) | Expr::Code(block) => {
+            collect_calls_from_exprs(block.iter(), caller, sites)
+        }
+        Expr::Dummy(dummy) => collect_calls_from_exprs(dummy.iter(), caller, sites),
+        Expr::Accessor(_) | Expr::Lit(_) | Expr::Import(_) => {}
+    }
+}
+
+fn is_defined_at(def_loc: &AbsLocation, path: &Path, range: Range) -> bool {
+    def_loc.module.as_deref() == Some(path) && util::loc_to_range(def_loc.loc) == Some(range)
+}
+
+fn subr_kind(ident: &Identifier) -> SymbolKind {
+    if ident.ref_t().is_method() {
+        SymbolKind::METHOD
+    } else {
+        SymbolKind::FUNCTION
+    }
+}
+
+fn ident_item(ident: &Identifier, uri: Url, range: Range) -> Option<CallHierarchyItem> {
+    let selection_range = util::loc_to_range(ident.vi.def_loc.loc)?;
+    Some(CallHierarchyItem {
+        name: ident.inspect().to_string(),
+        kind: subr_kind(ident),
+        tags: None,
+        detail: Some(ident.ref_t().to_string()),
+        uri,
+        range,
+        selection_range,
+        data: None,
+    })
+}
+
+fn caller_item(caller: Option<&Def>, path: &Path) -> Option<CallHierarchyItem> {
+    let uri = Url::from_file_path(path).ok()?;
+    if let Some(def) = caller {
+        let ident = def.sig.ident();
+        let selection_range = util::loc_to_range(ident.loc())?;
+        let range = util::loc_to_range(def.loc()).unwrap_or(selection_range);
+        Some(CallHierarchyItem {
+            selection_range,
+            ..ident_item(ident, uri, range)?
+        })
+    } else {
+        let name = path.file_stem()?.to_string_lossy().to_string();
+        Some(CallHierarchyItem {
+            name,
+            kind: SymbolKind::FILE,
+            tags: None,
+            detail: None,
+            uri,
+            range: Range::default(),
+            selection_range: Range::default(),
+            data: None,
+        })
+    }
+}
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    pub(crate) fn prepare_call_hierarchy(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log("call hierarchy requested")?;
+        let params = CallHierarchyPrepareParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let result = self.get_call_hierarchy_items(&uri, pos)?;
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn show_incoming_calls(&mut self, msg: &Value) -> ELSResult<()> {
+        let params = CallHierarchyIncomingCallsParams::deserialize(&msg["params"])?;
+        send_log(format!("incoming calls requested: {}", params.item.name))?;
+        let result = self.get_incoming_calls(&params.item);
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn show_outgoing_calls(&mut self, msg: &Value) -> ELSResult<()> {
+        let params = CallHierarchyOutgoingCallsParams::deserialize(&msg["params"])?;
+        send_log(format!("outgoing calls requested: {}", params.item.name))?;
+        let result = self.get_outgoing_calls(&params.item);
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    fn get_call_hierarchy_items(
+        &self,
+        uri: &NormalizedUrl,
+        pos: lsp_types::Position,
+    ) -> ELSResult<Option<Vec<CallHierarchyItem>>> {
+        let Some(token) = self.file_cache.get_token(uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self.get_definition(uri, &token)? else {
+            return Ok(None);
+        };
+        if !vi.t.is_subr() {
+            send_log(format!("not a subroutine: {token}"))?;
+            return Ok(None);
+        }
+        let (Some(path), Some(selection_range)) = (
+            vi.def_loc.module.as_ref(),
+            util::loc_to_range(vi.def_loc.loc),
+        ) else {
+            send_log("not found (maybe builtin)")?;
+            return Ok(None);
+        };
+        let item = Url::from_file_path(path).ok().map(|uri| CallHierarchyItem {
+            name: token.inspect().to_string(),
+            kind: if vi.t.is_method() {
+                SymbolKind::METHOD
+            } else {
+                SymbolKind::FUNCTION
+            },
+            tags: None,
+            detail: Some(vi.t.to_string()),
+            uri,
+            range: selection_range,
+            selection_range,
+            data: None,
+        });
+        Ok(item.map(|item| vec![item]))
+    }
+
+    /// Collects the `Call`s of `item` in the workspace and groups them by the callers.
+    fn get_incoming_calls(&self, item: &CallHierarchyItem) -> Vec<CallHierarchyIncomingCall> {
+        let target_path = util::uri_to_path(&NormalizedUrl::new(item.uri.clone()));
+        let mut incoming: Vec<((PathBuf, Option<Range>), CallHierarchyIncomingCall)> = vec![];
+        for (path, hir) in self.workspace_hirs() {
+            let mut sites = vec![];
+            collect_calls_from_exprs(hir.module.iter(), None, &mut sites);
+            for site in sites {
+                if !is_defined_at(&site.callee.vi.def_loc, &target_path, item.selection_range) {
+                    continue;
+                }
+                let Some(from_range) = util::loc_to_range(site.callee.loc()) else {
+                    continue;
+                };
+                let caller_range = site
+                    .caller
+                    .and_then(|def| util::loc_to_range(def.sig.ident().loc()));
+                let key = (path.clone(), caller_range);
+                if let Some((_, call)) = incoming.iter_mut().find(|(k, _)| k == &key) {
+                    call.from_ranges.push(from_range);
+                } else if let Some(from) = caller_item(site.caller, &path) {
+                    let call = CallHierarchyIncomingCall {
+                        from,
+                        from_ranges: vec![from_range],
+                    };
+                    incoming.push((key, call));
+                }
+            }
+        }
+        incoming.into_iter().map(|(_, call)| call).collect()
+    }
+
+    /// Collects the `Call`s in the body of `item` and groups them by the callees.
+    fn get_outgoing_calls(&self, item: &CallHierarchyItem) -> Vec<CallHierarchyOutgoingCall> {
+        let path = util::uri_to_path(&NormalizedUrl::new(item.uri.clone()));
+        let hirs = self.workspace_hirs();
+        let Some(hir) = hirs.get(&path) else {
+            return vec![];
+        };
+        let mut sites = vec![];
+        collect_calls_from_exprs(hir.module.iter(), None, &mut sites);
+        let mut outgoing: Vec<(AbsLocation, CallHierarchyOutgoingCall)> = vec![];
+        for site in sites {
+            let Some(caller) = site.caller else {
+                continue;
+            };
+            if util::loc_to_range(caller.sig.ident().loc()) != Some(item.selection_range) {
+                continue;
+            }
+            let def_loc = &site.callee.vi.def_loc;
+            let (Some(callee_path), Some(from_range), Some(range)) = (
+                def_loc.module.as_ref(),
+                util::loc_to_range(site.callee.loc()),
+                util::loc_to_range(def_loc.loc),
+            ) else {
+                continue;
+            };
+            if let Some((_, call)) = outgoing.iter_mut().find(|(loc, _)| loc == def_loc) {
+                call.from_ranges.push(from_range);
+            } else if let Some(to) = Url::from_file_path(callee_path)
+                .ok()
+                .and_then(|uri| ident_item(site.callee, uri, range))
+            {
+                let call = CallHierarchyOutgoingCall {
+                    to,
+                    from_ranges: vec![from_range],
+                };
+                outgoing.push((def_loc.clone(), call));
+            }
+        }
+        outgoing.into_iter().map(|(_, call)| call).collect()
+    }
+}
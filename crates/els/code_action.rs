@@ -4,16 +4,146 @@ use serde::Deserialize;
 use serde_json::json;
 use serde_json::Value;
 
+use erg_common::env::erg_pystd_path;
+use erg_common::erg_util::BUILTIN_ERG_MODS;
+use erg_common::error::{ErrorKind, Location};
+use erg_common::python_util::BUILTIN_PYTHON_MODS;
 use erg_common::traits::Locational;
 use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::context::Context;
 use erg_compiler::erg_parser::token::{Token, TokenKind};
-use erg_compiler::hir::Expr;
+use erg_compiler::error::CompileError;
+use erg_compiler::hir::{Def, Expr, Signature};
+use erg_compiler::ty::Type;
 
-use lsp_types::{CodeAction, CodeActionKind, CodeActionParams, TextEdit, Url, WorkspaceEdit};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionParams, Diagnostic, Position, Range, TextEdit, Url,
+    WorkspaceEdit,
+};
 
 use crate::server::{send, send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
 
+fn quick_fix(
+    title: String,
+    uri: &NormalizedUrl,
+    edits: Vec<TextEdit>,
+    diag: &Diagnostic,
+) -> CodeAction {
+    let mut map = HashMap::new();
+    map.insert(uri.clone().raw(), edits);
+    CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diag.clone()]),
+        edit: Some(WorkspaceEdit::new(map)),
+        ..Default::default()
+    }
+}
+
+/// `foo/bar.er` -> `foo/bar`, `foo/__init__.er` -> `foo`
+fn module_name(path: &std::path::Path, ext: &str) -> String {
+    let name = path.display().to_string().replace('\\', "/");
+    name.trim_end_matches(&format!("/__init__{ext}"))
+        .trim_end_matches(ext)
+        .to_string()
+}
+
+fn exports(ctx: &Context, name: &str) -> bool {
+    ctx.local_dir()
+        .iter()
+        .any(|(vn, vi)| &vn.inspect()[..] == name && vi.vis.is_public())
+}
+
+/// Returns the class and the trait of the `C|<: T|.` block headed at `header`,
+/// i.e. the class of the first method implementing `trait_name` after the header.
+fn impl_trait_block<'a>(
+    exprs: impl Iterator<Item = &'a Expr>,
+    header: Range,
+    trait_name: &str,
+) -> Option<(&'a Type, &'a Type)> {
+    exprs
+        .filter_map(|expr| match expr {
+            Expr::ClassDef(class_def) => Some(class_def),
+            _ => None,
+        })
+        .flat_map(|class_def| {
+            class_def.methods.iter().filter_map(move |expr| match expr {
+                Expr::Def(def) => Some((class_def.obj.typ(), def)),
+                _ => None,
+            })
+        })
+        .filter_map(|(class, def)| {
+            let impl_trait = def.sig.ident().vi.impl_of.as_ref()?;
+            let start = util::loc_to_range(def.loc())?.start;
+            (start.line > header.start.line && &impl_trait.local_name()[..] == trait_name)
+                .then_some((start, class, impl_trait))
+        })
+        .min_by_key(|(start, _, _)| (start.line, start.character))
+        .map(|(_, class, impl_trait)| (class, impl_trait))
+}
+
+/// Returns the innermost `Def` in `expr` containing `range`.
+fn enclosing_def(expr: &Expr, range: Range) -> Option<&Def> {
+    match expr {
+        Expr::Def(def) => {
+            if !util::loc_to_range(def.loc()).is_some_and(|r| util::contains(r, range)) {
+                return None;
+            }
+            def.body
+                .block
+                .iter()
+                .find_map(|expr| enclosing_def(expr, range))
+                .or(Some(def))
+        }
+        Expr::ClassDef(class_def) => class_def
+            .methods
+            .iter()
+            .find_map(|expr| enclosing_def(expr, range)),
+        Expr::PatchDef(patch_def) => patch_def
+            .methods
+            .iter()
+            .find_map(|expr| enclosing_def(expr, range)),
+        Expr::Compound(block) | Expr::Code(block) => {
+            block.iter().find_map(|expr| enclosing_def(expr, range))
+        }
+        Expr::Dummy(dummy) => dummy.iter().find_map(|expr| enclosing_def(expr, range)),
+        Expr::Lambda(lambda) => lambda
+            .body
+            .iter()
+            .find_map(|expr| enclosing_def(expr, range)),
+        Expr::Call(call) => call
+            .args
+            .pos_args
+            .iter()
+            .find_map(|arg| enclosing_def(&arg.expr, range)),
+        _ => None,
+    }
+}
+
+/// e.g. `f self, x = panic "not implemented"`
+fn method_stub(name: &str, t: &Type) -> String {
+    let params = t
+        .non_default_params()
+        .map(|params| {
+            params
+                .iter()
+                .enumerate()
+                .map(|(i, param)| match (i, param.name()) {
+                    (0, _) => "self".to_string(),
+                    (_, Some(name)) => name.to_string(),
+                    (_, None) => format!("x{i}"),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if params.is_empty() {
+        format!("{name} = panic \"not implemented\"")
+    } else {
+        format!("{name} {} = panic \"not implemented\"", params.join(", "))
+    }
+}
+
 impl<Checker: BuildRunnable> Server<Checker> {
     fn gen_eliminate_unused_vars_action(
         &self,
@@ -141,6 +271,7 @@ impl<Checker: BuildRunnable> Server<Checker> {
         let mut actions = vec![];
         let uri = NormalizedUrl::new(params.text_document.uri.clone());
         if let Some(token) = self.file_cache.get_token(&uri, params.range.start) {
+            actions.extend(self.gen_type_annotation_action(&uri, &token, &params));
             if token.is(TokenKind::Symbol) && !token.is_const() && !token.content.is_snake_case() {
                 let action = self.gen_change_case_action(token, &uri, params.clone());
                 actions.extend(action);
@@ -156,6 +287,12 @@ impl<Checker: BuildRunnable> Server<Checker> {
         if diags.is_empty() {
             return Ok(result);
         }
+        let uri = NormalizedUrl::new(params.text_document.uri.clone());
+        for diag in diags.iter() {
+            for err in self.get_errors_at(&uri, diag.range) {
+                result.extend(self.gen_quick_fixes(&uri, err, diag));
+            }
+        }
         if diags.first().unwrap().message.ends_with("is not used") {
             let actions = self.gen_eliminate_unused_vars_action(msg, params)?;
             result.extend(actions);
@@ -163,6 +300,244 @@ impl<Checker: BuildRunnable> Server<Checker> {
         Ok(result)
     }
 
+    fn get_errors_at(&self, uri: &NormalizedUrl, range: Range) -> Vec<&CompileError> {
+        let Some(artifact) = self.artifacts.get(uri) else {
+            return vec![];
+        };
+        artifact
+            .errors
+            .iter()
+            .chain(artifact.warns.iter())
+            .filter(|err| util::loc_to_range(err.core.get_loc_with_fallback()) == Some(range))
+            .collect()
+    }
+
+    fn gen_quick_fixes(
+        &self,
+        uri: &NormalizedUrl,
+        err: &CompileError,
+        diag: &Diagnostic,
+    ) -> Vec<CodeAction> {
+        let loc = err.core.get_loc_with_fallback();
+        let mut actions = vec![];
+        match err.core.kind {
+            ErrorKind::NameError => {
+                let Some(token) =
+                    util::loc_to_pos(loc).and_then(|pos| self.file_cache.get_token(uri, pos))
+                else {
+                    return actions;
+                };
+                let ctxs = self.get_local_ctx(uri, diag.range.start);
+                let undefined = token.is(TokenKind::Symbol)
+                    && ctxs
+                        .iter()
+                        .all(|ctx| ctx.get_var_info(&token.content).is_none());
+                if undefined {
+                    actions.extend(self.gen_similar_name_action(uri, &token, diag));
+                    actions.extend(self.gen_import_actions(uri, &token.content, diag));
+                }
+            }
+            ErrorKind::HasEffect => actions.extend(self.gen_to_procedure_action(uri, diag)),
+            ErrorKind::TypeError => actions.extend(self.gen_trait_stubs_action(uri, loc, diag)),
+            _ => {}
+        }
+        actions
+    }
+
+    /// `foo` is not defined (similar name: `fooo`) => replace `foo` with `fooo`
+    fn gen_similar_name_action(
+        &self,
+        uri: &NormalizedUrl,
+        token: &Token,
+        diag: &Diagnostic,
+    ) -> Option<CodeAction> {
+        let similar = self
+            .get_local_ctx(uri, diag.range.start)
+            .into_iter()
+            .find_map(|ctx| ctx.get_similar_name(&token.content))?;
+        let edit = TextEdit::new(util::loc_to_range(token.loc())?, similar.to_string());
+        let title = format!("Change to `{similar}`");
+        Some(quick_fix(title, uri, vec![edit], diag))
+    }
+
+    /// Suggests importing the undefined `name` from the standard library, the workspace modules and the Python modules loaded so far.
+    fn gen_import_actions(
+        &self,
+        uri: &NormalizedUrl,
+        name: &str,
+        diag: &Diagnostic,
+    ) -> Vec<CodeAction> {
+        let mut imports = vec![];
+        if BUILTIN_ERG_MODS.contains(&name) {
+            imports.push(format!("{name} = import \"{name}\""));
+        }
+        if BUILTIN_PYTHON_MODS.contains(&name) {
+            imports.push(format!("{name} = pyimport \"{name}\""));
+        }
+        let path = util::uri_to_path(uri);
+        if let (Some(shared), Some(dir)) = (self.get_shared(), path.parent()) {
+            for (mod_path, entry) in shared.mod_cache.iter() {
+                let Ok(rel_path) = mod_path.strip_prefix(dir) else {
+                    continue;
+                };
+                if mod_path == &path || rel_path.to_string_lossy().ends_with(".d.er") {
+                    continue;
+                }
+                let mod_name = module_name(rel_path, ".er");
+                if mod_name.rsplit('/').next() == Some(name) {
+                    imports.push(format!("{name} = import \"{mod_name}\""));
+                }
+                if exports(&entry.module.context, name) {
+                    imports.push(format!("{{{name};}} = import \"{mod_name}\""));
+                }
+            }
+            let std_path = erg_pystd_path();
+            for (mod_path, entry) in shared.py_mod_cache.iter() {
+                let Ok(rel_path) = mod_path.strip_prefix(&std_path) else {
+                    continue;
+                };
+                if exports(&entry.module.context, name) {
+                    let mod_name = module_name(rel_path, ".d.er");
+                    imports.push(format!("{{{name};}} = pyimport \"{mod_name}\""));
+                }
+            }
+        }
+        imports
+            .into_iter()
+            .map(|import| {
+                let start = Range::new(Position::new(0, 0), Position::new(0, 0));
+                let edit = TextEdit::new(start, format!("{import}\n"));
+                quick_fix(format!("Add `{import}`"), uri, vec![edit], diag)
+            })
+            .collect()
+    }
+
+    /// A function causes side-effects => rename `f` to `f!` (the definition and all references)
+    fn gen_to_procedure_action(
+        &self,
+        uri: &NormalizedUrl,
+        diag: &Diagnostic,
+    ) -> Option<CodeAction> {
+        let hir = self.artifacts.get(uri)?.object.as_ref()?;
+        let def = hir
+            .module
+            .iter()
+            .find_map(|expr| enclosing_def(expr, diag.range))?;
+        if def.sig.is_procedural() {
+            return None;
+        }
+        if let Signature::Subr(subr) = &def.sig {
+            // the parameter should be renamed instead
+            if util::loc_to_range(subr.params.loc()).is_some_and(|r| util::contains(r, diag.range))
+            {
+                return None;
+            }
+        }
+        let ident = def.sig.ident();
+        let new_name = format!("{}!", ident.inspect());
+        let mut map = HashMap::new();
+        let edit = TextEdit::new(util::loc_to_range(ident.raw.name.loc())?, new_name.clone());
+        map.insert(uri.clone().raw(), vec![edit]);
+        if let Some(value) = self.get_index().get_refs(&ident.vi.def_loc) {
+            for refer in value.referrers.iter() {
+                let url = Url::from_file_path(refer.module.as_ref()?).ok()?;
+                let edit = TextEdit::new(util::loc_to_range(refer.loc)?, new_name.clone());
+                map.entry(url).or_insert(vec![]).push(edit);
+            }
+        }
+        Some(CodeAction {
+            title: format!(
+                "Convert `{}` to a procedure (`{new_name}`)",
+                ident.inspect()
+            ),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diag.clone()]),
+            edit: Some(WorkspaceEdit::new(map)),
+            ..Default::default()
+        })
+    }
+
+    /// `C|<: T|.` lacks members of `T` => insert the stubs of them
+    fn gen_trait_stubs_action(
+        &self,
+        uri: &NormalizedUrl,
+        loc: Location,
+        diag: &Diagnostic,
+    ) -> Option<CodeAction> {
+        let trait_pos = Position::new(
+            diag.range.end.line,
+            diag.range.end.character.saturating_sub(1),
+        );
+        let trait_name = self.file_cache.get_token(uri, trait_pos)?.content;
+        let hir = self.artifacts.get(uri)?.object.as_ref()?;
+        let (class, impl_trait) = impl_trait_block(hir.module.iter(), diag.range, &trait_name)?;
+        let mod_ctx = &self.modules.get(uri)?.context;
+        let class_ctx = mod_ctx.get_type_ctx(class)?;
+        let trait_ctx = mod_ctx.get_type_ctx(impl_trait)?;
+        let members = trait_ctx.unimplemented_members(class_ctx);
+        if members.is_empty() {
+            return None;
+        }
+        let entry = self.file_cache.get(uri).ok()?;
+        let line = entry.get_line(diag.range.start.line)?;
+        let header_indent = line.len() - line.trim_start().len();
+        let indent = entry
+            .get_line(diag.range.start.line + 1)
+            .map(|next| next.len() - next.trim_start().len())
+            .filter(|indent| *indent > header_indent)
+            .unwrap_or(header_indent + 4);
+        let stubs = members
+            .iter()
+            .map(|(name, vi)| {
+                format!(
+                    "{}{}\n",
+                    " ".repeat(indent),
+                    method_stub(name.inspect(), &vi.t)
+                )
+            })
+            .collect::<String>();
+        let ln_end = loc.ln_end()?;
+        let pos = Position::new(ln_end, 0);
+        let edit = TextEdit::new(Range::new(pos, pos), stubs);
+        let title = format!("Implement the missing members of `{trait_name}`");
+        Some(quick_fix(title, uri, vec![edit], diag))
+    }
+
+    /// `x = 1` => `x: Nat = 1`
+    fn gen_type_annotation_action(
+        &self,
+        uri: &NormalizedUrl,
+        token: &Token,
+        params: &CodeActionParams,
+    ) -> Option<CodeAction> {
+        let visitor = self.get_visitor(uri)?;
+        let Some(Expr::Def(def)) = visitor.get_min_expr(token) else {
+            return None;
+        };
+        let Signature::Var(var) = &def.sig else {
+            return None;
+        };
+        if var.t_spec.is_some() || var.ident.raw.name.token() != token {
+            return None;
+        }
+        let t = var.ident.vi.t.derefine().to_string();
+        // the types of unresolved or compiler internal variables cannot be written
+        if t.contains(['%', '?']) || var.ident.vi.t.has_qvar() {
+            return None;
+        }
+        let end = util::loc_to_range(var.ident.loc())?.end;
+        let edit = TextEdit::new(Range::new(end, end), format!(": {t}"));
+        let mut map = HashMap::new();
+        map.insert(uri.clone().raw(), vec![edit]);
+        Some(CodeAction {
+            title: format!("Add the type annotation (`{}: {t}`)", var.ident.inspect()),
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            diagnostics: Some(params.context.diagnostics.clone()),
+            edit: Some(WorkspaceEdit::new(map)),
+            ..Default::default()
+        })
+    }
+
     pub(crate) fn send_code_action(&self, msg: &Value) -> ELSResult<()> {
         send_log(format!("code action requested: {msg}"))?;
         let params = CodeActionParams::deserialize(&msg["params"])?;
@@ -5,7 +5,7 @@ use serde_json::Value;
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::hir::Expr;
 
-use lsp_types::{CodeLens, CodeLensParams};
+use lsp_types::{CodeLens, CodeLensParams, Location, SymbolKind};
 
 use crate::server::{send, send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
@@ -51,7 +51,30 @@ impl<Checker: BuildRunnable> Server<Checker> {
         Ok(result)
     }
 
-    fn send_class_inherits_lens(&mut self, _uri: &NormalizedUrl) -> ELSResult<Vec<CodeLens>> {
-        Ok(vec![])
+    fn send_class_inherits_lens(&mut self, uri: &NormalizedUrl) -> ELSResult<Vec<CodeLens>> {
+        let mut result = vec![];
+        let classes = self.workspace_types().into_iter().filter(|entry| {
+            entry.item.kind == SymbolKind::CLASS
+                && &NormalizedUrl::new(entry.item.uri.clone()) == uri
+        });
+        for class in classes {
+            let inheritors = self
+                .get_subtypes(&class.item)
+                .into_iter()
+                .filter(|sub| sub.kind == SymbolKind::CLASS)
+                .map(|sub| Location::new(sub.uri, sub.selection_range))
+                .collect::<Vec<_>>();
+            if inheritors.is_empty() {
+                continue;
+            }
+            let command = self.gen_show_class_inherits_command(&class.item, inheritors)?;
+            let lens = CodeLens {
+                range: class.item.selection_range,
+                command: Some(command),
+                data: None,
+            };
+            result.push(lens);
+        }
+        Ok(result)
     }
 }
@@ -10,6 +10,7 @@ use erg_compiler::hir::Expr;
 use lsp_types::{ExecuteCommandParams, Location, Url};
 
 use crate::server::{send, send_log, ELSResult, Server};
+use crate::type_hierarchy::TypeHierarchyItem;
 use crate::util::{self, NormalizedUrl};
 
 impl<Checker: BuildRunnable> Server<Checker> {
@@ -55,4 +56,20 @@ impl<Checker: BuildRunnable> Server<Checker> {
             arguments: Some(vec![uri, position, locations]),
         })
     }
+
+    pub(crate) fn gen_show_class_inherits_command(
+        &self,
+        class: &TypeHierarchyItem,
+        inheritors: Vec<Location>,
+    ) -> ELSResult<Command> {
+        let inherits_len = inheritors.len();
+        let locations = serde_json::to_value(inheritors)?;
+        let uri = serde_json::to_value(&class.uri)?;
+        let position = serde_json::to_value(class.selection_range.start)?;
+        Ok(Command {
+            title: format!("{inherits_len} inheritors"),
+            command: "erg.showReferences".to_string(),
+            arguments: Some(vec![uri, position, locations]),
+        })
+    }
 }
@@ -0,0 +1,378 @@
+use std::path::PathBuf;
+
+use serde_json::json;
+use serde_json::{Map, Value};
+
+use erg_common::config::ErgConfig;
+use erg_common::dict::Dict;
+use erg_common::error::ErrorKind;
+use erg_common::normalize_path;
+use erg_common::python_util::{detect_magic_number, opt_get_python_version, PythonVersion};
+use erg_compiler::artifact::BuildRunnable;
+
+use lsp_types::DiagnosticSeverity;
+
+use crate::scheduler::CheckKind;
+use crate::server::{
+    send, send_log, send_warn_info, DefaultFeatures, ELSResult, OptionalFeatures, Server,
+};
+
+/// Which kinds of inlay hints are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InlayHintConfig {
+    pub variable_types: bool,
+    pub parameter_types: bool,
+    pub return_types: bool,
+    pub type_bounds: bool,
+    pub parameter_names: bool,
+}
+
+impl Default for InlayHintConfig {
+    fn default() -> Self {
+        Self {
+            variable_types: true,
+            parameter_types: true,
+            return_types: true,
+            type_bounds: true,
+            parameter_names: true,
+        }
+    }
+}
+
+/// Settings given by `initializationOptions` or `workspace/configuration`.
+/// Feature toggles are kept in `Server::disabled_features` and `Server::opt_features`.
+#[derive(Debug, Clone, Default)]
+pub struct ELSConfig {
+    pub python_command: Option<String>,
+    pub python_version: Option<PythonVersion>,
+    pub search_paths: Vec<PathBuf>,
+    pub inlay_hints: InlayHintConfig,
+    /// error kind name (e.g. `UnusedWarning`) -> severity (`None` means the diagnostics are not reported)
+    pub severity_overrides: Dict<String, Option<DiagnosticSeverity>>,
+}
+
+/// The settings given by the command line arguments are the defaults
+impl From<&ErgConfig> for ELSConfig {
+    fn from(cfg: &ErgConfig) -> Self {
+        Self {
+            python_command: cfg.py_command.map(str::to_string),
+            python_version: cfg.target_version,
+            search_paths: cfg.search_paths.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl ELSConfig {
+    /// `None`: the diagnostics of `kind` are not reported
+    pub fn severity_of(&self, kind: ErrorKind) -> Option<DiagnosticSeverity> {
+        if let Some(severity) = self.severity_overrides.get(&format!("{kind:?}")) {
+            *severity
+        } else if kind.is_warning() {
+            Some(DiagnosticSeverity::WARNING)
+        } else {
+            Some(DiagnosticSeverity::ERROR)
+        }
+    }
+}
+
+fn parse_python_version(s: &str) -> Option<PythonVersion> {
+    let nums = s
+        .trim()
+        .split('.')
+        .map(|n| n.parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    match nums[..] {
+        [major] => Some(PythonVersion::new(major, None, None)),
+        [major, minor] => Some(PythonVersion::new(major, Some(minor), None)),
+        [major, minor, micro] => Some(PythonVersion::new(major, Some(minor), Some(micro))),
+        _ => None,
+    }
+}
+
+fn parse_severity(s: &str) -> Result<Option<DiagnosticSeverity>, String> {
+    match s {
+        "error" => Ok(Some(DiagnosticSeverity::ERROR)),
+        "warning" => Ok(Some(DiagnosticSeverity::WARNING)),
+        "information" | "info" => Ok(Some(DiagnosticSeverity::INFORMATION)),
+        "hint" => Ok(Some(DiagnosticSeverity::HINT)),
+        "none" | "off" => Ok(None),
+        _ => Err(format!("invalid diagnostic severity: {s}")),
+    }
+}
+
+fn is_error_kind(s: &str) -> bool {
+    // `ErrorKind::from` falls back to `UserError` for unknown names
+    format!("{:?}", ErrorKind::from(s)) == s
+}
+
+fn str_list<'v>(key: &str, value: &'v Value, warns: &mut Vec<String>) -> Vec<&'v str> {
+    let Some(values) = value.as_array() else {
+        warns.push(format!("`{key}` must be an array of strings"));
+        return vec![];
+    };
+    values
+        .iter()
+        .filter_map(|v| {
+            let s = v.as_str();
+            if s.is_none() {
+                warns.push(format!("`{key}` must be an array of strings: {v}"));
+            }
+            s
+        })
+        .collect()
+}
+
+fn table<'v>(key: &str, value: &'v Value, warns: &mut Vec<String>) -> Vec<(&'v String, &'v Value)> {
+    let Some(table) = value.as_object() else {
+        warns.push(format!("`{key}` must be an object"));
+        return vec![];
+    };
+    table.iter().collect()
+}
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    /// Parses the command line arguments (`--disable <feature>`, `--enable <feature>`).
+    pub(crate) fn apply_runtime_args(&mut self) -> ELSResult<()> {
+        let mut warns = vec![];
+        let mut args = self.cfg.runtime_args.iter();
+        while let Some(&arg) = args.next() {
+            if arg == "--disable" {
+                if let Some(&feature) = args.next() {
+                    match DefaultFeatures::try_from(feature) {
+                        Ok(feature) => self.disabled_features.push(feature),
+                        Err(warn) => warns.push(warn),
+                    }
+                }
+            } else if arg == "--enable" {
+                if let Some(&feature) = args.next() {
+                    match OptionalFeatures::try_from(feature) {
+                        Ok(feature) => self.opt_features.push(feature),
+                        Err(warn) => warns.push(warn),
+                    }
+                }
+            }
+        }
+        for warn in warns {
+            send_warn_info(format!("ELS: {warn}"))?;
+        }
+        Ok(())
+    }
+
+    /// Applies the settings given by `initializationOptions`, `workspace/didChangeConfiguration` or `workspace/configuration`.
+    /// The settings may be nested in the `els` section.
+    /// Invalid values are ignored and reported by `window/showMessage`.
+    pub(crate) fn apply_settings(&mut self, settings: &Value) -> ELSResult<()> {
+        let settings = settings.get("els").unwrap_or(settings);
+        if settings.is_null() {
+            return Ok(());
+        }
+        let Some(settings) = settings.as_object() else {
+            return send_warn_info(format!("ELS: invalid settings: {settings}"));
+        };
+        send_log(format!("settings: {settings:?}"))?;
+        let mut warns = vec![];
+        self.apply_feature_settings(settings, &mut warns);
+        let mut config = self.config.clone();
+        for (key, value) in settings.iter() {
+            match &key[..] {
+                "disable" | "enable" => {}
+                "pythonCommand" => match value.as_str() {
+                    None if value.is_null() => config.python_command = None,
+                    Some("") => config.python_command = None,
+                    Some(command) => {
+                        if let Some(version) = opt_get_python_version(command) {
+                            config.python_command = Some(command.to_string());
+                            if !settings.contains_key("pythonVersion") {
+                                config.python_version = Some(version);
+                            }
+                        } else {
+                            warns.push(format!("cannot run the Python command `{command}`"));
+                        }
+                    }
+                    None => warns.push(format!("`pythonCommand` must be a string: {value}")),
+                },
+                "pythonVersion" => match value.as_str() {
+                    Some(version) => {
+                        if let Some(version) = parse_python_version(version) {
+                            config.python_version = Some(version);
+                        } else {
+                            warns.push(format!("invalid Python version: {version}"));
+                        }
+                    }
+                    None if value.is_null() => config.python_version = None,
+                    None => warns.push(format!("`pythonVersion` must be a string: {value}")),
+                },
+                "searchPaths" => {
+                    config.search_paths.clear();
+                    for path in str_list(key, value, &mut warns) {
+                        let path = self.home.join(path);
+                        if path.is_dir() {
+                            config.search_paths.push(normalize_path(path));
+                        } else {
+                            warns.push(format!("no such directory: {}", path.display()));
+                        }
+                    }
+                }
+                "inlayHints" => {
+                    for (kind, enabled) in table(key, value, &mut warns) {
+                        let Some(enabled) = enabled.as_bool() else {
+                            warns.push(format!("`inlayHints.{kind}` must be a boolean"));
+                            continue;
+                        };
+                        match &kind[..] {
+                            "variableTypes" => config.inlay_hints.variable_types = enabled,
+                            "parameterTypes" => config.inlay_hints.parameter_types = enabled,
+                            "returnTypes" => config.inlay_hints.return_types = enabled,
+                            "typeBounds" => config.inlay_hints.type_bounds = enabled,
+                            "parameterNames" => config.inlay_hints.parameter_names = enabled,
+                            _ => warns.push(format!("unknown inlay hint kind: {kind}")),
+                        }
+                    }
+                }
+                "diagnosticSeverity" => {
+                    config.severity_overrides.clear();
+                    for (kind, severity) in table(key, value, &mut warns) {
+                        if !is_error_kind(kind) {
+                            warns.push(format!("unknown error kind: {kind}"));
+                            continue;
+                        }
+                        match severity.as_str().ok_or(severity.to_string()) {
+                            Ok(severity) => match parse_severity(severity) {
+                                Ok(severity) => {
+                                    config.severity_overrides.insert(kind.clone(), severity);
+                                }
+                                Err(warn) => warns.push(warn),
+                            },
+                            Err(severity) => {
+                                warns.push(format!("invalid diagnostic severity: {severity}"))
+                            }
+                        }
+                    }
+                }
+                _ => warns.push(format!("unknown setting: {key}")),
+            }
+        }
+        self.set_config(config);
+        for warn in warns {
+            send_warn_info(format!("ELS: {warn}"))?;
+        }
+        Ok(())
+    }
+
+    /// `disable` and `enable` replace the features given so far (including the command line arguments).
+    /// Note that the capabilities are fixed on initialization,
+    /// so disabling a feature via `workspace/configuration` only affects the features checked on each request (e.g. diagnostics).
+    fn apply_feature_settings(&mut self, settings: &Map<String, Value>, warns: &mut Vec<String>) {
+        if let Some(value) = settings.get("disable") {
+            let mut disabled = vec![];
+            for feature in str_list("disable", value, warns) {
+                match DefaultFeatures::try_from(feature) {
+                    Ok(feature) => disabled.push(feature),
+                    Err(warn) => warns.push(warn),
+                }
+            }
+            self.disabled_features = disabled;
+        }
+        if let Some(value) = settings.get("enable") {
+            let mut enabled = vec![];
+            for feature in str_list("enable", value, warns) {
+                match OptionalFeatures::try_from(feature) {
+                    Ok(feature) => enabled.push(feature),
+                    Err(warn) => warns.push(warn),
+                }
+            }
+            self.opt_features = enabled;
+        }
+    }
+
+    /// Reflects the settings to the compiler configuration and re-checks the open files if they are affected.
+    fn set_config(&mut self, config: ELSConfig) {
+        let recheck = config.python_command != self.config.python_command
+            || config.python_version != self.config.python_version
+            || config.search_paths != self.config.search_paths
+            || config.severity_overrides.len() != self.config.severity_overrides.len()
+            || config
+                .severity_overrides
+                .iter()
+                .any(|(kind, severity)| self.config.severity_overrides.get(kind) != Some(severity));
+        // `ErgConfig::py_command` is `&'static str`, so the command is leaked only when it is changed
+        if config.python_command != self.config.python_command {
+            self.cfg.py_command = config
+                .python_command
+                .as_ref()
+                .map(|command| &*Box::leak(command.clone().into_boxed_str()));
+        }
+        if config.python_command != self.config.python_command
+            || config.python_version != self.config.python_version
+        {
+            // the magic number cannot be determined from the version,
+            // so it is detected only if the command runs the target version
+            let target = config.python_version.map(|ver| (ver.major, ver.minor));
+            self.cfg.py_magic_num = self
+                .cfg
+                .py_command
+                .filter(|command| {
+                    opt_get_python_version(command).is_some_and(|ver| {
+                        target.is_none_or(|target| target == (ver.major, ver.minor))
+                    })
+                })
+                .map(detect_magic_number);
+        }
+        self.cfg.target_version = config.python_version;
+        self.cfg.search_paths = config.search_paths.clone();
+        self.config = config;
+        if recheck {
+            let uris = self.artifacts.keys().cloned().collect::<Vec<_>>();
+            for uri in uris {
+                self.schedule_check(uri, CheckKind::Clean);
+            }
+        }
+    }
+
+    /// The responses are distinguished from the client requests because they have no `method`.
+    /// Only the response to the latest request is applied (see `Server::dispatch`).
+    pub(crate) fn request_configuration(&mut self) -> ELSResult<()> {
+        let supported = self
+            .client_capas
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.configuration)
+            .unwrap_or(false);
+        if !supported {
+            return Ok(());
+        }
+        self.config_request_id += 1;
+        send(&json!({
+            "jsonrpc": "2.0",
+            "id": self.config_request_id,
+            "method": "workspace/configuration",
+            "params": { "items": [{ "section": "els" }] },
+        }))
+    }
+
+    /// Handles `workspace/didChangeConfiguration`.
+    /// In the pull model, `settings` is empty and the settings are requested by `workspace/configuration`.
+    pub(crate) fn change_configuration(&mut self, msg: &Value) -> ELSResult<()> {
+        let settings = &msg["params"]["settings"];
+        let is_empty = settings.is_null() || settings.as_object().is_some_and(|s| s.is_empty());
+        if is_empty {
+            self.request_configuration()
+        } else {
+            self.apply_settings(settings)
+        }
+    }
+
+    /// Handles the response of `workspace/configuration`.
+    pub(crate) fn receive_configuration(&mut self, msg: &Value) -> ELSResult<()> {
+        if let Some(error) = msg.get("error") {
+            return send_log(format!("workspace/configuration failed: {error}"));
+        }
+        let settings = msg["result"]
+            .as_array()
+            .and_then(|items| items.first())
+            .unwrap_or(&Value::Null);
+        self.apply_settings(settings)
+    }
+}
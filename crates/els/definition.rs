@@ -9,7 +9,8 @@ use erg_compiler::hir::Expr;
 use erg_compiler::ty::HasType;
 use erg_compiler::varinfo::VarInfo;
 
-use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Url};
+use lsp_types::request::{GotoDeclarationParams, GotoTypeDefinitionParams};
+use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Position, Url};
 
 use crate::server::{send, send_log, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
@@ -31,9 +32,11 @@ impl<Checker: BuildRunnable> Server<Checker> {
         }
     }
 
+    /// If `decl` is `true`, jumps to the declaration file (`.d.er`) of a Python module instead of the `.py` source.
     fn get_definition_response(
         &self,
         params: GotoDefinitionParams,
+        decl: bool,
     ) -> ELSResult<GotoDefinitionResponse> {
         let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
         let pos = params.text_document_position_params.position;
@@ -54,6 +57,11 @@ impl<Checker: BuildRunnable> Server<Checker> {
                                     .first()
                                     .and_then(|ctx| ctx.get_path_from_mod_t(&vi.t))
                                 {
+                                    let path = if decl {
+                                        path
+                                    } else {
+                                        util::get_py_source_path(&path).unwrap_or(path)
+                                    };
                                     let mod_uri = Url::from_file_path(path).unwrap();
                                     let resp = GotoDefinitionResponse::Array(vec![
                                         lsp_types::Location::new(
@@ -126,7 +134,63 @@ impl<Checker: BuildRunnable> Server<Checker> {
     pub(crate) fn show_definition(&mut self, msg: &Value) -> ELSResult<()> {
         send_log(format!("definition requested: {msg}"))?;
         let params = GotoDefinitionParams::deserialize(&msg["params"])?;
-        let result = self.get_definition_response(params)?;
+        let result = self.get_definition_response(params, false)?;
         send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
     }
+
+    pub(crate) fn show_declaration(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("declaration requested: {msg}"))?;
+        let params = GotoDeclarationParams::deserialize(&msg["params"])?;
+        let result = self.get_definition_response(params, true)?;
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn show_type_definition(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("type definition requested: {msg}"))?;
+        let params = GotoTypeDefinitionParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let result = self.get_type_definition_response(&uri, pos)?;
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    /// Jumps to the definition of the class/trait of the variable.
+    fn get_type_definition_response(
+        &self,
+        uri: &NormalizedUrl,
+        pos: Position,
+    ) -> ELSResult<GotoDefinitionResponse> {
+        let Some(token) = self.file_cache.get_token(uri, pos) else {
+            send_log("lex error occurred")?;
+            return Ok(GotoDefinitionResponse::Array(vec![]));
+        };
+        let Some(vi) = self.get_definition(uri, &token)? else {
+            return Ok(GotoDefinitionResponse::Array(vec![]));
+        };
+        let t = vi.t.derefine();
+        if let Some(entry) = self
+            .workspace_types()
+            .into_iter()
+            .find(|entry| entry.is(&t))
+        {
+            let loc = lsp_types::Location::new(entry.item.uri, entry.item.selection_range);
+            return Ok(GotoDefinitionResponse::Array(vec![loc]));
+        }
+        // e.g. the types declared in `.d.er` files
+        for ctx in self.get_local_ctx(uri, pos) {
+            let Some((_, type_vi)) = ctx.get_type_info(&t) else {
+                continue;
+            };
+            if let (Some(path), Some(range)) = (
+                type_vi.def_loc.module.as_ref(),
+                util::loc_to_range(type_vi.def_loc.loc),
+            ) {
+                let def_uri = Url::from_file_path(path).unwrap();
+                let loc = lsp_types::Location::new(def_uri, range);
+                return Ok(GotoDefinitionResponse::Array(vec![loc]));
+            }
+        }
+        send_log("not found (maybe builtin)")?;
+        Ok(GotoDefinitionResponse::Array(vec![]))
+    }
 }
@@ -4,10 +4,9 @@ use erg_common::style::*;
 use erg_common::traits::Stream;
 
 use erg_compiler::artifact::BuildRunnable;
-use erg_compiler::erg_parser::Parser;
 use erg_compiler::error::CompileErrors;
 
-use lsp_types::{Diagnostic, DiagnosticSeverity, Position, PublishDiagnosticsParams, Range, Url};
+use lsp_types::{Diagnostic, Position, PublishDiagnosticsParams, Range, Url};
 
 use crate::server::{send, send_log, DefaultFeatures, ELSResult, Server};
 use crate::util::{self, NormalizedUrl};
@@ -18,6 +17,11 @@ impl<Checker: BuildRunnable> Server<Checker> {
         uri: NormalizedUrl,
         code: S,
     ) -> ELSResult<()> {
+        self.check_module(uri.clone(), code)?;
+        self.recheck_dependents(&uri)
+    }
+
+    fn check_module<S: Into<String>>(&mut self, uri: NormalizedUrl, code: S) -> ELSResult<()> {
         send_log(format!("checking {uri}"))?;
         let path = util::uri_to_path(&uri);
         let mode = if path.to_string_lossy().ends_with(".d.er") {
@@ -25,8 +29,13 @@ impl<Checker: BuildRunnable> Server<Checker> {
         } else {
             "exec"
         };
-        let mut checker = self.get_checker(path);
-        match checker.build(code.into(), mode) {
+        let code = code.into();
+        let mut checker = self.get_checker(path.clone());
+        let result = match self.file_cache.get_ast(&uri, &code, self.cfg.inherit(path)) {
+            Some(ast) => checker.build_from_ast(ast, mode),
+            None => checker.build(code, mode),
+        };
+        match result {
             Ok(artifact) => {
                 send_log(format!("checking {uri} passed"))?;
                 let uri_and_diags = self.make_uri_and_diags(uri.clone(), artifact.warns.clone());
@@ -62,33 +71,27 @@ impl<Checker: BuildRunnable> Server<Checker> {
             send_log(format!("{uri}: {}", module.context.name))?;
             self.modules.insert(uri.clone(), module);
         }
-        let dependents = self.dependents_of(&uri);
-        for dep in dependents {
-            // _log!("dep: {dep}");
-            let code = self.file_cache.get_code(&dep)?.to_string();
-            self.check_file(dep, code)?;
-        }
         Ok(())
     }
 
+    /// The dependents are not rechecked here, since they import `uri` from the disk,
+    /// which doesn't have the unsaved changes yet. They are rechecked when `uri` is saved.
     pub(crate) fn quick_check_file(&mut self, uri: NormalizedUrl) -> ELSResult<()> {
         // send_log(format!("checking {uri}"))?;
-        let Some(ts) = self.file_cache.get_token_stream(&uri) else {
-            return Ok(());
-        };
-        let mut parser = Parser::new(ts.clone());
-        if parser.parse().is_err() {
-            return Ok(());
-        }
         let path = util::uri_to_path(&uri);
-        let code = &self.file_cache.get(&uri).unwrap().code;
+        let code = self.file_cache.get_code(&uri)?.to_string();
         let mode = if path.to_string_lossy().ends_with(".d.er") {
             "declare"
         } else {
             "exec"
         };
-        let mut checker = self.get_checker(path);
-        match checker.build(code.into(), mode) {
+        let mut checker = self.get_checker(path.clone());
+        // if the cached tokens cannot be parsed incrementally, parse the entire code
+        let result = match self.file_cache.get_ast(&uri, &code, self.cfg.inherit(path)) {
+            Some(ast) => checker.build_from_ast(ast, mode),
+            None => checker.build(code, mode),
+        };
+        match result {
             Ok(artifact) => {
                 self.artifacts.insert(uri.clone(), artifact.into());
             }
@@ -99,10 +102,35 @@ impl<Checker: BuildRunnable> Server<Checker> {
         if let Some(module) = checker.pop_context() {
             self.modules.insert(uri.clone(), module);
         }
-        let dependents = self.dependents_of(&uri);
+        Ok(())
+    }
+
+    /// Invalidates the modules which import `uri` directly or indirectly,
+    /// and re-checks the open ones (each only once, in topological order).
+    /// Otherwise, the importers keep the stale types and diagnostics until they are edited themselves.
+    fn recheck_dependents(&mut self, uri: &NormalizedUrl) -> ELSResult<()> {
+        let Some(shared) = self.get_shared().cloned() else {
+            return Ok(());
+        };
+        let dependents = self.all_dependents_of(uri);
+        if dependents.is_empty() {
+            return Ok(());
+        }
+        // the importers must not refer to the cached (old) version of `uri`
+        shared.mod_cache.remove(&util::uri_to_path(uri));
+        for dep in dependents.iter() {
+            let path = util::uri_to_path(dep);
+            shared.mod_cache.remove(&path);
+            shared.index.remove_path(&path);
+            shared.graph.remove(&path);
+        }
         for dep in dependents {
-            // _log!("dep: {dep}");
-            self.quick_check_file(dep)?;
+            if !self.modules.contains_key(&dep) {
+                continue;
+            }
+            send_log(format!("rechecking dependent {dep}"))?;
+            let code = self.file_cache.get_code(&dep)?.to_string();
+            self.check_module(dep, code)?;
         }
         Ok(())
     }
@@ -137,10 +165,8 @@ impl<Checker: BuildRunnable> Server<Checker> {
                 loc.col_begin().unwrap_or(0),
             );
             let end = Position::new(loc.ln_end().unwrap_or(1) - 1, loc.col_end().unwrap_or(0));
-            let severity = if err.core.kind.is_warning() {
-                DiagnosticSeverity::WARNING
-            } else {
-                DiagnosticSeverity::ERROR
+            let Some(severity) = self.config.severity_of(err.core.kind) else {
+                continue;
             };
             let diag = Diagnostic::new(
                 Range::new(start, end),
@@ -0,0 +1,90 @@
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::token::TokenKind;
+
+use lsp_types::{DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams, Position};
+
+use crate::server::{send, send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    pub(crate) fn show_document_highlights(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("document highlight requested: {msg}"))?;
+        let params = DocumentHighlightParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let result = self.get_document_highlights(&uri, pos);
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    /// The definition is highlighted as `Write` and the references are highlighted as `Read`.
+    /// The references which mutate the variable (e.g. `x!.update! ...`, `x! = ...`) are also highlighted as `Write`.
+    fn get_document_highlights(
+        &self,
+        uri: &NormalizedUrl,
+        pos: Position,
+    ) -> Vec<DocumentHighlight> {
+        let Some(token) = self.file_cache.get_token(uri, pos) else {
+            return vec![];
+        };
+        let Some(vi) = self
+            .get_visitor(uri)
+            .and_then(|visitor| visitor.get_info(&token))
+        else {
+            return vec![];
+        };
+        let path = util::uri_to_path(uri);
+        let mut highlights = vec![];
+        if vi.def_loc.module.as_ref() == Some(&path) {
+            if let Some(range) = util::loc_to_range(vi.def_loc.loc) {
+                highlights.push(DocumentHighlight {
+                    range,
+                    kind: Some(DocumentHighlightKind::WRITE),
+                });
+            }
+        }
+        if let Some(refs) = self.get_index().get_refs(&vi.def_loc) {
+            for referrer in refs.referrers.iter() {
+                if referrer.module.as_ref() != Some(&path) {
+                    continue;
+                }
+                let Some(range) = util::loc_to_range(referrer.loc) else {
+                    continue;
+                };
+                if highlights.iter().any(|highlight| highlight.range == range) {
+                    continue;
+                }
+                let kind = if self.is_mutation(uri, range.start) {
+                    DocumentHighlightKind::WRITE
+                } else {
+                    DocumentHighlightKind::READ
+                };
+                highlights.push(DocumentHighlight {
+                    range,
+                    kind: Some(kind),
+                });
+            }
+        }
+        highlights
+            .sort_by_key(|highlight| (highlight.range.start.line, highlight.range.start.character));
+        highlights
+    }
+
+    /// `x! = ...` (reassignment) or `x!.method! ...` (a procedural method call)
+    fn is_mutation(&self, uri: &NormalizedUrl, pos: Position) -> bool {
+        let Some(next) = self.file_cache.get_token_relatively(uri, pos, 1) else {
+            return false;
+        };
+        match next.kind {
+            TokenKind::Assign => true,
+            TokenKind::Dot => self
+                .file_cache
+                .get_token_relatively(uri, pos, 2)
+                .is_some_and(|attr| attr.is(TokenKind::Symbol) && attr.content.ends_with('!')),
+            _ => false,
+        }
+    }
+}
@@ -9,10 +9,15 @@ use lsp_types::{
     WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
 };
 
+use erg_common::config::ErgConfig;
 use erg_common::dict::Dict;
 use erg_common::shared::Shared;
 use erg_common::traits::DequeStream;
+use erg_common::traits::Runnable;
+use erg_compiler::erg_parser::ast::AST;
+use erg_compiler::erg_parser::build_ast::ASTBuilder;
 use erg_compiler::erg_parser::lex::Lexer;
+use erg_compiler::erg_parser::parse::ParsedModule;
 use erg_compiler::erg_parser::token::{Token, TokenStream};
 
 use crate::server::ELSResult;
@@ -30,12 +35,22 @@ pub fn _get_code_from_uri(uri: &Url) -> ELSResult<String> {
     Ok(code)
 }
 
+/// Re-lexes only the changed part of the code if the tokens of the old code are available (see `Lexer::relex`).
+fn relex(tokens: Option<TokenStream>, old_code: &str, new_code: &str) -> Option<TokenStream> {
+    match tokens {
+        Some(mut ts) => Lexer::relex(&mut ts, old_code, new_code).ok().map(|_| ts),
+        None => Lexer::from_str(new_code.to_string()).lex().ok(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileCacheEntry {
     pub code: String,
     pub ver: i32,
     pub metadata: Metadata,
     pub token_stream: Option<TokenStream>,
+    /// the last successfully parsed module, which is used for incremental re-parsing
+    pub parsed: Option<ParsedModule>,
 }
 
 impl FileCacheEntry {
@@ -119,6 +134,22 @@ impl FileCache {
         self.get(uri).ok().and_then(|ent| ent.token_stream.as_ref())
     }
 
+    /// Returns the AST of the cached code if it is the same as `code`.
+    /// Only the top-level chunks changed since the last call are re-parsed.
+    pub(crate) fn get_ast(&self, uri: &NormalizedUrl, code: &str, cfg: ErgConfig) -> Option<AST> {
+        let entry = unsafe { self.files.as_mut() }.get_mut(uri)?;
+        if entry.code != code {
+            return None;
+        }
+        let ts = entry.token_stream.clone()?;
+        let mut builder = ASTBuilder::new(cfg);
+        let (ast, parsed) = builder
+            .build_incrementally(entry.parsed.as_ref(), ts)
+            .ok()?;
+        entry.parsed = Some(parsed);
+        Some(ast)
+    }
+
     pub fn get_token_index(&self, uri: &NormalizedUrl, pos: Position) -> Option<usize> {
         let tokens = self.get_token_stream(uri)?;
         for (i, tok) in tokens.iter().enumerate() {
@@ -168,7 +199,11 @@ impl FileCache {
                 .unwrap_or_else(|_| util::denormalize(uri.clone().raw()).to_file_path().unwrap()),
         )
         .unwrap();
-        let token_stream = Lexer::from_str(code.clone()).lex().ok();
+        let token_stream = match entry {
+            Some(entry) => relex(entry.token_stream.clone(), &entry.code, &code),
+            None => Lexer::from_str(code.clone()).lex().ok(),
+        };
+        let parsed = entry.and_then(|entry| entry.parsed.clone());
         let ver = ver.unwrap_or({
             if let Some(entry) = entry {
                 entry.ver
@@ -183,6 +218,7 @@ impl FileCache {
                 ver,
                 metadata,
                 token_stream,
+                parsed,
             },
         );
     }
@@ -196,11 +232,10 @@ impl FileCache {
         let start = util::pos_to_byte_index(&code, old.start);
         let end = util::pos_to_byte_index(&code, old.end);
         code.replace_range(start..end, new_code);
-        let token_stream = Lexer::from_str(code.clone()).lex().ok();
+        entry.token_stream = relex(entry.token_stream.take(), &entry.code, &code);
         entry.code = code;
         // entry.ver += 1;
         entry.metadata = metadata;
-        entry.token_stream = token_stream;
     }
 
     pub(crate) fn incremental_update(&self, params: DidChangeTextDocumentParams) {
@@ -220,11 +255,10 @@ impl FileCache {
             let end = util::pos_to_byte_index(&code, range.end);
             code.replace_range(start..end, &change.text);
         }
-        let token_stream = Lexer::from_str(code.clone()).lex().ok();
+        entry.token_stream = relex(entry.token_stream.take(), &entry.code, &code);
         entry.code = code;
         entry.ver = params.text_document.version;
         entry.metadata = metadata;
-        entry.token_stream = token_stream;
     }
 
     #[allow(unused)]
@@ -0,0 +1,159 @@
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use erg_common::traits::{DequeStream, Locational};
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::erg_parser::token::{TokenKind, TokenStream};
+
+use lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
+
+use crate::server::{send, send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+fn folding_range(start: u32, end: u32, kind: Option<FoldingRangeKind>) -> FoldingRange {
+    FoldingRange {
+        start_line: start,
+        start_character: None,
+        end_line: end,
+        end_character: None,
+        kind,
+    }
+}
+
+/// Indentation blocks (including class method blocks), multi-line containers and doc comments.
+/// An indentation block is folded from the line of the header (e.g. `C.`, `f x =`) to the last line of the block.
+fn block_ranges(tokens: &TokenStream) -> Vec<FoldingRange> {
+    let mut ranges = vec![];
+    // 0-based line numbers of the headers / opening brackets
+    let mut starts = vec![];
+    // 0-based line number of the last token which is not a layout token (`Newline`, `Indent`, `Dedent`)
+    let mut last_line = 0;
+    for token in tokens.iter() {
+        let Some(ln_begin) = token.ln_begin() else {
+            continue;
+        };
+        // `Token::loc` does not consider multi-line tokens (e.g. doc comments)
+        let ln_end = ln_begin + token.content.lines().count().saturating_sub(1) as u32;
+        match token.kind {
+            TokenKind::Indent => starts.push(last_line),
+            TokenKind::LParen | TokenKind::LSqBr | TokenKind::LBrace => {
+                starts.push(ln_begin.saturating_sub(1))
+            }
+            TokenKind::Dedent => {
+                if let Some(start) = starts.pop() {
+                    if last_line > start {
+                        ranges.push(folding_range(start, last_line, None));
+                    }
+                }
+            }
+            // the closing bracket remains visible
+            TokenKind::RParen | TokenKind::RSqBr | TokenKind::RBrace => {
+                let end = ln_begin.saturating_sub(2);
+                if let Some(start) = starts.pop() {
+                    if end > start {
+                        ranges.push(folding_range(start, end, None));
+                    }
+                }
+            }
+            TokenKind::DocComment if ln_end > ln_begin => {
+                ranges.push(folding_range(
+                    ln_begin - 1,
+                    ln_end - 1,
+                    Some(FoldingRangeKind::Comment),
+                ));
+            }
+            _ => {}
+        }
+        if !matches!(
+            token.kind,
+            TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent | TokenKind::EOF
+        ) {
+            last_line = ln_end - 1;
+        }
+    }
+    ranges
+}
+
+/// Multi-line comments (`#[ ... ]#`).
+/// Comments are not included in the token stream, so the code is scanned directly, skipping the literals that may contain `#`.
+fn comment_ranges(code: &str, tokens: &TokenStream) -> Vec<FoldingRange> {
+    let literals = tokens
+        .iter()
+        .filter(|token| token.content.contains('#'))
+        .filter_map(|token| {
+            let start = util::pos_to_byte_index(code, util::loc_to_pos(token.loc())?);
+            Some((start, start + token.content.len()))
+        })
+        .collect::<Vec<_>>();
+    let mut ranges = vec![];
+    let bytes = code.as_bytes();
+    let mut line = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if let Some((_, end)) = literals.iter().find(|(start, _)| *start == i) {
+            line += bytes[i..*end].iter().filter(|&&b| b == b'\n').count() as u32;
+            i = *end;
+            continue;
+        }
+        match bytes[i] {
+            b'\n' => line += 1,
+            b'#' if bytes.get(i + 1) == Some(&b'[') => {
+                let start = line;
+                let mut nest_level = 0;
+                while i < bytes.len() {
+                    match (bytes[i], bytes.get(i + 1)) {
+                        (b'#', Some(b'[')) => {
+                            nest_level += 1;
+                            i += 1;
+                        }
+                        (b']', Some(b'#')) => {
+                            nest_level -= 1;
+                            i += 1;
+                            if nest_level == 0 {
+                                break;
+                            }
+                        }
+                        (b'\n', _) => line += 1,
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                if line > start {
+                    ranges.push(folding_range(start, line, Some(FoldingRangeKind::Comment)));
+                }
+            }
+            b'#' => {
+                while i + 1 < bytes.len() && bytes[i + 1] != b'\n' {
+                    i += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    ranges
+}
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    pub(crate) fn show_folding_ranges(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("folding range requested: {msg}"))?;
+        let params = FoldingRangeParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let result = self.get_folding_ranges(&uri)?;
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    fn get_folding_ranges(&self, uri: &NormalizedUrl) -> ELSResult<Vec<FoldingRange>> {
+        let code = self.file_cache.get_code(uri)?;
+        // the token stream is not available if the code has a lexical error
+        let Some(tokens) = self.file_cache.get_token_stream(uri) else {
+            return Ok(vec![]);
+        };
+        let mut ranges = block_ranges(tokens);
+        ranges.extend(comment_ranges(code, tokens));
+        ranges.sort_by_key(|range| (range.start_line, range.end_line));
+        ranges.dedup_by_key(|range| (range.start_line, range.end_line));
+        Ok(ranges)
+    }
+}
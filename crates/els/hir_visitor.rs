@@ -10,6 +10,7 @@ use crate::util::{self, NormalizedUrl};
 /// This struct provides:
 /// * namespace where the cursor is located (`get_namespace`)
 /// * cursor(`Token`) -> `Expr` mapping (`get_min_expr`)
+/// * cursor(`Token`) -> enclosing `Expr`s mapping (`get_enclosing_exprs`)
 /// * cursor(`Token`) -> `VarInfo` mapping (`get_info`)
 pub struct HIRVisitor<'a> {
     hir: &'a HIR,
@@ -160,6 +161,30 @@ impl<'a> HIRVisitor<'a> {
         None
     }
 
+    /// Returns the expressions containing `token`, from the outermost to the innermost (= `get_min_expr`).
+    pub fn get_enclosing_exprs(&self, token: &Token) -> Vec<&Expr> {
+        let Some(min_expr) = self.get_min_expr(token) else {
+            return vec![];
+        };
+        let mut exprs = vec![];
+        let mut candidates = self.hir.module.iter().collect::<Vec<_>>();
+        'search: loop {
+            for expr in candidates {
+                if std::ptr::eq(expr, min_expr) {
+                    break 'search;
+                }
+                if expr.loc().contains(min_expr.loc()) {
+                    exprs.push(expr);
+                    candidates = sub_exprs(expr);
+                    continue 'search;
+                }
+            }
+            break;
+        }
+        exprs.push(min_expr);
+        exprs
+    }
+
     fn return_expr_if_same<'e>(&'e self, expr: &'e Expr, l: &Token, r: &Token) -> Option<&Expr> {
         if self.strict_cmp {
             if l.deep_eq(r) {
@@ -265,6 +290,11 @@ impl<'a> HIRVisitor<'a> {
                 return Some(expr);
             }
         }
+        if let Some(kw_var) = &args.kw_var_args {
+            if let Some(expr) = self.get_expr(&kw_var.expr, token) {
+                return Some(expr);
+            }
+        }
         None
     }
 
@@ -329,6 +359,12 @@ impl<'a> HIRVisitor<'a> {
     ) -> Option<&Expr> {
         self.return_expr_if_same(expr, patch_def.sig.name().token(), token)
             .or_else(|| self.get_expr(&patch_def.base, token))
+            .or_else(|| {
+                patch_def
+                    .impls
+                    .as_ref()
+                    .and_then(|impls| self.get_expr(impls, token))
+            })
             .or_else(|| self.get_expr_from_block(&patch_def.methods, token))
             .or_else(|| patch_def.loc().contains(token.loc()).then_some(expr))
     }
@@ -373,6 +409,11 @@ impl<'a> HIRVisitor<'a> {
         }
         match dict {
             Dict::Normal(dict) => {
+                for spread in &dict.spreads {
+                    if let Some(expr) = self.get_expr(spread, token) {
+                        return Some(expr);
+                    }
+                }
                 for kv in &dict.kvs {
                     if let Some(expr) = self
                         .get_expr(&kv.key, token)
@@ -536,6 +577,11 @@ impl<'a> HIRVisitor<'a> {
                 return Some(expr);
             }
         }
+        if let Some(kw_var) = &args.kw_var_args {
+            if let Some(expr) = self.get_expr_info(&kw_var.expr, token) {
+                return Some(expr);
+            }
+        }
         None
     }
 
@@ -568,6 +614,11 @@ impl<'a> HIRVisitor<'a> {
                 return Some(vi);
             }
         }
+        if let Some(kw_var) = &params.kw_var_params {
+            if kw_var.raw.pat.loc() == token.loc() {
+                return Some(kw_var.vi.clone());
+            }
+        }
         None
     }
 
@@ -587,6 +638,12 @@ impl<'a> HIRVisitor<'a> {
 
     fn get_patch_def_info(&self, patch_def: &PatchDef, token: &Token) -> Option<VarInfo> {
         self.get_expr_info(&patch_def.base, token)
+            .or_else(|| {
+                patch_def
+                    .impls
+                    .as_ref()
+                    .and_then(|impls| self.get_expr_info(impls, token))
+            })
             .or_else(|| self.get_sig_info(&patch_def.sig, token))
             .or_else(|| self.get_block_info(&patch_def.methods, token))
     }
@@ -629,6 +686,11 @@ impl<'a> HIRVisitor<'a> {
     fn get_dict_info(&self, dict: &Dict, token: &Token) -> Option<VarInfo> {
         match dict {
             Dict::Normal(dict) => {
+                for spread in &dict.spreads {
+                    if let Some(expr) = self.get_expr_info(spread, token) {
+                        return Some(expr);
+                    }
+                }
                 for kv in &dict.kvs {
                     if let Some(expr) = self.get_expr_info(&kv.key, token) {
                         return Some(expr);
@@ -665,3 +727,73 @@ impl<'a> HIRVisitor<'a> {
         }
     }
 }
+
+fn args_exprs(args: &Args) -> Vec<&Expr> {
+    let mut exprs = args
+        .pos_args
+        .iter()
+        .map(|arg| &arg.expr)
+        .collect::<Vec<_>>();
+    if let Some(var) = &args.var_args {
+        exprs.push(&var.expr);
+    }
+    exprs.extend(args.kw_args.iter().map(|arg| &arg.expr));
+    if let Some(kw_var) = &args.kw_var_args {
+        exprs.push(&kw_var.expr);
+    }
+    exprs
+}
+
+/// Returns the direct children of `expr`.
+pub(crate) fn sub_exprs(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Lit(_) | Expr::Import(_) => vec![],
+        Expr::Accessor(Accessor::Ident(_)) => vec![],
+        Expr::Accessor(Accessor::Attr(attr)) => vec![&attr.obj],
+        Expr::BinOp(bin) => vec![&bin.lhs, &bin.rhs],
+        Expr::UnaryOp(unary) => vec![&unary.expr],
+        Expr::Call(call) => {
+            let mut exprs = vec![call.obj.as_ref()];
+            exprs.extend(args_exprs(&call.args));
+            exprs
+        }
+        Expr::ClassDef(class_def) => {
+            let mut exprs = class_def
+                .require_or_sup
+                .iter()
+                .map(|req_sup| req_sup.as_ref())
+                .collect::<Vec<_>>();
+            exprs.extend(class_def.methods.iter());
+            exprs
+        }
+        Expr::Def(def) => def.body.block.iter().collect(),
+        Expr::PatchDef(patch_def) => {
+            let mut exprs = vec![patch_def.base.as_ref()];
+            exprs.extend(patch_def.impls.as_deref());
+            exprs.extend(patch_def.methods.iter());
+            exprs
+        }
+        Expr::Lambda(lambda) => lambda.body.iter().collect(),
+        Expr::Array(Array::Normal(arr)) => args_exprs(&arr.elems),
+        Expr::Array(Array::WithLength(arr)) => vec![&arr.elem, &arr.len],
+        Expr::Array(Array::Comprehension(arr)) => vec![&arr.elem, &arr.guard],
+        Expr::Dict(Dict::Normal(dict)) => dict
+            .spreads
+            .iter()
+            .chain(dict.kvs.iter().flat_map(|kv| [&kv.key, &kv.value]))
+            .collect(),
+        Expr::Dict(Dict::Comprehension(dict)) => vec![&dict.key, &dict.value, &dict.guard],
+        Expr::Record(record) => record
+            .attrs
+            .iter()
+            .flat_map(|field| field.body.block.iter())
+            .collect(),
+        Expr::Set(Set::Normal(set)) => args_exprs(&set.elems),
+        Expr::Set(Set::WithLength(set)) => vec![&set.elem, &set.len],
+        Expr::Tuple(Tuple::Normal(tuple)) => args_exprs(&tuple.elems),
+        Expr::TypeAsc(type_asc) => vec![&type_asc.expr],
+        Expr::Dummy(dummy) => dummy.iter().collect(),
+        Expr::Compound(block) | Expr::Code(block) => block.iter().collect(),
+        Expr::ReDef(redef) => redef.block.iter().collect(),
+    }
+}
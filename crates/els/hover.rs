@@ -149,9 +149,18 @@ impl<Checker: BuildRunnable> Server<Checker> {
                             MarkedString::from_language_code(PROG_LANG.into(), code_block);
                         contents.push(definition);
                     }
+                    // show each signature of an overloaded subroutine on its own line
+                    let sigs = if vi.t.is_overloaded() {
+                        vi.t.intersection_types()
+                    } else {
+                        vec![vi.t.clone()]
+                    };
                     let typ = MarkedString::from_language_code(
                         ERG_LANG.into(),
-                        format!("{}: {}", token.content, vi.t),
+                        sigs.iter()
+                            .map(|t| format!("{}: {t}", token.content))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
                     );
                     contents.push(typ);
                     self.show_type_defs(&vi, &mut contents)?;
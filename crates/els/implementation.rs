@@ -0,0 +1,97 @@
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use erg_common::traits::Locational;
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::hir::{Def, Expr};
+
+use lsp_types::request::GotoImplementationParams;
+use lsp_types::{Location, Position, SymbolKind, Url};
+
+use crate::server::{send, send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+fn method_defs<'e>(exprs: impl Iterator<Item = &'e Expr>) -> Vec<&'e Def> {
+    let mut defs = vec![];
+    for expr in exprs {
+        match expr {
+            Expr::ClassDef(class_def) => {
+                defs.extend(class_def.methods.iter().filter_map(|expr| match expr {
+                    Expr::Def(def) => Some(def),
+                    _ => None,
+                }));
+            }
+            Expr::Compound(block) | Expr::Code(block) => defs.extend(method_defs(block.iter())),
+            Expr::Dummy(dummy) => defs.extend(method_defs(dummy.iter())),
+            _ => {}
+        }
+    }
+    defs
+}
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    pub(crate) fn show_implementation(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("implementation requested: {msg}"))?;
+        let params = GotoImplementationParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let result = self.get_implementations(&uri, pos)?;
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    /// * trait -> the types implementing it
+    /// * trait member -> the methods implementing it
+    fn get_implementations(&self, uri: &NormalizedUrl, pos: Position) -> ELSResult<Vec<Location>> {
+        let Some(token) = self.file_cache.get_token(uri, pos) else {
+            return Ok(vec![]);
+        };
+        let Some(vi) = self.get_definition(uri, &token)? else {
+            return Ok(vec![]);
+        };
+        let (Some(path), Some(range)) = (vi.def_loc.module, util::loc_to_range(vi.def_loc.loc))
+        else {
+            send_log("not found (maybe builtin)")?;
+            return Ok(vec![]);
+        };
+        let Ok(def_uri) = Url::from_file_path(path) else {
+            return Ok(vec![]);
+        };
+        let entries = self.workspace_types();
+        let Some(trait_) = entries.iter().find(|entry| {
+            entry.item.kind == SymbolKind::INTERFACE
+                && entry.item.uri == def_uri
+                && util::contains(entry.item.range, range)
+        }) else {
+            send_log(format!("not a trait or a trait member: {token}"))?;
+            return Ok(vec![]);
+        };
+        if trait_.item.selection_range == range {
+            let impls = self
+                .get_subtypes(&trait_.item)
+                .into_iter()
+                .filter(|sub| sub.kind != SymbolKind::INTERFACE)
+                .map(|sub| Location::new(sub.uri, sub.selection_range));
+            return Ok(impls.collect());
+        }
+        let mut impls = vec![];
+        for (path, hir) in self.workspace_hirs() {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            for def in method_defs(hir.module.iter()) {
+                let ident = def.sig.ident();
+                if ident.inspect() != token.inspect() {
+                    continue;
+                }
+                let Some(impl_of) = ident.vi.impl_of.as_ref() else {
+                    continue;
+                };
+                if let (true, Some(range)) = (trait_.is(impl_of), util::loc_to_range(ident.loc())) {
+                    impls.push(Location::new(uri.clone(), range));
+                }
+            }
+        }
+        Ok(impls)
+    }
+}
@@ -118,6 +118,9 @@ impl<Checker: BuildRunnable> Server<Checker> {
 
     fn get_param_hint(&self, params: &Params) -> Vec<InlayHint> {
         let mut result = vec![];
+        if !self.config.inlay_hints.parameter_types {
+            return result;
+        }
         for nd_param in params.non_defaults.iter() {
             if nd_param.raw.t_spec.is_some() {
                 continue;
@@ -154,6 +157,18 @@ impl<Checker: BuildRunnable> Server<Checker> {
             );
             result.push(hint);
         }
+        if let Some(kw_var_params) = &params.kw_var_params {
+            if kw_var_params.raw.t_spec.is_some() {
+                return result;
+            }
+            let hint = type_anot(
+                kw_var_params.ln_end().unwrap(),
+                kw_var_params.col_end().unwrap(),
+                &kw_var_params.vi.t,
+                false,
+            );
+            result.push(hint);
+        }
         result
     }
 
@@ -161,7 +176,10 @@ impl<Checker: BuildRunnable> Server<Checker> {
         let mut result = vec![];
         result.extend(self.get_block_hint(&def.body.block));
         let Signature::Subr(subr) = &def.sig else { unreachable!() };
-        if subr.ref_t().is_quantified_subr() && subr.bounds.is_empty() {
+        if self.config.inlay_hints.type_bounds
+            && subr.ref_t().is_quantified_subr()
+            && subr.bounds.is_empty()
+        {
             let subr = subr.ref_t().to_string();
             let ty_bounds = format!("|{}|", subr.split('|').nth(1).unwrap_or(""));
             let hint = type_bounds_anot(
@@ -172,7 +190,7 @@ impl<Checker: BuildRunnable> Server<Checker> {
             result.push(hint);
         }
         result.extend(self.get_param_hint(&subr.params));
-        if def.sig.t_spec().is_none() {
+        if self.config.inlay_hints.return_types && def.sig.t_spec().is_none() {
             let Some(return_t) = subr.ref_t().return_t() else {
                 return result;
             };
@@ -198,7 +216,10 @@ impl<Checker: BuildRunnable> Server<Checker> {
     fn get_var_def_hint(&self, def: &Def) -> Vec<InlayHint> {
         let mut result = self.get_block_hint(&def.body.block);
         // don't show hints for compiler internal variables
-        if def.sig.t_spec().is_none() && !def.sig.ident().inspect().starts_with(['%']) {
+        if self.config.inlay_hints.variable_types
+            && def.sig.t_spec().is_none()
+            && !def.sig.ident().inspect().starts_with(['%'])
+        {
             let hint = type_anot(
                 def.sig.ln_end().unwrap(),
                 def.sig.col_end().unwrap(),
@@ -214,6 +235,9 @@ impl<Checker: BuildRunnable> Server<Checker> {
         let mut result = vec![];
         result.extend(self.get_block_hint(&lambda.body));
         result.extend(self.get_param_hint(&lambda.params));
+        if !self.config.inlay_hints.return_types {
+            return result;
+        }
         let return_t = lambda.ref_t().return_t().unwrap();
         let hint = type_anot(
             lambda.params.ln_end().unwrap(),
@@ -261,6 +285,9 @@ impl<Checker: BuildRunnable> Server<Checker> {
         for (i, pos_arg) in call.args.pos_args.iter().enumerate() {
             let arg_is_lambda = matches!(&pos_arg.expr, Expr::Lambda(_));
             result.extend(self.get_expr_hint(&pos_arg.expr));
+            if !self.config.inlay_hints.parameter_names {
+                continue;
+            }
             let index = if is_method { i + 1 } else { i };
             if let Some(name) = param_ts.clone().nth(index).and_then(|pt| pt.name()) {
                 if name.starts_with(['%']) {
@@ -1,19 +1,28 @@
+mod call_hierarchy;
 mod code_action;
 mod code_lens;
 mod command;
 mod completion;
+mod config;
 mod definition;
 mod diagnostics;
+mod doc_highlight;
 mod file_cache;
+mod folding_range;
 mod hir_visitor;
 mod hover;
+mod implementation;
 mod inlay_hint;
 mod message;
 mod references;
 mod rename;
+mod scheduler;
+mod selection_range;
 mod semantic;
 mod server;
 mod sig_help;
+mod symbol;
+mod type_hierarchy;
 mod util;
 
 use erg_common::config::ErgConfig;
@@ -64,6 +64,19 @@ impl ShowMessage {
         }
     }
 
+    pub fn warn<S: Into<String>>(message: S) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            method: "window/showMessage".into(),
+            params: json! {
+                {
+                    "type": 2,
+                    "message": message.into(),
+                }
+            },
+        }
+    }
+
     pub fn error<S: Into<String>>(message: S) -> Self {
         Self {
             jsonrpc: "2.0".into(),
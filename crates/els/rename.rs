@@ -10,6 +10,8 @@ use serde_json::json;
 use serde_json::Value;
 
 use erg_common::dict::Dict;
+use erg_common::set;
+use erg_common::tsort::{tsort, Node};
 
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::hir::{Expr, Literal};
@@ -159,6 +161,53 @@ impl<Checker: BuildRunnable> Server<Checker> {
             .map(|node| NormalizedUrl::new(Url::from_file_path(&node.id).unwrap()))
             .collect()
     }
+
+    /// Returns the modules which depend on `uri` directly or indirectly, in topological order.
+    ///
+    /// self is __not included__
+    pub fn all_dependents_of(&self, uri: &NormalizedUrl) -> Vec<NormalizedUrl> {
+        let Some(shared) = self.get_shared() else {
+            return vec![];
+        };
+        let graph = &shared.graph;
+        let path = util::uri_to_path(uri);
+        let mut affected = set! { path.clone() };
+        // walk the reverse edges until no new dependents are found
+        loop {
+            let found = graph
+                .iter()
+                .filter(|node| !affected.contains(&node.id))
+                .filter(|node| node.depends_on.iter().any(|dep| affected.contains(dep)))
+                .map(|node| node.id.clone())
+                .collect::<Vec<_>>();
+            if found.is_empty() {
+                break;
+            }
+            affected.extend(found);
+        }
+        // the affected subgraph is sorted locally (the shared graph is not reordered)
+        let subgraph = graph
+            .iter()
+            .filter(|node| affected.contains(&node.id))
+            .map(|node| {
+                let depends_on = node
+                    .depends_on
+                    .iter()
+                    .filter(|dep| affected.contains(*dep))
+                    .cloned()
+                    .collect();
+                Node::new(node.id.clone(), (), depends_on)
+            })
+            .collect::<Vec<_>>();
+        // if the imports are cyclic, the dependents are returned in the order of registration
+        let subgraph = tsort(subgraph.clone()).unwrap_or(subgraph);
+        subgraph
+            .into_iter()
+            .filter(|node| node.id != path)
+            .filter_map(|node| Url::from_file_path(&node.id).ok())
+            .map(NormalizedUrl::new)
+            .collect()
+    }
 }
 
 impl<Checker: BuildRunnable> Server<Checker> {
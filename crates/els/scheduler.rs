@@ -0,0 +1,140 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver};
+
+use serde_json::Value;
+
+use erg_common::set::Set;
+use erg_common::shared::AtomicShared;
+use erg_compiler::artifact::BuildRunnable;
+
+use crate::server::{read_message, send_error, send_log, ELSResult, Server};
+use crate::util::NormalizedUrl;
+
+/// The request has been cancelled by `$/cancelRequest`.
+const REQUEST_CANCELLED: i64 = -32800;
+/// The document has been modified after the request was sent, so the result would be stale.
+const CONTENT_MODIFIED: i64 = -32801;
+
+/// Larger kinds subsume smaller ones when the checks of the same file are coalesced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum CheckKind {
+    /// Re-check without publishing diagnostics (`checkOnType`)
+    Quick,
+    Full,
+    /// Clear the cache and then check (on save)
+    Clean,
+}
+
+/// The ids of the requests cancelled by `$/cancelRequest`.
+/// They are recorded by the reader thread as soon as they arrive,
+/// so a request can be cancelled even if it has been received in an earlier batch than the cancellation
+/// (e.g. the request is waiting for a check run by an earlier request of the batch).
+pub(crate) type CancelledRequests = AtomicShared<Set<i64>>;
+
+/// Reads messages from stdin on a dedicated thread,
+/// so that the messages which arrive during a check can be cancelled or answered as soon as the check finishes.
+pub(crate) fn spawn_reader(cancelled: CancelledRequests) -> Receiver<io::Result<Value>> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || loop {
+        let msg = read_message();
+        if let Ok(msg) = &msg {
+            if msg["method"].as_str() == Some("$/cancelRequest") {
+                if let Some(id) = msg["params"]["id"].as_i64() {
+                    cancelled.borrow_mut().insert(id);
+                }
+            }
+        }
+        let failed = msg.is_err();
+        if sender.send(msg).is_err() || failed {
+            break;
+        }
+    });
+    receiver
+}
+
+fn text_document_uri(msg: &Value) -> Option<&str> {
+    msg["params"]["textDocument"]["uri"].as_str()
+}
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    /// Checks are deferred until no messages are pending.
+    /// The same file is checked only once, however many times it has been changed in the meantime.
+    ///
+    /// Note that the checks themselves run on this thread,
+    /// because the compiler's data structures (`Shared`, `Str`, etc.) are `Rc`-based and not `Send`.
+    /// Read-only requests are served from the last artifact before the pending checks run.
+    pub(crate) fn schedule_check(&mut self, uri: NormalizedUrl, kind: CheckKind) {
+        if let Some((_, scheduled)) = self.pending_checks.iter_mut().find(|(u, _)| u == &uri) {
+            *scheduled = (*scheduled).max(kind);
+        } else {
+            self.pending_checks.push((uri, kind));
+        }
+    }
+
+    pub(crate) fn run_pending_check(&mut self) -> ELSResult<()> {
+        if self.pending_checks.is_empty() {
+            return Ok(());
+        }
+        let (uri, kind) = self.pending_checks.remove(0);
+        self.run_check(uri, kind)
+    }
+
+    /// If the file has never been checked, there is no artifact to serve the request from,
+    /// so the pending check is run first.
+    fn check_if_unchecked(&mut self, uri: &str) -> ELSResult<()> {
+        let uri = NormalizedUrl::parse(uri)?;
+        if self.artifacts.contains_key(&uri) {
+            return Ok(());
+        }
+        if let Some(idx) = self.pending_checks.iter().position(|(u, _)| u == &uri) {
+            let (uri, kind) = self.pending_checks.remove(idx);
+            self.run_check(uri, kind)?;
+        }
+        Ok(())
+    }
+
+    /// A running check cannot be interrupted, so the messages received meanwhile are handled after it.
+    fn run_check(&mut self, uri: NormalizedUrl, kind: CheckKind) -> ELSResult<()> {
+        match kind {
+            CheckKind::Quick => self.quick_check_file(uri),
+            CheckKind::Full | CheckKind::Clean => {
+                // take a snapshot of the latest code
+                let code = self.file_cache.get_code(&uri)?.to_string();
+                if kind == CheckKind::Clean {
+                    self.clear_cache(&uri);
+                }
+                self.check_file(uri, code)
+            }
+        }
+    }
+
+    /// Handles the messages received at once.
+    /// * the requests cancelled by `$/cancelRequest` (including the ones received after this batch) are not processed
+    /// * the requests on a document changed by later messages are not processed (the result would be stale)
+    pub(crate) fn handle_messages(&mut self, msgs: Vec<Value>) -> ELSResult<()> {
+        for (i, msg) in msgs.iter().enumerate() {
+            if let (Some(id), Some(method)) = (msg["id"].as_i64(), msg["method"].as_str()) {
+                if self.cancelled.borrow_mut().contains(&id) {
+                    send_log(format!("request cancelled: {method} ({id})"))?;
+                    send_error(Some(id), REQUEST_CANCELLED, "request cancelled")?;
+                    continue;
+                }
+                let modified = text_document_uri(msg).is_some()
+                    && msgs[i + 1..].iter().any(|later| {
+                        later["method"].as_str() == Some("textDocument/didChange")
+                            && text_document_uri(later) == text_document_uri(msg)
+                    });
+                if modified {
+                    send_log(format!("request outdated: {method} ({id})"))?;
+                    send_error(Some(id), CONTENT_MODIFIED, "content modified")?;
+                    continue;
+                }
+                if let Some(uri) = text_document_uri(msg) {
+                    self.check_if_unchecked(uri)?;
+                }
+            }
+            self.dispatch(msg.clone())?;
+        }
+        Ok(())
+    }
+}
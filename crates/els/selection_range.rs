@@ -0,0 +1,60 @@
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use erg_common::traits::Locational;
+use erg_compiler::artifact::BuildRunnable;
+
+use lsp_types::{Position, Range, SelectionRange, SelectionRangeParams};
+
+use crate::server::{send, send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    pub(crate) fn show_selection_ranges(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("selection range requested: {msg}"))?;
+        let params = SelectionRangeParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let result = params
+            .positions
+            .into_iter()
+            .map(|pos| self.get_selection_range(&uri, pos))
+            .collect::<Vec<_>>();
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    /// token -> the smallest expression containing the token -> ... -> the top-level expression
+    fn get_selection_range(&self, uri: &NormalizedUrl, pos: Position) -> SelectionRange {
+        let empty = SelectionRange {
+            range: Range::new(pos, pos),
+            parent: None,
+        };
+        let Some(token) = self.file_cache.get_token(uri, pos) else {
+            return empty;
+        };
+        let mut ranges = vec![];
+        if let Some(visitor) = self.get_visitor(uri) {
+            ranges.extend(
+                visitor
+                    .get_enclosing_exprs(&token)
+                    .into_iter()
+                    .filter_map(|expr| util::loc_to_range(expr.loc())),
+            );
+        }
+        ranges.extend(util::loc_to_range(token.loc()));
+        let mut selection: Option<SelectionRange> = None;
+        // from the outermost
+        for range in ranges {
+            if let Some(parent) = &selection {
+                if parent.range == range || !util::contains(parent.range, range) {
+                    continue;
+                }
+            }
+            selection = Some(SelectionRange {
+                range,
+                parent: selection.map(Box::new),
+            });
+        }
+        selection.unwrap_or(empty)
+    }
+}
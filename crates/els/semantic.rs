@@ -1,302 +1,516 @@
-use lsp_types::SemanticToken;
+use std::path::Path;
+
 use serde::Deserialize;
 use serde_json::json;
 use serde_json::Value;
 
 use erg_common::dict::Dict;
-use erg_common::error::Location;
-use erg_common::traits::{Locational, Runnable};
+use erg_common::env::{erg_pystd_path, erg_std_decl_path, erg_std_path};
+use erg_common::traits::DequeStream;
 
 use erg_compiler::artifact::BuildRunnable;
-use erg_compiler::erg_parser::ast::{
-    Accessor, Args, BinOp, Block, Call, ClassAttr, Def, DefKind, Expr, Methods, Params,
-    PreDeclTypeSpec, SimpleTypeSpec, TypeSpec, UnaryOp, AST,
-};
-use erg_compiler::erg_parser::token::TokenKind;
-use erg_compiler::ASTBuilder;
+use erg_compiler::erg_parser::ast::ParamPattern;
+use erg_compiler::erg_parser::token::{Token, TokenCategory, TokenKind, TokenStream};
+use erg_compiler::hir::{Accessor, Args, Def, Expr, Identifier, Params, Signature, HIR};
+use erg_compiler::ty::typaram::TyParam;
+use erg_compiler::ty::value::{TypeObj, ValueObj};
+use erg_compiler::ty::Type;
+use erg_compiler::varinfo::{Mutability, VarInfo, VarKind};
 
-use lsp_types::{SemanticTokenType, SemanticTokens, SemanticTokensParams};
+use lsp_types::{
+    Position, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensDelta, SemanticTokensDeltaParams, SemanticTokensEdit, SemanticTokensFullOptions,
+    SemanticTokensLegend, SemanticTokensOptions, SemanticTokensParams, SemanticTokensRangeParams,
+};
 
+use crate::hir_visitor::sub_exprs;
 use crate::server::{send, send_log, ELSResult, Server};
-use crate::util::{self, NormalizedUrl};
-
-#[derive(Debug)]
-struct ASTSemanticState {
-    prev_line: u32,
-    prev_col: u32,
-    namespaces: Vec<Dict<String, SemanticTokenType>>,
-    tokens: Vec<SemanticToken>,
-}
+use crate::util::NormalizedUrl;
 
-impl ASTSemanticState {
-    fn new() -> Self {
-        Self {
-            prev_line: 1,
-            prev_col: 0,
-            namespaces: vec![Dict::new()],
-            tokens: Vec::new(),
-        }
-    }
+/// The index of each type is used as the token type of `SemanticToken`.
+const TOKEN_TYPES: [SemanticTokenType; 14] = [
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::COMMENT,
+];
 
-    fn get_type(&self, key: &str) -> Option<SemanticTokenType> {
-        for namespace in self.namespaces.iter().rev() {
-            if let Some(typ) = namespace.get(key) {
-                return Some(typ.clone());
-            }
-        }
-        None
-    }
+/// The `n`th modifier corresponds to the `n`th bit of `token_modifiers_bitset`.
+const TOKEN_MODIFIERS: [SemanticTokenModifier; 6] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+    // non-standard modifiers
+    SemanticTokenModifier::new("mutable"),
+    SemanticTokenModifier::new("procedure"),
+    SemanticTokenModifier::new("public"),
+];
 
-    fn get_variable_type(&self, key: &str) -> SemanticTokenType {
-        self.get_type(key).unwrap_or(SemanticTokenType::VARIABLE)
+pub(crate) fn semantic_tokens_options() -> SemanticTokensOptions {
+    SemanticTokensOptions {
+        legend: SemanticTokensLegend {
+            token_types: TOKEN_TYPES.to_vec(),
+            token_modifiers: TOKEN_MODIFIERS.to_vec(),
+        },
+        range: Some(true),
+        full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+        ..Default::default()
     }
+}
+
+fn type_index(typ: SemanticTokenType) -> u32 {
+    TOKEN_TYPES.iter().position(|t| t == &typ).unwrap_or(6) as u32
+}
+
+fn modifier_bit(modifier: SemanticTokenModifier) -> u32 {
+    TOKEN_MODIFIERS
+        .iter()
+        .position(|m| m == &modifier)
+        .map_or(0, |i| 1 << i)
+}
+
+/// The last results of `semanticTokens/full` and `semanticTokens/full/delta`, used to calculate the deltas
+#[derive(Debug, Default)]
+pub struct SemanticTokensCache {
+    next_id: usize,
+    results: Dict<NormalizedUrl, SemanticTokens>,
+}
 
-    fn push_current_namespace(&mut self, key: String, typ: SemanticTokenType) {
-        let current = self.namespaces.last_mut().unwrap();
-        current.insert(key, typ);
+impl SemanticTokensCache {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn enumerate_tokens(&mut self, ast: AST) -> SemanticTokens {
-        for expr in ast.module.into_iter() {
-            let tokens = self.gen_from_expr(expr);
-            self.tokens.extend(tokens);
-        }
-        SemanticTokens {
-            result_id: None,
-            data: std::mem::take(&mut self.tokens),
-        }
+    fn register(&mut self, uri: NormalizedUrl, data: Vec<SemanticToken>) -> SemanticTokens {
+        self.next_id += 1;
+        let tokens = SemanticTokens {
+            result_id: Some(self.next_id.to_string()),
+            data,
+        };
+        self.results.insert(uri, tokens.clone());
+        tokens
     }
 
-    fn token_type_as_u32(token_type: SemanticTokenType) -> u32 {
-        match token_type.as_str() {
-            "namespace" => 0,
-            "type" => 1,
-            "class" => 2,
-            "enum" => 3,
-            "interface" => 4,
-            "struct" => 5,
-            "typeParameter" => 6,
-            "parameter" => 7,
-            "variable" => 8,
-            "property" => 9,
-            "enumMember" => 10,
-            "event" => 11,
-            "function" => 12,
-            "method" => 13,
-            "macro" => 14,
-            "keyword" => 15,
-            "modifier" => 16,
-            "comment" => 17,
-            "string" => 18,
-            "number" => 19,
-            "regexp" => 20,
-            "operator" => 21,
-            _ => 8,
-        }
+    fn get(&self, uri: &NormalizedUrl, result_id: &str) -> Option<&SemanticTokens> {
+        self.results
+            .get(uri)
+            .filter(|tokens| tokens.result_id.as_deref() == Some(result_id))
     }
+}
+
+/// A semantic token with the absolute position (0-origin)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AbsToken {
+    line: u32,
+    col: u32,
+    length: u32,
+    token_type: u32,
+    modifiers: u32,
+}
 
-    fn gen_token(&mut self, loc: Location, token_type: SemanticTokenType) -> SemanticToken {
-        let delta_line = loc.ln_begin().unwrap_or(1).saturating_sub(self.prev_line);
+/// Converts a column counted in characters into one counted in UTF-16 code units (the default encoding of LSP).
+fn utf16_col(line: &str, col: u32) -> u32 {
+    line.chars()
+        .take(col as usize)
+        .map(char::len_utf16)
+        .sum::<usize>() as u32
+}
+
+fn encode(tokens: &[AbsToken]) -> Vec<SemanticToken> {
+    let mut data = Vec::with_capacity(tokens.len());
+    let (mut prev_line, mut prev_col) = (0, 0);
+    for token in tokens {
+        let delta_line = token.line - prev_line;
         let delta_start = if delta_line == 0 {
-            loc.col_begin().unwrap_or(0).saturating_sub(self.prev_col)
+            token.col - prev_col
         } else {
-            loc.col_begin().unwrap_or(0)
+            token.col
         };
-        let token = SemanticToken {
+        data.push(SemanticToken {
             delta_line,
             delta_start,
-            length: loc.length().unwrap_or(1),
-            token_type: Self::token_type_as_u32(token_type),
-            token_modifiers_bitset: 0,
-        };
-        self.prev_line = loc.ln_begin().unwrap_or(self.prev_line);
-        self.prev_col = loc.col_begin().unwrap_or(self.prev_col);
-        token
+            length: token.length,
+            token_type: token.token_type,
+            token_modifiers_bitset: token.modifiers,
+        });
+        (prev_line, prev_col) = (token.line, token.col);
     }
+    data
+}
 
-    fn gen_from_typespec(&mut self, t_spec: TypeSpec) -> Vec<SemanticToken> {
-        match t_spec {
-            TypeSpec::PreDeclTy(predecl) => match predecl {
-                PreDeclTypeSpec::Simple(simple) => self.gen_from_simple_typespec(simple),
-                PreDeclTypeSpec::Attr { namespace, t } => {
-                    let mut tokens = self.gen_from_expr(*namespace);
-                    let ts = self.gen_from_simple_typespec(t);
-                    tokens.extend(ts);
-                    tokens
-                }
-                _ => vec![],
-            },
-            _ => vec![],
-        }
+/// Replaces the range which differs between `old` and `new`.
+fn token_edits(old: &[SemanticToken], new: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = old.iter().zip(new).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    if prefix == old.len() && prefix == new.len() {
+        return vec![];
     }
+    // `start` and `delete_count` are the indices of the flattened `u32` array
+    vec![SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: ((old.len() - prefix - suffix) * 5) as u32,
+        data: Some(new[prefix..new.len() - suffix].to_vec()),
+    }]
+}
 
-    fn gen_from_simple_typespec(&mut self, t_spec: SimpleTypeSpec) -> Vec<SemanticToken> {
-        let mut tokens = vec![];
-        let token = self.gen_token(t_spec.ident.name.loc(), SemanticTokenType::TYPE);
-        tokens.push(token);
-        tokens
+/// `{C}` (the type of `C`) -> `C`
+fn type_object(vi: &VarInfo) -> Option<&TypeObj> {
+    match vi.t.singleton_value()? {
+        TyParam::Value(ValueObj::Type(t_obj)) => Some(t_obj),
+        _ => None,
     }
+}
 
-    fn gen_from_expr(&mut self, expr: Expr) -> Vec<SemanticToken> {
-        match expr {
-            Expr::Literal(lit) => {
-                let typ = match lit.token.kind {
-                    TokenKind::StrLit => SemanticTokenType::STRING,
-                    TokenKind::NatLit | TokenKind::IntLit | TokenKind::RatioLit => {
-                        SemanticTokenType::NUMBER
-                    }
-                    _ => SemanticTokenType::VARIABLE,
+fn is_meta_type(t: &Type) -> bool {
+    matches!(t, Type::Type | Type::ClassType | Type::TraitType)
+}
+
+/// Collects the semantic information of the identifiers in the HIR.
+struct HIRSemanticState {
+    idents: Dict<(u32, u32), (u32, u32)>,
+}
+
+impl HIRSemanticState {
+    fn new() -> Self {
+        Self {
+            idents: Dict::new(),
+        }
+    }
+
+    fn token_type(vi: &VarInfo, name: &str, is_attr: bool) -> SemanticTokenType {
+        if let Some(t_obj) = type_object(vi) {
+            // `T = Int`, `{ .x = Int }`: `T` and `x` are variables holding a type
+            if &t_obj.typ().local_name()[..] != name.trim_end_matches('!') {
+                return if is_attr {
+                    SemanticTokenType::PROPERTY
+                } else {
+                    SemanticTokenType::TYPE
                 };
-                let token = self.gen_token(lit.loc(), typ);
-                vec![token]
             }
-            Expr::Def(def) => self.gen_from_def(def),
-            Expr::Lambda(lambda) => self.gen_from_block(Some(lambda.sig.params), lambda.body),
-            Expr::Methods(methods) => self.gen_from_methods(methods),
-            Expr::Accessor(acc) => self.gen_from_acc(acc),
-            Expr::Call(call) => self.gen_from_call(call),
-            Expr::BinOp(bin) => self.gen_from_bin(bin),
-            Expr::UnaryOp(unary) => self.gen_from_unary(unary),
-            _ => vec![],
+            let meta_t = match t_obj {
+                TypeObj::Builtin { meta_t, .. } => meta_t.clone(),
+                TypeObj::Generated(gen) => gen.meta_type(),
+            };
+            return match meta_t {
+                Type::ClassType => SemanticTokenType::CLASS,
+                Type::TraitType => SemanticTokenType::INTERFACE,
+                _ => SemanticTokenType::TYPE,
+            };
+        }
+        match &vi.kind {
+            VarKind::Parameter { .. } if is_meta_type(&vi.t) => SemanticTokenType::TYPE_PARAMETER,
+            VarKind::Parameter { .. } => SemanticTokenType::PARAMETER,
+            _ if vi.t.is_module() => SemanticTokenType::NAMESPACE,
+            _ if is_meta_type(&vi.t) => SemanticTokenType::TYPE,
+            _ if vi.t.is_subr() && (is_attr || vi.t.is_method()) => SemanticTokenType::METHOD,
+            _ if vi.t.is_subr() => SemanticTokenType::FUNCTION,
+            _ if is_attr => SemanticTokenType::PROPERTY,
+            _ => SemanticTokenType::VARIABLE,
         }
     }
 
-    fn gen_from_def(&mut self, def: Def) -> Vec<SemanticToken> {
-        let mut tokens = vec![];
-        let (_loc, name) = def
-            .sig
-            .ident()
-            .map(|id| (Some(id.name.loc()), id.name.to_string()))
-            .unwrap_or_else(|| (None, "_".to_string()));
-        let typ = match def.def_kind() {
-            DefKind::Class => SemanticTokenType::CLASS,
-            DefKind::Trait => SemanticTokenType::INTERFACE,
-            _ if def.is_subr() => SemanticTokenType::FUNCTION,
-            _ => SemanticTokenType::VARIABLE,
+    fn modifiers(vi: &VarInfo, name: &str, decl: bool) -> u32 {
+        let mut modifiers = 0;
+        if decl {
+            modifiers |= modifier_bit(SemanticTokenModifier::DECLARATION);
+        }
+        if vi.muty == Mutability::Const {
+            modifiers |= modifier_bit(SemanticTokenModifier::READONLY);
+        }
+        let in_std = |path: &Path| {
+            path.starts_with(erg_std_path())
+                || path.starts_with(erg_std_decl_path())
+                || path.starts_with(erg_pystd_path())
         };
-        self.push_current_namespace(name, typ);
-        if let Some(decos) = def.sig.decorators() {
-            for deco in decos.iter() {
-                tokens.extend(self.gen_from_expr(deco.expr().clone()));
-            }
+        if vi.kind == VarKind::Builtin || vi.def_loc.module.as_deref().is_some_and(in_std) {
+            modifiers |= modifier_bit(SemanticTokenModifier::DEFAULT_LIBRARY);
         }
-        // HACK: the cause is unknown, but pushing _loc will break the order of tokens
-        /*if let Some(loc) = loc {
-            tokens.push(self.gen_token(loc, typ));
+        if vi.t.is_procedure() {
+            modifiers |= modifier_bit(SemanticTokenModifier::new("procedure"));
+        } else if name.ends_with('!') || vi.t.is_mut_type() {
+            modifiers |= modifier_bit(SemanticTokenModifier::new("mutable"));
         }
-        if let Some(t_spec) = def.sig.t_spec() {
-            tokens.extend(self.gen_from_typespec(t_spec.clone()));
-        }*/
-        let params = def.sig.params();
-        tokens.extend(self.gen_from_block(params, def.body.block));
-        tokens
+        if vi.vis.is_public() {
+            modifiers |= modifier_bit(SemanticTokenModifier::new("public"));
+        }
+        modifiers
     }
 
-    fn gen_from_methods(&mut self, methods: Methods) -> Vec<SemanticToken> {
-        let mut tokens = vec![];
-        tokens.extend(self.gen_from_typespec(methods.class));
-        for attr in methods.attrs.into_iter() {
-            #[allow(clippy::single_match)]
-            match attr {
-                ClassAttr::Def(def) => tokens.extend(self.gen_from_def(def)),
-                _ => {}
-            }
+    fn add(&mut self, token: &Token, vi: &VarInfo, is_attr: bool, decl: bool) {
+        if vi.t == Type::Failure {
+            return;
         }
-        tokens
+        let token_type = type_index(Self::token_type(vi, &token.content, is_attr));
+        let modifiers = Self::modifiers(vi, &token.content, decl);
+        self.idents
+            .insert((token.lineno, token.col_begin), (token_type, modifiers));
     }
 
-    fn gen_from_acc(&mut self, acc: Accessor) -> Vec<SemanticToken> {
+    fn add_ident(&mut self, ident: &Identifier, is_attr: bool, decl: bool) {
+        self.add(ident.raw.name.token(), &ident.vi, is_attr, decl);
+    }
+
+    fn visit_acc(&mut self, acc: &Accessor) {
         match acc {
-            Accessor::Ident(ident) => {
-                let typ = self.get_variable_type(ident.inspect());
-                vec![self.gen_token(ident.name.loc(), typ)]
-            }
+            Accessor::Ident(ident) => self.add_ident(ident, false, false),
             Accessor::Attr(attr) => {
-                let mut tokens = self.gen_from_expr(*attr.obj);
-                tokens.push(self.gen_token(attr.ident.name.loc(), SemanticTokenType::PROPERTY));
-                tokens
+                self.visit_expr(&attr.obj);
+                self.add_ident(&attr.ident, true, false);
             }
-            _ => vec![],
         }
     }
 
-    fn gen_from_call(&mut self, call: Call) -> Vec<SemanticToken> {
-        let mut tokens = self.gen_from_expr(*call.obj);
-        tokens.extend(self.gen_from_args(call.args));
-        tokens
-    }
-
-    fn gen_from_args(&mut self, args: Args) -> Vec<SemanticToken> {
-        let mut tokens = vec![];
-        let (pos_args, var_args, kw_args, ..) = args.deconstruct();
-        for arg in pos_args {
-            tokens.extend(self.gen_from_expr(arg.expr));
-        }
-        if let Some(var_args) = var_args {
-            tokens.extend(self.gen_from_expr(var_args.expr));
+    fn visit_params(&mut self, params: &Params) {
+        let non_defaults = params
+            .non_defaults
+            .iter()
+            .chain(params.var_params.as_deref())
+            .chain(params.defaults.iter().map(|param| &param.sig))
+            .chain(params.kw_var_params.as_deref());
+        for param in non_defaults {
+            match &param.raw.pat {
+                ParamPattern::VarName(name)
+                | ParamPattern::Ref(name)
+                | ParamPattern::RefMut(name) => self.add(name.token(), &param.vi, false, true),
+                _ => {}
+            }
+            if let Some(t_spec) = &param.t_spec_as_expr {
+                self.visit_expr(t_spec);
+            }
         }
-        for arg in kw_args {
-            tokens.extend(self.gen_from_expr(arg.expr));
+        for param in params.defaults.iter() {
+            self.visit_expr(&param.default_val);
         }
-        tokens
     }
 
-    fn gen_from_bin(&mut self, bin: BinOp) -> Vec<SemanticToken> {
-        let mut args = bin.args.into_iter();
-        let mut tokens = self.gen_from_expr(*args.next().unwrap());
-        tokens.push(self.gen_token(bin.op.loc(), SemanticTokenType::OPERATOR));
-        tokens.extend(self.gen_from_expr(*args.next().unwrap()));
-        tokens
+    fn visit_def(&mut self, def: &Def, is_attr: bool) {
+        self.add_ident(def.sig.ident(), is_attr, true);
+        if let Signature::Subr(subr) = &def.sig {
+            self.visit_params(&subr.params);
+        }
+        for expr in def.body.block.iter() {
+            self.visit_expr(expr);
+        }
     }
 
-    fn gen_from_unary(&mut self, unary: UnaryOp) -> Vec<SemanticToken> {
-        let mut tokens = vec![self.gen_token(unary.op.loc(), SemanticTokenType::OPERATOR)];
-        let mut args = unary.args.into_iter();
-        tokens.extend(self.gen_from_expr(*args.next().unwrap()));
-        tokens
+    fn visit_args(&mut self, args: &Args) {
+        for arg in args.pos_args.iter() {
+            self.visit_expr(&arg.expr);
+        }
+        if let Some(var) = &args.var_args {
+            self.visit_expr(&var.expr);
+        }
+        for arg in args.kw_args.iter() {
+            self.visit_expr(&arg.expr);
+        }
+        if let Some(kw_var) = &args.kw_var_args {
+            self.visit_expr(&kw_var.expr);
+        }
     }
 
-    fn gen_from_block(&mut self, params: Option<Params>, block: Block) -> Vec<SemanticToken> {
-        self.namespaces.push(Dict::new());
-        let mut tokens = vec![];
-        if let Some(params) = params {
-            let (nd_params, var_params, d_params, ..) = params.deconstruct();
-            for param in nd_params.into_iter() {
-                let typ = SemanticTokenType::PARAMETER;
-                tokens.push(self.gen_token(param.loc(), typ));
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Accessor(acc) | Expr::Import(acc) => self.visit_acc(acc),
+            Expr::Call(call) => {
+                self.visit_expr(&call.obj);
+                if let Some(attr) = &call.attr_name {
+                    self.add_ident(attr, true, false);
+                }
+                self.visit_args(&call.args);
             }
-            if let Some(var_param) = var_params {
-                let typ = SemanticTokenType::PARAMETER;
-                tokens.push(self.gen_token(var_param.loc(), typ));
+            Expr::Def(def) => self.visit_def(def, false),
+            Expr::ClassDef(class_def) => {
+                self.add_ident(class_def.sig.ident(), false, true);
+                for expr in sub_exprs(expr) {
+                    self.visit_expr(expr);
+                }
             }
-            for param in d_params.into_iter() {
-                let typ = SemanticTokenType::PARAMETER;
-                tokens.push(self.gen_token(param.loc(), typ));
+            Expr::PatchDef(patch_def) => {
+                self.add_ident(patch_def.sig.ident(), false, true);
+                for expr in sub_exprs(expr) {
+                    self.visit_expr(expr);
+                }
+            }
+            Expr::Lambda(lambda) => {
+                self.visit_params(&lambda.params);
+                for expr in lambda.body.iter() {
+                    self.visit_expr(expr);
+                }
+            }
+            Expr::Record(record) => {
+                for field in record.attrs.iter() {
+                    self.visit_def(field, true);
+                }
+            }
+            Expr::TypeAsc(type_asc) => {
+                self.visit_expr(&type_asc.expr);
+                self.visit_expr(&type_asc.spec.t_spec_as_expr);
+            }
+            Expr::ReDef(redef) => {
+                self.visit_acc(&redef.attr);
+                for expr in redef.block.iter() {
+                    self.visit_expr(expr);
+                }
+            }
+            _ => {
+                for expr in sub_exprs(expr) {
+                    self.visit_expr(expr);
+                }
             }
         }
-        for expr in block.into_iter() {
-            tokens.extend(self.gen_from_expr(expr));
+    }
+
+    /// Classifies the tokens. The identifiers are classified by the HIR, others are classified by the token kinds.
+    /// Identifiers not in the HIR (e.g. undefined variables) are not reported.
+    fn enumerate_tokens(mut self, hir: Option<&HIR>, tokens: &TokenStream) -> Vec<AbsToken> {
+        if let Some(hir) = hir {
+            for expr in hir.module.iter() {
+                self.visit_expr(expr);
+            }
         }
-        self.namespaces.pop();
-        tokens
+        let mut result = vec![];
+        for token in tokens.iter() {
+            let token_type = match token.category() {
+                TokenCategory::Symbol => {
+                    if let Some((token_type, modifiers)) =
+                        self.idents.get(&(token.lineno, token.col_begin))
+                    {
+                        result.push(AbsToken {
+                            line: token.lineno.saturating_sub(1),
+                            col: token.col_begin,
+                            length: token.content.encode_utf16().count() as u32,
+                            token_type: *token_type,
+                            modifiers: *modifiers,
+                        });
+                    }
+                    continue;
+                }
+                TokenCategory::Literal
+                | TokenCategory::StrInterpLeft
+                | TokenCategory::StrInterpMid
+                | TokenCategory::StrInterpRight => match token.kind {
+                    TokenKind::NatLit | TokenKind::IntLit | TokenKind::RatioLit => {
+                        SemanticTokenType::NUMBER
+                    }
+                    TokenKind::DocComment => SemanticTokenType::COMMENT,
+                    TokenKind::StrLit
+                    | TokenKind::BytesLit
+                    | TokenKind::StrInterpLeft
+                    | TokenKind::StrInterpMid
+                    | TokenKind::StrInterpRight => SemanticTokenType::STRING,
+                    _ => continue,
+                },
+                TokenCategory::BinOp | TokenCategory::UnaryOp => SemanticTokenType::OPERATOR,
+                _ => continue,
+            };
+            // multi-line tokens (e.g. doc comments) are split into lines
+            for (i, line) in token.content.lines().enumerate() {
+                result.push(AbsToken {
+                    line: token.lineno.saturating_sub(1) + i as u32,
+                    col: if i == 0 { token.col_begin } else { 0 },
+                    length: line.encode_utf16().count() as u32,
+                    token_type: type_index(token_type.clone()),
+                    modifiers: 0,
+                });
+            }
+        }
+        result
     }
 }
 
 impl<Checker: BuildRunnable> Server<Checker> {
+    fn get_abs_tokens(&self, uri: &NormalizedUrl) -> Option<Vec<AbsToken>> {
+        // the token stream is not available if the code has a lexical error
+        let tokens = self.file_cache.get_token_stream(uri)?;
+        let hir = self
+            .artifacts
+            .get(uri)
+            .and_then(|artifact| artifact.object.as_ref());
+        let code = &self.file_cache.get(uri).ok()?.code;
+        let lines = code.lines().collect::<Vec<_>>();
+        let mut tokens = HIRSemanticState::new().enumerate_tokens(hir, tokens);
+        // the columns of the tokens are counted in characters
+        for token in tokens.iter_mut() {
+            if let Some(line) = lines.get(token.line as usize) {
+                token.col = utf16_col(line, token.col);
+            }
+        }
+        Some(tokens)
+    }
+
     pub(crate) fn get_semantic_tokens_full(&mut self, msg: &Value) -> ELSResult<()> {
         send_log(format!("full semantic tokens request: {msg}"))?;
         let params = SemanticTokensParams::deserialize(&msg["params"])?;
         let uri = NormalizedUrl::new(params.text_document.uri);
-        let path = util::uri_to_path(&uri);
-        let src = self.file_cache.get_code(&uri)?;
-        let mut builder = ASTBuilder::new(self.cfg.inherit(path));
-        let result = match builder.build_without_desugaring(src.to_string()) {
-            Ok(ast) => {
-                let mut state = ASTSemanticState::new();
-                let tokens = state.enumerate_tokens(ast);
-                json!(tokens)
+        let result = match self.get_abs_tokens(&uri) {
+            Some(tokens) => json!(self.sema_cache.register(uri, encode(&tokens))),
+            None => json!(null),
+        };
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn get_semantic_tokens_range(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("range semantic tokens request: {msg}"))?;
+        let params = SemanticTokensRangeParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let range = params.range;
+        let result = match self.get_abs_tokens(&uri) {
+            Some(tokens) => {
+                let tokens = tokens
+                    .into_iter()
+                    .filter(|token| {
+                        let pos = Position::new(token.line, token.col);
+                        range.start <= pos && pos < range.end
+                    })
+                    .collect::<Vec<_>>();
+                json!(SemanticTokens {
+                    result_id: None,
+                    data: encode(&tokens),
+                })
+            }
+            None => json!(null),
+        };
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    /// If the previous result is not cached (e.g. the server has been restarted), all the tokens are sent.
+    pub(crate) fn get_semantic_tokens_delta(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log(format!("delta semantic tokens request: {msg}"))?;
+        let params = SemanticTokensDeltaParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let result = match self.get_abs_tokens(&uri) {
+            Some(tokens) => {
+                let data = encode(&tokens);
+                let edits = self
+                    .sema_cache
+                    .get(&uri, &params.previous_result_id)
+                    .map(|prev| token_edits(&prev.data, &data));
+                let tokens = self.sema_cache.register(uri, data);
+                if let Some(edits) = edits {
+                    json!(SemanticTokensDelta {
+                        result_id: tokens.result_id,
+                        edits,
+                    })
+                } else {
+                    json!(tokens)
+                }
             }
-            Err(_) => json!(null),
+            None => json!(null),
         };
         send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
     }
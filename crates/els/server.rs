@@ -22,18 +22,22 @@ use erg_compiler::module::{SharedCompilerResource, SharedModuleIndex};
 use erg_compiler::ty::HasType;
 
 use lsp_types::{
-    ClientCapabilities, CodeActionKind, CodeActionOptions, CodeActionProviderCapability,
-    CodeLensOptions, CompletionOptions, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
-    ExecuteCommandOptions, HoverProviderCapability, InitializeResult, OneOf, Position,
-    SemanticTokenType, SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
+    CallHierarchyServerCapability, ClientCapabilities, CodeActionKind, CodeActionOptions,
+    CodeActionProviderCapability, CodeLensOptions, CompletionOptions, DeclarationCapability,
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, ExecuteCommandOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, ImplementationProviderCapability,
+    InitializeResult, OneOf, Position, SelectionRangeProviderCapability,
     SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelpOptions,
-    WorkDoneProgressOptions,
+    TypeDefinitionProviderCapability, WorkDoneProgressOptions,
 };
 
 use crate::completion::CompletionCache;
+use crate::config::ELSConfig;
 use crate::file_cache::FileCache;
 use crate::hir_visitor::HIRVisitor;
 use crate::message::{ErrorMessage, LogMessage, ShowMessage};
+use crate::scheduler::{spawn_reader, CancelledRequests, CheckKind};
+use crate::semantic::{semantic_tokens_options, SemanticTokensCache};
 use crate::util::{self, NormalizedUrl};
 
 pub type ELSResult<T> = Result<T, Box<dyn std::error::Error>>;
@@ -49,19 +53,30 @@ pub enum DefaultFeatures {
     Diagnostics,
     FindReferences,
     GotoDefinition,
+    GotoDeclaration,
+    GotoTypeDefinition,
+    GotoImplementation,
     Hover,
     InlayHint,
     Rename,
     SemanticTokens,
     SignatureHelp,
+    DocumentSymbol,
+    WorkspaceSymbol,
+    CallHierarchy,
+    TypeHierarchy,
+    FoldingRange,
+    SelectionRange,
+    DocumentHighlight,
     /* ELS specific features */
     SmartCompletion,
     DeepCompletion,
 }
 
-impl From<&str> for DefaultFeatures {
-    fn from(s: &str) -> Self {
-        match s {
+impl TryFrom<&str> for DefaultFeatures {
+    type Error = String;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let feature = match s {
             "codeaction" | "codeAction" | "code-action" => DefaultFeatures::CodeAction,
             "codelens" | "codeLens" | "code-lens" => DefaultFeatures::CodeLens,
             "completion" => DefaultFeatures::Completion,
@@ -75,18 +90,43 @@ impl From<&str> for DefaultFeatures {
             "findreferences" | "findReferences" | "find-references" => {
                 DefaultFeatures::FindReferences
             }
-            "gotodefinition" | "gotoDefinition" | "goto-completion" => {
+            "gotodefinition" | "gotoDefinition" | "goto-definition" => {
                 DefaultFeatures::GotoDefinition
             }
+            "gotodeclaration" | "gotoDeclaration" | "goto-declaration" => {
+                DefaultFeatures::GotoDeclaration
+            }
+            "gototypedefinition" | "gotoTypeDefinition" | "goto-type-definition" => {
+                DefaultFeatures::GotoTypeDefinition
+            }
+            "gotoimplementation" | "gotoImplementation" | "goto-implementation" => {
+                DefaultFeatures::GotoImplementation
+            }
             "signaturehelp" | "signatureHelp" | "signature-help" => DefaultFeatures::SignatureHelp,
+            "documentsymbol" | "documentSymbol" | "document-symbol" => {
+                DefaultFeatures::DocumentSymbol
+            }
+            "workspacesymbol" | "workspaceSymbol" | "workspace-symbol" => {
+                DefaultFeatures::WorkspaceSymbol
+            }
+            "callhierarchy" | "callHierarchy" | "call-hierarchy" => DefaultFeatures::CallHierarchy,
+            "typehierarchy" | "typeHierarchy" | "type-hierarchy" => DefaultFeatures::TypeHierarchy,
+            "foldingrange" | "foldingRange" | "folding-range" => DefaultFeatures::FoldingRange,
+            "selectionrange" | "selectionRange" | "selection-range" => {
+                DefaultFeatures::SelectionRange
+            }
+            "documenthighlight" | "documentHighlight" | "document-highlight" => {
+                DefaultFeatures::DocumentHighlight
+            }
             "smartcompletion" | "smartCompletion" | "smart-completion" => {
                 DefaultFeatures::SmartCompletion
             }
             "deepcompletion" | "deepCompletion" | "deep-completion" => {
                 DefaultFeatures::DeepCompletion
             }
-            _ => panic!("unknown feature: {s}"),
-        }
+            _ => return Err(format!("unknown feature: {s}")),
+        };
+        Ok(feature)
     }
 }
 
@@ -95,11 +135,12 @@ pub enum OptionalFeatures {
     CheckOnType,
 }
 
-impl From<&str> for OptionalFeatures {
-    fn from(s: &str) -> Self {
+impl TryFrom<&str> for OptionalFeatures {
+    type Error = String;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
         match s {
-            "checkontype" | "checkOnType" | "check-on-type" => OptionalFeatures::CheckOnType,
-            _ => panic!("unknown feature: {s}"),
+            "checkontype" | "checkOnType" | "check-on-type" => Ok(OptionalFeatures::CheckOnType),
+            _ => Err(format!("unknown feature: {s}")),
         }
     }
 }
@@ -146,6 +187,73 @@ fn read_exact(len: usize) -> io::Result<Vec<u8>> {
     })
 }
 
+/// Copied and modified from RLS, https://github.com/rust-lang/rls/blob/master/rls/src/server/io.rs
+pub(crate) fn read_message() -> Result<Value, io::Error> {
+    // Read in the "Content-Length: xx" part.
+    let mut size: Option<usize> = None;
+    loop {
+        let buffer = read_line()?;
+
+        // End of input.
+        if buffer.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "EOF encountered in the middle of reading LSP headers",
+            ));
+        }
+
+        // Header section is finished, break from the loop.
+        if buffer == "\r\n" {
+            break;
+        }
+
+        let res: Vec<&str> = buffer.split(' ').collect();
+
+        // Make sure header is valid.
+        if res.len() != 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Header '{buffer}' is malformed"),
+            ));
+        }
+        let header_name = res[0].to_lowercase();
+        let header_value = res[1].trim();
+
+        match header_name.as_ref() {
+            "content-length:" => {
+                size = Some(header_value.parse::<usize>().map_err(|_e| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Couldn't read size")
+                })?);
+            }
+            "content-type:" => {
+                if header_value != "utf8" && header_value != "utf-8" {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Content type '{header_value}' is invalid"),
+                    ));
+                }
+            }
+            // Ignore unknown headers (specification doesn't say what to do in this case).
+            _ => (),
+        }
+    }
+    let size = match size {
+        Some(size) => size,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Message is missing 'content-length' header",
+            ));
+        }
+    };
+
+    let content = read_exact(size)?;
+
+    let s =
+        String::from_utf8(content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Value::from_str(&s)?)
+}
+
 pub(crate) fn send<T: ?Sized + Serialize>(message: &T) -> ELSResult<()> {
     send_stdout(message)
 }
@@ -159,6 +267,10 @@ pub(crate) fn send_info<S: Into<String>>(msg: S) -> ELSResult<()> {
     send(&ShowMessage::info(msg))
 }
 
+pub(crate) fn send_warn_info<S: Into<String>>(msg: S) -> ELSResult<()> {
+    send(&ShowMessage::warn(msg))
+}
+
 pub(crate) fn send_error_info<S: Into<String>>(msg: S) -> ELSResult<()> {
     send(&ShowMessage::error(msg))
 }
@@ -183,17 +295,25 @@ pub struct Server<Checker: BuildRunnable = HIRBuilder> {
     pub(crate) client_capas: ClientCapabilities,
     pub(crate) disabled_features: Vec<DefaultFeatures>,
     pub(crate) opt_features: Vec<OptionalFeatures>,
+    pub(crate) config: ELSConfig,
+    /// The id of the last `workspace/configuration` request sent by ELS
+    pub(crate) config_request_id: i64,
     pub(crate) file_cache: FileCache,
     pub(crate) comp_cache: CompletionCache,
+    pub(crate) sema_cache: SemanticTokensCache,
     pub(crate) modules: Dict<NormalizedUrl, ModuleContext>,
     pub(crate) artifacts: Dict<NormalizedUrl, IncompleteArtifact>,
     pub(crate) current_sig: Option<Expr>,
+    pub(crate) pending_checks: Vec<(NormalizedUrl, CheckKind)>,
+    pub(crate) cancelled: CancelledRequests,
     pub(crate) _checker: std::marker::PhantomData<Checker>,
 }
 
 impl<Checker: BuildRunnable> Server<Checker> {
     pub fn new(cfg: ErgConfig) -> Self {
         Self {
+            config: ELSConfig::from(&cfg),
+            config_request_id: 0,
             cfg,
             home: normalize_path(std::env::current_dir().unwrap()),
             erg_path: erg_path(), // already normalized
@@ -201,18 +321,33 @@ impl<Checker: BuildRunnable> Server<Checker> {
             disabled_features: vec![],
             opt_features: vec![],
             comp_cache: CompletionCache::new(),
+            sema_cache: SemanticTokensCache::new(),
             file_cache: FileCache::new(),
             modules: Dict::new(),
             artifacts: Dict::new(),
             current_sig: None,
+            pending_checks: vec![],
+            cancelled: CancelledRequests::default(),
             _checker: std::marker::PhantomData,
         }
     }
 
     pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let receiver = spawn_reader(self.cancelled.clone());
         loop {
-            let msg = self.read_message()?;
-            self.dispatch(msg)?;
+            let mut msgs = vec![];
+            // wait for a message only if there is nothing to check
+            if self.pending_checks.is_empty() {
+                msgs.push(receiver.recv()??);
+            }
+            while let Ok(msg) = receiver.try_recv() {
+                msgs.push(msg?);
+            }
+            if msgs.is_empty() {
+                self.run_pending_check()?;
+            } else {
+                self.handle_messages(msgs)?;
+            }
         }
         // Ok(())
     }
@@ -233,17 +368,9 @@ impl<Checker: BuildRunnable> Server<Checker> {
             self.client_capas = ClientCapabilities::deserialize(&msg["params"]["capabilities"])?;
             // send_log(format!("set client capabilities: {:?}", self.client_capas))?;
         }
-        let mut args = self.cfg.runtime_args.iter();
-        while let Some(&arg) = args.next() {
-            if arg == "--disable" {
-                if let Some(&feature) = args.next() {
-                    self.disabled_features.push(DefaultFeatures::from(feature));
-                }
-            } else if arg == "--enable" {
-                if let Some(&feature) = args.next() {
-                    self.opt_features.push(OptionalFeatures::from(feature));
-                }
-            }
+        self.apply_runtime_args()?;
+        if let Some(options) = msg["params"].get("initializationOptions") {
+            self.apply_settings(options)?;
         }
         let mut result = InitializeResult::default();
         result.capabilities = ServerCapabilities::default();
@@ -256,6 +383,21 @@ impl<Checker: BuildRunnable> Server<Checker> {
         result.capabilities.rename_provider = Some(OneOf::Left(true));
         result.capabilities.references_provider = Some(OneOf::Left(true));
         result.capabilities.definition_provider = Some(OneOf::Left(true));
+        result.capabilities.declaration_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::GotoDeclaration)
+            .not()
+            .then_some(DeclarationCapability::Simple(true));
+        result.capabilities.type_definition_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::GotoTypeDefinition)
+            .not()
+            .then_some(TypeDefinitionProviderCapability::Simple(true));
+        result.capabilities.implementation_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::GotoImplementation)
+            .not()
+            .then_some(ImplementationProviderCapability::Simple(true));
         result.capabilities.hover_provider = self
             .disabled_features
             .contains(&DefaultFeatures::Hover)
@@ -266,34 +408,13 @@ impl<Checker: BuildRunnable> Server<Checker> {
             .contains(&DefaultFeatures::InlayHint)
             .not()
             .then_some(OneOf::Left(true));
-        let mut sema_options = SemanticTokensOptions::default();
-        sema_options.range = Some(false);
-        sema_options.full = Some(SemanticTokensFullOptions::Bool(true));
-        sema_options.legend = SemanticTokensLegend {
-            token_types: vec![
-                SemanticTokenType::NAMESPACE,
-                SemanticTokenType::TYPE,
-                SemanticTokenType::CLASS,
-                SemanticTokenType::INTERFACE,
-                SemanticTokenType::TYPE_PARAMETER,
-                SemanticTokenType::PARAMETER,
-                SemanticTokenType::VARIABLE,
-                SemanticTokenType::PROPERTY,
-                SemanticTokenType::FUNCTION,
-                SemanticTokenType::METHOD,
-                SemanticTokenType::STRING,
-                SemanticTokenType::NUMBER,
-                SemanticTokenType::OPERATOR,
-            ],
-            token_modifiers: vec![],
-        };
         result.capabilities.semantic_tokens_provider = self
             .disabled_features
             .contains(&DefaultFeatures::SemanticTokens)
             .not()
-            .then_some(SemanticTokensServerCapabilities::SemanticTokensOptions(
-                sema_options,
-            ));
+            .then(|| {
+                SemanticTokensServerCapabilities::SemanticTokensOptions(semantic_tokens_options())
+            });
         result.capabilities.code_action_provider = if self
             .disabled_features
             .contains(&DefaultFeatures::CodeAction)
@@ -325,6 +446,44 @@ impl<Checker: BuildRunnable> Server<Checker> {
         result.capabilities.code_lens_provider = Some(CodeLensOptions {
             resolve_provider: Some(false),
         });
+        result.capabilities.document_symbol_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::DocumentSymbol)
+            .not()
+            .then_some(OneOf::Left(true));
+        result.capabilities.workspace_symbol_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::WorkspaceSymbol)
+            .not()
+            .then_some(OneOf::Left(true));
+        result.capabilities.call_hierarchy_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::CallHierarchy)
+            .not()
+            .then_some(CallHierarchyServerCapability::Simple(true));
+        result.capabilities.folding_range_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::FoldingRange)
+            .not()
+            .then_some(FoldingRangeProviderCapability::Simple(true));
+        result.capabilities.selection_range_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::SelectionRange)
+            .not()
+            .then_some(SelectionRangeProviderCapability::Simple(true));
+        result.capabilities.document_highlight_provider = self
+            .disabled_features
+            .contains(&DefaultFeatures::DocumentHighlight)
+            .not()
+            .then_some(OneOf::Left(true));
+        let mut result = serde_json::to_value(result)?;
+        // `typeHierarchyProvider` is not supported by lsp-types yet
+        if !self
+            .disabled_features
+            .contains(&DefaultFeatures::TypeHierarchy)
+        {
+            result["capabilities"]["typeHierarchyProvider"] = json!(true);
+        }
         send(&json!({
             "jsonrpc": "2.0",
             "id": id,
@@ -346,79 +505,14 @@ impl<Checker: BuildRunnable> Server<Checker> {
         }))
     }
 
-    /// Copied and modified from RLS, https://github.com/rust-lang/rls/blob/master/rls/src/server/io.rs
-    fn read_message(&self) -> Result<Value, io::Error> {
-        // Read in the "Content-Length: xx" part.
-        let mut size: Option<usize> = None;
-        loop {
-            let buffer = read_line()?;
-
-            // End of input.
-            if buffer.is_empty() {
-                return Err(io::Error::new(
-                    io::ErrorKind::UnexpectedEof,
-                    "EOF encountered in the middle of reading LSP headers",
-                ));
-            }
-
-            // Header section is finished, break from the loop.
-            if buffer == "\r\n" {
-                break;
-            }
-
-            let res: Vec<&str> = buffer.split(' ').collect();
-
-            // Make sure header is valid.
-            if res.len() != 2 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Header '{buffer}' is malformed"),
-                ));
-            }
-            let header_name = res[0].to_lowercase();
-            let header_value = res[1].trim();
-
-            match header_name.as_ref() {
-                "content-length:" => {
-                    size = Some(header_value.parse::<usize>().map_err(|_e| {
-                        io::Error::new(io::ErrorKind::InvalidData, "Couldn't read size")
-                    })?);
-                }
-                "content-type:" => {
-                    if header_value != "utf8" && header_value != "utf-8" {
-                        return Err(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            format!("Content type '{header_value}' is invalid"),
-                        ));
-                    }
-                }
-                // Ignore unknown headers (specification doesn't say what to do in this case).
-                _ => (),
-            }
-        }
-        let size = match size {
-            Some(size) => size,
-            None => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Message is missing 'content-length' header",
-                ));
-            }
-        };
-
-        let content = read_exact(size)?;
-
-        let s = String::from_utf8(content)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        Ok(Value::from_str(&s)?)
-    }
-
-    fn dispatch(&mut self, msg: Value) -> ELSResult<()> {
+    pub(crate) fn dispatch(&mut self, msg: Value) -> ELSResult<()> {
         match (
             msg.get("id").and_then(|i| i.as_i64()),
             msg.get("method").and_then(|m| m.as_str()),
         ) {
             (Some(id), Some(method)) => self.handle_request(&msg, id, method),
+            (Some(id), None) if id == self.config_request_id => self.receive_configuration(&msg),
+            // including the responses to the outdated `workspace/configuration` requests
             (Some(_id), None) => {
                 // ignore at this time
                 Ok(())
@@ -435,14 +529,30 @@ impl<Checker: BuildRunnable> Server<Checker> {
             "textDocument/completion" => self.show_completion(msg),
             "completionItem/resolve" => self.resolve_completion(msg),
             "textDocument/definition" => self.show_definition(msg),
+            "textDocument/declaration" => self.show_declaration(msg),
+            "textDocument/typeDefinition" => self.show_type_definition(msg),
+            "textDocument/implementation" => self.show_implementation(msg),
             "textDocument/hover" => self.show_hover(msg),
             "textDocument/rename" => self.rename(msg),
             "textDocument/references" => self.show_references(msg),
             "textDocument/semanticTokens/full" => self.get_semantic_tokens_full(msg),
+            "textDocument/semanticTokens/full/delta" => self.get_semantic_tokens_delta(msg),
+            "textDocument/semanticTokens/range" => self.get_semantic_tokens_range(msg),
             "textDocument/inlayHint" => self.get_inlay_hint(msg),
             "textDocument/codeAction" => self.send_code_action(msg),
             "textDocument/signatureHelp" => self.show_signature_help(msg),
             "textDocument/codeLens" => self.show_code_lens(msg),
+            "textDocument/documentSymbol" => self.show_document_symbols(msg),
+            "workspace/symbol" => self.show_workspace_symbols(msg),
+            "textDocument/prepareCallHierarchy" => self.prepare_call_hierarchy(msg),
+            "callHierarchy/incomingCalls" => self.show_incoming_calls(msg),
+            "callHierarchy/outgoingCalls" => self.show_outgoing_calls(msg),
+            "textDocument/prepareTypeHierarchy" => self.prepare_type_hierarchy(msg),
+            "typeHierarchy/supertypes" => self.show_supertypes(msg),
+            "typeHierarchy/subtypes" => self.show_subtypes(msg),
+            "textDocument/foldingRange" => self.show_folding_ranges(msg),
+            "textDocument/selectionRange" => self.show_selection_ranges(msg),
+            "textDocument/documentHighlight" => self.show_document_highlights(msg),
             "workspace/willRenameFiles" => self.rename_files(msg),
             "workspace/executeCommand" => self.execute_command(msg),
             other => send_error(Some(id), -32600, format!("{other} is not supported")),
@@ -451,7 +561,11 @@ impl<Checker: BuildRunnable> Server<Checker> {
 
     fn handle_notification(&mut self, msg: &Value, method: &str) -> ELSResult<()> {
         match method {
-            "initialized" => send_log("successfully bound"),
+            "initialized" => {
+                send_log("successfully bound")?;
+                self.request_configuration()
+            }
+            "workspace/didChangeConfiguration" => self.change_configuration(msg),
             "exit" => self.exit(),
             "textDocument/didOpen" => {
                 let params = DidOpenTextDocumentParams::deserialize(msg["params"].clone())?;
@@ -459,23 +573,31 @@ impl<Checker: BuildRunnable> Server<Checker> {
                 send_log(format!("{method}: {uri}"))?;
                 let code = params.text_document.text;
                 let ver = params.text_document.version;
-                self.file_cache.update(&uri, code.clone(), Some(ver));
-                self.check_file(uri, code)
+                self.file_cache.update(&uri, code, Some(ver));
+                self.schedule_check(uri, CheckKind::Full);
+                Ok(())
             }
             "textDocument/didSave" => {
                 let uri =
                     NormalizedUrl::parse(msg["params"]["textDocument"]["uri"].as_str().unwrap())?;
                 send_log(format!("{method}: {uri}"))?;
-                let code = self.file_cache.get_code(&uri)?.to_string();
-                self.clear_cache(&uri);
-                self.check_file(uri, code)
+                self.schedule_check(uri, CheckKind::Clean);
+                Ok(())
             }
             "textDocument/didChange" => {
                 let params = DidChangeTextDocumentParams::deserialize(msg["params"].clone())?;
                 self.file_cache.incremental_update(params.clone());
                 if self.opt_features.contains(&OptionalFeatures::CheckOnType) {
                     let uri = NormalizedUrl::new(params.text_document.uri);
-                    self.quick_check_file(uri)?;
+                    self.schedule_check(uri, CheckKind::Quick);
+                }
+                Ok(())
+            }
+            // the request has already been processed
+            // the cancelled request has already been handled (the notification is received after it)
+            "$/cancelRequest" => {
+                if let Some(id) = msg["params"]["id"].as_i64() {
+                    self.cancelled.borrow_mut().remove(&id);
                 }
                 Ok(())
             }
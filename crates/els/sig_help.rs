@@ -6,7 +6,7 @@ use erg_common::traits::{DequeStream, Locational, NoTypeDisplay};
 use erg_compiler::artifact::BuildRunnable;
 use erg_compiler::erg_parser::token::{Token, TokenKind};
 use erg_compiler::hir::Expr;
-use erg_compiler::ty::{HasType, ParamTy};
+use erg_compiler::ty::{HasType, ParamTy, Type};
 
 use lsp_types::{
     ParameterInformation, ParameterLabel, Position, SignatureHelp, SignatureHelpContext,
@@ -38,6 +38,37 @@ fn get_end(start: usize, pt: &ParamTy) -> usize {
     start + pt.name().map(|n| n.len() + 2).unwrap_or(0) + pt.typ().to_string().len()
 }
 
+fn make_sig_info(sig: &str, sig_t: &Type, nth: u32) -> Option<SignatureInformation> {
+    let mut parameters = vec![];
+    let label = format!("{sig}: {sig_t}");
+    let mut end = sig.len() + 1; // +1: (
+    for nd_param in sig_t.non_default_params()? {
+        let start = end + 2;
+        end = get_end(start, nd_param);
+        let param_info = ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
+            documentation: None, //Some(Documentation::String(nd_param.typ().to_string())),
+        };
+        parameters.push(param_info);
+    }
+    if let Some(var_params) = sig_t.var_params() {
+        let start = end + 2;
+        end = get_end(start, var_params);
+        let param_info = ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
+            documentation: None, //Some(Documentation::String(var_params.typ().to_string())),
+        };
+        parameters.push(param_info);
+    }
+    let nth = (parameters.len() as u32).saturating_sub(1).min(nth);
+    Some(SignatureInformation {
+        label,
+        documentation: None,
+        parameters: Some(parameters),
+        active_parameter: Some(nth),
+    })
+}
+
 impl<Checker: BuildRunnable> Server<Checker> {
     pub(crate) fn show_signature_help(&mut self, msg: &Value) -> ELSResult<()> {
         send_log(format!("signature help requested: {msg}"))?;
@@ -155,37 +186,15 @@ impl<Checker: BuildRunnable> Server<Checker> {
         nth: u32,
     ) -> Option<SignatureHelp> {
         let sig_t = sig.ref_t();
-        let mut parameters = vec![];
         let sig = sig.to_string_notype();
-        let label = format!("{sig}: {sig_t}");
-        let mut end = sig.len() + 1; // +1: (
-        for nd_param in sig_t.non_default_params()? {
-            let start = end + 2;
-            end = get_end(start, nd_param);
-            let param_info = ParameterInformation {
-                label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
-                documentation: None, //Some(Documentation::String(nd_param.typ().to_string())),
-            };
-            parameters.push(param_info);
-        }
-        if let Some(var_params) = sig_t.var_params() {
-            let start = end + 2;
-            end = get_end(start, var_params);
-            let param_info = ParameterInformation {
-                label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
-                documentation: None, //Some(Documentation::String(var_params.typ().to_string())),
-            };
-            parameters.push(param_info);
-        }
-        let nth = (parameters.len() as u32 - 1).min(nth);
-        let info = SignatureInformation {
-            label,
-            documentation: None,
-            parameters: Some(parameters),
-            active_parameter: Some(nth),
-        };
+        // an overloaded subroutine has a signature for each overload
+        let signatures = sig_t
+            .intersection_types()
+            .iter()
+            .map(|sig_t| make_sig_info(&sig, sig_t, nth))
+            .collect::<Option<Vec<_>>>()?;
         Some(SignatureHelp {
-            signatures: vec![info],
+            signatures,
             active_parameter: None,
             active_signature: None,
         })
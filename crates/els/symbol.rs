@@ -0,0 +1,262 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+
+use erg_common::dict::Dict;
+use erg_common::traits::Locational;
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::hir::{Accessor, Def, Expr, Identifier, Signature, HIR};
+use erg_compiler::ty::HasType;
+
+use lsp_types::{
+    DocumentSymbol, DocumentSymbolParams, Range, SymbolInformation, SymbolKind, Url,
+    WorkspaceSymbolParams,
+};
+
+use crate::server::{send, send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+/// Returns the matching score of `query` and `name` (smaller is better).
+/// `None` if `name` does not contain all the characters of `query` in order.
+///
+/// e.g. `fuzzy_score("gn", "get_name") == Some(2)`
+fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let query = query.to_lowercase();
+    let name = name.to_lowercase();
+    if query.is_empty() || name.starts_with(&query) {
+        return Some(0);
+    }
+    if name.contains(&query) {
+        return Some(1);
+    }
+    let mut chars = name.chars();
+    query.chars().all(|q| chars.any(|c| c == q)).then_some(2)
+}
+
+fn is_hidden(ident: &Identifier) -> bool {
+    let name = ident.inspect();
+    name.is_empty() || name.starts_with('%') || &name[..] == "_"
+}
+
+fn ident_symbol(
+    ident: &Identifier,
+    kind: SymbolKind,
+    range: Range,
+    children: Vec<DocumentSymbol>,
+) -> Option<DocumentSymbol> {
+    if is_hidden(ident) {
+        return None;
+    }
+    let selection_range = util::loc_to_range(ident.loc())?;
+    let detail = match kind {
+        SymbolKind::CLASS | SymbolKind::INTERFACE | SymbolKind::OBJECT | SymbolKind::MODULE => None,
+        _ => Some(ident.ref_t().to_string()),
+    };
+    #[allow(deprecated)]
+    Some(DocumentSymbol {
+        name: ident.inspect().to_string(),
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children: (!children.is_empty()).then_some(children),
+    })
+}
+
+fn def_symbol(def: &Def, in_class: bool) -> Option<DocumentSymbol> {
+    let range = util::loc_to_range(def.loc())?;
+    let def_kind = def.def_kind();
+    let kind = if def_kind.is_trait() {
+        SymbolKind::INTERFACE
+    } else if def_kind.is_class() {
+        SymbolKind::CLASS
+    } else if def_kind.is_import() {
+        SymbolKind::MODULE
+    } else {
+        match (&def.sig, in_class) {
+            (Signature::Subr(_), true) => SymbolKind::METHOD,
+            (Signature::Subr(_), false) => SymbolKind::FUNCTION,
+            (Signature::Var(_), true) => SymbolKind::FIELD,
+            (Signature::Var(var), false) if var.ident.is_const() => SymbolKind::CONSTANT,
+            (Signature::Var(_), false) => SymbolKind::VARIABLE,
+        }
+    };
+    // the members of a module are not shown
+    let children = if def_kind.is_import() {
+        vec![]
+    } else {
+        exprs_to_symbols(def.body.block.iter(), false)
+    };
+    ident_symbol(def.sig.ident(), kind, range, children)
+}
+
+fn expr_to_symbols(expr: &Expr, in_class: bool) -> Vec<DocumentSymbol> {
+    match expr {
+        Expr::Def(def) => def_symbol(def, in_class).into_iter().collect(),
+        Expr::ClassDef(class_def) => {
+            let Some(range) = util::loc_to_range(class_def.loc()) else {
+                return vec![];
+            };
+            let methods = exprs_to_symbols(class_def.methods.iter(), true);
+            ident_symbol(class_def.sig.ident(), SymbolKind::CLASS, range, methods)
+                .into_iter()
+                .collect()
+        }
+        Expr::PatchDef(patch_def) => {
+            let Some(range) = util::loc_to_range(patch_def.loc()) else {
+                return vec![];
+            };
+            let methods = exprs_to_symbols(patch_def.methods.iter(), true);
+            ident_symbol(patch_def.sig.ident(), SymbolKind::OBJECT, range, methods)
+                .into_iter()
+                .collect()
+        }
+        // e.g. `.x: Int` in a class, `x: Int` in a declaration file
+        Expr::TypeAsc(type_asc) => {
+            let (Expr::Accessor(Accessor::Ident(ident)), Some(range)) =
+                (type_asc.expr.as_ref(), util::loc_to_range(type_asc.loc()))
+            else {
+                return vec![];
+            };
+            let kind = if in_class {
+                SymbolKind::FIELD
+            } else {
+                SymbolKind::VARIABLE
+            };
+            ident_symbol(ident, kind, range, vec![])
+                .into_iter()
+                .collect()
+        }
+        Expr::Compound(block) | Expr::Code(block) => exprs_to_symbols(block.iter(), in_class),
+        Expr::Dummy(dummy) => exprs_to_symbols(dummy.iter(), in_class),
+        _ => vec![],
+    }
+}
+
+fn exprs_to_symbols<'e>(
+    exprs: impl Iterator<Item = &'e Expr>,
+    in_class: bool,
+) -> Vec<DocumentSymbol> {
+    exprs
+        .flat_map(|expr| expr_to_symbols(expr, in_class))
+        .collect()
+}
+
+fn flatten_symbols(
+    symbols: Vec<DocumentSymbol>,
+    container_name: Option<&str>,
+    uri: &Url,
+    flattened: &mut Vec<SymbolInformation>,
+) {
+    for symbol in symbols {
+        #[allow(deprecated)]
+        flattened.push(SymbolInformation {
+            name: symbol.name.clone(),
+            kind: symbol.kind,
+            tags: None,
+            deprecated: None,
+            location: lsp_types::Location::new(uri.clone(), symbol.selection_range),
+            container_name: container_name.map(|s| s.to_string()),
+        });
+        if let Some(children) = symbol.children {
+            flatten_symbols(children, Some(&symbol.name), uri, flattened);
+        }
+    }
+}
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    pub(crate) fn show_document_symbols(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log("document symbol requested")?;
+        let params = DocumentSymbolParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document.uri);
+        let result = self.get_document_symbols(&uri);
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn show_workspace_symbols(&mut self, msg: &Value) -> ELSResult<()> {
+        let params = WorkspaceSymbolParams::deserialize(&msg["params"])?;
+        send_log(format!("workspace symbol requested: {}", params.query))?;
+        let result = self.get_workspace_symbols(&params.query);
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn get_document_symbols(&self, uri: &NormalizedUrl) -> Vec<DocumentSymbol> {
+        self.artifacts
+            .get(uri)
+            .and_then(|artifact| artifact.object.as_ref())
+            .map(|hir| exprs_to_symbols(hir.module.iter(), false))
+            .unwrap_or_default()
+    }
+
+    /// Collects the HIRs of the opened files and the user modules they depend on
+    /// (the standard library is excluded).
+    pub(crate) fn workspace_hirs(&self) -> Dict<PathBuf, &HIR> {
+        let mut hirs = Dict::new();
+        for (uri, artifact) in self.artifacts.iter() {
+            if let Some(hir) = artifact.object.as_ref() {
+                hirs.insert(util::uri_to_path(uri), hir);
+            }
+        }
+        if let Some(shared) = self.get_shared() {
+            for (path, entry) in shared.mod_cache.iter() {
+                if path.starts_with(&self.erg_path) || hirs.contains_key(path) {
+                    continue;
+                }
+                if let Some(hir) = entry.hir.as_ref() {
+                    hirs.insert(path.clone(), hir);
+                }
+            }
+        }
+        hirs
+    }
+
+    /// The symbols matching `query` are sorted by the matching score and then by the number of references.
+    pub(crate) fn get_workspace_symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        let mut ref_counts = Dict::new();
+        if !self.modules.is_empty() {
+            for (referee, value) in self.get_index().iter() {
+                if let (Some(path), Some(range)) =
+                    (&referee.module, util::loc_to_range(referee.loc))
+                {
+                    let key = (path.clone(), range.start.line, range.start.character);
+                    ref_counts.insert(key, value.referrers.len());
+                }
+            }
+        }
+        let mut symbols = vec![];
+        for (path, hir) in self.workspace_hirs() {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let mut flattened = vec![];
+            flatten_symbols(
+                exprs_to_symbols(hir.module.iter(), false),
+                None,
+                &uri,
+                &mut flattened,
+            );
+            for symbol in flattened {
+                let Some(score) = fuzzy_score(query, &symbol.name) else {
+                    continue;
+                };
+                let start = symbol.location.range.start;
+                let refs = ref_counts
+                    .get(&(path.clone(), start.line, start.character))
+                    .copied()
+                    .unwrap_or(0);
+                symbols.push((score, refs, symbol));
+            }
+        }
+        symbols.sort_by(|(l_score, l_refs, l), (r_score, r_refs, r)| {
+            l_score
+                .cmp(r_score)
+                .then(r_refs.cmp(l_refs))
+                .then(l.name.cmp(&r.name))
+        });
+        symbols.into_iter().map(|(_, _, symbol)| symbol).collect()
+    }
+}
@@ -0,0 +1,228 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_json::Value;
+
+use erg_common::traits::Locational;
+use erg_compiler::artifact::BuildRunnable;
+use erg_compiler::context::Context;
+use erg_compiler::hir::{Expr, Identifier};
+use erg_compiler::ty::Type;
+
+use lsp_types::{Range, SymbolKind, SymbolTag, TextDocumentPositionParams, Url};
+
+use crate::server::{send, send_log, ELSResult, Server};
+use crate::util::{self, NormalizedUrl};
+
+// The type hierarchy request is introduced in LSP 3.17, but lsp-types (0.93) does not support it yet.
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyItem {
+    pub name: String,
+    pub kind: SymbolKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<SymbolTag>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub uri: Url,
+    pub range: Range,
+    pub selection_range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyPrepareParams {
+    #[serde(flatten)]
+    pub text_document_position_params: TextDocumentPositionParams,
+}
+
+/// Params of `typeHierarchy/supertypes` and `typeHierarchy/subtypes`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyParams {
+    pub item: TypeHierarchyItem,
+}
+
+/// A type (class, trait or patch) defined in the workspace.
+pub(crate) struct TypeEntry<'a> {
+    pub item: TypeHierarchyItem,
+    pub typ: &'a Type,
+    pub ctx: &'a Context,
+}
+
+impl TypeEntry<'_> {
+    pub(crate) fn is(&self, typ: &Type) -> bool {
+        self.typ.qual_name() == typ.qual_name()
+    }
+
+    fn is_defined_at(&self, item: &TypeHierarchyItem) -> bool {
+        self.item.uri == item.uri && self.item.selection_range == item.selection_range
+    }
+}
+
+fn type_def_idents<'e>(exprs: impl Iterator<Item = &'e Expr>) -> Vec<(&'e Identifier, Range)> {
+    let mut idents = vec![];
+    for expr in exprs {
+        let ident = match expr {
+            Expr::ClassDef(class_def) => class_def.sig.ident(),
+            Expr::PatchDef(patch_def) => patch_def.sig.ident(),
+            Expr::Def(def) if def.def_kind().is_trait() => def.sig.ident(),
+            Expr::Compound(block) | Expr::Code(block) => {
+                idents.extend(type_def_idents(block.iter()));
+                continue;
+            }
+            Expr::Dummy(dummy) => {
+                idents.extend(type_def_idents(dummy.iter()));
+                continue;
+            }
+            _ => continue,
+        };
+        if let Some(range) = util::loc_to_range(expr.loc()) {
+            idents.push((ident, range));
+        }
+    }
+    idents
+}
+
+impl<Checker: BuildRunnable> Server<Checker> {
+    pub(crate) fn prepare_type_hierarchy(&mut self, msg: &Value) -> ELSResult<()> {
+        send_log("type hierarchy requested")?;
+        let params = TypeHierarchyPrepareParams::deserialize(&msg["params"])?;
+        let uri = NormalizedUrl::new(params.text_document_position_params.text_document.uri);
+        let pos = params.text_document_position_params.position;
+        let result = self.get_type_hierarchy_items(&uri, pos)?;
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn show_supertypes(&mut self, msg: &Value) -> ELSResult<()> {
+        let params = TypeHierarchyParams::deserialize(&msg["params"])?;
+        send_log(format!("supertypes requested: {}", params.item.name))?;
+        let result = self.get_supertypes(&params.item);
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    pub(crate) fn show_subtypes(&mut self, msg: &Value) -> ELSResult<()> {
+        let params = TypeHierarchyParams::deserialize(&msg["params"])?;
+        send_log(format!("subtypes requested: {}", params.item.name))?;
+        let result = self.get_subtypes(&params.item);
+        send(&json!({ "jsonrpc": "2.0", "id": msg["id"].as_i64().unwrap(), "result": result }))
+    }
+
+    fn get_module_ctx(&self, path: &Path) -> Option<&Context> {
+        let uri = Url::from_file_path(path).ok()?;
+        if let Some(module) = self.modules.get(&NormalizedUrl::new(uri)) {
+            return Some(&module.context);
+        }
+        self.get_shared()?
+            .mod_cache
+            .ref_ctx(path)
+            .map(|module| &module.context)
+    }
+
+    /// Collects the module-level classes, traits and patches in the workspace.
+    pub(crate) fn workspace_types(&self) -> Vec<TypeEntry<'_>> {
+        let mut entries = vec![];
+        for (path, hir) in self.workspace_hirs() {
+            let (Some(mod_ctx), Ok(uri)) = (self.get_module_ctx(&path), Url::from_file_path(&path))
+            else {
+                continue;
+            };
+            for (ident, range) in type_def_idents(hir.module.iter()) {
+                let Some(typ) = mod_ctx.get_type_by_name(ident.inspect()) else {
+                    continue;
+                };
+                let (Some(ctx), Some(selection_range)) =
+                    (mod_ctx.get_type_ctx(typ), util::loc_to_range(ident.loc()))
+                else {
+                    continue;
+                };
+                let kind = if ctx.kind.is_trait() {
+                    SymbolKind::INTERFACE
+                } else if ctx.kind.is_patch() {
+                    SymbolKind::OBJECT
+                } else {
+                    SymbolKind::CLASS
+                };
+                let item = TypeHierarchyItem {
+                    name: ident.inspect().to_string(),
+                    kind,
+                    tags: None,
+                    detail: Some(typ.qual_name().to_string()),
+                    uri: uri.clone(),
+                    range,
+                    selection_range,
+                    data: None,
+                };
+                entries.push(TypeEntry { item, typ, ctx });
+            }
+        }
+        entries
+    }
+
+    fn get_type_hierarchy_items(
+        &self,
+        uri: &NormalizedUrl,
+        pos: lsp_types::Position,
+    ) -> ELSResult<Option<Vec<TypeHierarchyItem>>> {
+        let Some(token) = self.file_cache.get_token(uri, pos) else {
+            return Ok(None);
+        };
+        let Some(vi) = self.get_definition(uri, &token)? else {
+            return Ok(None);
+        };
+        let (Some(path), Some(range)) = (vi.def_loc.module, util::loc_to_range(vi.def_loc.loc))
+        else {
+            send_log("not found (maybe builtin)")?;
+            return Ok(None);
+        };
+        let Ok(def_uri) = Url::from_file_path(path) else {
+            return Ok(None);
+        };
+        let item = self.workspace_types().into_iter().find_map(|entry| {
+            (entry.item.uri == def_uri && entry.item.selection_range == range).then_some(entry.item)
+        });
+        Ok(item.map(|item| vec![item]))
+    }
+
+    /// Returns the inherited classes, the implemented traits and the patched types of `item`.
+    pub(crate) fn get_supertypes(&self, item: &TypeHierarchyItem) -> Vec<TypeHierarchyItem> {
+        let entries = self.workspace_types();
+        let Some(target) = entries.iter().find(|entry| entry.is_defined_at(item)) else {
+            return vec![];
+        };
+        target
+            .ctx
+            .direct_super_types()
+            .filter_map(|sup| entries.iter().find(|entry| entry.is(sup)))
+            .map(|entry| entry.item.clone())
+            .collect()
+    }
+
+    /// Returns the classes inheriting `item`, the types implementing `item` and the patches of `item`.
+    pub(crate) fn get_subtypes(&self, item: &TypeHierarchyItem) -> Vec<TypeHierarchyItem> {
+        let entries = self.workspace_types();
+        let Some(target) = entries.iter().find(|entry| entry.is_defined_at(item)) else {
+            return vec![];
+        };
+        let mut subtypes: Vec<TypeHierarchyItem> = entries
+            .iter()
+            .filter(|entry| entry.ctx.direct_super_types().any(|sup| target.is(sup)))
+            .map(|entry| entry.item.clone())
+            .collect();
+        let impls = self
+            .get_shared()
+            .and_then(|shared| shared.trait_impls.get(&target.typ.qual_name()));
+        for imp in impls.into_iter().flat_map(|impls| impls.iter()) {
+            if let Some(entry) = entries.iter().find(|entry| entry.is(&imp.sub_type)) {
+                if !subtypes.contains(&entry.item) {
+                    subtypes.push(entry.item.clone());
+                }
+            }
+        }
+        subtypes
+    }
+}
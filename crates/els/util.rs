@@ -1,7 +1,7 @@
 use std::fmt;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use erg_common::normalize_path;
 use erg_common::traits::{DequeStream, Locational};
@@ -66,6 +66,10 @@ pub fn loc_to_range(loc: erg_common::error::Location) -> Option<Range> {
     Some(Range::new(start, end))
 }
 
+pub fn contains(outer: Range, inner: Range) -> bool {
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
 pub fn loc_to_pos(loc: erg_common::error::Location) -> Option<Position> {
     // FIXME: should `Position::new(loc.ln_begin()? - 1, loc.col_begin()?)`
     // but completion doesn't work (because the newline will be included)
@@ -185,6 +189,18 @@ pub fn uri_to_path(uri: &NormalizedUrl) -> std::path::PathBuf {
     )
 }
 
+/// `foo.d.er` or `__pycache__/foo.d.er` -> `foo.py` (if exists)
+pub fn get_py_source_path(decl_path: &Path) -> Option<PathBuf> {
+    let file_name = decl_path.file_name()?.to_str()?;
+    let stem = file_name.strip_suffix(".d.er")?;
+    let mut dir = decl_path.parent()?;
+    if dir.ends_with("__pycache__") {
+        dir = dir.parent()?;
+    }
+    let py_path = dir.join(format!("{stem}.py"));
+    py_path.exists().then_some(py_path)
+}
+
 pub fn denormalize(uri: Url) -> Url {
     Url::parse(&uri.as_str().replace("c:", "file:///c%3A")).unwrap()
 }
@@ -16,7 +16,7 @@ use crate::normalize_path;
 use crate::pathutil::add_postfix_foreach;
 use crate::python_util::{detect_magic_number, get_python_version, PythonVersion};
 use crate::random::random;
-use crate::serialize::{get_magic_num_from_bytes, get_ver_from_magic_num};
+use crate::serialize::{get_magic_num_from_bytes, get_ver_from_magic_num, PycInvalidationMode};
 use crate::stdin::GLOBAL_STDIN;
 use crate::{power_assert, read_file};
 
@@ -32,6 +32,7 @@ pub enum ErgMode {
     Execute,
     LanguageServer,
     Read,
+    Assemble,
 }
 
 impl TryFrom<&str> for ErgMode {
@@ -48,6 +49,7 @@ impl TryFrom<&str> for ErgMode {
             "run" | "execute" => Ok(Self::Execute),
             "server" | "language-server" => Ok(Self::LanguageServer),
             "byteread" | "read" | "reader" => Ok(Self::Read),
+            "asm" | "assemble" | "assembler" => Ok(Self::Assemble),
             _ => Err(()),
         }
     }
@@ -66,6 +68,7 @@ impl From<ErgMode> for &str {
             ErgMode::Execute => "execute",
             ErgMode::LanguageServer => "language-server",
             ErgMode::Read => "read",
+            ErgMode::Assemble => "assemble",
         }
     }
 }
@@ -76,6 +79,48 @@ impl fmt::Display for ErgMode {
     }
 }
 
+/// Flavor of the Python script generated by the transpiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TranspileTarget {
+    /// Wraps values in the Erg runtime classes to behave exactly like the compiled bytecode.
+    #[default]
+    Compatible,
+    /// Emits plain Python statements, literals and operators where the inferred types allow it.
+    Native,
+}
+
+impl TryFrom<&str> for TranspileTarget {
+    type Error = ();
+    fn try_from(s: &str) -> Result<Self, ()> {
+        match s {
+            "compat" | "compatible" => Ok(Self::Compatible),
+            "native" | "py-native" => Ok(Self::Native),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<TranspileTarget> for &str {
+    fn from(target: TranspileTarget) -> Self {
+        match target {
+            TranspileTarget::Compatible => "compat",
+            TranspileTarget::Native => "native",
+        }
+    }
+}
+
+impl fmt::Display for TranspileTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", <&str>::from(*self))
+    }
+}
+
+impl TranspileTarget {
+    pub const fn is_native(&self) -> bool {
+        matches!(self, Self::Native)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DummyStdin {
     pub name: String,
@@ -496,6 +541,8 @@ pub struct ErgConfig {
     pub py_magic_num: Option<u32>, // the magic number cannot be uniquely determined from `target_version`
     pub py_command: Option<&'static str>,
     pub target_version: Option<PythonVersion>,
+    pub transpile_target: TranspileTarget,
+    pub pyc_invalidation_mode: PycInvalidationMode,
     pub py_server_timeout: u64,
     pub quiet_repl: bool,
     pub show_type: bool,
@@ -511,6 +558,8 @@ pub struct ErgConfig {
     pub ps1: &'static str,
     pub ps2: &'static str,
     pub runtime_args: Vec<&'static str>,
+    /// additional directories to search for modules (after the directory of the input)
+    pub search_paths: Vec<PathBuf>,
 }
 
 impl Default for ErgConfig {
@@ -523,6 +572,8 @@ impl Default for ErgConfig {
             py_magic_num: None,
             py_command: None,
             target_version: None,
+            transpile_target: TranspileTarget::default(),
+            pyc_invalidation_mode: PycInvalidationMode::default(),
             py_server_timeout: 10,
             quiet_repl: false,
             show_type: false,
@@ -533,6 +584,7 @@ impl Default for ErgConfig {
             ps1: ">>> ",
             ps2: "... ",
             runtime_args: vec![],
+            search_paths: vec![],
         }
     }
 }
@@ -628,6 +680,16 @@ impl ErgConfig {
                 "--compile" | "--dump-as-pyc" => {
                     cfg.mode = ErgMode::Compile;
                 }
+                "--invalidation-mode" => {
+                    let mode = args
+                        .next()
+                        .expect("the value of `--invalidation-mode` is not passed");
+                    cfg.pyc_invalidation_mode = PycInvalidationMode::try_from(&mode[..])
+                        .unwrap_or_else(|_| {
+                            eprintln!("invalid invalidation mode: {mode}");
+                            process::exit(1);
+                        });
+                }
                 "--language-server" => {
                     cfg.mode = ErgMode::LanguageServer;
                 }
@@ -741,6 +803,16 @@ impl ErgConfig {
                         .expect("the value of `--target-version` is not a valid Python version");
                     cfg.target_version = Some(target_version);
                 }
+                "--transpile-target" => {
+                    let target = args
+                        .next()
+                        .expect("the value of `--transpile-target` is not passed");
+                    cfg.transpile_target =
+                        TranspileTarget::try_from(&target[..]).unwrap_or_else(|_| {
+                            eprintln!("invalid transpile target: {target}");
+                            process::exit(1);
+                        });
+                }
                 "--verbose" => {
                     cfg.verbose = args
                         .next()
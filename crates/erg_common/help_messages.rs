@@ -23,6 +23,8 @@ OPTIONS
     --mode (mode)                        指定モードで実行(詳細は--mode --helpを参照)
     --code/-c (string)                   文字列として渡したプログラムを実行
     --module/-m (string)                 モジュールを実行
+    --transpile-target compat|native     トランスパイル結果の形式を指定
+    --invalidation-mode (mode)           .pycの検証方式(timestamp|checked-hash|unchecked-hash)
 
 COMMAND
     lex                                  字句解析
@@ -54,6 +56,8 @@ OPTIONS
     --mode (mode)                        执行模式 (更多信息见`--mode --help`)
     --code/-c (string)                   作为字符串传入程序
     --module/-m (string)                 要执行的模块
+    --transpile-target compat|native     转译输出的形式
+    --invalidation-mode (mode)           .pyc 的验证方式 (timestamp|checked-hash|unchecked-hash)
 
 COMMAND
     lex                                  字词解析
@@ -85,6 +89,8 @@ OPTIONS
     --mode (mode)                        執行模式 (更多信息見`--mode --help`)
     --code/-c (string)                   作為字串傳入程式
     --module/-m (string)                 要執行的模塊
+    --transpile-target compat|native     轉譯輸出的形式
+    --invalidation-mode (mode)           .pyc 的驗證方式 (timestamp|checked-hash|unchecked-hash)
 
 COMMAND
     lex                                  字詞解析
@@ -116,6 +122,8 @@ OPTIONS
     --mode (mode)                        execution mode (See `--mode --help` for details)
     --code/-c (string)                   program passed in as string
     --module/-m (string)                 module to be executed
+    --transpile-target compat|native     flavor of the transpiled Python script
+    --invalidation-mode (mode)           .pyc validation (timestamp|checked-hash|unchecked-hash)
 
 COMMAND
     lex                                  lexical analysis
@@ -134,7 +142,7 @@ pub fn mode_message<'a>() -> &'a str {
         "japanese" =>
         "\
 USAGE:
-    erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+    erg --mode [lex | parse | lower | check | compile | exec | read | assemble] [SUBCOMMAND] [ARGS]...
 
 lex
     <filename>.erやREPLなどから入力を受け取り、字句を解析
@@ -164,12 +172,16 @@ run/exec
     compileを実行し、更に<filename>.pycを実行
 
 read
-    <filename>.pycをデシリアライズしコードオブジェクトの情報をダンプ",
+    <filename>.pycをデシリアライズし、ネストしたコードオブジェクトを含む完全なリスティングを出力
+    出力はassembleで再び.pycに変換できる
+
+assemble/asm
+    readが出力したリスティングを読み込み、<filename>.pycを出力",
 
     "simplified_chinese" =>
     "\
 USAGE:
-    erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+    erg --mode [lex | parse | lower | check | compile | exec | read | assemble] [SUBCOMMAND] [ARGS]...
 
 lex
     从 <filename>.er, REPL 等接受输入, 并标记文本
@@ -200,12 +212,16 @@ run/exec
     在执行 <文件名>.pyc 后删除 <文件名>.pyc
 
 read
-    反序列化 <文件名>.pyc 和 dump",
+    反序列化 <文件名>.pyc, 并输出包含嵌套代码对象的完整清单
+    输出可以通过 assemble 再转换为 .pyc
+
+assemble/asm
+    读取 read 输出的清单并返回 <文件名>.pyc",
 
     "traditional_chinese" =>
     "\
 USAGE:
-        erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+        erg --mode [lex | parse | lower | check | compile | exec | read | assemble] [SUBCOMMAND] [ARGS]...
 
 lex
     從 <檔名>.er, REPL 等接受輸入, 並標記文字
@@ -236,12 +252,16 @@ exec
     在執行 <檔名>.pyc 後删除 <檔名>.pyc
 
 read
-    反序列化 <檔名>.pyc 和 dump",
+    反序列化 <檔名>.pyc, 並輸出包含嵌套代碼對象的完整清單
+    輸出可以通過 assemble 再轉換為 .pyc
+
+assemble/asm
+    讀取 read 輸出的清單並返回 <檔名>.pyc",
 
     "english" =>
     "\
 USAGE:
-    erg --mode [lex | parse | lower | check | compile | exec | read] [SUBCOMMAND] [ARGS]...
+    erg --mode [lex | parse | lower | check | compile | exec | read | assemble] [SUBCOMMAND] [ARGS]...
 
 lex
     Receive input from <filename>.er, REPL, etc. and lex the text
@@ -271,7 +291,11 @@ run/exec
     Execute compile and then <filename>.pyc
 
 read
-    Deserialize <filename>.pyc and dump code object information",
+    Deserialize <filename>.pyc and print a complete listing, including nested code objects
+    The listing can be turned back into a .pyc with assemble
+
+assemble/asm
+    Read a listing printed by read and output <filename>.pyc",
     )
 }
 
@@ -290,6 +314,7 @@ pub const OPTIONS: &[&str] = &[
     "-h",
     "--hex-py-magic-num",
     "--hex-python-magic-number",
+    "--invalidation-mode",
     "--mode",
     "--module",
     "-m",
@@ -312,6 +337,7 @@ pub const OPTIONS: &[&str] = &[
     "--show-type",
     "-t",
     "--target-version",
+    "--transpile-target",
     "--version",
     "-V",
     "--verbose",
@@ -95,6 +95,21 @@ macro_rules! impl_u8_enum {
             pub const fn take_arg(&self) -> bool {
                 90 <= (*self as u8) && (*self as u8) < 220
             }
+
+            /// Unlike `From<u8>`, this does not panic if `byte` is unknown.
+            pub fn try_from_byte(byte: u8) -> Option<Self> {
+                match byte {
+                    $($val => Some($Enum::$Variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn try_from_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($Variant) => Some($Enum::$Variant),)*
+                    _ => None,
+                }
+            }
         }
     };
     ($Enum: ident; $size: tt; $($Variant: ident = $val: expr $(,)?)*) => {
@@ -57,6 +57,8 @@ impl_u8_enum! {Opcode308;
     GET_YIELD_FROM_ITER = 69,
     PRINT_EXPR = 70,
     LOAD_BUILD_CLASS = 71,
+    YIELD_FROM = 72,
+    GET_AWAITABLE = 73,
     // LOAD_ASSERTION_ERROR = 74,
     WITH_CLEANUP_START = 81,
     WITH_CLEANUP_FINISH = 82,
@@ -95,6 +97,7 @@ impl_u8_enum! {Opcode308;
     IS_OP = 117,
     CONTAINS_OP = 118,
     RERAISE = 119,
+    SETUP_FINALLY = 122,
     LOAD_FAST = 124,
     STORE_FAST = 125,
     DELETE_FAST = 126,
@@ -108,6 +111,12 @@ impl_u8_enum! {Opcode308;
     CALL_FUNCTION_EX = 142,
     SETUP_WITH = 143,
     EXTENDED_ARG = 144,
+    LIST_APPEND = 145,
+    SET_ADD = 146,
+    BUILD_LIST_UNPACK = 149,
+    BUILD_MAP_UNPACK = 150,
+    BUILD_MAP_UNPACK_WITH_CALL = 151,
+    BUILD_SET_UNPACK = 153,
     BUILD_TUPLE_UNPACK_WITH_CALL = 158,
     LOAD_METHOD = 160,
     CALL_METHOD = 161,
@@ -654,32 +654,37 @@ impl std::str::FromStr for PythonVersion {
 }
 
 pub fn get_python_version(py_command: &str) -> PythonVersion {
+    opt_get_python_version(py_command).expect("cannot get the python version")
+}
+
+/// Returns `None` if `py_command` cannot be executed or does not print its version.
+pub fn opt_get_python_version(py_command: &str) -> Option<PythonVersion> {
     let out = if cfg!(windows) {
         Command::new("cmd")
             .arg("/C")
             .arg(py_command)
             .arg("--version")
             .output()
-            .expect("cannot get the python version")
+            .ok()?
     } else {
         let exec_command = format!("{py_command} --version");
         Command::new("sh")
             .arg("-c")
             .arg(exec_command)
             .output()
-            .expect("cannot get the python version")
+            .ok()?
     };
-    let s_version = String::from_utf8(out.stdout).unwrap();
+    let s_version = String::from_utf8(out.stdout).ok()?;
     let mut iter = s_version.split(' ');
-    let mut iter = iter.nth(1).unwrap().split('.');
+    let mut iter = iter.nth(1)?.split('.');
     let major = iter.next().and_then(|i| i.parse().ok()).unwrap_or(3);
     let minor = iter.next().and_then(|i| i.parse().ok());
     let micro = iter.next().and_then(|i| i.trim_end().parse().ok());
-    PythonVersion {
+    Some(PythonVersion {
         major,
         minor,
         micro,
-    }
+    })
 }
 
 pub fn env_python_version() -> PythonVersion {
@@ -1,4 +1,6 @@
 //! オブジェクトのシリアライズ(バイナリ列化)のためのユーティリティーを定義・実装する
+use std::env;
+use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::impl_display_from_debug;
@@ -7,9 +9,12 @@ use crate::Str;
 
 /* Python bytecode specification */
 // 0~3 byte: magic number
-// 4~7 byte: padding (0;4)
-// 8~B byte: UNIX timestamp
-// C~F byte: padding (0;4)
+// 4~7 byte: flags (PEP 552)
+// (flags & 0b01 == 0)
+//   8~B byte: UNIX timestamp
+//   C~F byte: source size
+// (flags & 0b01 == 1)
+//   8~F byte: SipHash of the source
 // 10~ byte: marshalled code objects
 // the unary magic number of Python bytecode
 // magic number = version number (2byte) + 168624128 (0x0A0D0000)
@@ -45,6 +50,160 @@ pub fn get_timestamp_bytes() -> [u8; 4] {
     secs.to_le_bytes()
 }
 
+/// How the Python interpreter checks whether a .pyc file is up to date (PEP 552).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PycInvalidationMode {
+    /// The header contains the time of compilation and the source size.
+    Timestamp,
+    /// The header contains the hash of the source, which is checked on import.
+    CheckedHash,
+    /// The header contains the hash of the source, which is never checked.
+    UncheckedHash,
+}
+
+/// Same as `py_compile`: hash-based .pyc files are used if `SOURCE_DATE_EPOCH` is set.
+impl Default for PycInvalidationMode {
+    fn default() -> Self {
+        if env::var_os("SOURCE_DATE_EPOCH").is_some() {
+            Self::CheckedHash
+        } else {
+            Self::Timestamp
+        }
+    }
+}
+
+impl TryFrom<&str> for PycInvalidationMode {
+    type Error = ();
+    fn try_from(s: &str) -> Result<Self, ()> {
+        match s {
+            "timestamp" => Ok(Self::Timestamp),
+            "checked-hash" | "checked_hash" => Ok(Self::CheckedHash),
+            "unchecked-hash" | "unchecked_hash" => Ok(Self::UncheckedHash),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<PycInvalidationMode> for &str {
+    fn from(mode: PycInvalidationMode) -> Self {
+        match mode {
+            PycInvalidationMode::Timestamp => "timestamp",
+            PycInvalidationMode::CheckedHash => "checked-hash",
+            PycInvalidationMode::UncheckedHash => "unchecked-hash",
+        }
+    }
+}
+
+impl fmt::Display for PycInvalidationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", <&str>::from(*self))
+    }
+}
+
+impl PycInvalidationMode {
+    pub const FLAG_HASH_BASED: u32 = 0b01;
+    pub const FLAG_CHECK_SOURCE: u32 = 0b10;
+
+    pub const fn is_hash_based(&self) -> bool {
+        matches!(self, Self::CheckedHash | Self::UncheckedHash)
+    }
+
+    pub const fn flags(&self) -> u32 {
+        match self {
+            Self::Timestamp => 0,
+            Self::CheckedHash => Self::FLAG_HASH_BASED | Self::FLAG_CHECK_SOURCE,
+            Self::UncheckedHash => Self::FLAG_HASH_BASED,
+        }
+    }
+
+    /// Returns `None` if `flags` has unknown bits (or only `FLAG_CHECK_SOURCE`).
+    pub const fn from_flags(flags: u32) -> Option<Self> {
+        match flags {
+            0 => Some(Self::Timestamp),
+            0b11 => Some(Self::CheckedHash),
+            0b01 => Some(Self::UncheckedHash),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the header of a .pyc file except the magic number (flags + 8 bytes).
+pub fn get_pyc_header_bytes(py_magic_num: u32, src: &[u8], mode: PycInvalidationMode) -> [u8; 12] {
+    let mut header = [0u8; 12];
+    header[..4].copy_from_slice(&mode.flags().to_le_bytes());
+    if mode.is_hash_based() {
+        let python_ver = get_ver_from_magic_num(py_magic_num);
+        header[4..].copy_from_slice(&get_source_hash_bytes(py_magic_num, src, python_ver));
+    } else {
+        header[4..8].copy_from_slice(&get_timestamp_bytes());
+        header[8..].copy_from_slice(&(src.len() as u32).to_le_bytes());
+    }
+    header
+}
+
+/// Equivalent to `importlib.util.source_hash`.
+/// The key is the whole magic number (including `\r\n`).
+/// CPython uses SipHash-1-3 since 3.11 and SipHash-2-4 before that.
+pub fn get_source_hash_bytes(py_magic_num: u32, src: &[u8], python_ver: PythonVersion) -> [u8; 8] {
+    let key = u32::from_le_bytes(get_magic_num_bytes(py_magic_num)) as u64;
+    let hash = if python_ver.minor >= Some(11) {
+        siphash::<1, 3>(key, 0, src)
+    } else {
+        siphash::<2, 4>(key, 0, src)
+    };
+    hash.to_le_bytes()
+}
+
+fn siphash<const C: usize, const D: usize>(k0: u64, k1: u64, src: &[u8]) -> u64 {
+    let mut v = [
+        k0 ^ 0x736f6d6570736575,
+        k1 ^ 0x646f72616e646f6d,
+        k0 ^ 0x6c7967656e657261,
+        k1 ^ 0x7465646279746573,
+    ];
+    fn sip_round(v: &mut [u64; 4]) {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    }
+    let compress = |v: &mut [u64; 4], m: u64| {
+        v[3] ^= m;
+        for _ in 0..C {
+            sip_round(v);
+        }
+        v[0] ^= m;
+    };
+    let chunks = src.chunks_exact(8);
+    let rest = chunks.remainder();
+    for chunk in chunks {
+        let mut word = [0u8; 8];
+        word.copy_from_slice(chunk);
+        compress(&mut v, u64::from_le_bytes(word));
+    }
+    let mut last = [0u8; 8];
+    last[..rest.len()].copy_from_slice(rest);
+    compress(
+        &mut v,
+        u64::from_le_bytes(last) | ((src.len() as u64) << 56),
+    );
+    v[2] ^= 0xff;
+    for _ in 0..D {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+/// If the MSB of a prefix is set, the object is registered in the reference table
+/// and can be referred to later by `DataTypePrefix::Ref` + index (u32).
+pub const FLAG_REF: u8 = 0x80;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum DataTypePrefix {
@@ -60,12 +219,15 @@ pub enum DataTypePrefix {
     False = b'F',      // 0x46
     None = b'N',       // 0x4E
     StopIter = b'S',   // 0x53
+    Ellipsis = b'.',   // 0x2E
     Ref = b'r',
     /* unsized objects (ref counted) */
     Long = b'l', // 0x6C + len:  u32 + payload: 2*len+3byte (~ -2^31-1 && 2^31 ~)
     Str = b's',  // 0x73 + len:  u32 + payload
     ShortAscii = b'z' + 0x80, // 0x7A (0xFA) + len:  u8 + payload
     ShortAsciiInterned = b'Z' + 0x80, // 0x5A (0xDA) + len:  u8 + payload
+    Ascii = b'a' + 0x80, // 0x61 (0xE1) + len:  u32 + payload
+    AsciiInterned = b'A' + 0x80, // 0x41 (0xC1) + len:  u32 + payload
     Unicode = b'u', // 0x75 + len:  u32 + payload
     Interned = b't', // 0x74 + len + payload
     SmallTuple = b')', // 0x29 + len:  u8 + payload
@@ -92,7 +254,11 @@ impl From<u8> for DataTypePrefix {
             'F' => Self::False,
             'N' => Self::None,
             'S' => Self::StopIter,
+            '.' => Self::Ellipsis,
+            'r' => Self::Ref,
             's' | '\u{00F3}' => Self::Str,
+            'a' | '\u{00E1}' => Self::Ascii,
+            'A' | '\u{00C1}' => Self::AsciiInterned,
             'Z' | '\u{00DA}' => Self::ShortAsciiInterned,
             'z' | '\u{00FA}' => Self::ShortAscii,
             'u' => Self::Unicode,
@@ -120,6 +286,8 @@ impl DataTypePrefix {
             self,
             Self::Long
                 | Self::Str
+                | Self::Ascii
+                | Self::AsciiInterned
                 | Self::ShortAscii
                 | Self::ShortAsciiInterned
                 | Self::Unicode
@@ -147,15 +315,28 @@ pub fn strs_into_bytes(names: Vec<Str>) -> Vec<u8> {
     tuple
 }
 
+/// CPython interns string constants consisting only of name characters (`[a-zA-Z0-9_]`).
+/// Using the same rule makes the output independent of how the `Str` was created.
+pub fn is_interned_str(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
 pub fn str_into_bytes(cont: Str, is_interned: bool) -> Vec<u8> {
     let mut bytes = vec![];
-    if cont.is_ascii() {
+    if cont.is_ascii() && cont.len() <= u8::MAX as usize {
         if is_interned {
             bytes.push(DataTypePrefix::ShortAsciiInterned as u8);
         } else {
             bytes.push(DataTypePrefix::ShortAscii as u8);
         }
         bytes.push(cont.len() as u8);
+    } else if cont.is_ascii() {
+        if is_interned {
+            bytes.push(DataTypePrefix::AsciiInterned as u8);
+        } else {
+            bytes.push(DataTypePrefix::Ascii as u8);
+        }
+        bytes.append(&mut (cont.len() as u32).to_le_bytes().to_vec());
     } else {
         bytes.push(DataTypePrefix::Unicode as u8);
         bytes.append(&mut (cont.len() as u32).to_le_bytes().to_vec());
@@ -170,3 +351,29 @@ pub fn raw_string_into_bytes(mut cont: Vec<u8>) -> Vec<u8> {
     tuple.append(&mut cont);
     tuple
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::python_util::PythonVersion;
+    use crate::serialize::{get_source_hash_bytes, siphash};
+
+    #[test]
+    fn test_siphash24() {
+        // test vector of the reference implementation
+        let k0 = u64::from_le_bytes([0, 1, 2, 3, 4, 5, 6, 7]);
+        let k1 = u64::from_le_bytes([8, 9, 10, 11, 12, 13, 14, 15]);
+        let src = (0..15).collect::<Vec<u8>>();
+        assert_eq!(siphash::<2, 4>(k0, k1, &src), 0xa129ca6149be45e5);
+    }
+
+    #[test]
+    fn test_source_hash() {
+        // `importlib.util.source_hash` of Python 3.11
+        let py311 = PythonVersion::new(3, Some(11), Some(0));
+        let hash = |src: &[u8]| u64::from_be_bytes(get_source_hash_bytes(3495, src, py311));
+        assert_eq!(hash(b""), 0x738d9cd5d5e87f73);
+        assert_eq!(hash(b"abc"), 0xc7af82e77c1da4f5);
+        assert_eq!(hash(b"print! \"hello\"\n"), 0x0dfd4b4afe7a5709);
+        assert_eq!(hash(&(0..64).collect::<Vec<u8>>()), 0x21c86d58e2c53a01);
+    }
+}
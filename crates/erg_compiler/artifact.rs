@@ -2,6 +2,7 @@ use std::fmt;
 
 use erg_common::config::ErgConfig;
 use erg_common::traits::{Runnable, Stream};
+use erg_parser::ast::AST;
 
 use crate::context::ModuleContext;
 use crate::error::CompileErrors;
@@ -99,6 +100,11 @@ pub trait Buildable<T = HIR> {
         src: String,
         mode: &str,
     ) -> Result<CompleteArtifact<T>, IncompleteArtifact<T>>;
+    fn build_from_ast(
+        &mut self,
+        ast: AST,
+        mode: &str,
+    ) -> Result<CompleteArtifact<T>, IncompleteArtifact<T>>;
     fn pop_context(&mut self) -> Option<ModuleContext>;
     fn get_context(&self) -> Option<&ModuleContext>;
 }
@@ -90,6 +90,13 @@ impl Buildable for HIRBuilder {
     fn build(&mut self, src: String, mode: &str) -> Result<CompleteArtifact, IncompleteArtifact> {
         self.build(src, mode)
     }
+    fn build_from_ast(
+        &mut self,
+        ast: AST,
+        mode: &str,
+    ) -> Result<CompleteArtifact, IncompleteArtifact> {
+        self.check(ast, mode)
+    }
     fn pop_context(&mut self) -> Option<ModuleContext> {
         self.pop_mod_ctx()
     }
@@ -5,7 +5,7 @@ use std::fmt;
 use std::process;
 
 use crate::ty::codeobj::{CodeObj, CodeObjFlags, MakeFunctionFlags};
-use crate::ty::value::GenTypeObj;
+use crate::ty::value::{EnumTypeObj, GenTypeObj, InheritedTypeObj, TypeObj};
 use erg_common::cache::CacheSet;
 use erg_common::config::{ErgConfig, Input};
 use erg_common::env::erg_std_path;
@@ -16,6 +16,7 @@ use erg_common::opcode310::Opcode310;
 use erg_common::opcode311::{BinOpCode, Opcode311};
 use erg_common::option_enum_unwrap;
 use erg_common::python_util::{env_python_version, PythonVersion};
+use erg_common::set::Set;
 use erg_common::traits::{Locational, Stream};
 use erg_common::Str;
 use erg_common::{debug_power_assert, fn_name, fn_name_full, impl_stream, log, switch_unreachable};
@@ -35,6 +36,7 @@ use crate::hir::{
     Literal, NonDefaultParamSignature, Params, PatchDef, PosArg, ReDef, Record, Signature,
     SubrSignature, Tuple, UnaryOp, VarSignature, HIR,
 };
+use crate::ty::constructors::func1;
 use crate::ty::value::ValueObj;
 use crate::ty::{HasType, Type, TypeCode, TypePair, VisibilityModifier};
 use crate::varinfo::VarInfo;
@@ -84,6 +86,54 @@ fn escape_name(name: &str, vis: &VisibilityModifier, def_line: u32, def_col: u32
     }
 }
 
+/// Returns the keywords of `args` that are passed to the `**kwargs` of `callee_t`.
+/// They are used as dictionary keys, so they must not be escaped.
+fn kw_var_keywords(callee_t: &Type, args: &Args) -> Set<Str> {
+    if callee_t.kw_var_params().is_none() {
+        return Set::new();
+    }
+    let params = param_names(callee_t);
+    args.kw_args
+        .iter()
+        .map(|arg| arg.keyword.inspect())
+        .filter(|kw| !params.contains(*kw))
+        .cloned()
+        .collect()
+}
+
+/// Returns the parameter names of `callee_t` that the keys of `**kwargs` arguments may refer to.
+/// They must be escaped at runtime (see `rename_kwargs` in `_erg_std_prelude`).
+fn kw_spread_params(callee_t: &Type, args: &Args, is_py_api: bool) -> Set<Str> {
+    if is_py_api || args.kw_var_args.is_none() {
+        return Set::new();
+    }
+    param_names(callee_t)
+}
+
+fn escape_keyword(keyword: Str, is_py_api: bool, kw_var_keys: &Set<Str>) -> Str {
+    if is_py_api || kw_var_keys.contains(&keyword) {
+        keyword
+    } else {
+        escape_name(&keyword, &VisibilityModifier::Private, 0, 0)
+    }
+}
+
+fn is_spread(expr: &Expr) -> bool {
+    matches!(expr, Expr::UnaryOp(unary) if unary.op.is(TokenKind::PreStar))
+}
+
+fn param_names(callee_t: &Type) -> Set<Str> {
+    let empty = vec![];
+    callee_t
+        .non_default_params()
+        .unwrap_or(&empty)
+        .iter()
+        .chain(callee_t.default_params().unwrap_or(&empty).iter())
+        .filter_map(|pt| pt.name())
+        .cloned()
+        .collect()
+}
+
 fn escape_ident(ident: Identifier) -> Str {
     let vis = ident.vis();
     if let Some(py_name) = ident.vi.py_name {
@@ -98,6 +148,38 @@ fn escape_ident(ident: Identifier) -> Str {
     }
 }
 
+/// An entry of the exception table (>= 3.11).
+/// All offsets are in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionTableEntry {
+    pub start: usize,
+    pub end: usize,
+    pub target: usize,
+    pub depth: usize,
+}
+
+/// see Objects/exception_handling_notes.txt in CPython
+fn encode_exception_table(entries: &[ExceptionTableEntry]) -> Vec<u8> {
+    fn write_varint(table: &mut Vec<u8>, val: usize, mut msb: u8) {
+        for shift in [24, 18, 12, 6] {
+            if val >= 1 << shift {
+                table.push(((val >> shift) & 63) as u8 | 64 | msb);
+                msb = 0;
+            }
+        }
+        table.push((val & 63) as u8 | msb);
+    }
+    let mut table = vec![];
+    for entry in entries {
+        write_varint(&mut table, entry.start / 2, 128);
+        write_varint(&mut table, (entry.end - entry.start) / 2, 0);
+        write_varint(&mut table, entry.target / 2, 0);
+        // lasti is not pushed
+        write_varint(&mut table, entry.depth << 1, 0);
+    }
+    table
+}
+
 #[derive(Debug, Clone)]
 pub struct PyCodeGenUnit {
     pub(crate) id: usize,
@@ -107,6 +189,7 @@ pub struct PyCodeGenUnit {
     pub(crate) prev_lineno: u32,
     pub(crate) lasti: usize,
     pub(crate) prev_lasti: usize,
+    pub(crate) exception_table: Vec<ExceptionTableEntry>,
     pub(crate) _refs: Vec<ValueObj>, // ref-counted objects
 }
 
@@ -146,6 +229,7 @@ impl PyCodeGenUnit {
             prev_lineno: firstlineno,
             lasti: 0,
             prev_lasti: 0,
+            exception_table: vec![],
             _refs: vec![],
         }
     }
@@ -163,6 +247,7 @@ pub struct PyCodeGenerator {
     str_cache: CacheSet<str>,
     prelude_loaded: bool,
     mutate_op_loaded: bool,
+    rename_kwargs_loaded: bool,
     in_op_loaded: bool,
     record_type_loaded: bool,
     module_type_loaded: bool,
@@ -181,6 +266,7 @@ impl PyCodeGenerator {
             str_cache: CacheSet::new(),
             prelude_loaded: false,
             mutate_op_loaded: false,
+            rename_kwargs_loaded: false,
             in_op_loaded: false,
             record_type_loaded: false,
             module_type_loaded: false,
@@ -199,6 +285,7 @@ impl PyCodeGenerator {
     pub fn initialize(&mut self) {
         self.prelude_loaded = false;
         self.mutate_op_loaded = false;
+        self.rename_kwargs_loaded = false;
         self.in_op_loaded = false;
         self.record_type_loaded = false;
         self.module_type_loaded = false;
@@ -401,6 +488,14 @@ impl PyCodeGenerator {
             self.mut_cur_block().lasti += 2;
             shift_bytes += 2;
         }
+        // the handled ranges after the inserted `EXTENDED_ARG`s are shifted
+        for entry in self.mut_cur_block().exception_table.iter_mut() {
+            for offset in [&mut entry.start, &mut entry.end, &mut entry.target] {
+                if *offset > before_instr {
+                    *offset += shift_bytes;
+                }
+            }
+        }
         shift_bytes
     }
 
@@ -926,6 +1021,14 @@ impl PyCodeGenerator {
                     .iter()
                     .map(|p| (p.inspect().map(|s| &s[..]).unwrap_or("_"), &p.sig.vi)),
             )
+            .chain(if let Some(kw_var_args) = &params.kw_var_params {
+                vec![(
+                    kw_var_args.inspect().map(|s| &s[..]).unwrap_or("_"),
+                    &kw_var_args.vi,
+                )]
+            } else {
+                vec![]
+            })
             .enumerate()
             .map(|(i, (s, vi))| {
                 if s == "_" {
@@ -1201,6 +1304,67 @@ impl PyCodeGenerator {
 
     fn emit_class_def(&mut self, class_def: ClassDef) {
         log!(info "entered {} ({})", fn_name!(), class_def.sig);
+        let ident = class_def.sig.ident().clone();
+        let enum_ = if let GenTypeObj::Enum(enum_) = &class_def.obj {
+            Some(enum_.clone())
+        } else {
+            None
+        };
+        self.emit_build_class(class_def);
+        self.emit_store_instr(ident.clone(), Name);
+        self.stack_dec();
+        if let Some(enum_) = enum_ {
+            for (tag, (name, payload)) in enum_.variants.iter().enumerate() {
+                self.emit_variant_def(&enum_, &ident, tag, name, payload);
+            }
+        }
+    }
+
+    /// ```python
+    /// class Lit(Expr):
+    ///     __tag__ = 0
+    ///     def __init__(self, base): self::base = base
+    /// Expr.Lit = Lit
+    /// ```
+    fn emit_variant_def(
+        &mut self,
+        enum_: &EnumTypeObj,
+        enum_ident: &Identifier,
+        tag: usize,
+        name: &Str,
+        payload: &TypeObj,
+    ) {
+        log!(info "entered {} ({enum_ident}.{name})", fn_name!());
+        // the methods of the enum have already been emitted
+        let line = self.cur_block().prev_lineno;
+        let variant_t = enum_.variant_type(name);
+        let sup = TypeObj::Generated(GenTypeObj::Enum(enum_.clone()));
+        let obj = GenTypeObj::Subclass(InheritedTypeObj::new(
+            variant_t.clone(),
+            sup,
+            None,
+            Some(payload.clone()),
+        ));
+        let ident = Identifier::public_with_line(DOT, name.clone(), line);
+        let sig = Signature::Var(VarSignature::new(ident.clone(), None));
+        let sup = Expr::Accessor(Accessor::Ident(enum_ident.clone()));
+        let tag_lit = Token::new(TokenKind::NatLit, tag.to_string(), line, 0);
+        let tag_lit = Expr::Lit(Literal::new(ValueObj::Nat(tag as u64), tag_lit));
+        let tag_def = ReDef::new(
+            Accessor::Ident(Identifier::public_with_line(DOT, "__tag__".into(), line)),
+            Block::new(vec![tag_lit]),
+        );
+        let __new__ = func1(payload.typ().clone(), variant_t);
+        let methods = Block::new(vec![Expr::ReDef(tag_def)]);
+        let class_def = ClassDef::new(obj, sig, Some(sup.clone()), false, __new__, methods);
+        self.emit_build_class(class_def);
+        self.emit_expr(sup);
+        self.emit_store_instr(ident, Attr);
+        self.stack_dec();
+    }
+
+    /// Leaves the built class object on the stack
+    fn emit_build_class(&mut self, class_def: ClassDef) {
         self.emit_push_null();
         let ident = class_def.sig.ident().clone();
         let require_or_sup = class_def.require_or_sup.clone().map(|x| *x);
@@ -1222,8 +1386,6 @@ impl PyCodeGenerator {
         let subclasses_len = self.emit_require_type(obj, require_or_sup);
         self.emit_call_instr(2 + subclasses_len, Name);
         self.stack_dec_n((1 + 2 + subclasses_len) - 1);
-        self.emit_store_instr(ident, Name);
-        self.stack_dec();
     }
 
     fn emit_patch_def(&mut self, patch_def: PatchDef) {
@@ -1241,10 +1403,25 @@ impl PyCodeGenerator {
                 patch_def.sig.ident().to_string_notype(),
                 def.sig.ident().to_string_notype()
             );
+            let mut method = def.sig.ident().clone();
             def.sig.ident_mut().raw.name = VarName::from_str(Str::from(name));
             def.sig.ident_mut().raw.vis = VisModifierSpec::Private;
+            let func = def.sig.ident().clone();
             self.emit_def(def);
+            // The methods of a glue patch are called by dynamic dispatch (e.g. `x.reverse()` where `x: Reverse`),
+            // so they are also set as attributes of the base class
+            // ↓
+            // Str.reverse = StrReverse::reverse
+            if patch_def.impls.is_some() {
+                method.vi.py_name = None;
+                self.emit_load_name_instr(func);
+                self.store_acc(Accessor::attr(*patch_def.base.clone(), method));
+            }
         }
+        // A patch has no runtime representation,
+        // so the name is bound to the base (to be imported from other modules)
+        self.emit_expr(*patch_def.base);
+        self.emit_store_instr(patch_def.sig.ident().clone(), Name);
     }
 
     // NOTE: use `TypeVar`, `Generic` in `typing` module
@@ -1254,7 +1431,7 @@ impl PyCodeGenerator {
     fn emit_require_type(&mut self, obj: GenTypeObj, require_or_sup: Option<Expr>) -> usize {
         log!(info "entered {} ({obj}, {require_or_sup:?})", fn_name!());
         match obj {
-            GenTypeObj::Class(_) => 0,
+            GenTypeObj::Class(_) | GenTypeObj::Enum(_) => 0,
             GenTypeObj::Subclass(_) => {
                 self.emit_expr(require_or_sup.unwrap());
                 1 // TODO: not always 1
@@ -1295,11 +1472,21 @@ impl PyCodeGenerator {
             self.stack_dec_n(defaults_len - 1);
             make_function_flag += MakeFunctionFlags::Defaults as usize;
         }
-        let flags = if sig.params.var_params.is_some() {
+        let mut flags = if sig.params.var_params.is_some() {
             CodeObjFlags::VarArgs as u32
         } else {
             0
         };
+        if sig.params.kw_var_params.is_some() {
+            flags += CodeObjFlags::VarKeywords as u32;
+        }
+        if matches!(sig.ident.ref_t().return_t(), Some(ret) if &ret.qual_name()[..] == "Generator")
+        {
+            flags += CodeObjFlags::Generator as u32;
+        }
+        if sig.ident.vi.is_async() {
+            flags += CodeObjFlags::Coroutine as u32;
+        }
         let code = self.emit_block(body.block, Some(name.clone()), params, flags);
         // code.flags += CodeObjFlags::Optimized as u32;
         self.register_cellvars(&mut make_function_flag);
@@ -1339,11 +1526,14 @@ impl PyCodeGenerator {
             self.stack_dec_n(defaults_len - 1);
             make_function_flag += MakeFunctionFlags::Defaults as usize;
         }
-        let flags = if lambda.params.var_params.is_some() {
+        let mut flags = if lambda.params.var_params.is_some() {
             CodeObjFlags::VarArgs as u32
         } else {
             0
         };
+        if lambda.params.kw_var_params.is_some() {
+            flags += CodeObjFlags::VarKeywords as u32;
+        }
         let code = self.emit_block(lambda.body, Some("<lambda>".into()), params, flags);
         self.register_cellvars(&mut make_function_flag);
         self.emit_load_const(code);
@@ -1874,6 +2064,8 @@ impl PyCodeGenerator {
         self.emit_expr(expr);
         let len = args.len();
         let mut jump_forward_points = vec![];
+        // (jump instruction point, jump target)
+        let mut pop_jump_targets = vec![];
         while let Some(expr) = args.try_remove(0) {
             if len > 1 && !args.is_empty() {
                 self.dup_top();
@@ -1891,25 +2083,92 @@ impl PyCodeGenerator {
             // so `self.stack_dec();` for now (+1 at the end).
             self.stack_dec();
             for pop_jump_point in pop_jump_points.into_iter() {
-                let idx = if self.py_version.minor >= Some(11) {
-                    self.lasti() - pop_jump_point // - 2
-                } else {
-                    self.lasti() + 2
-                };
-                self.calc_edit_jump(pop_jump_point + 1, idx); // jump to POP_TOP
+                // edited after all arms are emitted (jump to POP_TOP)
+                pop_jump_targets.push((pop_jump_point, self.lasti() + 2));
                 jump_forward_points.push(self.lasti());
                 self.write_instr(JUMP_FORWARD); // jump to the end
                 self.write_arg(0);
             }
         }
         let lasti = self.lasti();
-        for jump_point in jump_forward_points.into_iter() {
-            self.calc_edit_jump(jump_point + 1, lasti - jump_point - 1);
-        }
+        self.edit_match_jumps(pop_jump_targets, jump_forward_points, lasti);
         self.stack_inc();
         debug_assert_eq!(self.stack_len(), init_stack_len + 1);
     }
 
+    /// Edits the jumps of a match expression.
+    /// Extending a jump argument with `EXTENDED_ARG`s shifts the instructions behind it
+    /// (and may make other jumps need extending), so the arguments are settled first,
+    /// and then written from the back.
+    /// The pop jumps are absolute before 3.11, the forward jumps are always relative.
+    fn edit_match_jumps(
+        &mut self,
+        pop_jump_targets: Vec<(usize, usize)>,
+        jump_forward_points: Vec<usize>,
+        lasti: usize,
+    ) {
+        // `extend_arg` inserts 3 `EXTENDED_ARG`s
+        const EXTENDED_BYTES: usize = 6;
+        let pop_jump_is_absolute = self.py_version.minor < Some(11);
+        let unit = if self.py_version.minor >= Some(10) {
+            2
+        } else {
+            1
+        };
+        // (jump instruction point, jump target, is absolute)
+        let mut jumps = pop_jump_targets
+            .into_iter()
+            .map(|(point, target)| (point, target, pop_jump_is_absolute))
+            .chain(
+                jump_forward_points
+                    .into_iter()
+                    .map(|point| (point, lasti, false)),
+            )
+            .collect::<Vec<_>>();
+        jumps.sort();
+        let mut extended: Vec<usize> = vec![];
+        let args = loop {
+            let shift =
+                |pos: usize| extended.iter().filter(|point| **point < pos).count() * EXTENDED_BYTES;
+            let args = jumps
+                .iter()
+                .map(|&(point, target, absolute)| {
+                    let target = target + shift(target);
+                    if absolute {
+                        target / unit
+                    } else {
+                        // the jump instruction follows its own `EXTENDED_ARG`s
+                        (target - (point + shift(point + 1)) - 2) / unit
+                    }
+                })
+                .collect::<Vec<_>>();
+            let newly_extended = jumps
+                .iter()
+                .zip(args.iter())
+                .filter(|((point, ..), arg)| **arg > u8::MAX as usize && !extended.contains(point))
+                .map(|((point, ..), _)| *point)
+                .collect::<Vec<_>>();
+            if newly_extended.is_empty() {
+                break args;
+            }
+            extended.extend(newly_extended);
+        };
+        for ((point, ..), arg) in jumps.into_iter().zip(args).rev() {
+            if !CommonOpcode::is_jump_op(*self.cur_block_codeobj().code.get(point).unwrap()) {
+                self.crash(&format!("edit_match_jumps: not jump op: {point} {arg}"));
+            }
+            let bytes = u32::try_from(arg).unwrap().to_be_bytes();
+            *self
+                .mut_cur_block_codeobj()
+                .code
+                .get_mut(point + 1)
+                .unwrap() = bytes[3];
+            if arg > u8::MAX as usize {
+                self.extend_arg(point, &bytes);
+            }
+        }
+    }
+
     fn emit_match_pattern(
         &mut self,
         param: NonDefaultParamSignature,
@@ -2111,6 +2370,170 @@ impl PyCodeGenerator {
         self.emit_load_name_instr(stash);
     }
 
+    /// TOS: an object -> the result of awaiting it
+    /// `where_`: 0 (`await`), 1 (`__aenter__`), 2 (`__aexit__`)
+    fn emit_await(&mut self, where_: usize) {
+        if self.py_version.minor >= Some(11) {
+            self.write_instr(Opcode311::GET_AWAITABLE);
+            self.write_arg(where_);
+        } else {
+            self.write_instr(Opcode310::GET_AWAITABLE);
+            self.write_arg(0);
+        }
+        self.emit_yield_from_awaitable();
+    }
+
+    /// TOS: an awaitable object -> the result
+    fn emit_yield_from_awaitable(&mut self) {
+        self.emit_load_const(ValueObj::None);
+        if self.py_version.minor >= Some(11) {
+            self.write_instr(Opcode311::SEND);
+            self.write_arg(3);
+            self.write_instr(YIELD_VALUE);
+            self.write_arg(0);
+            self.write_instr(Opcode311::RESUME);
+            self.write_arg(3);
+            self.write_instr(Opcode311::JUMP_BACKWARD_NO_INTERRUPT);
+            self.write_arg(4);
+        } else {
+            self.write_instr(Opcode310::YIELD_FROM);
+            self.write_arg(0);
+        }
+        self.stack_dec();
+    }
+
+    fn emit_await_instr(&mut self, mut args: Args) {
+        log!(info "entered {}", fn_name!());
+        self.emit_expr(args.remove(0));
+        self.emit_await(0);
+    }
+
+    /// Stores the TOS to the parameters of `body` and runs it.
+    /// If `body` is not a lambda, it is called with the TOS.
+    fn emit_async_body(&mut self, body: Expr) {
+        match body {
+            Expr::Lambda(lambda) => {
+                let params = self.gen_param_names(&lambda.params);
+                self.emit_frameless_block(lambda.body, params);
+            }
+            other => {
+                let line = other.ln_begin().unwrap_or(0);
+                let stash = Identifier::private_with_line(Str::from(fresh_varname()), line);
+                self.emit_store_instr(stash.clone(), Name);
+                let arg = PosArg::new(Expr::Accessor(Accessor::Ident(stash)));
+                self.emit_expr(other.call_expr(Args::single(arg)));
+            }
+        }
+    }
+
+    fn emit_async_for_instr(&mut self, mut args: Args) {
+        log!(info "entered {} ({})", fn_name!(), args);
+        let _init_stack_len = self.stack_len();
+        let iterable = args.remove(0);
+        self.emit_expr(iterable);
+        self.write_instr(Opcode311::GET_AITER);
+        self.write_arg(0);
+        let idx_get_anext = self.lasti();
+        // the async iterator remains on the stack while looping
+        let depth = self.stack_len() as usize;
+        let idx_setup_finally = if self.py_version.minor >= Some(11) {
+            None
+        } else {
+            let idx_setup_finally = self.lasti();
+            self.write_instr(Opcode310::SETUP_FINALLY);
+            self.write_arg(0);
+            Some(idx_setup_finally)
+        };
+        self.write_instr(Opcode311::GET_ANEXT);
+        self.write_arg(0);
+        self.stack_inc();
+        self.emit_yield_from_awaitable();
+        let idx_await_end = self.lasti();
+        if self.py_version.minor < Some(11) {
+            self.write_instr(POP_BLOCK);
+            self.write_arg(0);
+        }
+        let init_stack_len = self.stack_len();
+        self.emit_async_body(args.remove(0));
+        if self.stack_len() > init_stack_len - 1 {
+            self.emit_pop_top();
+        }
+        debug_assert_eq!(self.stack_len(), init_stack_len - 1); // the async iterator is remained
+        match self.py_version.minor {
+            Some(11) => {
+                self.write_instr(Opcode311::JUMP_BACKWARD);
+                self.write_arg((self.lasti() - idx_get_anext + 2) / 2);
+            }
+            Some(10) => {
+                self.write_instr(Opcode310::JUMP_ABSOLUTE);
+                self.write_arg(idx_get_anext / 2);
+            }
+            Some(7..=9) => {
+                self.write_instr(Opcode308::JUMP_ABSOLUTE);
+                self.write_arg(idx_get_anext);
+            }
+            _ => todo!("not supported Python version"),
+        }
+        let idx_handler = self.lasti();
+        if let Some(idx_setup_finally) = idx_setup_finally {
+            let delta = idx_handler - idx_setup_finally - 2;
+            let arg = if self.py_version.minor >= Some(10) {
+                delta / 2
+            } else {
+                delta
+            };
+            self.edit_code(idx_setup_finally + 1, arg);
+            // the exception handler pushes 6 objects (see `SETUP_FINALLY`)
+            self.stack_inc_n(6);
+            self.stack_dec_n(6);
+        } else {
+            // `StopAsyncIteration` raised by `__anext__` is caught by `END_ASYNC_FOR`
+            self.mut_cur_block()
+                .exception_table
+                .push(ExceptionTableEntry {
+                    start: idx_get_anext,
+                    end: idx_await_end,
+                    target: idx_handler,
+                    depth,
+                });
+        }
+        self.write_instr(Opcode311::END_ASYNC_FOR);
+        self.write_arg(0);
+        self.stack_dec();
+        self.emit_load_const(ValueObj::None);
+        debug_assert_eq!(self.stack_len(), _init_stack_len + 1);
+    }
+
+    fn emit_async_with_instr(&mut self, mut args: Args) {
+        log!(info "entered {}", fn_name!());
+        let expr = args.remove(0);
+        let body = args.remove(0);
+        let line = body.ln_begin().unwrap_or(0);
+        self.emit_expr(expr);
+        self.write_instr(Opcode311::BEFORE_ASYNC_WITH);
+        self.write_arg(0);
+        // push __aexit__, __aenter__() to the stack
+        self.stack_inc();
+        self.emit_await(1);
+        self.emit_async_body(body);
+        let stash = Identifier::private_with_line(Str::from(fresh_varname()), line);
+        self.emit_store_instr(stash.clone(), Name);
+        self.emit_load_const(ValueObj::None);
+        self.emit_load_const(ValueObj::None);
+        self.emit_load_const(ValueObj::None);
+        if self.py_version.minor >= Some(11) {
+            self.emit_precall_and_call(2);
+        } else {
+            self.write_instr(Opcode310::CALL_FUNCTION);
+            self.write_arg(3);
+            self.stack_dec();
+        }
+        self.stack_dec_n(2);
+        self.emit_await(2);
+        self.emit_pop_top();
+        self.emit_load_name_instr(stash);
+    }
+
     fn emit_call(&mut self, call: Call) {
         log!(info "entered {} ({call})", fn_name!());
         let init_stack_len = self.stack_len();
@@ -2124,9 +2547,11 @@ impl PyCodeGenerator {
                 }
                 other => {
                     let is_py_api = other.is_py_api();
+                    let kw_var_keys = kw_var_keywords(other.ref_t(), &call.args);
+                    let kw_spread_params = kw_spread_params(other.ref_t(), &call.args, is_py_api);
                     self.emit_push_null();
                     self.emit_expr(other);
-                    self.emit_args_311(call.args, Name, is_py_api);
+                    self.emit_args_311(call.args, Name, is_py_api, kw_var_keys, kw_spread_params);
                 }
             }
         }
@@ -2144,6 +2569,9 @@ impl PyCodeGenerator {
             "while!" => self.emit_while_instr(args),
             "if" | "if!" => self.emit_if_instr(args),
             "match" | "match!" => self.emit_match_instr(args, true),
+            "await" => self.emit_await_instr(args),
+            "async_for!" => self.emit_async_for_instr(args),
+            "async_with!" => self.emit_async_with_instr(args),
             "with!" => match self.py_version.minor {
                 Some(11) => self.emit_with_instr_311(args),
                 Some(10) => self.emit_with_instr_310(args),
@@ -2153,9 +2581,11 @@ impl PyCodeGenerator {
             // "pyimport" | "py" are here
             _ => {
                 let is_py_api = local.is_py_api();
+                let kw_var_keys = kw_var_keywords(local.ref_t(), &args);
+                let kw_spread_params = kw_spread_params(local.ref_t(), &args, is_py_api);
                 self.emit_push_null();
                 self.emit_load_name_instr(local);
-                self.emit_args_311(args, Name, is_py_api);
+                self.emit_args_311(args, Name, is_py_api, kw_var_keys, kw_spread_params);
             }
         }
     }
@@ -2173,8 +2603,7 @@ impl PyCodeGenerator {
             "return" if obj.ref_t().is_callable() => {
                 return self.emit_return_instr(args);
             }
-            // TODO: create `Generator` type
-            "yield" /* if obj.ref_t().is_callable() */ => {
+            "yield" if obj.ref_t().is_callable() => {
                 return self.emit_yield_instr(args);
             }
             _ => {}
@@ -2183,57 +2612,178 @@ impl PyCodeGenerator {
             return self.emit_call_fake_method(obj, func_name, method_name, args);
         }
         let is_py_api = method_name.is_py_api();
+        let kw_var_keys = kw_var_keywords(method_name.ref_t(), &args);
+        let kw_spread_params = kw_spread_params(method_name.ref_t(), &args, is_py_api);
+        if args.has_spread() {
+            self.emit_push_null();
+            self.emit_expr(obj);
+            self.emit_load_attr_instr(method_name);
+            return self.emit_args_311(args, Name, is_py_api, kw_var_keys, kw_spread_params);
+        }
         self.emit_expr(obj);
         self.emit_load_method_instr(method_name);
-        self.emit_args_311(args, Method, is_py_api);
+        self.emit_args_311(args, Method, is_py_api, kw_var_keys, kw_spread_params);
     }
 
-    fn emit_var_args_311(&mut self, pos_len: usize, var_args: &PosArg) {
+    /// Builds the positional argument tuple of `CALL_FUNCTION_EX`.
+    fn emit_var_args_311(&mut self, pos_args: Vec<PosArg>, var_args: Option<Box<PosArg>>) {
+        let pos_len = pos_args.len();
+        for arg in pos_args {
+            self.emit_expr(arg.expr);
+        }
+        let Some(var_args) = var_args else {
+            self.write_instr(BUILD_TUPLE);
+            self.write_arg(pos_len);
+            if pos_len == 0 {
+                self.stack_inc();
+            } else {
+                self.stack_dec_n(pos_len - 1);
+            }
+            return;
+        };
         if pos_len > 0 {
             self.write_instr(BUILD_LIST);
             self.write_arg(pos_len);
+            self.stack_dec_n(pos_len - 1);
         }
-        self.emit_expr(var_args.expr.clone());
+        self.emit_expr(var_args.expr);
         if pos_len > 0 {
             self.write_instr(Opcode310::LIST_EXTEND);
             self.write_arg(1);
+            self.stack_dec();
             self.write_instr(Opcode310::LIST_TO_TUPLE);
             self.write_arg(0);
         }
     }
 
-    fn emit_var_args_38(&mut self, pos_len: usize, var_args: &PosArg) {
-        if pos_len > 0 {
+    fn emit_var_args_38(&mut self, pos_args: Vec<PosArg>, var_args: Option<Box<PosArg>>) {
+        let pos_len = pos_args.len();
+        for arg in pos_args {
+            self.emit_expr(arg.expr);
+        }
+        if pos_len > 0 || var_args.is_none() {
             self.write_instr(BUILD_TUPLE);
             self.write_arg(pos_len);
+            if pos_len == 0 {
+                self.stack_inc();
+            } else {
+                self.stack_dec_n(pos_len - 1);
+            }
         }
-        self.emit_expr(var_args.expr.clone());
-        if pos_len > 0 {
-            self.write_instr(Opcode308::BUILD_TUPLE_UNPACK_WITH_CALL);
-            self.write_arg(2);
+        if let Some(var_args) = var_args {
+            self.emit_expr(var_args.expr);
+            if pos_len > 0 {
+                self.write_instr(Opcode308::BUILD_TUPLE_UNPACK_WITH_CALL);
+                self.write_arg(2);
+                self.stack_dec();
+            }
+        }
+    }
+
+    /// Builds the keyword argument dict of `CALL_FUNCTION_EX`.
+    fn emit_kw_var_args_311(
+        &mut self,
+        kws: Vec<(Str, Expr)>,
+        kw_var_args: Option<Box<PosArg>>,
+        kw_spread_params: Set<Str>,
+    ) {
+        let kw_len = kws.len();
+        for (kw, expr) in kws {
+            self.emit_load_const(kw);
+            self.emit_expr(expr);
+        }
+        self.write_instr(BUILD_MAP);
+        self.write_arg(kw_len);
+        if kw_len == 0 {
+            self.stack_inc();
+        } else {
+            self.stack_dec_n(2 * kw_len - 1);
+        }
+        if let Some(kw_var_args) = kw_var_args {
+            self.emit_kw_spread(kw_var_args.expr, kw_spread_params);
+            self.write_instr(Opcode310::DICT_MERGE);
+            self.write_arg(1);
+            self.stack_dec();
+        }
+    }
+
+    fn emit_kw_var_args_38(
+        &mut self,
+        kws: Vec<(Str, Expr)>,
+        kw_var_args: Option<Box<PosArg>>,
+        kw_spread_params: Set<Str>,
+    ) {
+        let kw_len = kws.len();
+        let mut maps = 0;
+        for (kw, expr) in kws {
+            self.emit_load_const(kw);
+            self.emit_expr(expr);
+        }
+        if kw_len > 0 || kw_var_args.is_none() {
+            self.write_instr(BUILD_MAP);
+            self.write_arg(kw_len);
+            if kw_len == 0 {
+                self.stack_inc();
+            } else {
+                self.stack_dec_n(2 * kw_len - 1);
+            }
+            maps += 1;
+        }
+        if let Some(kw_var_args) = kw_var_args {
+            self.emit_kw_spread(kw_var_args.expr, kw_spread_params);
+            maps += 1;
+        }
+        if maps > 1 {
+            self.write_instr(Opcode308::BUILD_MAP_UNPACK_WITH_CALL);
+            self.write_arg(maps);
+            self.stack_dec_n(maps - 1);
+        }
+    }
+
+    /// The keys of `**kwargs` that refer to the parameters of an Erg subroutine must be escaped
+    /// `**kwargs` => `rename_kwargs(kwargs, ("x", ...), ("::x", ...))`
+    fn emit_kw_spread(&mut self, kwargs: Expr, params: Set<Str>) {
+        if params.is_empty() {
+            return self.emit_expr(kwargs);
         }
+        if !self.rename_kwargs_loaded {
+            self.load_rename_kwargs();
+        }
+        let (names, escaped): (Vec<_>, Vec<_>) = params
+            .into_iter()
+            .map(|name| {
+                let escaped = escape_name(&name, &VisibilityModifier::Private, 0, 0);
+                (ValueObj::Str(name), ValueObj::Str(escaped))
+            })
+            .unzip();
+        self.emit_push_null();
+        self.emit_load_name_instr(Identifier::private("#rename_kwargs"));
+        self.emit_expr(kwargs);
+        self.emit_load_const(names);
+        self.emit_load_const(escaped);
+        self.emit_call_instr(3, Name);
+        // (1 (subroutine) + argc) input objects -> 1 return object
+        self.stack_dec_n((1 + 3) - 1);
     }
 
-    fn emit_args_311(&mut self, mut args: Args, kind: AccessKind, is_py_api: bool) {
+    fn emit_args_311(
+        &mut self,
+        mut args: Args,
+        kind: AccessKind,
+        is_py_api: bool,
+        kw_var_keys: Set<Str>,
+        kw_spread_params: Set<Str>,
+    ) {
+        if args.has_spread() {
+            return self.emit_args_ex(args, is_py_api, kw_var_keys, kw_spread_params);
+        }
         let argc = args.len();
-        let pos_len = args.pos_args.len();
         let mut kws = Vec::with_capacity(args.kw_len());
         while let Some(arg) = args.try_remove_pos(0) {
             self.emit_expr(arg.expr);
         }
-        if let Some(var_args) = &args.var_args {
-            if self.py_version.minor >= Some(10) {
-                self.emit_var_args_311(pos_len, var_args);
-            } else {
-                self.emit_var_args_38(pos_len, var_args);
-            }
-        }
         while let Some(arg) = args.try_remove_kw(0) {
-            let kw = if is_py_api {
-                arg.keyword.content
-            } else {
-                escape_name(&arg.keyword.content, &VisibilityModifier::Private, 0, 0)
-            };
+            let kw = escape_keyword(arg.keyword.content, is_py_api, &kw_var_keys);
             kws.push(ValueObj::Str(kw));
             self.emit_expr(arg.expr);
         }
@@ -2246,22 +2796,52 @@ impl PyCodeGenerator {
                 1
             }
         } else {
-            if args.var_args.is_some() {
-                self.write_instr(CALL_FUNCTION_EX);
-                if kws.is_empty() {
-                    self.write_arg(0);
-                } else {
-                    self.write_arg(1);
-                }
-            } else {
-                self.emit_call_instr(argc, kind);
-            }
+            self.emit_call_instr(argc, kind);
             0
         };
         // (1 (subroutine) + argc + kwsc) input objects -> 1 return object
         self.stack_dec_n((1 + argc + kwsc) - 1);
     }
 
+    /// Calls with `*args` or `**kwargs` are compiled into `CALL_FUNCTION_EX`
+    /// (the callee must have been loaded by `LOAD_ATTR` rather than `LOAD_METHOD`).
+    fn emit_args_ex(
+        &mut self,
+        args: Args,
+        is_py_api: bool,
+        kw_var_keys: Set<Str>,
+        kw_spread_params: Set<Str>,
+    ) {
+        let kws = args
+            .kw_args
+            .into_iter()
+            .map(|arg| {
+                let kw = escape_keyword(arg.keyword.content, is_py_api, &kw_var_keys);
+                (kw, arg.expr)
+            })
+            .collect::<Vec<_>>();
+        let has_kws = !kws.is_empty() || args.kw_var_args.is_some();
+        if self.py_version.minor >= Some(9) {
+            self.emit_var_args_311(args.pos_args, args.var_args);
+            if has_kws {
+                self.emit_kw_var_args_311(kws, args.kw_var_args, kw_spread_params);
+            }
+        } else {
+            self.emit_var_args_38(args.pos_args, args.var_args);
+            if has_kws {
+                self.emit_kw_var_args_38(kws, args.kw_var_args, kw_spread_params);
+            }
+        }
+        self.write_instr(CALL_FUNCTION_EX);
+        self.write_arg(usize::from(has_kws));
+        // (1 (subroutine) + 1 (args) + 1 (kwargs)) input objects -> 1 return object
+        self.stack_dec_n(1 + usize::from(has_kws));
+        // NULL
+        if self.py_version.minor >= Some(11) {
+            self.stack_dec();
+        }
+    }
+
     /// X.update! x -> x + 1
     /// => X = mutate_operator((x -> x + 1)(X))
     /// TODO: should be `X = X + 1` in the above case
@@ -2332,6 +2912,10 @@ impl PyCodeGenerator {
         }
         self.write_instr(YIELD_VALUE);
         self.write_arg(0);
+        if self.py_version.minor >= Some(11) {
+            self.write_instr(Opcode311::RESUME);
+            self.write_arg(1);
+        }
     }
 
     /// 1.abs() => abs(1)
@@ -2348,7 +2932,7 @@ impl PyCodeGenerator {
         self.emit_push_null();
         self.emit_load_name_instr(method_name);
         args.insert_pos(0, PosArg::new(obj));
-        self.emit_args_311(args, Name, true);
+        self.emit_args_311(args, Name, true, Set::new(), Set::new());
     }
 
     // assert takes 1 or 2 arguments (0: cond, 1: message)
@@ -2398,6 +2982,9 @@ impl PyCodeGenerator {
             self.emit_load_name_instr(Identifier::public("Array"));
         }
         match array {
+            Array::Normal(arr) if arr.elems.pos_args.iter().any(|arg| is_spread(&arg.expr)) => {
+                self.emit_spread_elems(arr.elems.pos_args, false);
+            }
             Array::Normal(mut arr) => {
                 let len = arr.elems.len();
                 while let Some(arg) = arr.elems.try_remove_pos(0) {
@@ -2452,6 +3039,11 @@ impl PyCodeGenerator {
 
     fn emit_set(&mut self, set: crate::hir::Set) {
         match set {
+            crate::hir::Set::Normal(set)
+                if set.elems.pos_args.iter().any(|arg| is_spread(&arg.expr)) =>
+            {
+                self.emit_spread_elems(set.elems.pos_args, true);
+            }
             crate::hir::Set::Normal(mut set) => {
                 let len = set.elems.len();
                 while let Some(arg) = set.elems.try_remove_pos(0) {
@@ -2475,6 +3067,9 @@ impl PyCodeGenerator {
 
     fn emit_dict(&mut self, dict: crate::hir::Dict) {
         match dict {
+            crate::hir::Dict::Normal(dic) if !dic.spreads.is_empty() => {
+                self.emit_dict_with_spreads(dic);
+            }
             crate::hir::Dict::Normal(dic) => {
                 let len = dic.kvs.len();
                 for kv in dic.kvs.into_iter() {
@@ -2493,6 +3088,123 @@ impl PyCodeGenerator {
         }
     }
 
+    /// `[x, *xs, y]` => `BUILD_LIST 1 (x); LIST_EXTEND 1 (xs); LIST_APPEND 1 (y)`
+    /// (<= 3.8: `BUILD_TUPLE 1 (x); xs; BUILD_TUPLE 1 (y); BUILD_LIST_UNPACK 3`)
+    fn emit_spread_elems(&mut self, elems: Vec<PosArg>, is_set: bool) {
+        if self.py_version.minor >= Some(9) {
+            let leading = elems.iter().take_while(|arg| !is_spread(&arg.expr)).count();
+            let mut elems = elems.into_iter();
+            for arg in elems.by_ref().take(leading) {
+                self.emit_expr(arg.expr);
+            }
+            self.write_instr(if is_set { BUILD_SET } else { BUILD_LIST });
+            self.write_arg(leading);
+            if leading == 0 {
+                self.stack_inc();
+            } else {
+                self.stack_dec_n(leading - 1);
+            }
+            for arg in elems {
+                match arg.expr {
+                    Expr::UnaryOp(unary) if unary.op.is(TokenKind::PreStar) => {
+                        self.emit_expr(*unary.expr);
+                        if is_set {
+                            self.write_instr(Opcode310::SET_UPDATE);
+                        } else {
+                            self.write_instr(Opcode310::LIST_EXTEND);
+                        }
+                    }
+                    other => {
+                        self.emit_expr(other);
+                        if is_set {
+                            self.write_instr(Opcode310::SET_ADD);
+                        } else {
+                            self.write_instr(Opcode310::LIST_APPEND);
+                        }
+                    }
+                }
+                self.write_arg(1);
+                self.stack_dec();
+            }
+        } else {
+            let mut seqs = 0;
+            let mut chunk = 0;
+            for arg in elems {
+                match arg.expr {
+                    Expr::UnaryOp(unary) if unary.op.is(TokenKind::PreStar) => {
+                        if chunk > 0 {
+                            self.write_instr(BUILD_TUPLE);
+                            self.write_arg(chunk);
+                            self.stack_dec_n(chunk - 1);
+                            seqs += 1;
+                            chunk = 0;
+                        }
+                        self.emit_expr(*unary.expr);
+                        seqs += 1;
+                    }
+                    other => {
+                        self.emit_expr(other);
+                        chunk += 1;
+                    }
+                }
+            }
+            if chunk > 0 {
+                self.write_instr(BUILD_TUPLE);
+                self.write_arg(chunk);
+                self.stack_dec_n(chunk - 1);
+                seqs += 1;
+            }
+            if is_set {
+                self.write_instr(Opcode308::BUILD_SET_UNPACK);
+            } else {
+                self.write_instr(Opcode308::BUILD_LIST_UNPACK);
+            }
+            self.write_arg(seqs);
+            self.stack_dec_n(seqs - 1);
+        }
+    }
+
+    /// `{**d1, **d2, k: v}` => `BUILD_MAP 0; DICT_UPDATE 1 (d1); DICT_UPDATE 1 (d2); BUILD_MAP 1 (k: v); DICT_UPDATE 1`
+    /// (<= 3.8: `d1; d2; BUILD_MAP 1 (k: v); BUILD_MAP_UNPACK 3`)
+    fn emit_dict_with_spreads(&mut self, dic: crate::hir::NormalDict) {
+        let len = dic.kvs.len();
+        let new_version = self.py_version.minor >= Some(9);
+        if new_version {
+            self.write_instr(BUILD_MAP);
+            self.write_arg(0);
+            self.stack_inc();
+        }
+        let mut maps = dic.spreads.len();
+        for spread in dic.spreads {
+            self.emit_expr(spread);
+            if new_version {
+                self.write_instr(Opcode310::DICT_UPDATE);
+                self.write_arg(1);
+                self.stack_dec();
+            }
+        }
+        if len > 0 {
+            for kv in dic.kvs {
+                self.emit_expr(kv.key);
+                self.emit_expr(kv.value);
+            }
+            self.write_instr(BUILD_MAP);
+            self.write_arg(len);
+            self.stack_dec_n(2 * len - 1);
+            if new_version {
+                self.write_instr(Opcode310::DICT_UPDATE);
+                self.write_arg(1);
+                self.stack_dec();
+            }
+            maps += 1;
+        }
+        if !new_version {
+            self.write_instr(Opcode308::BUILD_MAP_UNPACK);
+            self.write_arg(maps);
+            self.stack_dec_n(maps - 1);
+        }
+    }
+
     #[allow(clippy::identity_op)]
     fn emit_record(&mut self, rec: Record) {
         log!(info "entered {} ({rec})", fn_name!());
@@ -2649,8 +3361,9 @@ impl PyCodeGenerator {
     fn emit_expr(&mut self, expr: Expr) {
         log!(info "entered {} ({expr})", fn_name!());
         self.push_lnotab(&expr);
-        let mut wrapped = true;
-        if !self.cfg.no_std {
+        // `Array(arr)` makes a copy, so the update by `arr.map!` (`arr: [Int!; N]`) would be lost
+        let mut wrapped = !(matches!(expr, Expr::Accessor(_)) && expr.ref_t().has_mut_content());
+        if !self.cfg.no_std && wrapped {
             match expr.ref_t().derefine() {
                 Bool => {
                     self.emit_push_null();
@@ -2783,9 +3496,20 @@ impl PyCodeGenerator {
         self.emit_store_instr(Identifier::public("__module__"), Name);
         self.emit_load_const(name);
         self.emit_store_instr(Identifier::public("__qualname__"), Name);
-        self.emit_init_method(&class.sig, class.__new__.clone());
-        if class.need_to_gen_new {
-            self.emit_new_func(&class.sig, class.__new__);
+        match &class.obj {
+            // an enum itself cannot be instantiated
+            GenTypeObj::Enum(_) => {}
+            GenTypeObj::Subclass(sub)
+                if matches!(sub.sup.as_ref(), TypeObj::Generated(GenTypeObj::Enum(_))) =>
+            {
+                self.emit_init_method(&class.sig, class.__new__.clone(), true);
+            }
+            _ => {
+                self.emit_init_method(&class.sig, class.__new__.clone(), false);
+                if class.need_to_gen_new {
+                    self.emit_new_func(&class.sig, class.__new__);
+                }
+            }
         }
         if !class.methods.is_empty() {
             self.emit_frameless_block(class.methods, vec![]);
@@ -2826,7 +3550,8 @@ impl PyCodeGenerator {
         unit.codeobj
     }
 
-    fn emit_init_method(&mut self, sig: &Signature, __new__: Type) {
+    /// If `is_variant` is true, the argument is also stored as `self::base` (even if it is a record)
+    fn emit_init_method(&mut self, sig: &Signature, __new__: Type, is_variant: bool) {
         log!(info "entered {}", fn_name!());
         let new_first_param = __new__.non_default_params().unwrap().first();
         let line = sig.ln_begin().unwrap_or(0);
@@ -2856,7 +3581,7 @@ impl PyCodeGenerator {
                 "?".into(),
             );
             let param = NonDefaultParamSignature::new(raw, vi, None);
-            let params = Params::new(vec![self_param, param], None, vec![], None);
+            let params = Params::new(vec![self_param, param], None, vec![], None, None);
             (param_name, params)
         } else {
             ("_".into(), Params::single(self_param))
@@ -2878,7 +3603,7 @@ impl PyCodeGenerator {
                     let ident = erg_parser::ast::Identifier::public(field.symbol.clone());
                     let expr = obj.attr_expr(Identifier::bare(ident));
                     let obj = Expr::Accessor(Accessor::private_with_line(Str::ever("self"), line));
-                    let dot = if field.vis.is_private() {
+                    let dot = if field.vis.is_private() && !is_variant {
                         VisModifierSpec::Private
                     } else {
                         VisModifierSpec::Public(DOT)
@@ -2891,6 +3616,14 @@ impl PyCodeGenerator {
                     let redef = ReDef::new(attr, Block::new(vec![expr]));
                     attrs.push(Expr::ReDef(redef));
                 }
+                if is_variant {
+                    let expr =
+                        Expr::Accessor(Accessor::private_with_line(Str::from(&param_name), line));
+                    let obj = Expr::Accessor(Accessor::private_with_line(Str::ever("self"), line));
+                    let attr = obj.attr(Identifier::private_with_line(Str::ever("base"), line));
+                    let redef = ReDef::new(attr, Block::new(vec![expr]));
+                    attrs.push(Expr::ReDef(redef));
+                }
             }
             // self::base = %x
             Some(_) => {
@@ -2992,10 +3725,26 @@ impl PyCodeGenerator {
             let idx_copy_free_vars = self.lasti();
             self.write_instr(Opcode311::COPY_FREE_VARS);
             self.write_arg(0);
+            if flags & (CodeObjFlags::Generator as u32 | CodeObjFlags::Coroutine as u32) != 0 {
+                self.write_instr(Opcode311::RETURN_GENERATOR);
+                self.write_arg(0);
+                self.write_instr(Opcode311::POP_TOP);
+                self.write_arg(0);
+            }
             self.write_instr(Opcode311::RESUME);
             self.write_arg(0);
             idx_copy_free_vars
         } else {
+            if self.py_version.minor == Some(10) {
+                // 0: generator, 1: coroutine
+                if flags & CodeObjFlags::Generator as u32 != 0 {
+                    self.write_instr(Opcode310::GEN_START);
+                    self.write_arg(0);
+                } else if flags & CodeObjFlags::Coroutine as u32 != 0 {
+                    self.write_instr(Opcode310::GEN_START);
+                    self.write_arg(1);
+                }
+            }
             0
         };
         let init_stack_len = self.stack_len();
@@ -3043,7 +3792,11 @@ impl PyCodeGenerator {
             self.edit_code(idx_copy_free_vars, CommonOpcode::NOP as usize);
         }
         // end of flagging
-        let unit = self.units.pop().unwrap();
+        let mut unit = self.units.pop().unwrap();
+        if !unit.exception_table.is_empty() {
+            unit.exception_table.sort_by_key(|entry| entry.start);
+            unit.codeobj.exceptiontable = encode_exception_table(&unit.exception_table);
+        }
         // increase lineno
         if !self.units.is_empty() {
             let ld = unit
@@ -3095,6 +3848,18 @@ impl PyCodeGenerator {
         self.mutate_op_loaded = true;
     }
 
+    fn load_rename_kwargs(&mut self) {
+        let mod_name = Identifier::public("_erg_std_prelude");
+        self.emit_global_import_items(
+            mod_name,
+            vec![(
+                Identifier::public("rename_kwargs"),
+                Some(Identifier::private("#rename_kwargs")),
+            )],
+        );
+        self.rename_kwargs_loaded = true;
+    }
+
     fn load_control(&mut self) {
         let mod_name = Identifier::public("_erg_control");
         self.emit_import_all_instr(mod_name);
@@ -193,9 +193,14 @@ impl Compiler {
         src: String,
         mode: &str,
     ) -> Result<CompileWarnings, ErrorArtifact> {
-        let arti = self.compile(src, mode)?;
+        let arti = self.compile(src.clone(), mode)?;
         arti.object
-            .dump_as_pyc(pyc_path, self.cfg.py_magic_num)
+            .dump_as_pyc(
+                pyc_path,
+                &src,
+                self.cfg.py_magic_num,
+                self.cfg.pyc_invalidation_mode,
+            )
             .expect("failed to dump a .pyc file (maybe permission denied)");
         Ok(arti.warns)
     }
@@ -206,10 +211,15 @@ impl Compiler {
         src: String,
         mode: &str,
     ) -> Result<CompleteArtifact<Option<Expr>>, ErrorArtifact> {
-        let arti = self.eval_compile(src, mode)?;
+        let arti = self.eval_compile(src.clone(), mode)?;
         let (code, last) = arti.object;
-        code.dump_as_pyc(pyc_path, self.cfg.py_magic_num)
-            .expect("failed to dump a .pyc file (maybe permission denied)");
+        code.dump_as_pyc(
+            pyc_path,
+            &src,
+            self.cfg.py_magic_num,
+            self.cfg.pyc_invalidation_mode,
+        )
+        .expect("failed to dump a .pyc file (maybe permission denied)");
         Ok(CompleteArtifact::new(last, arti.warns))
     }
 
@@ -20,7 +20,7 @@ use TyParamOrdering::*;
 use Type::*;
 
 use crate::context::cache::{SubtypePair, GLOBAL_TYPE_CACHE};
-use crate::context::{Context, Variance};
+use crate::context::{Context, TraitImpl, Variance};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Credibility {
@@ -112,10 +112,19 @@ impl Context {
             (l, r) if l == r => {
                 return true;
             }
+            // e.g. `Nat(1) == Int(1)` (`Int(1)` is the result of `N - 1`)
+            (
+                TyParam::Value(l @ (ValueObj::Nat(_) | ValueObj::Int(_))),
+                TyParam::Value(r @ (ValueObj::Nat(_) | ValueObj::Int(_))),
+            ) => {
+                return l.try_cmp(r) == Some(std::cmp::Ordering::Equal);
+            }
             (l, r) if l.has_unbound_var() || r.has_unbound_var() => {
-                let lt = self.get_tp_t(l).unwrap();
-                let rt = self.get_tp_t(r).unwrap();
-                return self.same_type_of(&lt, &rt);
+                return match (self.get_tp_t(l), self.get_tp_t(r)) {
+                    (Ok(lt), Ok(rt)) => self.same_type_of(&lt, &rt),
+                    // e.g. `1 == ?N - 1` (can be fixed by `?N := 2`)
+                    _ => true,
+                };
             }
             _ => {}
         }
@@ -230,6 +239,13 @@ impl Context {
     /// make judgments that include supertypes in the same namespace & take into account glue patches
     /// 同一名前空間にある上位型を含めた判定&接着パッチを考慮した判定を行う
     fn nominal_supertype_of(&self, lhs: &Type, rhs: &Type) -> bool {
+        // glue patches are only available where they are visible, so the result is not cached
+        if self.is_trait(lhs)
+            && !self.is_trait(rhs)
+            && self.find_compatible_glue_patch(lhs, rhs).is_some()
+        {
+            return true;
+        }
         if let Some(res) = self.inquire_cache(lhs, rhs) {
             return res;
         }
@@ -253,18 +269,21 @@ impl Context {
         &'a self,
         typ: &'a Type,
     ) -> impl Iterator<Item = &'a Context> {
-        self.all_patches().into_iter().filter(move |ctx| {
-            if let ContextKind::Patch(base) = &ctx.kind {
-                return self.supertype_of(base, typ);
-            }
-            false
-        })
+        self.all_patches()
+            .into_iter()
+            .filter(move |ctx| match &ctx.kind {
+                ContextKind::Patch(base) => self.supertype_of(base, typ),
+                ContextKind::GluePatch(tr_impl) => self.supertype_of(&tr_impl.sub_type, typ),
+                _ => false,
+            })
     }
 
-    fn _find_compatible_glue_patch(&self, sup: &Type, sub: &Type) -> Option<&Context> {
+    /// Builtin glue patches are not considered here (they are registered as `TraitImpl`s)
+    fn find_compatible_glue_patch(&self, sup: &Type, sub: &Type) -> Option<&Context> {
         for patch in self.all_patches().into_iter() {
             if let ContextKind::GluePatch(tr_impl) = &patch.kind {
-                if self.subtype_of(sub, &tr_impl.sub_type)
+                if tr_impl.glue_patch.is_some()
+                    && self.subtype_of(sub, &tr_impl.sub_type)
                     && self.subtype_of(&tr_impl.sup_trait, sup)
                 {
                     return Some(patch);
@@ -274,6 +293,31 @@ impl Context {
         None
     }
 
+    /// Returns a visible glue patch that associates the same trait with `tr_impl.sub_type` (or its super/sub class).
+    /// ```erg
+    /// StrReverse = Patch Str, Impl := Reverse
+    /// NumStrRev = Patch NumericStr, Impl := Reverse # conflicts with `StrReverse`
+    /// ```
+    pub(crate) fn get_conflicting_glue_patch(
+        &self,
+        tr_impl: &TraitImpl,
+    ) -> Option<(&TraitImpl, &Context)> {
+        self.all_patches().into_iter().find_map(|patch| {
+            let ContextKind::GluePatch(other) = &patch.kind else {
+                return None;
+            };
+            if other != tr_impl
+                && self.same_type_of(&other.sup_trait, &tr_impl.sup_trait)
+                && (self.subtype_of(&tr_impl.sub_type, &other.sub_type)
+                    || self.subtype_of(&other.sub_type, &tr_impl.sub_type))
+            {
+                Some((other, patch))
+            } else {
+                None
+            }
+        })
+    }
+
     fn classes_supertype_of(&self, lhs: &Type, rhs: &Type) -> (Credibility, bool) {
         if !self.is_class(lhs) || !self.is_class(rhs) {
             return (Maybe, false);
@@ -333,6 +377,22 @@ impl Context {
     /// 単一化、評価等はここでは行わない、スーパータイプになる可能性があるかだけ判定する
     /// ので、lhsが(未連携)型変数の場合は単一化せずにtrueを返す
     pub(crate) fn structural_supertype_of(&self, lhs: &Type, rhs: &Type) -> bool {
+        if let Some(cands) = self.get_type_app_candidates(lhs) {
+            // List(?T, ?N) :> List(Int, 1)
+            if lhs.qual_name() == rhs.qual_name() {
+                return lhs
+                    .typarams()
+                    .iter()
+                    .zip(rhs.typarams().iter())
+                    .all(|(l, r)| self.supertype_of_tp(l, r, Variance::Invariant));
+            }
+            // List(?T, ?N) :> Nil(Int) (a case of `List` may be `Nil(?T)`)
+            return cands.iter().any(|cand| self.supertype_of(cand, rhs));
+        }
+        // Cons(?T, ?N) :> List(?T, ?N) only if all cases are subtypes of `Cons(?T, ?N)`
+        if let Some(cands) = self.get_type_app_candidates(rhs) {
+            return !cands.is_empty() && cands.iter().all(|cand| self.supertype_of(lhs, cand));
+        }
         match (lhs, rhs) {
             // Proc :> Func if params are compatible
             // * default params can be omitted (e.g. (Int, x := Int) -> Int <: (Int) -> Int)
@@ -369,10 +429,17 @@ impl Context {
                     .zip(rs.var_params.as_ref())
                     .map(|(l, r)| self.subtype_of(l.typ(), r.typ()))
                     .unwrap_or(true);
+                let kw_var_params_judge = ls
+                    .kw_var_params
+                    .as_ref()
+                    .zip(rs.kw_var_params.as_ref())
+                    .map(|(l, r)| self.subtype_of(l.typ(), r.typ()))
+                    .unwrap_or(true);
                 same_params_len
                     && return_t_judge
                     && non_defaults_judge
                     && var_params_judge
+                    && kw_var_params_judge
                     && default_check() // contravariant
             }
             // ?T(<: Nat) !:> ?U(:> Int)
@@ -389,6 +456,56 @@ impl Context {
                     }
                 }
             },
+            (
+                ProjCall {
+                    lhs: l_ctor,
+                    attr_name: l_attr,
+                    args: l_args,
+                },
+                ProjCall {
+                    lhs: r_ctor,
+                    attr_name: r_attr,
+                    args: r_args,
+                },
+            ) if &l_attr[..] == "__call__" && &r_attr[..] == "__call__" => {
+                self.supertype_of_tp(l_ctor, r_ctor, Variance::Invariant)
+                    && self.type_args_supertype_of(l_args, r_args)
+            }
+            // ?F(?T) :> Array(Int, 2) (can be fixed by `?F := Array(_: Type, 2)` and `?T := Int`)
+            (
+                ProjCall {
+                    lhs: ctor,
+                    attr_name,
+                    args,
+                },
+                _,
+            ) if &attr_name[..] == "__call__" => {
+                if let Some(t) = <&Type>::try_from(ctor.as_ref())
+                    .ok()
+                    .and_then(|ctor| ctor.apply_type_ctor(args))
+                {
+                    return self.supertype_of(&t, rhs);
+                }
+                rhs.split_type_ctor(args.len())
+                    .is_some_and(|(_, r_args)| self.type_args_supertype_of(args, &r_args))
+            }
+            (
+                _,
+                ProjCall {
+                    lhs: ctor,
+                    attr_name,
+                    args,
+                },
+            ) if &attr_name[..] == "__call__" => {
+                if let Some(t) = <&Type>::try_from(ctor.as_ref())
+                    .ok()
+                    .and_then(|ctor| ctor.apply_type_ctor(args))
+                {
+                    return self.supertype_of(lhs, &t);
+                }
+                lhs.split_type_ctor(args.len())
+                    .is_some_and(|(_, l_args)| self.type_args_supertype_of(&l_args, args))
+            }
             (_, Proj { .. }) => {
                 if let Some(cands) = self.get_candidates(rhs) {
                     for cand in cands.into_iter() {
@@ -623,6 +740,19 @@ impl Context {
             }
             // not Nat :> not Int == true
             (Not(l), Not(r)) => self.subtype_of(l, r),
+            // (Expr.Lit or Expr.Add) :> Expr == true (if `Expr = Enum {.Lit = ...; .Add = ...}`)
+            (Or(_, _), rhs)
+                if self.get_enum_variants(rhs).is_some_and(|variants| {
+                    // each variant must be covered by one of the union members
+                    // (`lhs :> variant` would recurse into this arm via `variant <: rhs`)
+                    let ors = lhs.union_types();
+                    variants
+                        .iter()
+                        .all(|variant| ors.iter().any(|l| self.supertype_of(l, variant)))
+                }) =>
+            {
+                true
+            }
             // (Int or Str) :> Nat == Int :> Nat || Str :> Nat == true
             // (Num or Show) :> Show == Num :> Show || Show :> Num == true
             (Or(l_or, r_or), rhs) => self.supertype_of(l_or, rhs) || self.supertype_of(r_or, rhs),
@@ -768,6 +898,16 @@ impl Context {
             .all(|((lp, rp), variance)| self.supertype_of_tp(lp, rp, *variance))
     }
 
+    /// The arguments of a higher-kinded type variable are compared invariantly,
+    /// because the variance of the type constructor is not known.
+    fn type_args_supertype_of(&self, l_args: &[TyParam], r_args: &[TyParam]) -> bool {
+        l_args.len() == r_args.len()
+            && l_args
+                .iter()
+                .zip(r_args.iter())
+                .all(|(l, r)| self.supertype_of_tp(l, r, Variance::Invariant))
+    }
+
     fn supertype_of_tp(&self, lp: &TyParam, rp: &TyParam, variance: Variance) -> bool {
         if lp == rp {
             return true;
@@ -779,6 +919,13 @@ impl Context {
             (_, TyParam::FreeVar(fv), _) if fv.is_linked() => {
                 self.supertype_of_tp(lp, &fv.crack(), variance)
             }
+            // e.g. `Generator(Nat, NoneType, Str)` (spec) :> `Generator({1}, NoneType, {"a"})`
+            (TyParam::Value(ValueObj::Type(l)), _, _) => {
+                self.supertype_of_tp(&TyParam::t(l.typ().clone()), rp, variance)
+            }
+            (_, TyParam::Value(ValueObj::Type(r)), _) => {
+                self.supertype_of_tp(lp, &TyParam::t(r.typ().clone()), variance)
+            }
             // _: Type :> T == true
             (TyParam::Erased(t), TyParam::Type(_), _)
             | (TyParam::Type(_), TyParam::Erased(t), _)
@@ -830,7 +977,10 @@ impl Context {
                 } else if variance == Variance::Covariant {
                     self.supertype_of(&fv_t, &rp_t)
                 } else {
-                    self.same_type_of(&fv_t, &rp_t) || self.same_type_of(&fv_t, &rp_t.derefine())
+                    self.same_type_of(&fv_t, &rp_t)
+                        || self.same_type_of(&fv_t, &rp_t.derefine())
+                        // e.g. `?N: Nat` :> `1: Int` (the result of `N - 1`)
+                        || (matches!(rp, TyParam::Value(_)) && self.subtype_of(&rp_t, &fv_t))
                 }
             }
             _ => self.eq_tp(lp, rp),
@@ -1117,7 +1267,10 @@ impl Context {
                 (true, false) => rhs.clone(), // lhs :> rhs
                 (false, true) => lhs.clone(),
                 (false, false) => {
-                    if self.is_trait(lhs) && self.is_trait(rhs) {
+                    // (Int -> Int) and (Str -> Str) is an overloaded subroutine type
+                    let is_subr = |t: &Type| t.is_subr() || t.is_overloaded();
+                    if (self.is_trait(lhs) && self.is_trait(rhs)) || (is_subr(lhs) && is_subr(rhs))
+                    {
                         and(lhs.clone(), rhs.clone())
                     } else {
                         Type::Never
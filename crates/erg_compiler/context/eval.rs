@@ -16,8 +16,8 @@ use erg_parser::ast::*;
 use erg_parser::token::{Token, TokenKind};
 
 use crate::ty::constructors::{
-    array_t, dict_t, mono, poly, proj, proj_call, ref_, ref_mut, refinement, subr_t, tuple_t,
-    v_enum,
+    array_mut, array_t, dict_t, mono, poly, proj, proj_call, ref_, ref_mut, refinement, subr_t,
+    tuple_t, v_enum,
 };
 use crate::ty::free::{FreeTyVar, HasLevel};
 use crate::ty::typaram::{OpKind, TyParam};
@@ -51,6 +51,7 @@ pub fn type_from_token_kind(kind: TokenKind) -> Type {
         IntLit => Type::Int,
         RatioLit => Type::Ratio,
         StrLit | DocComment => Type::Str,
+        BytesLit => mono("Bytes"),
         BoolLit => Type::Bool,
         NoneLit => Type::NoneType,
         NoImplLit => Type::NotImplementedType,
@@ -376,6 +377,38 @@ impl Context {
                 }
                 Ok(ValueObj::Array(RcArray::from(elems)))
             }
+            Array::WithLength(arr) => self.eval_const_array_with_length(arr),
+            _ => Err(EvalErrors::from(EvalError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                arr.loc(),
+                self.caused_by(),
+            ))),
+        }
+    }
+
+    /// Only array types can be evaluated, e.g.
+    /// `[Int; 3]` == `Array(Int, 3)`, `[Int!; 3]` == `Array(Int!, 3)`, `[Int; !3]` == `Array!(Int, 3)`
+    pub(crate) fn eval_const_array_with_length(
+        &self,
+        arr: &ArrayWithLength,
+    ) -> EvalResult<ValueObj> {
+        let elem = self.eval_const_expr(&arr.elem.expr)?;
+        let (len, telescoping) = match arr.len.as_ref() {
+            Expr::UnaryOp(unary) if unary.op.is(TokenKind::Mutate) => {
+                (self.eval_const_expr(&unary.args[0])?, true)
+            }
+            len => (self.eval_const_expr(len)?, false),
+        };
+        match (elem, len) {
+            (ValueObj::Type(elem), len @ ValueObj::Nat(_)) => {
+                let t = if telescoping {
+                    array_mut(elem.into_typ(), TyParam::Value(len))
+                } else {
+                    array_t(elem.into_typ(), TyParam::Value(len))
+                };
+                Ok(ValueObj::builtin_class(t))
+            }
             _ => Err(EvalErrors::from(EvalError::not_const_expr(
                 self.cfg.input.clone(),
                 line!() as usize,
@@ -407,7 +440,7 @@ impl Context {
     fn eval_const_dict(&self, dict: &AstDict) -> EvalResult<ValueObj> {
         let mut elems = dict! {};
         match dict {
-            AstDict::Normal(dic) => {
+            AstDict::Normal(dic) if dic.spreads.is_empty() => {
                 for elem in dic.kvs.iter() {
                     let key = self.eval_const_expr(&elem.key)?;
                     let value = self.eval_const_expr(&elem.value)?;
@@ -439,6 +472,14 @@ impl Context {
 
     fn eval_const_record(&self, record: &Record) -> EvalResult<ValueObj> {
         match record {
+            Record::Normal(rec) if !rec.spreads.is_empty() => {
+                Err(EvalErrors::from(EvalError::not_const_expr(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    record.loc(),
+                    self.caused_by(),
+                )))
+            }
             Record::Normal(rec) => self.eval_const_normal_record(rec),
             Record::Mixed(_rec) => unreachable_error!(self), // should be desugared
         }
@@ -512,6 +553,18 @@ impl Context {
             )?;
             default_params.push(pt);
         }
+        let kw_var_params = if let Some(p) = lambda.sig.params.kw_var_params.as_ref() {
+            let pt = self.instantiate_param_ty(
+                p,
+                None,
+                &mut tmp_tv_cache,
+                RegistrationMode::Normal,
+                ParamKind::KwParams,
+            )?;
+            Some(pt)
+        } else {
+            None
+        };
         // HACK: should avoid cloning
         let mut lambda_ctx = Context::instant(
             Str::ever("<lambda>"),
@@ -526,6 +579,7 @@ impl Context {
             non_default_params.clone(),
             var_params,
             default_params.clone(),
+            kw_var_params,
             return_t,
         );
         let block =
@@ -842,12 +896,29 @@ impl Context {
             }
             (lhs @ TyParam::FreeVar(_), rhs) => Ok(TyParam::bin(op, lhs, rhs)),
             (lhs, rhs @ TyParam::FreeVar(_)) => Ok(TyParam::bin(op, lhs, rhs)),
-            (e @ TyParam::Erased(_), _) | (_, e @ TyParam::Erased(_)) => Ok(e),
+            // _: Nat - 1 => _: Int
+            (TyParam::Erased(t), rhs) => Ok(TyParam::erased(
+                self.erased_bin_op_t(op, &t, &self.get_tp_t(&rhs).unwrap_or(Type::Obj))
+                    .unwrap_or(*t),
+            )),
+            (lhs, TyParam::Erased(t)) => Ok(TyParam::erased(
+                self.erased_bin_op_t(op, &self.get_tp_t(&lhs).unwrap_or(Type::Obj), &t)
+                    .unwrap_or(*t),
+            )),
             (l, r) => feature_error!(self, Location::Unknown, &format!("{l:?} {op} {r:?}"))
                 .map_err(Into::into),
         }
     }
 
+    fn erased_bin_op_t(&self, op: OpKind, lhs_t: &Type, rhs_t: &Type) -> Option<Type> {
+        let both = |t: &Type| self.subtype_of(lhs_t, t) && self.subtype_of(rhs_t, t);
+        match op {
+            OpKind::Add | OpKind::Mul if both(&Type::Nat) => Some(Type::Nat),
+            OpKind::Add | OpKind::Sub | OpKind::Mul if both(&Type::Int) => Some(Type::Int),
+            _ => None,
+        }
+    }
+
     fn eval_unary_val(&self, op: OpKind, val: ValueObj) -> EvalResult<ValueObj> {
         match op {
             Pos => Err(EvalErrors::from(EvalError::unreachable(
@@ -943,6 +1014,128 @@ impl Context {
         }
     }
 
+    /// Apply a type-level function defined by cases.
+    /// Returns `None` if it cannot be determined which case matches yet.
+    /// e.g. `List(Int, 0)` => `Nil(Int)`, `List(Int, 2)` => `Cons(Int, 2)`, `List(T, N-1)` => `None`
+    pub(crate) fn eval_type_app(
+        &self,
+        subr: &UserConstSubr,
+        args: &[TyParam],
+        t_loc: &impl Locational,
+    ) -> EvalResult<Option<Type>> {
+        for (params, block) in subr.cases() {
+            let mut tv_cache = TyVarCache::new(self.level, self);
+            match self.bind_type_app_case(params, args, &mut tv_cache)? {
+                Some(undetermined) if undetermined.is_empty() => {
+                    return self
+                        .instantiate_type_app_body(block, &mut tv_cache)
+                        .map(Some);
+                }
+                // e.g. `N-1` may or may not be `0`
+                Some(_) => return Ok(None),
+                None => {}
+            }
+        }
+        Err(EvalErrors::from(EvalError::no_candidate_error(
+            self.cfg.input.clone(),
+            line!() as usize,
+            &poly(subr.name().clone(), args.to_vec()),
+            t_loc.loc(),
+            self.caused_by(),
+            None,
+        )))
+    }
+
+    /// Returns the cases that a type-level function application may be evaluated to.
+    /// e.g. `List(?T, ?N)` => `[Nil(?T), Cons(?T, ?N)]`, `List(Int, 0)` => `[Nil(Int)]`
+    pub(crate) fn get_type_app_candidates(&self, app: &Type) -> Option<Vec<Type>> {
+        let Type::Poly { name, params: args } = app else {
+            return None;
+        };
+        let Some(ValueObj::Subr(ConstSubr::User(subr))) = self.rec_get_const_obj(name) else {
+            return None;
+        };
+        let mut cands = vec![];
+        for (params, block) in subr.cases() {
+            let mut tv_cache = TyVarCache::new(self.level, self);
+            let Ok(Some(undetermined)) = self.bind_type_app_case(params, args, &mut tv_cache)
+            else {
+                continue;
+            };
+            if let Ok(t) = self.instantiate_type_app_body(block, &mut tv_cache) {
+                cands.push(t);
+            }
+            if undetermined.is_empty() {
+                break;
+            }
+        }
+        Some(cands)
+    }
+
+    /// Bind the parameters of a case to `args`. Returns `None` if the case does not match.
+    /// The refinement patterns whose arguments are not determined yet are returned,
+    /// e.g. `({0}, N-1)` for `List T, 0 = ...` and `List(T, N-1)`
+    pub(crate) fn bind_type_app_case(
+        &self,
+        params: &Params,
+        args: &[TyParam],
+        tv_cache: &mut TyVarCache,
+    ) -> EvalResult<Option<Vec<(Type, TyParam)>>> {
+        if params.non_defaults.len() != args.len() {
+            return Ok(None);
+        }
+        let mut undetermined = vec![];
+        for (param, arg) in params.non_defaults.iter().zip(args) {
+            if let Some(spec) = param.t_spec.as_ref() {
+                let spec_t = self.instantiate_typespec(
+                    &spec.t_spec,
+                    None,
+                    tv_cache,
+                    RegistrationMode::Normal,
+                    false,
+                )?;
+                if arg.has_qvar() || arg.has_unbound_var() {
+                    if spec_t.is_refinement() {
+                        undetermined.push((spec_t, arg.clone()));
+                    }
+                } else if let Some(value) = spec_t.singleton_value() {
+                    // `eq_tp` is used because `N-1` may be an `Int` value
+                    if !self.eq_tp(value, arg) {
+                        return Ok(None);
+                    }
+                } else if !self.subtype_of(&self.get_tp_t(arg)?, &spec_t) {
+                    return Ok(None);
+                }
+            }
+            if let Some(name) = param.inspect() {
+                if let Ok(t) = <&Type>::try_from(arg) {
+                    tv_cache.tyvar_instances.insert(name.clone(), t.clone());
+                } else {
+                    tv_cache.typaram_instances.insert(name.clone(), arg.clone());
+                }
+            }
+        }
+        Ok(Some(undetermined))
+    }
+
+    /// The body of a type-level function is instantiated as a type specification
+    pub(crate) fn instantiate_type_app_body(
+        &self,
+        block: &ConstBlock,
+        tv_cache: &mut TyVarCache,
+    ) -> EvalResult<Type> {
+        let body = block.last().unwrap().clone().downcast();
+        let spec = erg_parser::Parser::expr_to_type_spec(body.clone()).map_err(|_| {
+            EvalErrors::from(EvalError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                body.loc(),
+                self.caused_by(),
+            ))
+        })?;
+        self.instantiate_typespec(&spec, None, tv_cache, RegistrationMode::Normal, false)
+    }
+
     /// Evaluate `substituted`.
     /// If the evaluation fails, return a harmless type (filled with `Failure`) and errors
     pub(crate) fn eval_t_params(
@@ -980,12 +1173,20 @@ impl Context {
                         Err((_, errs)) => return Err((Subr(subr), errs)),
                     };
                 }
+                if let Some(kw_var_args) = subr.kw_var_params.as_mut() {
+                    *kw_var_args.typ_mut() =
+                        match self.eval_t_params(mem::take(kw_var_args.typ_mut()), level, t_loc) {
+                            Ok(t) => t,
+                            Err((_, errs)) => return Err((Subr(subr), errs)),
+                        };
+                }
                 match self.eval_t_params(*subr.return_t, level, t_loc) {
                     Ok(return_t) => Ok(subr_t(
                         subr.kind,
                         subr.non_default_params,
                         subr.var_params.map(|v| *v),
                         subr.default_params,
+                        subr.kw_var_params.map(|v| *v),
                         return_t,
                     )),
                     Err((_, errs)) => {
@@ -994,6 +1195,7 @@ impl Context {
                             subr.non_default_params,
                             subr.var_params.map(|v| *v),
                             subr.default_params,
+                            subr.kw_var_params.map(|v| *v),
                             Failure,
                         );
                         Err((subr, errs))
@@ -1053,6 +1255,14 @@ impl Context {
                         }
                     };
                 }
+                // type-level recursion, e.g. `List(Int, 2)` => `Cons(Int, 2)` (`.rest: List(Int, 1)`)
+                if let Some(ValueObj::Subr(ConstSubr::User(subr))) = self.rec_get_const_obj(&name) {
+                    match self.eval_type_app(subr, &params, t_loc) {
+                        Ok(Some(t)) => return self.eval_t_params(t, level, t_loc),
+                        Ok(None) => {}
+                        Err(errs) => return Err((poly(name, params), errs)),
+                    }
+                }
                 Ok(poly(name, params))
             }
             Type::And(l, r) => {
@@ -1470,6 +1680,20 @@ impl Context {
         level: usize,
         t_loc: &impl Locational,
     ) -> EvalResult<Type> {
+        // higher-kinded type variable application
+        // e.g. `F(Int)` (`F := Array(_: Type, 2)`) => `Array(Int, 2)`
+        if &attr_name[..] == "__call__" {
+            if let Some(t) = <&Type>::try_from(&lhs)
+                .ok()
+                .and_then(|ctor| ctor.apply_type_ctor(&args))
+            {
+                return self
+                    .eval_t_params(t, level, t_loc)
+                    .map_err(|(_, errs)| errs);
+            } else if lhs.is_unbound_var() {
+                return Ok(proj_call(lhs, attr_name, args));
+            }
+        }
         let t = self.get_tp_t(&lhs)?;
         for ty_ctx in self.get_nominal_super_type_ctxs(&t).ok_or_else(|| {
             EvalError::type_not_found(
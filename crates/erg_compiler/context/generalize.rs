@@ -183,6 +183,10 @@ impl Generalizer {
                 subr.default_params.iter_mut().for_each(|d_param| {
                     *d_param.typ_mut() = self.generalize_t(mem::take(d_param.typ_mut()), uninit);
                 });
+                if let Some(kw_var_args) = &mut subr.kw_var_params {
+                    *kw_var_args.typ_mut() =
+                        self.generalize_t(mem::take(kw_var_args.typ_mut()), uninit);
+                }
                 self.variance = Covariant;
                 let return_t = self.generalize_t(*subr.return_t, uninit);
                 self.qnames = self.qnames.difference(&qnames);
@@ -191,6 +195,7 @@ impl Generalizer {
                     subr.non_default_params,
                     subr.var_params.map(|x| *x),
                     subr.default_params,
+                    subr.kw_var_params.map(|x| *x),
                     return_t,
                 )
             }
@@ -577,6 +582,21 @@ impl<'c, 'q, 'l, L: Locational> Dereferencer<'c, 'q, 'l, L> {
             }
             Type::Poly { name, mut params } => {
                 let typ = poly(&name, params.clone());
+                // e.g. `List(?T(:> Int), 2 - 1)` => `Cons(Int, 1)`
+                if self.ctx.get_type_app_candidates(&typ).is_some() {
+                    for param in params.iter_mut() {
+                        *param = self.deref_tp(mem::take(param))?;
+                    }
+                    let app = poly(&name, params);
+                    return match self
+                        .ctx
+                        .eval_t_params(app.clone(), self.ctx.level, self.loc)
+                    {
+                        Ok(t) if t != app => self.deref_tyvar(t),
+                        Ok(t) => Ok(t),
+                        Err((_, errs)) => Err(errs),
+                    };
+                }
                 let (_, ctx) = self.ctx.get_nominal_type_ctx(&typ).ok_or_else(|| {
                     TyCheckError::type_not_found(
                         self.ctx.cfg.input.clone(),
@@ -610,6 +630,11 @@ impl<'c, 'q, 'l, L: Locational> Dereferencer<'c, 'q, 'l, L> {
                     *d_param.typ_mut() = self.deref_tyvar(mem::take(d_param.typ_mut()))?;
                     self.pop_variance();
                 }
+                if let Some(kw_var_args) = &mut subr.kw_var_params {
+                    self.push_variance(Contravariant);
+                    *kw_var_args.typ_mut() = self.deref_tyvar(mem::take(kw_var_args.typ_mut()))?;
+                    self.pop_variance();
+                }
                 self.push_variance(Covariant);
                 subr.return_t = Box::new(self.deref_tyvar(mem::take(&mut subr.return_t))?);
                 self.pop_variance();
@@ -833,6 +858,11 @@ impl<'c, 'q, 'l, L: Locational> Dereferencer<'c, 'q, 'l, L> {
             *d_param.typ_mut() = _self.deref_tyvar(mem::take(d_param.typ_mut()))?;
             _self.pop_variance();
         }
+        if let Some(kw_var_args) = &mut subr.kw_var_params {
+            _self.push_variance(Contravariant);
+            *kw_var_args.typ_mut() = _self.deref_tyvar(mem::take(kw_var_args.typ_mut()))?;
+            _self.pop_variance();
+        }
         _self.push_variance(Covariant);
         subr.return_t = Box::new(_self.deref_tyvar(mem::take(&mut subr.return_t))?);
         _self.pop_variance();
@@ -855,6 +885,20 @@ impl Context {
         let maybe_unbound_t = generalizer.generalize_t(free_type, false);
         if maybe_unbound_t.is_subr() && maybe_unbound_t.has_qvar() {
             maybe_unbound_t.quantify()
+        } else if maybe_unbound_t.is_overloaded() && maybe_unbound_t.has_qvar() {
+            // each signature of an overloaded type is quantified separately
+            maybe_unbound_t
+                .intersection_types()
+                .into_iter()
+                .map(|t| {
+                    if !t.is_quantified_subr() && t.has_qvar() {
+                        t.quantify()
+                    } else {
+                        t
+                    }
+                })
+                .reduce(|l, r| l & r)
+                .unwrap()
         } else {
             maybe_unbound_t
         }
@@ -1017,6 +1061,13 @@ impl Context {
             param.sig.vi.t = dereferencer.deref_tyvar(t)?;
             self.resolve_expr_t(&mut param.default_val, qnames)?;
         }
+        if let Some(kw_var_params) = &mut params.kw_var_params {
+            kw_var_params.vi.t.generalize();
+            let t = mem::take(&mut kw_var_params.vi.t);
+            let mut dereferencer =
+                Dereferencer::new(self, Contravariant, false, qnames, kw_var_params.as_ref());
+            kw_var_params.vi.t = dereferencer.deref_tyvar(t)?;
+        }
         Ok(())
     }
 
@@ -1099,6 +1150,9 @@ impl Context {
                     let t = mem::take(&mut dic.t);
                     let mut dereferencer = Dereferencer::simple(self, qnames, dic);
                     dic.t = dereferencer.deref_tyvar(t)?;
+                    for spread in dic.spreads.iter_mut() {
+                        self.resolve_expr_t(spread, qnames)?;
+                    }
                     for kv in dic.kvs.iter_mut() {
                         self.resolve_expr_t(&mut kv.key, qnames)?;
                         self.resolve_expr_t(&mut kv.value, qnames)?;
@@ -1159,6 +1213,9 @@ impl Context {
                 for arg in call.args.kw_args.iter_mut() {
                     self.resolve_expr_t(&mut arg.expr, qnames)?;
                 }
+                if let Some(kw_var_args) = &mut call.args.kw_var_args {
+                    self.resolve_expr_t(&mut kw_var_args.expr, qnames)?;
+                }
                 Ok(())
             }
             hir::Expr::Def(def) => {
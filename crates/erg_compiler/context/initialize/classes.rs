@@ -25,6 +25,8 @@ impl Context {
         let U = mono_q(TY_U, instanceof(Type));
         let L = mono_q(TY_L, instanceof(Type));
         let R = mono_q(TY_R, instanceof(Type));
+        let S = mono_q(TY_S, instanceof(Type));
+        let Y = mono_q(TY_Y, instanceof(Type));
         let N = mono_q_tp(TY_N, instanceof(Nat));
         let M = mono_q_tp(TY_M, instanceof(Nat));
         let never = Self::builtin_mono_class(NEVER, 1);
@@ -1309,6 +1311,28 @@ impl Context {
             array_t(T.clone(), TyParam::erased(Nat)),
         );
         array_.register_py_builtin(FUNC_DEDUP, t.quantify(), Some(FUNC_DEDUP), 28);
+        // into: |T, U, N: Nat|(self: Array(T, N), to: {[U; N]}) -> [U; N]
+        //    and |T, U, N: Nat|(self: Array(T, N), to: {[U; !N]}) -> [U; !N]
+        let into_t = |to: Type| {
+            fn1_kw_met(
+                array_t(T.clone(), N.clone()),
+                kw(KW_TO, tp_enum(Type, set! { ty_tp(to.clone()) })),
+                to,
+            )
+            .quantify()
+        };
+        let t = into_t(array_t(U.clone(), N.clone())) & into_t(array_mut(U.clone(), N.clone()));
+        array_.register_py_builtin(FUNC_INTO, t, Some(FUNC_INTO), 46);
+        // only the contents of `[T!; N]` can be updated, the length is fixed
+        // map!: |T <: Mutable, N: Nat|(self: Array(T, N), func: T.ImmutType -> T.ImmutType) => NoneType
+        let M = mono_q(TY_T, subtypeof(mono(MUTABLE)));
+        let immut_t = proj(M.clone(), IMMUT_TYPE);
+        let f_t = kw(
+            KW_FUNC,
+            func(vec![kw(KW_OLD, immut_t.clone())], None, vec![], immut_t),
+        );
+        let t = pr_met(array_t(M, N.clone()), vec![f_t], None, vec![], NoneType);
+        array_.register_py_builtin(PROC_MAP, t.quantify(), Some(FUNC_MAP_MUT), 56);
         /* GenericSet */
         let mut generic_set = Self::builtin_mono_class(GENERIC_SET, 1);
         generic_set.register_superclass(Obj, &obj);
@@ -1525,7 +1549,7 @@ impl Context {
         let mut record = Self::builtin_mono_class(RECORD, 2);
         record.register_superclass(Obj, &obj);
         /* Or (true or type) */
-        let or_t = poly(OR, vec![ty_tp(L), ty_tp(R)]);
+        let or_t = poly(OR, vec![ty_tp(L), ty_tp(R.clone())]);
         let mut or = Self::builtin_poly_class(OR, vec![PS::t_nd(TY_L), PS::t_nd(TY_R)], 2);
         or.register_superclass(Obj, &obj);
         /* Iterators */
@@ -1553,6 +1577,28 @@ impl Context {
         dict_items.register_superclass(Obj, &obj);
         dict_items.register_marker_trait(poly(ITERABLE, vec![ty_tp(T.clone())]));
         dict_items.register_marker_trait(poly(OUTPUT, vec![ty_tp(T.clone())]));
+        /* Generator */
+        // Generator(Y, S, R): yields `Y`, is sent `S` and returns `R`
+        let generator_t = poly(
+            GENERATOR,
+            vec![ty_tp(Y.clone()), ty_tp(S.clone()), ty_tp(R.clone())],
+        );
+        let mut generator = Self::builtin_poly_class(
+            GENERATOR,
+            vec![PS::t_nd(TY_Y), PS::t_nd(TY_S), PS::t_nd(TY_R)],
+            2,
+        );
+        generator.register_superclass(Obj, &obj);
+        generator.register_marker_trait(poly(ITERABLE, vec![ty_tp(Y.clone())]));
+        generator.register_marker_trait(poly(OUTPUT, vec![ty_tp(Y.clone())]));
+        generator.register_marker_trait(poly(INPUT, vec![ty_tp(S.clone())]));
+        generator.register_marker_trait(poly(OUTPUT, vec![ty_tp(R.clone())]));
+        /* Coroutine */
+        // Coroutine(T): returned by `@Async` procedures, resolves to `T` when awaited
+        let coroutine_t = poly(COROUTINE, vec![ty_tp(T.clone())]);
+        let mut coroutine = Self::builtin_poly_class(COROUTINE, vec![PS::t_nd(TY_T)], 2);
+        coroutine.register_superclass(Obj, &obj);
+        coroutine.register_marker_trait(poly(OUTPUT, vec![ty_tp(T.clone())]));
         /* Enumerate */
         let mut enumerate = Self::builtin_poly_class(ENUMERATE, vec![PS::t_nd(TY_T)], 2);
         enumerate.register_superclass(Obj, &obj);
@@ -2149,15 +2195,16 @@ impl Context {
             Immutable,
             Visibility::BUILTIN_PUBLIC,
         );
-        let mut g_generator = Self::builtin_mono_class(GENERIC_GENERATOR, 2);
-        g_generator.register_superclass(mono(GENERIC_CALLABLE), &g_callable);
-        let t_yield = fn1_met(mono(GENERIC_GENERATOR), Obj, Never).quantify();
-        g_generator.register_builtin_erg_impl(
+        // `f.yield x` makes the enclosing procedure `f` a generator
+        let t_yield = fn1_met(mono(GENERIC_CALLABLE), Obj, Never).quantify();
+        g_callable.register_builtin_erg_impl(
             FUNC_YIELD,
             t_yield,
             Immutable,
             Visibility::BUILTIN_PUBLIC,
         );
+        let mut g_generator = Self::builtin_mono_class(GENERIC_GENERATOR, 2);
+        g_generator.register_superclass(mono(GENERIC_CALLABLE), &g_callable);
         /* Proc */
         let mut proc = Self::builtin_mono_class(PROC, 2);
         proc.register_superclass(mono(GENERIC_CALLABLE), &g_callable);
@@ -2361,6 +2408,8 @@ impl Context {
             Const,
             Some(GENERATOR),
         );
+        self.register_builtin_type(generator_t, generator, vis.clone(), Const, Some(GENERATOR));
+        self.register_builtin_type(coroutine_t, coroutine, vis.clone(), Const, Some(COROUTINE));
         self.register_builtin_type(mono(PROC), proc, vis.clone(), Const, Some(PROC));
         self.register_builtin_type(mono(FUNC), func, vis.clone(), Const, Some(FUNC));
         self.register_builtin_type(range_t, range, vis.clone(), Const, Some(FUNC_RANGE));
@@ -23,6 +23,8 @@ const REQ_ERR: StyledStr = StyledStr::new("Requirement", Some(ERR), None);
 const REQ_WARN: StyledStr = StyledStr::new("Requirement", Some(WARN), None);
 const BASE_ERR: StyledStr = StyledStr::new("Base", Some(ERR), None);
 const BASE_WARN: StyledStr = StyledStr::new("Base", Some(WARN), None);
+const VARIANTS_ERR: StyledStr = StyledStr::new("Variants", Some(ERR), None);
+const VARIANTS_WARN: StyledStr = StyledStr::new("Variants", Some(WARN), None);
 
 /// Base := Type or NoneType, Impl := Type -> ClassType
 pub(crate) fn class_func(mut args: ValueArgs, ctx: &Context) -> EvalValueResult<ValueObj> {
@@ -84,6 +86,53 @@ pub(crate) fn inherit_func(mut args: ValueArgs, ctx: &Context) -> EvalValueResul
     )))
 }
 
+/// Variants: {Str: Type}, Impl := Type -> ClassType
+/// e.g. `Enum {.Lit = Int; .Add = {lhs = Expr; rhs = Expr}}`
+pub(crate) fn enum_func(mut args: ValueArgs, ctx: &Context) -> EvalValueResult<ValueObj> {
+    let variants = args.remove_left_or_key("Variants").ok_or_else(|| {
+        ErrorCore::new(
+            vec![SubMessage::only_loc(Location::Unknown)],
+            format!("{VARIANTS_ERR} is not passed"),
+            line!() as usize,
+            ErrorKind::KeyError,
+            Location::Unknown,
+        )
+    })?;
+    let ValueObj::Record(rec) = variants else {
+        let variants = StyledString::new(format!("{variants}"), Some(ERR), None);
+        return Err(ErrorCore::new(
+            vec![SubMessage::only_loc(Location::Unknown)],
+            format!("non-record object {variants} is passed to {VARIANTS_WARN}"),
+            line!() as usize,
+            ErrorKind::TypeError,
+            Location::Unknown,
+        )
+        .into());
+    };
+    let mut variants = vec![];
+    for (field, payload) in rec.into_iter() {
+        let Some(payload) = payload.as_type() else {
+            let payload = StyledString::new(format!("{payload}"), Some(ERR), None);
+            return Err(ErrorCore::new(
+                vec![SubMessage::only_loc(Location::Unknown)],
+                format!(
+                    "non-type object {payload} is passed to {VARIANTS_WARN}.{}",
+                    field.symbol
+                ),
+                line!() as usize,
+                ErrorKind::TypeError,
+                Location::Unknown,
+            )
+            .into());
+        };
+        variants.push((field.symbol, payload));
+    }
+    let impls = args.remove_left_or_key("Impl");
+    let impls = impls.map(|v| v.as_type().unwrap());
+    let t = mono(ctx.name.clone());
+    Ok(ValueObj::gen_t(GenTypeObj::enum_(t, variants, impls)))
+}
+
 /// Class: ClassType -> ClassType (with `InheritableType`)
 /// This function is used by the compiler to mark a class as inheritable and does nothing in terms of actual operation.
 pub(crate) fn inheritable_func(mut args: ValueArgs, _ctx: &Context) -> EvalValueResult<ValueObj> {
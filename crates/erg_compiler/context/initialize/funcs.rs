@@ -42,13 +42,25 @@ impl Context {
             vec![kw(KW_MSG, Str)],
             NoneType,
         );
-        let t_bin = nd_func(vec![kw(KW_N, Int)], None, Str);
-        // TODO: overload: Iterable(Int) -> Bytes
-        let t_bytes = nd_func(
-            vec![kw(KW_STR, Str), kw(KW_ENCODING, Str)],
+        // await can only be used in `@Async` procedures
+        let t_await = nd_func(
+            vec![kw(KW_CORO, poly(COROUTINE, vec![ty_tp(T.clone())]))],
             None,
-            mono(BYTES),
-        );
+            T.clone(),
+        )
+        .quantify();
+        let t_bin = nd_func(vec![kw(KW_N, Int)], None, Str);
+        let t_bytes = nd_func(vec![], None, mono(BYTES))
+            & nd_func(
+                vec![kw(KW_STR, Str), kw(KW_ENCODING, Str)],
+                None,
+                mono(BYTES),
+            )
+            & nd_func(
+                vec![kw(KW_ITERABLE, poly(ITERABLE, vec![ty_tp(Int)]))],
+                None,
+                mono(BYTES),
+            );
         let t_chr = nd_func(
             vec![kw(KW_I, Type::from(value(0usize)..=value(1_114_111usize)))],
             None,
@@ -160,7 +172,13 @@ impl Context {
             None,
             O.clone(),
         )
-        .quantify();
+        .quantify()
+            & nd_func(
+                vec![anon(O.clone()), anon(O.clone())],
+                Some(anon(O.clone())),
+                O.clone(),
+            )
+            .quantify();
         let t_memoryview = nd_func(
             vec![kw(
                 KW_OBJ,
@@ -172,9 +190,15 @@ impl Context {
         let t_min = nd_func(
             vec![kw(KW_ITERABLE, poly(ITERABLE, vec![ty_tp(O.clone())]))],
             None,
-            O,
+            O.clone(),
         )
-        .quantify();
+        .quantify()
+            & nd_func(
+                vec![anon(O.clone()), anon(O.clone())],
+                Some(anon(O.clone())),
+                O,
+            )
+            .quantify();
         let t_nat = nd_func(vec![kw(KW_OBJ, Obj)], None, or(Nat, NoneType));
         // e.g. not(b: Bool!): Bool!
         let B = mono_q(TY_B, subtypeof(Bool));
@@ -244,6 +268,7 @@ impl Context {
         self.register_py_builtin(FUNC_ASCII, t_ascii, Some(FUNC_ASCII), 53);
         // Leave as `Const`, as it may negatively affect assert casting.
         self.register_builtin_erg_impl(FUNC_ASSERT, t_assert, Const, vis.clone());
+        self.register_builtin_erg_impl(FUNC_AWAIT, t_await, Immutable, vis.clone());
         self.register_builtin_py_impl(FUNC_BIN, t_bin, Immutable, vis.clone(), Some(FUNC_BIN));
         self.register_builtin_py_impl(
             FUNC_BYTES,
@@ -415,15 +440,13 @@ impl Context {
                 Some(FUNC_EXIT),
             );
         } else {
-            let t_range = func(
-                vec![kw(KW_STOP, or(Int, NoneType))],
-                None,
-                vec![
-                    kw(KW_START, or(Int, NoneType)),
-                    kw(KW_STEP, or(Int, NoneType)),
-                ],
-                poly(RANGE, vec![ty_tp(Int)]),
-            );
+            let t_range = nd_func(vec![kw(KW_STOP, Int)], None, poly(RANGE, vec![ty_tp(Int)]))
+                & func(
+                    vec![kw(KW_START, Int), kw(KW_STOP, Int)],
+                    None,
+                    vec![kw(KW_STEP, Int)],
+                    poly(RANGE, vec![ty_tp(Int)]),
+                );
             self.register_builtin_py_impl(
                 FUNC_RANGE,
                 t_range,
@@ -493,6 +516,14 @@ impl Context {
             None,
         ));
         self.register_builtin_const(INHERIT, vis.clone(), ValueObj::Subr(inherit));
+        let enum_t = func(
+            vec![kw(KW_VARIANTS, Type)],
+            None,
+            vec![kw(KW_IMPL, Type)],
+            ClassType,
+        );
+        let enum_ = ConstSubr::Builtin(BuiltinConstSubr::new(ENUM, enum_func, enum_t, None));
+        self.register_builtin_const(ENUM, vis.clone(), ValueObj::Subr(enum_));
         let trait_t = func(
             vec![kw(KW_REQUIREMENT, Type)],
             None,
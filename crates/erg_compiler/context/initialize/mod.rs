@@ -82,6 +82,8 @@ const STR_ITERATOR: &str = "StrIterator";
 const FUNC_ITER: &str = "iter";
 const ITER: &str = "Iter";
 const CONTEXT_MANAGER: &str = "ContextManager";
+const ASYNC_ITERABLE: &str = "AsyncIterable";
+const ASYNC_CONTEXT_MANAGER: &str = "AsyncContextManager";
 const EXC_TYPE: &str = "exc_type";
 const EXC_VALUE: &str = "exc_value";
 const TRACEBACK: &str = "traceback";
@@ -182,6 +184,9 @@ const ARRAY: &str = "Array";
 const MUT_ARRAY: &str = "Array!";
 const FUNC_PARTITION: &str = "partition";
 const FUNC_DEDUP: &str = "dedup";
+const FUNC_INTO: &str = "into";
+const PROC_MAP: &str = "map!";
+const FUNC_MAP_MUT: &str = "map_mut";
 const FUNC_CONCAT: &str = "concat";
 const FUNC_PUSH: &str = "push";
 const PROC_PUSH: &str = "push!";
@@ -279,11 +284,13 @@ const FUNC_ZIP: &str = "zip";
 const FILE: &str = "File";
 const CALLABLE: &str = "Callable";
 const GENERATOR: &str = "Generator";
+const COROUTINE: &str = "Coroutine";
 const FUNC_RANGE: &str = "range";
 const FUNC_ALL: &str = "all";
 const FUNC_ANY: &str = "any";
 const FUNC_ASCII: &str = "ascii";
 const FUNC_ASSERT: &str = "assert";
+const FUNC_AWAIT: &str = "await";
 const FUNC_BIN: &str = "bin";
 const FUNC_BYTES: &str = "bytes";
 const FUNC_CHR: &str = "chr";
@@ -317,6 +324,7 @@ const FUNC_UNREACHABLE: &str = "unreachable";
 const SUBSUME: &str = "Subsume";
 const INHERIT: &str = "Inherit";
 const INHERITABLE: &str = "Inheritable";
+const ENUM: &str = "Enum";
 const DEL: &str = "Del";
 const PATCH: &str = "Patch";
 const STRUCTURAL: &str = "Structural";
@@ -378,6 +386,9 @@ const FUNDAMENTAL_SETITEM: &str = "__setitem__";
 const FUNDAMENTAL_IMPORT: &str = "__import__";
 const FUNDAMENTAL_ENTER: &str = "__enter__";
 const FUNDAMENTAL_EXIT: &str = "__exit__";
+const FUNDAMENTAL_AITER: &str = "__aiter__";
+const FUNDAMENTAL_AENTER: &str = "__aenter__";
+const FUNDAMENTAL_AEXIT: &str = "__aexit__";
 
 const LICENSE: &str = "license";
 const CREDITS: &str = "credits";
@@ -402,11 +413,13 @@ const TY_TS: &str = "Ts";
 const TY_I: &str = "I";
 const TY_P: &str = "P";
 const TY_R: &str = "R";
+const TY_S: &str = "S";
 const TY_U: &str = "U";
 const TY_L: &str = "L";
 const TY_N: &str = "N";
 const TY_M: &str = "M";
 const TY_O: &str = "O";
+const TY_Y: &str = "Y";
 const TY_SUB: &str = "Sub";
 const TY_SUP: &str = "Sup";
 
@@ -421,6 +434,7 @@ const KW_LENGTH: &str = "length";
 const KW_PROC: &str = "proc!";
 const KW_PAT: &str = "pat";
 const KW_INTO: &str = "into";
+const KW_TO: &str = "to";
 const KW_ENCODING: &str = "encoding";
 const KW_ERRORS: &str = "errors";
 const KW_ARGS: &str = "args";
@@ -444,6 +458,7 @@ const KW_SRC: &str = "src";
 const KW_THEN: &str = "then";
 const KW_ELSE: &str = "else";
 const KW_OBJ: &str = "obj";
+const KW_CORO: &str = "coro";
 const KW_START: &str = "start";
 const KW_COND: &str = "cond";
 const KW_CLASSINFO: &str = "classinfo";
@@ -467,6 +482,7 @@ const KW_REQUIREMENT: &str = "Requirement";
 const KW_IMPL: &str = "Impl";
 const KW_ADDITIONAL: &str = "Additional";
 const KW_SUPER: &str = "Super";
+const KW_VARIANTS: &str = "Variants";
 const KW_MAXSPLIT: &str = "maxsplit";
 const KW_SUB: &str = "sub";
 
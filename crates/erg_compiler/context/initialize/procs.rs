@@ -88,21 +88,29 @@ impl Context {
             NoneType,
         );
         let P = mono_q("P", subtypeof(mono("PathLike")));
+        let open_default_params = vec![
+            kw("mode", Str),
+            kw("buffering", Int),
+            kw("encoding", or(Str, NoneType)),
+            kw("errors", or(Str, NoneType)),
+            kw("newline", or(Str, NoneType)),
+            kw("closefd", Bool),
+            // param_t("opener", option),
+        ];
+        // open!(file: PathLike, ...) or open!(file: Int (file descriptor), ...)
         let t_open = proc(
             vec![kw("file", P)],
             None,
-            vec![
-                kw("mode", Str),
-                kw("buffering", Int),
-                kw("encoding", or(Str, NoneType)),
-                kw("errors", or(Str, NoneType)),
-                kw("newline", or(Str, NoneType)),
-                kw("closefd", Bool),
-                // param_t("opener", option),
-            ],
+            open_default_params.clone(),
             mono("File!"),
         )
-        .quantify();
+        .quantify()
+            & proc(
+                vec![kw("file", Int)],
+                None,
+                open_default_params,
+                mono("File!"),
+            );
         let C = if cfg!(feature = "py_compat") {
             mono("ContextManager").structuralize()
         } else {
@@ -111,6 +119,30 @@ impl Context {
         let t_with = nd_proc(
             vec![
                 kw("obj", C),
+                kw("proc!", nd_proc(vec![anon(T.clone())], None, U.clone())),
+            ],
+            None,
+            U.clone(),
+        )
+        .quantify();
+        // async_for! and async_with! can only be used in `@Async` procedures
+        let t_async_for = nd_proc(
+            vec![
+                kw("iterable", poly("AsyncIterable", vec![ty_tp(T.clone())])),
+                kw("proc!", nd_proc(vec![anon(T.clone())], None, NoneType)),
+            ],
+            None,
+            NoneType,
+        )
+        .quantify();
+        let AC = if cfg!(feature = "py_compat") {
+            mono("AsyncContextManager").structuralize()
+        } else {
+            mono("AsyncContextManager")
+        };
+        let t_async_with = nd_proc(
+            vec![
+                kw("obj", AC),
                 kw("proc!", nd_proc(vec![anon(T)], None, U.clone())),
             ],
             None,
@@ -154,6 +186,8 @@ impl Context {
         } else {
             "with__"
         };
-        self.register_builtin_py_impl("with!", t_with, Immutable, vis, Some(name));
+        self.register_builtin_py_impl("with!", t_with, Immutable, vis.clone(), Some(name));
+        self.register_builtin_erg_impl("async_for!", t_async_for, Immutable, vis.clone());
+        self.register_builtin_erg_impl("async_with!", t_async_with, Immutable, vis);
     }
 }
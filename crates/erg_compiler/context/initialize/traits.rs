@@ -160,6 +160,48 @@ impl Context {
             Visibility::BUILTIN_PUBLIC,
             Some(FUNDAMENTAL_EXIT),
         );
+        /* AsyncIterable */
+        let mut async_iterable = Self::builtin_poly_trait(ASYNC_ITERABLE, vec![PS::t_nd(TY_T)], 2);
+        async_iterable.register_superclass(poly(OUTPUT, vec![ty_tp(T.clone())]), &output);
+        let Slf = mono_q(
+            SELF,
+            subtypeof(poly(ASYNC_ITERABLE, vec![ty_tp(T.clone())])),
+        );
+        let t = fn0_met(Slf, Obj).quantify();
+        async_iterable.register_builtin_py_decl(
+            FUNDAMENTAL_AITER,
+            t,
+            Visibility::BUILTIN_PUBLIC,
+            Some(FUNDAMENTAL_AITER),
+        );
+        /* AsyncContextManager */
+        let mut async_context_manager = Self::builtin_mono_trait(ASYNC_CONTEXT_MANAGER, 2);
+        let Slf = mono_q(SELF, subtypeof(mono(ASYNC_CONTEXT_MANAGER)));
+        let t = fn0_met(Slf.clone(), poly(COROUTINE, vec![ty_tp(Obj)])).quantify();
+        async_context_manager.register_builtin_py_decl(
+            FUNDAMENTAL_AENTER,
+            t,
+            Visibility::BUILTIN_PUBLIC,
+            Some(FUNDAMENTAL_AENTER),
+        );
+        let t = fn_met(
+            Slf,
+            vec![
+                kw(EXC_TYPE, ClassType),
+                kw(EXC_VALUE, Obj),
+                kw(TRACEBACK, Obj), // TODO:
+            ],
+            None,
+            vec![],
+            poly(COROUTINE, vec![ty_tp(NoneType)]),
+        )
+        .quantify();
+        async_context_manager.register_builtin_py_decl(
+            FUNDAMENTAL_AEXIT,
+            t,
+            Visibility::BUILTIN_PUBLIC,
+            Some(FUNDAMENTAL_AEXIT),
+        );
         let R = mono_q(TY_R, instanceof(Type));
         let params = vec![PS::t(TY_R, false, WithDefault)];
         let ty_params = vec![ty_tp(R.clone())];
@@ -280,7 +322,7 @@ impl Context {
             None,
         );
         self.register_builtin_type(
-            poly(ITERABLE, vec![ty_tp(T)]),
+            poly(ITERABLE, vec![ty_tp(T.clone())]),
             iterable,
             Visibility::BUILTIN_PRIVATE,
             Const,
@@ -293,6 +335,20 @@ impl Context {
             Const,
             None,
         );
+        self.register_builtin_type(
+            poly(ASYNC_ITERABLE, vec![ty_tp(T)]),
+            async_iterable,
+            Visibility::BUILTIN_PRIVATE,
+            Const,
+            None,
+        );
+        self.register_builtin_type(
+            mono(ASYNC_CONTEXT_MANAGER),
+            async_context_manager,
+            Visibility::BUILTIN_PRIVATE,
+            Const,
+            None,
+        );
         self.register_builtin_type(poly(ADD, ty_params.clone()), add, vis.clone(), Const, None);
         self.register_builtin_type(poly(SUB, ty_params.clone()), sub, vis.clone(), Const, None);
         self.register_builtin_type(poly(MUL, ty_params.clone()), mul, vis.clone(), Const, None);
@@ -1,9 +1,11 @@
 // (type) getters & validators
+use std::borrow::Cow;
 use std::option::Option; // conflicting to Type::Option
 use std::path::{Path, PathBuf};
 
 use erg_common::config::{ErgConfig, Input};
 use erg_common::dict;
+use erg_common::dict::Dict;
 use erg_common::env::{erg_py_external_lib_path, erg_pystd_path, erg_std_path};
 use erg_common::error::{ErrorCore, Location, SubMessage};
 use erg_common::levenshtein;
@@ -17,11 +19,14 @@ use erg_common::{
 };
 
 use erg_parser::ast::{self, Identifier, VarName};
-use erg_parser::token::Token;
+use erg_parser::token::{Token, TokenKind};
 
-use crate::ty::constructors::{anon, fn_met, free_var, func, mono, poly, proc, proj, ref_, subr_t};
+use crate::ty::constructors::{
+    anon, dict_t, fn0_met, fn_met, free_var, func, func1, mono, poly, proc, proj, ref_, subr_t,
+    ty_tp, v_enum,
+};
 use crate::ty::free::Constraint;
-use crate::ty::typaram::TyParam;
+use crate::ty::typaram::{TyParam, TyParamOrdering};
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
 use crate::ty::{Field, HasType, ParamTy, Predicate, SubrKind, SubrType, Type, Visibility};
 use Type::*;
@@ -47,6 +52,50 @@ pub enum SubstituteResult {
     Coerced(Type),
 }
 
+/// An argument of type `t` taken out of `*expr` or `**expr` (only used for type checking)
+fn spread_elem(expr: &hir::Expr, t: Type) -> hir::Expr {
+    let symbol = Token::new(
+        TokenKind::Symbol,
+        expr.to_string_notype(),
+        expr.ln_begin().unwrap_or(0),
+        expr.col_begin().unwrap_or(0),
+    );
+    let vi = VarInfo {
+        t,
+        ..VarInfo::default()
+    };
+    let ident = hir::Identifier::new(Identifier::private_from_token(symbol), None, vi);
+    hir::Expr::Accessor(hir::Accessor::Ident(ident))
+}
+
+fn spread_keyword(expr: &hir::Expr, name: Str) -> Token {
+    Token::new(
+        TokenKind::Symbol,
+        name,
+        expr.ln_begin().unwrap_or(0),
+        expr.col_begin().unwrap_or(0),
+    )
+}
+
+/// `*expr`/`**expr` whose length is not statically known, the type is that of the elements (values)
+fn spread_marker(op: TokenKind, expr: &hir::Expr, elem_t: Type) -> hir::Expr {
+    let op = Token::new(
+        op,
+        if op == TokenKind::PreStar { "*" } else { "**" },
+        expr.ln_begin().unwrap_or(0),
+        expr.col_begin().unwrap_or(0),
+    );
+    let vi = VarInfo {
+        t: func1(expr.t(), elem_t),
+        ..VarInfo::default()
+    };
+    hir::Expr::UnaryOp(hir::UnaryOp::new(op, expr.clone(), vi))
+}
+
+fn is_spread_marker(expr: &hir::Expr) -> bool {
+    matches!(expr, hir::Expr::UnaryOp(unary) if unary.op.is(TokenKind::PreStar) || unary.op.is(TokenKind::PreDblStar))
+}
+
 impl Context {
     pub(crate) fn get_ctx_from_path(&self, path: &Path) -> Option<&Context> {
         self.opt_mod_cache()?
@@ -152,18 +201,16 @@ impl Context {
         match obj {
             hir::Expr::Accessor(hir::Accessor::Ident(ident)) => {
                 // e.g. ident.t: {Int}
-                if let Type::Refinement(refine) = ident.ref_t() {
-                    if let Predicate::Equal { rhs, .. } = refine.pred.as_ref() {
-                        if let Ok(t) = <&Type>::try_from(rhs) {
-                            if let Some(ctxs) = self.get_nominal_super_type_ctxs(t) {
-                                return Ok(ctxs);
-                            }
-                        }
-                    }
+                if let Some(ctxs) = self.get_singleton_type_ctxs(ident.ref_t()) {
+                    return Ok(ctxs);
                 }
                 self.get_singular_ctxs_by_ident(&ident.raw, namespace)
             }
             hir::Expr::Accessor(hir::Accessor::Attr(attr)) => {
+                // e.g. `Expr.Lit` (a variant of `Expr`): {Expr.Lit}
+                if let Some(ctxs) = self.get_singleton_type_ctxs(attr.ref_t()) {
+                    return Ok(ctxs);
+                }
                 // REVIEW: 両方singularとは限らない?
                 let mut ctxs = vec![];
                 for ctx in self.get_singular_ctxs_by_hir_expr(&attr.obj, namespace)? {
@@ -183,6 +230,18 @@ impl Context {
         }
     }
 
+    /// `{Int}` => the contexts of `Int`
+    fn get_singleton_type_ctxs(&self, t: &Type) -> Option<Vec<&Context>> {
+        if let Type::Refinement(refine) = t {
+            if let Predicate::Equal { rhs, .. } = refine.pred.as_ref() {
+                if let Ok(t) = <&Type>::try_from(rhs) {
+                    return self.get_nominal_super_type_ctxs(t);
+                }
+            }
+        }
+        None
+    }
+
     pub(crate) fn get_singular_ctxs_by_ident(
         &self,
         ident: &ast::Identifier,
@@ -318,6 +377,12 @@ impl Context {
             }
         }
         let match_target_expr_t = pos_args[0].expr.ref_t();
+        // `self` of an enum method is a type variable bounded by the enum (`?T <: Shape`),
+        // so the arms must cover the bound instead of narrowing the variable (`?T <: Shape.Circle`)
+        let enum_bound = match_target_expr_t.get_super().filter(|sup| {
+            match_target_expr_t.is_unbound_var() && self.get_enum_variants(sup).is_some()
+        });
+        let match_target_expr_t = enum_bound.as_ref().unwrap_or(match_target_expr_t);
         // Never or T => T
         let mut union_pat_t = Type::Never;
         let mut arm_ts = vec![];
@@ -353,6 +418,10 @@ impl Context {
             union_pat_t = self.union(&union_pat_t, &rhs);
             arm_ts.push(rhs);
         }
+        // e.g. union_pat_t: Expr.Lit or Expr.Add => Expr (if all variants are matched)
+        let union_pat_t = self
+            .get_exhaustive_enum(&union_pat_t)
+            .unwrap_or(union_pat_t);
         // NG: expr_t: Nat, union_pat_t: {1, 2}
         // OK: expr_t: Int, union_pat_t: {1} or 'T
         if let Err(err) = self.sub_unify(match_target_expr_t, &union_pat_t, &pos_args[0], None) {
@@ -630,6 +699,39 @@ impl Context {
         Triple::None
     }
 
+    /// The fields of polymorphic classes are declared with the type parameters.
+    /// ```erg
+    /// Queue! T, N = Class { .payload = Array!(T, N) }
+    /// q: Queue!(Int, 2)
+    /// q.payload: Array!(Int, 2) # not Array!(T, N)
+    /// ```
+    fn instantiate_field_t(&self, obj: &hir::Expr, mut vi: VarInfo) -> TyCheckResult<VarInfo> {
+        if !vi.t.has_qvar() {
+            return Ok(vi);
+        }
+        let Some((class_t, _)) = self.get_nominal_type_ctx(obj.ref_t()) else {
+            return Ok(vi);
+        };
+        if class_t.is_monomorphic() {
+            return Ok(vi);
+        }
+        let getter_t = fn0_met(class_t.clone(), vi.t.clone()).quantify();
+        let getter_t = self.instantiate_dummy(getter_t)?;
+        let (Some(self_t), Some(return_t)) = (getter_t.self_t(), getter_t.return_t()) else {
+            return Ok(vi);
+        };
+        let obj_t = match obj.ref_t() {
+            Type::Ref(t) | Type::RefMut { before: t, .. } => t.as_ref(),
+            t => t,
+        };
+        self.sub_unify(obj_t, self_t, obj, None)?;
+        // e.g. `Cons(Int, 2).rest: List(Int, 2-1)` => `Cons(Int, 1)`
+        vi.t = self
+            .eval_t_params(return_t.clone(), self.level, obj)
+            .map_err(|(_, errs)| errs)?;
+        Ok(vi)
+    }
+
     fn get_attr_from_nominal_t(
         &self,
         obj: &hir::Expr,
@@ -642,7 +744,10 @@ impl Context {
             for ctx in sups {
                 match ctx.rec_get_var_info(ident, AccessKind::Attr, input, namespace) {
                     Triple::Ok(vi) => {
-                        return Triple::Ok(vi);
+                        return match self.instantiate_field_t(obj, vi) {
+                            Ok(vi) => Triple::Ok(vi),
+                            Err(mut errs) => Triple::Err(errs.remove(0)),
+                        };
                     }
                     Triple::Err(e) => {
                         return Triple::Err(e);
@@ -780,6 +885,7 @@ impl Context {
                     vec![],
                     Some(ParamTy::Pos(ref_(Obj))),
                     vec![],
+                    None,
                     Failure,
                 )),
                 ..VarInfo::default()
@@ -1058,7 +1164,7 @@ impl Context {
                 )
             })?;
         let op = hir::Expr::Accessor(hir::Accessor::private(symbol, t));
-        self.get_call_t(&op, &None, args, &[], input, namespace)
+        self.get_call_t(&op, &None, args, None, &[], None, input, namespace)
             .map_err(|(_, errs)| {
                 let hir::Expr::Accessor(hir::Accessor::Ident(op_ident)) = op else {
                     return errs;
@@ -1102,7 +1208,7 @@ impl Context {
                 )
             })?;
         let op = hir::Expr::Accessor(hir::Accessor::private(symbol, vi));
-        self.get_call_t(&op, &None, args, &[], input, namespace)
+        self.get_call_t(&op, &None, args, None, &[], None, input, namespace)
             .map_err(|(_, errs)| {
                 let hir::Expr::Accessor(hir::Accessor::Ident(op_ident)) = op else {
                     return errs;
@@ -1134,11 +1240,24 @@ impl Context {
             if let Some(self_t) = subr.mut_self_t() {
                 log!(info "Propagating:\n {self_t}");
                 if let RefMut {
-                    after: Some(after), ..
+                    before,
+                    after: Some(after),
                 } = self_t
                 {
                     log!(info "~> {after}\n");
-                    *self_t = *after.clone();
+                    // A transition of an outer variable in a branch or a loop body may happen any number of times,
+                    // so the changed parameters are widened (e.g. `Queue!(Int, 0) ~> Queue!(Int, _: Nat)`)
+                    let widen = matches!(
+                        receiver,
+                        hir::Expr::Accessor(hir::Accessor::Ident(ident))
+                            if self.is_captured_var(&ident.raw.name)
+                    );
+                    self.check_transition_params(before, after, receiver, widen)?;
+                    *self_t = if widen {
+                        self.widen_transition(before, after)
+                    } else {
+                        *after.clone()
+                    };
                     if let hir::Expr::Accessor(hir::Accessor::Ident(ident)) = receiver {
                         if let Some(vi) = self.rec_get_mut_var_info(&ident.raw, AccessKind::Name) {
                             vi.t = self_t.clone();
@@ -1152,6 +1271,88 @@ impl Context {
         }
     }
 
+    /// Is `name` a variable captured by a subroutine (e.g. a branch or a loop body)?
+    fn is_captured_var(&self, name: &VarName) -> bool {
+        let mut ctx = self;
+        loop {
+            if ctx.get_current_scope_var(name).is_some() {
+                return false;
+            } else if ctx.kind.is_subr() {
+                return true;
+            }
+            match ctx.get_outer() {
+                Some(outer) => ctx = outer,
+                None => return false,
+            }
+        }
+    }
+
+    fn widen_transition(&self, before: &Type, after: &Type) -> Type {
+        let Some((_, ctx)) = self.get_nominal_type_ctx(after) else {
+            return after.clone();
+        };
+        let params = ctx
+            .params
+            .iter()
+            .zip(before.typarams().into_iter().zip(after.typarams()))
+            .map(|((_, vi), (before, after))| {
+                if self.eq_tp(&before, &after) {
+                    after
+                } else {
+                    TyParam::erased(vi.t.clone())
+                }
+            })
+            .collect();
+        poly(after.qual_name(), params)
+    }
+
+    /// Checks that the type arguments after a transition are still valid.
+    /// e.g. `Queue!(Int, 0).dequeue!()` (`N ~> N-1`) makes `N` `-1`, which is not a `Nat`.
+    /// If the transition is widened, `N` is decreased any number of times (`_: Nat - 1` is `Int`).
+    fn check_transition_params(
+        &self,
+        before: &Type,
+        after: &Type,
+        receiver: &hir::Expr,
+        widen: bool,
+    ) -> TyCheckResult<()> {
+        let Some((_, ctx)) = self.get_nominal_type_ctx(after) else {
+            return Ok(());
+        };
+        let params = before.typarams().into_iter().zip(after.typarams());
+        for ((name, vi), (before, after)) in ctx.params.iter().zip(params) {
+            let value_t = match self.eval_tp(after.clone()) {
+                Ok(TyParam::Value(_))
+                    if widen && self.try_cmp(&after, &before) == Some(TyParamOrdering::Less) =>
+                {
+                    match self.eval_tp(TyParam::erased(vi.t.clone()) - TyParam::value(1)) {
+                        Ok(TyParam::Erased(t)) => *t,
+                        _ => continue,
+                    }
+                }
+                Ok(TyParam::Value(value)) => v_enum(set! { value }),
+                Ok(TyParam::Erased(t)) => *t,
+                _ => continue,
+            };
+            if !self.subtype_of(&value_t, &vi.t) {
+                let name = name.as_ref().map_or("_", |name| &name.inspect()[..]);
+                return Err(TyCheckErrors::from(TyCheckError::type_mismatch_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    receiver.loc(),
+                    self.caused_by(),
+                    name,
+                    None,
+                    &vi.t,
+                    &value_t,
+                    None,
+                    None,
+                )));
+            }
+        }
+        Ok(())
+    }
+
     fn not_callable_error(
         &self,
         obj: &hir::Expr,
@@ -1243,7 +1444,7 @@ impl Context {
                     };
                     let ret_t = free_var(self.level, Constraint::new_type_of(Type));
                     let non_default_params = pos_args.iter().map(|a| anon(a.expr.t())).collect();
-                    let subr_t = subr_t(kind, non_default_params, None, vec![], ret_t);
+                    let subr_t = subr_t(kind, non_default_params, None, vec![], None, ret_t);
                     self.occur(&subr_t, instance, obj)?;
                     fv.link(&subr_t);
                     Ok(SubstituteResult::Ok)
@@ -1252,6 +1453,9 @@ impl Context {
             Type::Refinement(refine) => {
                 self.substitute_call(obj, attr_name, &refine.t, pos_args, kw_args)
             }
+            Type::And(_, _) if instance.is_overloaded() => {
+                self.substitute_overloaded_call(obj, attr_name, instance, pos_args, kw_args)
+            }
             // instance must be instantiated
             Type::Quantified(_) => unreachable_error!(TyCheckErrors, TyCheckError, self),
             Type::Subr(subr) => {
@@ -1261,6 +1465,9 @@ impl Context {
                 let is_method = subr
                     .self_t()
                     .map_or(false, |self_t| self.subtype_of(obj.ref_t(), self_t));
+                let (pos_args, kw_args) =
+                    self.expand_unsized_spreads(subr, is_method, pos_args, kw_args);
+                let (pos_args, kw_args) = (&pos_args[..], &kw_args[..]);
                 let callee = if let Some(ident) = attr_name {
                     if is_method {
                         obj.clone()
@@ -1273,8 +1480,10 @@ impl Context {
                     obj.clone()
                 };
                 let params_len = subr.non_default_params.len() + subr.default_params.len();
-                if (params_len < pos_args.len() || params_len < pos_args.len() + kw_args.len())
-                    && subr.var_params.is_none()
+                if subr.var_params.is_none()
+                    && (params_len < pos_args.len()
+                        || (params_len < pos_args.len() + kw_args.len()
+                            && subr.kw_var_params.is_none()))
                 {
                     return Err(self.gen_too_many_args_error(&callee, subr, pos_args, kw_args));
                 }
@@ -1669,11 +1878,13 @@ impl Context {
                 arg.keyword.inspect(),
             )));
         }
+        // keywords not matching any parameter are passed to `**kwargs` (if any)
         if let Some(pt) = subr_ty
             .non_default_params
             .iter()
             .chain(subr_ty.default_params.iter())
             .find(|pt| pt.name().as_ref() == Some(&kw_name))
+            .or(subr_ty.kw_var_params.as_deref())
         {
             let param_t = pt.typ();
             passed_params.insert(kw_name.clone());
@@ -1724,12 +1935,378 @@ impl Context {
         Ok(())
     }
 
+    /// Selects the most specific overload applicable to the arguments and substitutes it.
+    ///
+    /// e.g.
+    /// ```python
+    /// substitute_overloaded_call(instance: ((s: Str) -> Bytes) and ((i: Iterable(Int)) -> Bytes), ["a"]) => Coerced((s: Str) -> Bytes)
+    /// ```
+    fn substitute_overloaded_call(
+        &self,
+        obj: &hir::Expr,
+        attr_name: &Option<Identifier>,
+        overloaded: &Type,
+        pos_args: &[hir::PosArg],
+        kw_args: &[hir::KwArg],
+    ) -> TyCheckResult<SubstituteResult> {
+        let candidates = overloaded.intersection_types();
+        let mut applicable = vec![];
+        for candidate in candidates.iter() {
+            let Ok(instance) = self.instantiate(candidate.clone(), obj) else {
+                continue;
+            };
+            let Type::Subr(subr) = &instance else {
+                continue;
+            };
+            if let Some(param_ts) = self.overload_param_types(obj, subr, pos_args, kw_args) {
+                applicable.push((candidate, instance.clone(), param_ts));
+            }
+        }
+        // the most specific ones are those whose parameter types are subtypes of all the others'
+        let most_specific = applicable
+            .iter()
+            .filter(|(_, _, lhs)| {
+                applicable.iter().all(|(_, _, rhs)| {
+                    lhs.iter()
+                        .zip(rhs.iter())
+                        .all(|(l, r)| self.subtype_of(l, r))
+                })
+            })
+            .collect::<Vec<_>>();
+        let receiver = obj.show_acc().unwrap_or_else(|| obj.to_string_notype());
+        let (loc, callee) = if let Some(attr_name) = attr_name {
+            (
+                Location::concat(obj, attr_name),
+                format!("{receiver}.{}", attr_name.inspect()),
+            )
+        } else {
+            (obj.loc(), receiver)
+        };
+        match &most_specific[..] {
+            [(_, instance, _)] => {
+                self.substitute_call(obj, attr_name, instance, pos_args, kw_args)?;
+                Ok(SubstituteResult::Coerced(instance.clone()))
+            }
+            [] if applicable.is_empty() => {
+                let arg_ts = pos_args
+                    .iter()
+                    .map(|arg| arg.expr.ref_t().clone())
+                    .chain(kw_args.iter().map(|arg| arg.expr.ref_t().clone()))
+                    .collect::<Vec<_>>();
+                let candidates = candidates
+                    .into_iter()
+                    .map(|t| self.readable_type(t))
+                    .collect::<Vec<_>>();
+                Err(TyCheckErrors::from(
+                    TyCheckError::no_matching_overload_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        loc,
+                        &callee,
+                        &arg_ts,
+                        &candidates,
+                        self.caused_by(),
+                    ),
+                ))
+            }
+            _ => {
+                let candidates = applicable
+                    .into_iter()
+                    .map(|(candidate, _, _)| self.readable_type(candidate.clone()))
+                    .collect::<Vec<_>>();
+                Err(TyCheckErrors::from(TyCheckError::ambiguous_overload_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    &callee,
+                    &candidates,
+                    self.caused_by(),
+                )))
+            }
+        }
+    }
+
+    /// Returns the parameter types that the arguments are passed to, if `subr` can accept the arguments.
+    /// This does not unify any type variables.
+    fn overload_param_types(
+        &self,
+        obj: &hir::Expr,
+        subr: &SubrType,
+        pos_args: &[hir::PosArg],
+        kw_args: &[hir::KwArg],
+    ) -> Option<Vec<Type>> {
+        let is_method = subr
+            .self_t()
+            .is_some_and(|self_t| self.subtype_of(obj.ref_t(), self_t));
+        let (pos_args, kw_args) = self.expand_unsized_spreads(subr, is_method, pos_args, kw_args);
+        let (pos_args, kw_args) = (&pos_args[..], &kw_args[..]);
+        let params = subr
+            .non_default_params
+            .iter()
+            .skip(usize::from(is_method))
+            .chain(subr.default_params.iter())
+            .collect::<Vec<_>>();
+        let mut param_ts = vec![];
+        for (nth, arg) in pos_args.iter().enumerate() {
+            let param_t = if let Some(pt) = params.get(nth) {
+                pt.typ()
+            } else {
+                subr.var_params.as_ref()?.typ()
+            };
+            if !self.subtype_of(arg.expr.ref_t(), param_t) {
+                return None;
+            }
+            param_ts.push(param_t.clone());
+        }
+        let mut passed = params
+            .iter()
+            .take(pos_args.len())
+            .filter_map(|pt| pt.name())
+            .collect::<Set<_>>();
+        for kw_arg in kw_args.iter() {
+            let pt = params
+                .iter()
+                .copied()
+                .find(|pt| pt.name() == Some(kw_arg.keyword.inspect()))
+                .or(subr.kw_var_params.as_deref())?;
+            if !passed.insert(kw_arg.keyword.inspect())
+                || !self.subtype_of(kw_arg.expr.ref_t(), pt.typ())
+            {
+                return None;
+            }
+            param_ts.push(pt.typ().clone());
+        }
+        let non_default_len = subr.non_default_params.len() - usize::from(is_method);
+        let all_passed = params[..non_default_len]
+            .iter()
+            .enumerate()
+            .all(|(nth, pt)| {
+                nth < pos_args.len() || pt.name().is_some_and(|name| passed.contains(name))
+            });
+        all_passed.then_some(param_ts)
+    }
+
+    /// Returns the element types of `*expr` if the number of elements is statically known.
+    ///
+    /// ```erg
+    /// get_sized_spread_ts(Tuple([Int, Str])) == Some([Int, Str])
+    /// get_sized_spread_ts(Array(Int, 2)) == Some([Int, Int])
+    /// get_sized_spread_ts(Array(Int, _)) == None
+    /// ```
+    pub(crate) fn get_sized_spread_ts(&self, t: &Type) -> Option<Vec<Type>> {
+        match t {
+            FreeVar(fv) if fv.is_linked() => self.get_sized_spread_ts(&fv.crack()),
+            Refinement(refine) => self.get_sized_spread_ts(&refine.t),
+            Poly { name, params } if &name[..] == "Tuple" => {
+                let tps = Vec::try_from(params.first()?.clone()).ok()?;
+                tps.into_iter()
+                    .map(|tp| self.convert_tp_into_type(tp).ok())
+                    .collect()
+            }
+            Poly { name, params } if &name[..] == "Array" => {
+                let elem_t = self.convert_tp_into_type(params.first()?.clone()).ok()?;
+                let TyParam::Value(ValueObj::Nat(len)) = params.get(1)? else {
+                    return None;
+                };
+                Some(vec![elem_t; *len as usize])
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the fields of `**expr` if `t` is a record type
+    pub(crate) fn get_record_fields(&self, t: &Type) -> Option<Dict<Field, Type>> {
+        match t {
+            FreeVar(fv) if fv.is_linked() => self.get_record_fields(&fv.crack()),
+            Refinement(refine) => self.get_record_fields(&refine.t),
+            Record(rec) => Some(rec.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns `T` (`t <: Iterable(T)`), the element type of `*expr`
+    pub(crate) fn get_iterable_elem_t(&self, t: &Type, expr: &hir::Expr) -> TyCheckResult<Type> {
+        if let Some(ts) = self.get_sized_spread_ts(t) {
+            return Ok(ts
+                .iter()
+                .fold(Never, |union, elem_t| self.union(&union, elem_t)));
+        }
+        let elem_t = free_var(self.level, Constraint::new_type_of(Type));
+        let name = Str::from(expr.to_string_notype());
+        let iterable = poly("Iterable", vec![ty_tp(elem_t.clone())]);
+        self.sub_unify(t, &iterable, expr, Some(&name))?;
+        Ok(elem_t)
+    }
+
+    /// Returns `(K, V)` (`t <: Dict({K: V})`), the key and value types of `**expr`
+    pub(crate) fn get_mapping_kv_t(
+        &self,
+        t: &Type,
+        expr: &hir::Expr,
+    ) -> TyCheckResult<(Type, Type)> {
+        match t {
+            FreeVar(fv) if fv.is_linked() => self.get_mapping_kv_t(&fv.crack(), expr),
+            Refinement(refine) => self.get_mapping_kv_t(&refine.t, expr),
+            Poly { name, params } if &name[..] == "Dict" || &name[..] == "Dict!" => {
+                let Some(TyParam::Dict(kvs)) = params.first() else {
+                    return Ok((Obj, Obj));
+                };
+                let mut key_t = Never;
+                let mut val_t = Never;
+                for (k, v) in kvs.iter() {
+                    let k = self.convert_tp_into_type(k.clone()).unwrap_or(Obj);
+                    let v = self.convert_tp_into_type(v.clone()).unwrap_or(Obj);
+                    key_t = self.union(&key_t, &k);
+                    val_t = self.union(&val_t, &v);
+                }
+                Ok((key_t, val_t))
+            }
+            _ => {
+                let key_t = free_var(self.level, Constraint::new_type_of(Type));
+                let val_t = free_var(self.level, Constraint::new_type_of(Type));
+                let dict = dict! { ty_tp(key_t.clone()) => ty_tp(val_t.clone()) };
+                let name = Str::from(expr.to_string_notype());
+                self.sub_unify(t, &dict_t(TyParam::Dict(dict)), expr, Some(&name))?;
+                Ok((key_t, val_t))
+            }
+        }
+    }
+
+    /// Expands `*args` and `**kwargs` of a call for type checking.
+    ///
+    /// Tuples, arrays of known length and records are expanded into arguments precisely.
+    /// Other operands are left as `*`/`**` markers at the end of the positional arguments,
+    /// and are expanded according to the callee's parameters (see `expand_unsized_spreads`).
+    fn expand_spread_args(
+        &self,
+        pos_args: &[hir::PosArg],
+        var_args: Option<&hir::PosArg>,
+        kw_args: &[hir::KwArg],
+        kw_var_args: Option<&hir::PosArg>,
+    ) -> TyCheckResult<(Vec<hir::PosArg>, Vec<hir::KwArg>)> {
+        let mut pos_args = pos_args.to_vec();
+        let mut kw_args = kw_args.to_vec();
+        let mut markers = vec![];
+        if let Some(var_args) = var_args {
+            let expr = &var_args.expr;
+            if let Some(elem_ts) = self.get_sized_spread_ts(expr.ref_t()) {
+                pos_args.extend(
+                    elem_ts
+                        .into_iter()
+                        .map(|t| hir::PosArg::new(spread_elem(expr, t))),
+                );
+            } else {
+                let elem_t = self.get_iterable_elem_t(expr.ref_t(), expr)?;
+                markers.push(hir::PosArg::new(spread_marker(
+                    TokenKind::PreStar,
+                    expr,
+                    elem_t,
+                )));
+            }
+        }
+        if let Some(kw_var_args) = kw_var_args {
+            let expr = &kw_var_args.expr;
+            if let Some(rec) = self.get_record_fields(expr.ref_t()) {
+                for (field, t) in rec.into_iter() {
+                    let keyword = spread_keyword(expr, field.symbol.clone());
+                    kw_args.push(hir::KwArg::new(keyword, spread_elem(expr, t)));
+                }
+            } else {
+                let (key_t, val_t) = self.get_mapping_kv_t(expr.ref_t(), expr)?;
+                let name = Str::from(expr.to_string_notype());
+                self.sub_unify(&key_t, &Str, expr, Some(&name))?;
+                markers.push(hir::PosArg::new(spread_marker(
+                    TokenKind::PreDblStar,
+                    expr,
+                    val_t,
+                )));
+            }
+        }
+        pos_args.extend(markers);
+        Ok((pos_args, kw_args))
+    }
+
+    /// Expands the `*`/`**` markers made by `expand_spread_args` so that they fill the parameters not yet passed.
+    ///
+    /// ```erg
+    /// f(x: Int, y: Int, z := Int) = ...
+    /// xs: [Int; _]
+    /// f(*xs) # => f(xs[_]: Int, xs[_]: Int)
+    /// d: {Str: Int}
+    /// f(1, **d) # => f(1, y := d[_]: Int)
+    /// ```
+    fn expand_unsized_spreads<'a>(
+        &self,
+        subr: &SubrType,
+        is_method: bool,
+        pos_args: &'a [hir::PosArg],
+        kw_args: &'a [hir::KwArg],
+    ) -> (Cow<'a, [hir::PosArg]>, Cow<'a, [hir::KwArg]>) {
+        let n_markers = pos_args
+            .iter()
+            .rev()
+            .take_while(|arg| is_spread_marker(&arg.expr))
+            .count();
+        if n_markers == 0 {
+            return (Cow::Borrowed(pos_args), Cow::Borrowed(kw_args));
+        }
+        let (passed, markers) = pos_args.split_at(pos_args.len() - n_markers);
+        let mut pos_args = passed.to_vec();
+        let mut kw_args = kw_args.to_vec();
+        let non_default_params = subr
+            .non_default_params
+            .iter()
+            .skip(usize::from(is_method))
+            .collect::<Vec<_>>();
+        for marker in markers {
+            let hir::Expr::UnaryOp(marker) = &marker.expr else {
+                continue;
+            };
+            let keywords = kw_args
+                .iter()
+                .map(|arg| arg.keyword.inspect().clone())
+                .collect::<Set<_>>();
+            if marker.op.is(TokenKind::PreStar) {
+                for pt in non_default_params.iter().skip(pos_args.len()) {
+                    if pt.name().is_some_and(|name| keywords.contains(name)) {
+                        break;
+                    }
+                    pos_args.push(hir::PosArg::new(spread_elem(&marker.expr, marker.t())));
+                }
+                if subr.var_params.is_some() {
+                    pos_args.push(hir::PosArg::new(spread_elem(&marker.expr, marker.t())));
+                }
+            } else {
+                for pt in non_default_params.iter().skip(pos_args.len()) {
+                    let Some(name) = pt.name() else {
+                        continue;
+                    };
+                    if !keywords.contains(name) {
+                        let keyword = spread_keyword(&marker.expr, name.clone());
+                        let arg = spread_elem(&marker.expr, marker.t());
+                        kw_args.push(hir::KwArg::new(keyword, arg));
+                    }
+                }
+                if subr.kw_var_params.is_some() {
+                    // `**` never matches the parameter names, so this is checked against `kw_var_params`
+                    let keyword = spread_keyword(&marker.expr, Str::ever("**"));
+                    let arg = spread_elem(&marker.expr, marker.t());
+                    kw_args.push(hir::KwArg::new(keyword, arg));
+                }
+            }
+        }
+        (Cow::Owned(pos_args), Cow::Owned(kw_args))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn get_call_t(
         &self,
         obj: &hir::Expr,
         attr_name: &Option<Identifier>,
         pos_args: &[hir::PosArg],
+        var_args: Option<&hir::PosArg>,
         kw_args: &[hir::KwArg],
+        kw_var_args: Option<&hir::PosArg>,
         input: &Input,
         namespace: &Context,
     ) -> Result<VarInfo, (Option<VarInfo>, TyCheckErrors)> {
@@ -1750,6 +2327,15 @@ impl Context {
                 }
             }
         }
+        let (pos_args, kw_args) = if var_args.is_some() || kw_var_args.is_some() {
+            let (pos_args, kw_args) = self
+                .expand_spread_args(pos_args, var_args, kw_args, kw_var_args)
+                .map_err(|errs| (None, errs))?;
+            (Cow::Owned(pos_args), Cow::Owned(kw_args))
+        } else {
+            (Cow::Borrowed(pos_args), Cow::Borrowed(kw_args))
+        };
+        let (pos_args, kw_args) = (&pos_args[..], &kw_args[..]);
         let found = self
             .search_callee_info(obj, attr_name, pos_args, kw_args, input, namespace)
             .map_err(|err| (None, TyCheckErrors::from(err)))?;
@@ -1848,7 +2434,7 @@ impl Context {
         }
     }
 
-    pub(crate) fn get_similar_name(&self, name: &str) -> Option<&str> {
+    pub fn get_similar_name(&self, name: &str) -> Option<&str> {
         levenshtein::get_similar_name(
             self.dir().into_iter().map(|(vn, _)| &vn.inspect()[..]),
             name,
@@ -2106,8 +2692,10 @@ impl Context {
             .chain(ctx.super_traits.iter())
             .map(|sup| {
                 self.get_nominal_type_ctx(sup)
+                    .map(|(_, ctx)| ctx)
+                    // e.g. the super class of `Expr.Lit` is `Expr` (`self`)
+                    .or_else(|| (sup.qual_name() == self.name).then_some(self))
                     .unwrap_or_else(|| todo!("compiler bug: {sup} not found"))
-                    .1
             });
         Some(vec![ctx].into_iter().chain(sups))
     }
@@ -2349,11 +2937,23 @@ impl Context {
         } else {
             set! {}
         };
-        if let Some(outer) = self.get_outer().or_else(|| self.get_builtins()) {
+        let impls = if let Some(outer) = self.get_outer().or_else(|| self.get_builtins()) {
             current.union(&outer.get_simple_trait_impls(trait_))
         } else {
             current
-        }
+        };
+        // implementations by glue patches are only available where the patches are visible
+        impls
+            .into_iter()
+            .filter(|imp| match &imp.glue_patch {
+                Some(patch) => self.is_visible_glue_patch(patch),
+                None => true,
+            })
+            .collect()
+    }
+
+    fn is_visible_glue_patch(&self, name: &Str) -> bool {
+        self.all_patches().iter().any(|patch| &patch.name == name)
     }
 
     pub(crate) fn all_patches(&self) -> Vec<&Context> {
@@ -2371,6 +2971,12 @@ impl Context {
     pub(crate) fn resolve_real_path(cfg: &ErgConfig, path: &Path) -> Option<PathBuf> {
         if let Ok(path) = cfg.input.local_resolve(path) {
             Some(path)
+        } else if let Some(path) = cfg
+            .search_paths
+            .iter()
+            .find_map(|root| Self::resolve_real_path_in_root(root, path))
+        {
+            Some(path)
         } else if let Ok(path) = erg_std_path()
             .join(format!("{}.er", path.display()))
             .canonicalize()
@@ -2387,10 +2993,26 @@ impl Context {
         }
     }
 
+    /// {root}/{path}.er or {root}/{path}/__init__.er
+    fn resolve_real_path_in_root(root: &Path, path: &Path) -> Option<PathBuf> {
+        if let Ok(path) = root.join(format!("{}.er", path.display())).canonicalize() {
+            Some(normalize_path(path))
+        } else if let Ok(path) = root.join(path).join("__init__.er").canonicalize() {
+            Some(normalize_path(path))
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn resolve_decl_path(cfg: &ErgConfig, path: &Path) -> Option<PathBuf> {
         if let Ok(path) = cfg.input.local_decl_resolve(path) {
             Some(path)
         } else {
+            for root in cfg.search_paths.iter() {
+                if let Some(path) = Self::resolve_std_decl_path(root.clone(), path) {
+                    return Some(path);
+                }
+            }
             let py_roots = [erg_pystd_path, erg_py_external_lib_path];
             for root in py_roots {
                 if let Some(path) = Self::resolve_std_decl_path(root(), path) {
@@ -2493,14 +3115,32 @@ impl Context {
 
     // TODO: poly type
     pub(crate) fn rec_get_self_t(&self) -> Option<Type> {
-        if self.kind.is_method_def() || self.kind.is_type() {
-            Some(mono(self.name.clone()))
-        } else if let ContextKind::PatchMethodDefs(t) = &self.kind {
-            Some(t.clone())
-        } else if let Some(outer) = self.get_outer() {
-            outer.rec_get_self_t()
-        } else {
-            None
+        self.get_self_t_with(&TyVarCache::new(self.level, self))
+    }
+
+    /// In `C(T, N).` blocks, `Self` is `C(T, N)` with the type variables of the method being defined.
+    pub(crate) fn get_self_t_with(&self, tmp_tv_cache: &TyVarCache) -> Option<Type> {
+        let mut ctx = self;
+        loop {
+            if ctx.kind.is_method_def() && !ctx.params.is_empty() {
+                let params = ctx
+                    .params
+                    .iter()
+                    .map(|(name, vi)| {
+                        name.as_ref()
+                            .and_then(|name| {
+                                self.rec_get_tp_from_tv_cache(name.inspect(), tmp_tv_cache)
+                            })
+                            .unwrap_or_else(|| TyParam::erased(vi.t.clone()))
+                    })
+                    .collect();
+                return Some(poly(ctx.name.clone(), params));
+            } else if ctx.kind.is_method_def() || ctx.kind.is_type() {
+                return Some(mono(ctx.name.clone()));
+            } else if let ContextKind::PatchMethodDefs(t) = &ctx.kind {
+                return Some(t.clone());
+            }
+            ctx = ctx.get_outer()?;
         }
     }
 
@@ -2508,11 +3148,6 @@ impl Context {
         self.kind.is_subr() || self.get_outer().map_or(false, |ctx| ctx.in_subr())
     }
 
-    pub(crate) fn gen_type(&self, ident: &ast::Identifier) -> Type {
-        let vis = ident.vis.display_as_accessor();
-        mono(format!("{}{vis}{}", self.name, ident.inspect()))
-    }
-
     pub(crate) fn get_namespace(&self, namespace: &Str) -> Option<&Context> {
         let mut namespaces = namespace.split_with(&[".", "::"]);
         let mut namespace = namespaces.first().map(|n| n.to_string())?;
@@ -2537,6 +3172,15 @@ impl Context {
             return Some((t, ctx));
         }
         let typ = Type::Mono(Str::rc(name));
+        // e.g. `<module>::Expr.Lit` (a variant of `Expr`)
+        // this must be checked first (`Expr.Pos` is not the builtin `Pos`)
+        if !typ.namespace().is_empty() {
+            if let Some((_, ctx)) = self.get_mono_type(&typ.namespace()) {
+                if let Some((t, ctx)) = ctx.mono_types.get(&typ.local_name()[..]) {
+                    return Some((t, ctx));
+                }
+            }
+        }
         if self.name.starts_with(&typ.namespace()[..]) {
             if let Some((t, ctx)) = self.rec_local_get_mono_type(&typ.local_name()) {
                 return Some((t, ctx));
@@ -2550,6 +3194,30 @@ impl Context {
         None
     }
 
+    /// e.g. `Expr` => `[Expr.Add, Expr.Lit, ...]`, `Int` => `None`
+    pub(crate) fn get_enum_variants(&self, t: &Type) -> Option<Vec<&Type>> {
+        let (_, ctx) = self.get_nominal_type_ctx(t)?;
+        let variants = ctx
+            .mono_types
+            .values()
+            .filter(|(_, variant)| variant.super_classes.first() == Some(t))
+            .map(|(variant_t, _)| variant_t)
+            .collect::<Vec<_>>();
+        (!variants.is_empty()).then_some(variants)
+    }
+
+    /// e.g. `Expr.Lit or Expr.Add` => `Some(Expr)`, `Expr.Lit` => `None` (if `Expr = Enum {.Lit = ...; .Add = ...}`)
+    pub(crate) fn get_exhaustive_enum(&self, union: &Type) -> Option<Type> {
+        let ors = union.union_types();
+        let (_, ctx) = self.get_nominal_type_ctx(ors.first()?)?;
+        let enum_t = ctx.super_classes.first()?;
+        let variants = self.get_enum_variants(enum_t)?;
+        variants
+            .iter()
+            .all(|variant| ors.contains(variant))
+            .then(|| enum_t.clone())
+    }
+
     /// you should use `get_mono_type` instead of this
     pub(crate) fn rec_local_get_mono_type(&self, name: &str) -> Option<(&Type, &Context)> {
         #[cfg(feature = "py_compat")]
@@ -2650,6 +3318,16 @@ impl Context {
         }
     }
 
+    /// e.g. `foo.StrReverse` => the patch `StrReverse` defined in the module `foo`
+    pub(crate) fn get_patch_of_type(&self, typ: &Type) -> Option<&Context> {
+        if let Some(ctx) = self.get_namespace(&typ.namespace()) {
+            if let Some(patch) = ctx.patches.get(&typ.local_name()[..]) {
+                return Some(patch);
+            }
+        }
+        self.rec_get_patch(&typ.local_name())
+    }
+
     pub(crate) fn rec_get_patch(&self, name: &str) -> Option<&Context> {
         if let Some(ctx) = self.patches.get(name) {
             Some(ctx)
@@ -2687,7 +3365,9 @@ impl Context {
             {
                 return false;
             }
-            if cand.var_params().is_some() != first.var_params().is_some() {
+            if cand.var_params().is_some() != first.var_params().is_some()
+                || cand.kw_var_params().is_some() != first.kw_var_params().is_some()
+            {
                 return false;
             }
             if cand
@@ -2825,6 +3505,7 @@ impl Context {
                                 subr.non_default_params.clone(),
                                 subr.var_params.as_ref().map(|p| *p.clone()),
                                 subr.default_params.clone(),
+                                subr.kw_var_params.as_ref().map(|p| *p.clone()),
                                 ret_t,
                             );
                             Type::Subr(subr)
@@ -2937,6 +3618,27 @@ impl Context {
         }
     }
 
+    pub(crate) fn rec_get_tp_from_tv_cache(
+        &self,
+        name: &str,
+        tmp_tv_cache: &TyVarCache,
+    ) -> Option<TyParam> {
+        if let Some(tp) = self.get_tp_from_tv_cache(name, tmp_tv_cache) {
+            return Some(tp);
+        }
+        let mut ctx = self.get_outer()?;
+        loop {
+            if let Some(tv_cache) = &ctx.tv_cache {
+                if let Some(tp) = tv_cache.get_typaram(name) {
+                    return Some(tp.clone());
+                } else if let Some(t) = tv_cache.get_tyvar(name) {
+                    return Some(TyParam::t(t.clone()));
+                }
+            }
+            ctx = ctx.get_outer()?;
+        }
+    }
+
     pub(crate) fn get_tp_from_tv_cache(
         &self,
         name: &str,
@@ -2947,10 +3649,10 @@ impl Context {
         } else if let Some(t) = tmp_tv_cache.get_tyvar(name) {
             Some(TyParam::t(t.clone()))
         } else if let Some(tv_ctx) = &self.tv_cache {
-            if let Some(t) = tv_ctx.get_tyvar(name) {
-                Some(TyParam::t(t.clone()))
+            if let Some(tp) = tv_ctx.get_typaram(name) {
+                Some(tp.clone())
             } else {
-                tv_ctx.get_typaram(name).cloned()
+                tv_ctx.get_tyvar(name).map(|t| TyParam::t(t.clone()))
             }
         } else {
             None
@@ -453,12 +453,20 @@ impl Context {
                     *pt.typ_mut() =
                         self.instantiate_t_inner(mem::take(pt.typ_mut()), tmp_tv_cache, loc)?;
                 }
+                if let Some(kw_var_args) = subr.kw_var_params.as_mut() {
+                    *kw_var_args.typ_mut() = self.instantiate_t_inner(
+                        mem::take(kw_var_args.typ_mut()),
+                        tmp_tv_cache,
+                        loc,
+                    )?;
+                }
                 let return_t = self.instantiate_t_inner(*subr.return_t, tmp_tv_cache, loc)?;
                 let res = subr_t(
                     subr.kind,
                     subr.non_default_params,
                     subr.var_params.map(|p| *p),
                     subr.default_params,
+                    subr.kw_var_params.map(|p| *p),
                     return_t,
                 );
                 Ok(res)
@@ -19,7 +19,7 @@ use crate::ty::free::{CanbeFree, Constraint};
 use crate::ty::typaram::{IntervalOp, OpKind, TyParam, TyParamLambda, TyParamOrdering};
 use crate::ty::value::ValueObj;
 use crate::ty::{constructors::*, VisibilityModifier};
-use crate::ty::{Field, HasType, ParamTy, SubrKind, SubrType, Type};
+use crate::ty::{ConstSubr, Field, HasType, ParamTy, SubrKind, SubrType, Type};
 use crate::type_feature_error;
 use TyParamOrdering::*;
 use Type::*;
@@ -171,6 +171,25 @@ impl Context {
         for bound in bounds.iter() {
             self.instantiate_ty_bound(bound, &mut tv_cache, mode)?;
         }
+        // the type parameters of `C(T, N).` are bound for each method
+        if self.kind.is_method_def() {
+            for (name, vi) in self.params.iter() {
+                let Some(name) = name.as_ref().map(|name| name.inspect()) else {
+                    continue;
+                };
+                if tv_cache.get_tyvar(name).is_some() || tv_cache.get_typaram(name).is_some() {
+                    continue;
+                }
+                let constr = Constraint::new_type_of(vi.t.clone());
+                if constr.get_sub_sup().is_none() {
+                    let tp = TyParam::named_free_var(name.clone(), self.level, constr);
+                    tv_cache.push_or_init_typaram(name, &tp, self);
+                } else {
+                    let tv = named_free_var(name.clone(), self.level, constr);
+                    tv_cache.push_or_init_tyvar(name, &tv, self);
+                }
+            }
+        }
         for tv in tv_cache.tyvar_instances.values() {
             if tv.constraint().map(|c| c.is_uninited()).unwrap_or(false) {
                 return Err(TyCheckErrors::from(TyCheckError::no_var_error(
@@ -209,7 +228,12 @@ impl Context {
         } else {
             free_var(self.level, Constraint::new_type_of(Type))
         };
-        Ok(spec_t)
+        // e.g. `x: Cons = ...` (`Cons(T, N)`): don't link the type parameters of the class itself
+        if spec_t.has_qvar() && !spec_t.is_quantified_subr() {
+            self.instantiate_def_type(&spec_t)
+        } else {
+            Ok(spec_t)
+        }
     }
 
     pub(crate) fn instantiate_sub_sig_t(
@@ -299,6 +323,28 @@ impl Context {
                 }
             }
         }
+        let kw_var_args = if let Some(kw_var_args) = sig.params.kw_var_params.as_ref() {
+            let opt_decl_t = opt_decl_sig_t
+                .as_ref()
+                .and_then(|subr| subr.kw_var_params.as_ref())
+                .map(|pt| pt.as_ref().clone().map_type(str_dict_t));
+            let pt = match self.instantiate_param_ty(
+                kw_var_args,
+                opt_decl_t.as_ref(),
+                &mut tmp_tv_cache,
+                mode,
+                ParamKind::KwParams,
+            ) {
+                Ok(pt) => pt,
+                Err(es) => {
+                    errs.extend(es);
+                    ParamTy::pos_or_kw(kw_var_args.inspect().cloned(), Type::Failure)
+                }
+            };
+            Some(pt)
+        } else {
+            None
+        };
         let spec_return_t = if let Some(t_spec) = sig.return_t_spec.as_ref() {
             let opt_decl_t = opt_decl_sig_t
                 .as_ref()
@@ -325,11 +371,25 @@ impl Context {
             };
             free_var(level, Constraint::new_type_of(Type))
         };
-        let typ = if sig.ident.is_procedural() {
-            proc(non_defaults, var_args, defaults, spec_return_t)
+        // `@Async` procedures return `Coroutine(T)` (`T` is the type of the body)
+        let spec_return_t = if sig.is_async() {
+            poly("Coroutine", vec![TyParam::t(spec_return_t)])
+        } else {
+            spec_return_t
+        };
+        let kind = if sig.ident.is_procedural() {
+            SubrKind::Proc
         } else {
-            func(non_defaults, var_args, defaults, spec_return_t)
+            SubrKind::Func
         };
+        let typ = subr_t(
+            kind,
+            non_defaults,
+            var_args,
+            defaults,
+            kw_var_args,
+            spec_return_t,
+        );
         if errs.is_empty() {
             Ok(typ)
         } else {
@@ -337,6 +397,60 @@ impl Context {
         }
     }
 
+    /// `Self` -> `C(T, N)` (in `C(T, N).` blocks)
+    /// `Self(T, N ~> N+1)` -> `RefMut(C(T, N) ~> C(T, N+1))`
+    fn instantiate_self_t(
+        &self,
+        simple: &SimpleTypeSpec,
+        tmp_tv_cache: &mut TyVarCache,
+        not_found_is_qvar: bool,
+    ) -> TyCheckResult<Type> {
+        let Some(self_t) = self.get_self_t_with(tmp_tv_cache) else {
+            return Err(TyCheckErrors::from(TyCheckError::unreachable(
+                self.cfg.input.clone(),
+                erg_common::fn_name_full!(),
+                line!(),
+            )));
+        };
+        if simple.args.is_empty() {
+            return Ok(self_t);
+        }
+        let mut before = vec![];
+        let mut after = vec![];
+        let mut transition = false;
+        for arg in simple.args.pos_args() {
+            match &arg.expr {
+                ast::ConstExpr::BinOp(bin) if bin.op.is(TokenKind::Transition) => {
+                    transition = true;
+                    before.push(self.instantiate_const_expr(
+                        &bin.lhs,
+                        None,
+                        tmp_tv_cache,
+                        not_found_is_qvar,
+                    )?);
+                    after.push(self.instantiate_const_expr(
+                        &bin.rhs,
+                        None,
+                        tmp_tv_cache,
+                        not_found_is_qvar,
+                    )?);
+                }
+                other => {
+                    let tp =
+                        self.instantiate_const_expr(other, None, tmp_tv_cache, not_found_is_qvar)?;
+                    before.push(tp.clone());
+                    after.push(tp);
+                }
+            }
+        }
+        let before = poly(self_t.qual_name(), before);
+        if transition {
+            Ok(ref_mut(before, Some(poly(self_t.qual_name(), after))))
+        } else {
+            Ok(before)
+        }
+    }
+
     /// spec_t == Noneかつリテラル推論が不可能なら型変数を発行する
     pub(crate) fn instantiate_param_sig_t(
         &self,
@@ -376,6 +490,14 @@ impl Context {
                     &sig.t_spec.as_ref().ok_or(sig),
                     None,
                 )?;
+            } else if kind.is_kw_params() {
+                let spec_t = str_dict_t(spec_t.clone());
+                self.sub_unify(
+                    decl_pt.typ(),
+                    &spec_t,
+                    &sig.t_spec.as_ref().ok_or(sig),
+                    None,
+                )?;
             } else {
                 self.sub_unify(
                     decl_pt.typ(),
@@ -449,6 +571,12 @@ impl Context {
                         not_found_is_qvar,
                     ) {
                         let rhs = t.ident.inspect();
+                        // e.g. `Expr.Lit` (a variant of `Expr`)
+                        if let Some((_, ctx)) = self.get_nominal_type_ctx(&receiver_t) {
+                            if let Some((t, _)) = ctx.mono_types.get(rhs) {
+                                return Ok(t.clone());
+                            }
+                        }
                         return Ok(proj(receiver_t, rhs));
                     }
                 }
@@ -582,13 +710,7 @@ impl Context {
                 )?;
                 Ok(t.structuralize())
             }
-            "Self" => self.rec_get_self_t().ok_or_else(|| {
-                TyCheckErrors::from(TyCheckError::unreachable(
-                    self.cfg.input.clone(),
-                    erg_common::fn_name_full!(),
-                    line!(),
-                ))
-            }),
+            "Self" => self.instantiate_self_t(simple, tmp_tv_cache, not_found_is_qvar),
             other if simple.args.is_empty() => {
                 if let Some(TyParam::Type(t)) = self.get_tp_from_tv_cache(other, tmp_tv_cache) {
                     return Ok(*t);
@@ -624,8 +746,55 @@ impl Context {
                 }
             }
             other => {
-                let ctx = if let Some((_, ctx)) = self.get_type(&Str::rc(other)) {
-                    ctx
+                // higher-kinded type variable, e.g. `F(T)` (`F: Type -> Type`)
+                if let Some(ctor) = self.get_tp_from_tv_cache(other, tmp_tv_cache) {
+                    let arity = match &ctor {
+                        TyParam::FreeVar(fv) => fv.constraint().and_then(|c| c.type_ctor_arity()),
+                        _ => None,
+                    };
+                    if let Some(arity) = arity {
+                        let mut args = vec![];
+                        for arg in simple.args.pos_args() {
+                            let arg = self.instantiate_const_expr(
+                                &arg.expr,
+                                None,
+                                tmp_tv_cache,
+                                not_found_is_qvar,
+                            )?;
+                            args.push(arg);
+                        }
+                        if args.len() != arity {
+                            return Err(TyCheckErrors::from(TyCheckError::argument_error(
+                                self.cfg.input.clone(),
+                                line!() as usize,
+                                simple.args.loc(),
+                                self.caused_by(),
+                                arity,
+                                args.len(),
+                            )));
+                        }
+                        return Ok(proj_call(ctor, "__call__", args));
+                    }
+                }
+                if let Some(ValueObj::Subr(ConstSubr::User(subr))) = self.rec_get_const_obj(other) {
+                    let mut args = vec![];
+                    for arg in simple.args.pos_args() {
+                        let arg = self.instantiate_const_expr(
+                            &arg.expr,
+                            None,
+                            tmp_tv_cache,
+                            not_found_is_qvar,
+                        )?;
+                        args.push(arg);
+                    }
+                    // e.g. `List(T, N-1)` is evaluated when `T` and `N` are determined
+                    return match self.eval_type_app(subr, &args, simple)? {
+                        Some(t) => Ok(t),
+                        None => Ok(poly(simple.ident.inspect().clone(), args)),
+                    };
+                }
+                let (typ, ctx) = if let Some((typ, ctx)) = self.get_type(&Str::rc(other)) {
+                    (typ, ctx)
                 } else {
                     return Err(TyCheckErrors::from(TyCheckError::no_type_error(
                         self.cfg.input.clone(),
@@ -663,8 +832,7 @@ impl Context {
                     })?;
                     new_params.push(params);
                 }
-                // FIXME: non-builtin
-                Ok(poly(Str::rc(other), new_params))
+                Ok(poly(typ.qual_name(), new_params))
             }
         }
     }
@@ -735,6 +903,12 @@ impl Context {
         if let Some(tp) = self.get_tp_from_tv_cache(name, tmp_tv_cache) {
             return Ok(tp);
         }
+        // e.g. `Self.Lit`
+        if &name[..] == "Self" {
+            if let Some(self_t) = self.rec_get_self_t() {
+                return Ok(TyParam::t(self_t));
+            }
+        }
         if let Some(value) = self.rec_get_const_obj(name) {
             return Ok(TyParam::Value(value.clone()));
         }
@@ -1023,6 +1197,7 @@ impl Context {
                     lambda.nd_params,
                     lambda.var_params,
                     lambda.d_params,
+                    None,
                     return_t,
                 );
                 Ok(Type::Subr(subr))
@@ -1112,19 +1287,31 @@ impl Context {
                 not_found_is_qvar,
             )?)),
             TypeSpec::Array(arr) => {
+                // `opt_decl_t` is the type of the whole array, not of the elements
                 let elem_t = self.instantiate_typespec(
                     &arr.ty,
-                    opt_decl_t,
+                    None,
                     tmp_tv_cache,
                     mode,
                     not_found_is_qvar,
                 )?;
+                // [T; !N] == Array!(T, N)
+                let (len, telescoping) = match &arr.len {
+                    ast::ConstExpr::UnaryOp(unary) if unary.op.is(TokenKind::Mutate) => {
+                        (unary.expr.as_ref(), true)
+                    }
+                    len => (len, false),
+                };
                 let mut len =
-                    self.instantiate_const_expr(&arr.len, None, tmp_tv_cache, not_found_is_qvar)?;
+                    self.instantiate_const_expr(len, None, tmp_tv_cache, not_found_is_qvar)?;
                 if let TyParam::Erased(t) = &mut len {
                     *t.as_mut() = Type::Nat;
                 }
-                Ok(array_t(elem_t, len))
+                if telescoping {
+                    Ok(array_mut(elem_t, len))
+                } else {
+                    Ok(array_t(elem_t, len))
+                }
             }
             TypeSpec::SetWithLen(set) => {
                 let elem_t = self.instantiate_typespec(
@@ -1261,6 +1448,13 @@ impl Context {
                 })?
                 .into_iter()
                 .collect();
+                let kw_var_params = subr
+                    .kw_var_params
+                    .as_ref()
+                    .map(|p| {
+                        self.instantiate_func_param_spec(p, opt_decl_t, None, tmp_tv_ctx, mode)
+                    })
+                    .transpose()?;
                 let return_t = self.instantiate_typespec(
                     &subr.return_t,
                     opt_decl_t,
@@ -1273,6 +1467,7 @@ impl Context {
                     non_defaults,
                     var_params,
                     defaults,
+                    kw_var_params,
                     return_t,
                 ))
             }
@@ -31,7 +31,6 @@ use erg_common::{fmt_option, fn_name, get_hash, log};
 use ast::{DefId, DefKind, VarName};
 use erg_parser::ast;
 use erg_parser::ast::Def;
-use erg_parser::token::Token;
 
 use crate::context::instantiate::TyVarCache;
 use crate::context::instantiate_spec::ConstTemplate;
@@ -74,6 +73,8 @@ impl TryFrom<&str> for ControlKind {
             "match" | "match!" => Ok(ControlKind::Match),
             "try" | "try!" => Ok(ControlKind::Try),
             "with" | "with!" => Ok(ControlKind::With),
+            "async_for!" => Ok(ControlKind::For),
+            "async_with!" => Ok(ControlKind::With),
             "assert" => Ok(ControlKind::Assert),
             _ => Err(()),
         }
@@ -88,6 +89,9 @@ impl ControlKind {
     pub const fn is_conditional(&self) -> bool {
         matches!(self, Self::If | Self::While)
     }
+    pub const fn is_match(&self) -> bool {
+        matches!(self, Self::Match)
+    }
     pub const fn makes_scope(&self) -> bool {
         !matches!(self, Self::Assert)
     }
@@ -97,6 +101,9 @@ impl ControlKind {
 pub struct TraitImpl {
     pub sub_type: Type,
     pub sup_trait: Type,
+    /// the name of the glue patch that provides this implementation
+    /// (it is available only where the patch is visible)
+    pub glue_patch: Option<Str>,
 }
 
 impl std::fmt::Display for TraitImpl {
@@ -110,6 +117,15 @@ impl TraitImpl {
         Self {
             sub_type,
             sup_trait,
+            glue_patch: None,
+        }
+    }
+
+    pub const fn glue(sub_type: Type, sup_trait: Type, glue_patch: Str) -> Self {
+        Self {
+            sub_type,
+            sup_trait,
+            glue_patch: Some(glue_patch),
         }
     }
 }
@@ -180,7 +196,7 @@ impl std::ops::Mul for Variance {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ParamSpec {
-    pub(crate) name: Option<&'static str>,
+    pub(crate) name: Option<Str>,
     // TODO: `:` or `<:`
     pub(crate) t: Type,
     pub is_var_params: bool,
@@ -190,7 +206,7 @@ pub struct ParamSpec {
 
 impl ParamSpec {
     pub const fn new(
-        name: Option<&'static str>,
+        name: Option<Str>,
         t: Type,
         is_var_params: bool,
         default: DefaultInfo,
@@ -212,7 +228,7 @@ impl ParamSpec {
         default: DefaultInfo,
     ) -> Self {
         Self::new(
-            Some(name),
+            Some(Str::ever(name)),
             t,
             is_var_params,
             default,
@@ -222,7 +238,7 @@ impl ParamSpec {
 
     pub const fn named_nd(name: &'static str, t: Type) -> Self {
         Self::new(
-            Some(name),
+            Some(Str::ever(name)),
             t,
             false,
             DefaultInfo::NonDefault,
@@ -232,7 +248,7 @@ impl ParamSpec {
 
     pub const fn t(name: &'static str, is_var_params: bool, default: DefaultInfo) -> Self {
         Self::new(
-            Some(name),
+            Some(Str::ever(name)),
             Type,
             is_var_params,
             default,
@@ -242,7 +258,7 @@ impl ParamSpec {
 
     pub const fn t_nd(name: &'static str) -> Self {
         Self::new(
-            Some(name),
+            Some(Str::ever(name)),
             Type,
             false,
             DefaultInfo::NonDefault,
@@ -271,7 +287,7 @@ pub enum ContextKind {
 impl From<&Def> for ContextKind {
     fn from(def: &Def) -> Self {
         match def.def_kind() {
-            DefKind::Class | DefKind::Inherit => Self::Class,
+            DefKind::Class | DefKind::Inherit | DefKind::Enum => Self::Class,
             DefKind::Trait | DefKind::Subsume => Self::Trait,
             DefKind::StructuralTrait => Self::StructuralTrait,
             DefKind::ErgImport | DefKind::PyImport => Self::Module,
@@ -527,6 +543,41 @@ impl Context {
         let ctx = self.get_namespace(&namespace)?;
         ctx.get_var_info(&typ.local_name())
     }
+
+    /// Returns the type that the constant `name` (class, trait, patch, etc.) refers to.
+    pub fn get_type_by_name(&self, name: &str) -> Option<&Type> {
+        match self.rec_get_const_obj(name)? {
+            ValueObj::Type(t) => Some(t.typ()),
+            _ => None,
+        }
+    }
+
+    pub fn get_type_ctx(&self, typ: &Type) -> Option<&Context> {
+        self.get_nominal_type_ctx(typ).map(|(_, ctx)| ctx)
+    }
+
+    /// Returns the direct super classes and super traits.
+    /// If `self` is a patch, the super classes are the patched classes.
+    pub fn direct_super_types(&self) -> impl Iterator<Item = &Type> {
+        self.super_classes.iter().chain(self.super_traits.iter())
+    }
+
+    /// Returns the members declared in the trait `self` but not defined in the class `class_ctx`.
+    pub fn unimplemented_members<'a>(
+        &'a self,
+        class_ctx: &Context,
+    ) -> Vec<(&'a VarName, &'a VarInfo)> {
+        self.decls
+            .iter()
+            .filter(|(name, _)| {
+                class_ctx.get_var_kv(name.inspect()).is_none()
+                    && class_ctx
+                        .methods_list
+                        .iter()
+                        .all(|(_, methods)| methods.get_var_kv(name.inspect()).is_none())
+            })
+            .collect()
+    }
 }
 
 impl Context {
@@ -572,18 +623,18 @@ impl Context {
             let id = DefId(get_hash(&(&name, &param)));
             if let Some(name) = param.name {
                 let kind = VarKind::parameter(id, param.is_var_params, param.default_info);
-                let muty = Mutability::from(name);
+                let muty = Mutability::from(&name[..]);
                 let vi = VarInfo::new(
                     param.t,
                     muty,
-                    Visibility::private(name),
+                    Visibility::private(name.clone()),
                     kind,
                     None,
                     None,
                     None,
                     param.loc,
                 );
-                params_.push((Some(VarName::new(Token::static_symbol(name))), vi));
+                params_.push((Some(VarName::from_str(name)), vi));
             } else {
                 let kind = VarKind::parameter(id, param.is_var_params, param.default_info);
                 let muty = Mutability::Immutable;
@@ -835,6 +886,29 @@ impl Context {
         )
     }
 
+    #[inline]
+    pub fn mono_glue_patch<S: Into<Str>>(
+        name: S,
+        base: Type,
+        impls: Type,
+        cfg: ErgConfig,
+        shared: Option<SharedCompilerResource>,
+        capacity: usize,
+        level: usize,
+    ) -> Self {
+        let name = name.into();
+        Self::poly(
+            name.clone(),
+            cfg,
+            ContextKind::GluePatch(TraitImpl::glue(base, impls, name)),
+            vec![],
+            None,
+            shared,
+            capacity,
+            level,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn poly_glue_patch<S: Into<Str>>(
@@ -1143,6 +1217,29 @@ impl Context {
             .last()
             .and_then(|caller| ControlKind::try_from(&caller[..]).ok())
     }
+
+    /// Whether `await` can be used here, i.e. the innermost subroutine is an `@Async` procedure.
+    /// The blocks of control procedures (e.g. `for!`) are inlined, so they are skipped.
+    pub(crate) fn in_async_subr(&self) -> bool {
+        let Some(outer) = self.get_outer() else {
+            return false;
+        };
+        let Some(local) = self.name.strip_prefix(&outer.name[..]) else {
+            return false;
+        };
+        let local = local.trim_start_matches("::").trim_start_matches('.');
+        if local.starts_with("<lambda_") {
+            return outer.control_kind().is_some() && outer.in_async_subr();
+        }
+        match self.kind {
+            // the body of a variable definition
+            ContextKind::Instant => outer.in_async_subr(),
+            ContextKind::Func | ContextKind::Proc => outer
+                .get_current_scope_var(&ast::VarName::from_str(Str::rc(local)))
+                .is_some_and(|vi| vi.is_async()),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
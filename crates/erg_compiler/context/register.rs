@@ -18,24 +18,29 @@ use erg_common::{get_hash, log, set};
 
 use ast::{ConstIdentifier, Decorator, DefId, Identifier, OperationKind, SimpleTypeSpec, VarName};
 use erg_parser::ast::{self, PreDeclTypeSpec};
+use erg_parser::Parser;
 
 use crate::ty::constructors::{
-    free_var, func, func0, func1, proc, ref_, ref_mut, unknown_len_array_t, v_enum,
+    free_var, func0, func1, mono, mono_q, mono_q_tp, poly, ref_, ref_mut, str_dict_t, subr_t,
+    unknown_len_array_t, v_enum,
 };
 use crate::ty::free::{Constraint, HasLevel};
 use crate::ty::typaram::TyParam;
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
 use crate::ty::{
-    GuardType, HasType, ParamTy, SubrType, Type, Variable, Visibility, VisibilityModifier,
+    ConstSubr, Field, GuardType, HasType, ParamTy, SubrKind, SubrType, Type, UserConstSubr,
+    Variable, Visibility, VisibilityModifier,
 };
 
 use crate::build_hir::HIRBuilder;
 use crate::context::{
-    ClassDefType, Context, ContextKind, DefaultInfo, MethodInfo, RegistrationMode, TraitImpl,
+    ClassDefType, Context, ContextKind, DefaultInfo, MethodInfo, ParamSpec, RegistrationMode,
+    TraitImpl,
 };
 use crate::error::readable_name;
 use crate::error::{
-    CompileError, CompileErrors, CompileResult, TyCheckError, TyCheckErrors, TyCheckResult,
+    CompileError, CompileErrors, CompileResult, SingleCompileResult, TyCheckError, TyCheckErrors,
+    TyCheckResult,
 };
 use crate::hir::Literal;
 use crate::varinfo::{AbsLocation, Mutability, VarInfo, VarKind};
@@ -50,6 +55,17 @@ pub fn valid_mod_name(name: &str) -> bool {
     !name.is_empty() && name.trim() == name
 }
 
+/// A type-level function is a constant subroutine that is not a type definition,
+/// e.g. `List T, 0 = Nil T`
+pub(crate) fn is_type_func_def(def: &ast::Def) -> bool {
+    match &def.sig {
+        ast::Signature::Subr(sig) => {
+            sig.is_const() && def.def_kind().is_other() && !sig.params.non_defaults.is_empty()
+        }
+        ast::Signature::Var(_) => false,
+    }
+}
+
 /// format:
 /// ```python
 /// #[pylyzer] succeed foo.py 1234567890
@@ -144,11 +160,7 @@ impl Context {
         let vis = self.instantiate_vis_modifier(&ident.vis)?;
         let kind = id.map_or(VarKind::Declared, VarKind::Defined);
         let sig_t = self.instantiate_var_sig_t(sig.t_spec.as_ref(), PreRegister)?;
-        let py_name = if let ContextKind::PatchMethodDefs(_base) = &self.kind {
-            Some(Str::from(format!("::{}{}", self.name, ident)))
-        } else {
-            None
-        };
+        let py_name = self.patch_method_py_name(ident);
         if let Some(_decl) = self.decls.remove(&ident.name) {
             Err(TyCheckErrors::from(TyCheckError::duplicate_decl_error(
                 self.cfg.input.clone(),
@@ -199,11 +211,7 @@ impl Context {
             Ok(t) => (TyCheckErrors::empty(), t),
             Err((errs, t)) => (errs, t),
         };
-        let py_name = if let ContextKind::PatchMethodDefs(_base) = &self.kind {
-            Some(Str::from(format!("::{}{}", self.name, sig.ident)))
-        } else {
-            None
-        };
+        let py_name = self.patch_method_py_name(&sig.ident);
         let vi = VarInfo::new(
             t,
             muty,
@@ -346,6 +354,7 @@ impl Context {
         };
         let default = kind.default_info();
         let is_var_params = kind.is_var_params();
+        let is_kw_var_params = kind.is_kw_params();
         match &sig.raw.pat {
             // Literal patterns will be desugared to discard patterns
             ast::ParamPattern::Lit(_) => unreachable!(),
@@ -361,7 +370,11 @@ impl Context {
                     Err(errs) => (Type::Failure, errs),
                 };
                 let def_id = DefId(get_hash(&(&self.name, "_")));
-                let kind = VarKind::parameter(def_id, is_var_params, DefaultInfo::NonDefault);
+                let kind = if is_kw_var_params {
+                    VarKind::kw_var_parameter(def_id)
+                } else {
+                    VarKind::parameter(def_id, is_var_params, DefaultInfo::NonDefault)
+                };
                 let vi = VarInfo::new(
                     spec_t,
                     Immutable,
@@ -407,6 +420,8 @@ impl Context {
                     };
                     let spec_t = if is_var_params {
                         unknown_len_array_t(spec_t)
+                    } else if is_kw_var_params {
+                        str_dict_t(spec_t)
                     } else {
                         spec_t
                     };
@@ -414,7 +429,11 @@ impl Context {
                         self.type_self_param(&sig.raw.pat, name, &spec_t, &mut errs);
                     }
                     let def_id = DefId(get_hash(&(&self.name, name)));
-                    let kind = VarKind::parameter(def_id, is_var_params, default);
+                    let kind = if is_kw_var_params {
+                        VarKind::kw_var_parameter(def_id)
+                    } else {
+                        VarKind::parameter(def_id, is_var_params, default)
+                    };
                     let muty = Mutability::from(&name.inspect()[..]);
                     let vi = VarInfo::new(
                         spec_t,
@@ -592,6 +611,19 @@ impl Context {
                     errs.extend(es);
                 }
             }
+            if let Some(kw_var_params) = &mut params.kw_var_params {
+                if let Some(pt) = &decl_subr_t.kw_var_params {
+                    let pt = pt.clone().map_type(str_dict_t);
+                    if let Err(es) =
+                        self.assign_param(kw_var_params, Some(&pt), ParamKind::KwParams)
+                    {
+                        errs.extend(es);
+                    }
+                } else if let Err(es) = self.assign_param(kw_var_params, None, ParamKind::KwParams)
+                {
+                    errs.extend(es);
+                }
+            }
         } else {
             for non_default in params.non_defaults.iter_mut() {
                 if let Err(es) = self.assign_param(non_default, None, ParamKind::NonDefault) {
@@ -612,6 +644,11 @@ impl Context {
                     errs.extend(es);
                 }
             }
+            if let Some(kw_var_params) = &mut params.kw_var_params {
+                if let Err(es) = self.assign_param(kw_var_params, None, ParamKind::KwParams) {
+                    errs.extend(es);
+                }
+            }
         }
         if errs.is_empty() {
             Ok(())
@@ -657,6 +694,7 @@ impl Context {
         let non_default_params = t.non_default_params().unwrap_or(&empty);
         let var_args = t.var_params();
         let default_params = t.default_params().unwrap_or(&empty);
+        let kw_var_args = t.kw_var_params();
         if let Some(spec_ret_t) = t.return_t() {
             let unify_result = if let Some(t_spec) = sig.return_t_spec.as_ref() {
                 self.sub_unify(body_t, spec_ret_t, t_spec, None)
@@ -696,21 +734,19 @@ impl Context {
         }
         // NOTE: not `body_t.clone()` because the body may contain `return`
         let return_t = t.return_t().unwrap().clone();
-        let sub_t = if sig.ident.is_procedural() {
-            proc(
-                non_default_params.clone(),
-                var_args.cloned(),
-                default_params.clone(),
-                return_t,
-            )
+        let kind = if sig.ident.is_procedural() {
+            SubrKind::Proc
         } else {
-            func(
-                non_default_params.clone(),
-                var_args.cloned(),
-                default_params.clone(),
-                return_t,
-            )
+            SubrKind::Func
         };
+        let sub_t = subr_t(
+            kind,
+            non_default_params.clone(),
+            var_args.cloned(),
+            default_params.clone(),
+            kw_var_args.cloned(),
+            return_t,
+        );
         sub_t.lift();
         let found_t = self.generalize_t(sub_t);
         // let found_t = self.eliminate_needless_quant(found_t, crate::context::Variance::Covariant, sig)?;
@@ -811,8 +847,23 @@ impl Context {
     // To allow forward references and recursive definitions
     pub(crate) fn preregister(&mut self, block: &ast::Block) -> TyCheckResult<()> {
         let mut total_errs = TyCheckErrors::empty();
+        // type-level functions can be referred to by the type definitions above them
+        for expr in block.iter() {
+            match expr {
+                ast::Expr::Def(def) if is_type_func_def(def) => {
+                    let ast::Signature::Subr(sig) = &def.sig else {
+                        unreachable!()
+                    };
+                    if let Err(errs) = self.preregister_type_func(sig, &def.body) {
+                        total_errs.extend(errs);
+                    }
+                }
+                _ => {}
+            }
+        }
         for expr in block.iter() {
             match expr {
+                ast::Expr::Def(def) if is_type_func_def(def) => {}
                 ast::Expr::Def(def) => {
                     if let Err(errs) = self.preregister_def(def) {
                         total_errs.extend(errs);
@@ -888,7 +939,9 @@ impl Context {
         let __name__ = def.sig.ident().map(|i| i.inspect()).unwrap_or(UBAR);
         match &def.sig {
             ast::Signature::Subr(sig) => {
-                if sig.is_const() {
+                if sig.is_const() && def.def_kind().is_class() {
+                    self.preregister_poly_class(sig, &def.body)?;
+                } else if sig.is_const() {
                     let tv_cache = self.instantiate_ty_bounds(&sig.bounds, PreRegister)?;
                     let vis = self.instantiate_vis_modifier(sig.vis())?;
                     self.grow(__name__, ContextKind::Proc, vis, Some(tv_cache));
@@ -934,6 +987,12 @@ impl Context {
                     let kind = ContextKind::from(def);
                     let vis = self.instantiate_vis_modifier(sig.vis())?;
                     self.grow(__name__, kind, vis, None);
+                    if def.def_kind().is_enum() {
+                        // the variants can refer to the enum itself
+                        let enum_t = ValueObj::builtin_class(mono(self.name.clone()));
+                        self.consts
+                            .insert(VarName::from_str(__name__.clone()), enum_t);
+                    }
                     let (obj, const_t) = match self.eval_const_block(&def.body.block) {
                         Ok(obj) => (obj.clone(), v_enum(set! {obj})),
                         Err(errs) => {
@@ -973,6 +1032,208 @@ impl Context {
         Ok(())
     }
 
+    fn register_gen_poly_class(
+        &mut self,
+        ident: &Identifier,
+        gen: GenTypeObj,
+        params: Vec<ParamSpec>,
+    ) -> CompileResult<()> {
+        let mut ctx = Self::poly_class(
+            gen.typ().qual_name(),
+            params,
+            self.cfg.clone(),
+            self.shared.clone(),
+            2,
+            self.level,
+        );
+        // type parameters of user-defined classes are covariant
+        for tp in gen.typ().typarams() {
+            if let TyParam::Type(t) = tp {
+                ctx.super_traits
+                    .push(poly("Output", vec![TyParam::Type(t)]));
+            }
+        }
+        self.register_gen_class_attrs(&gen, &mut ctx)?;
+        self.register_gen_type_ctx(ident, gen, ctx, Const)
+    }
+
+    /// register the fields and `__new__`/`new` of a generated class
+    fn register_gen_class_attrs(&self, gen: &GenTypeObj, ctx: &mut Self) -> CompileResult<()> {
+        let mut methods = Self::methods(None, self.cfg.clone(), self.shared.clone(), 2, self.level);
+        let new_t = if let Some(base) = gen.base_or_sup() {
+            match base {
+                TypeObj::Builtin {
+                    t: Type::Record(rec),
+                    ..
+                } => {
+                    for (field, t) in rec.iter() {
+                        let varname = VarName::from_str(field.symbol.clone());
+                        let vi = VarInfo::instance_attr(
+                            field.clone(),
+                            t.clone(),
+                            self.impl_of(),
+                            ctx.name.clone(),
+                        );
+                        ctx.decls.insert(varname, vi);
+                    }
+                }
+                other => {
+                    methods.register_fixed_auto_impl(
+                        "base",
+                        other.typ().clone(),
+                        Immutable,
+                        Visibility::BUILTIN_PRIVATE,
+                        None,
+                    )?;
+                }
+            }
+            func1(base.typ().clone(), gen.typ().clone())
+        } else {
+            func0(gen.typ().clone())
+        };
+        // polymorphic classes: |T, N|{payload = Array!(T, N)} -> Queue!(T, N)
+        let new_t = if gen.typ().is_monomorphic() {
+            new_t
+        } else {
+            new_t.quantify()
+        };
+        methods.register_fixed_auto_impl(
+            "__new__",
+            new_t.clone(),
+            Immutable,
+            Visibility::BUILTIN_PRIVATE,
+            Some("__call__".into()),
+        )?;
+        // 必要なら、ユーザーが独自に上書きする
+        // users can override this if necessary
+        methods.register_auto_impl("new", new_t, Immutable, Visibility::BUILTIN_PUBLIC, None)?;
+        ctx.methods_list
+            .push((ClassDefType::Simple(gen.typ().clone()), methods));
+        Ok(())
+    }
+
+    /// ```erg
+    /// Queue! T: Type, N: Nat = Class { .payload = Array!(T, N) }
+    /// ```
+    /// The parameters are registered as quantified type variables,
+    /// and the class is registered as `Queue!(T, N)`.
+    fn preregister_poly_class(
+        &mut self,
+        sig: &ast::SubrSignature,
+        body: &ast::DefBody,
+    ) -> TyCheckResult<()> {
+        let mut tv_cache = self.instantiate_ty_bounds(&sig.bounds, PreRegister)?;
+        let mut params = vec![];
+        let mut tps = vec![];
+        for param in sig.params.non_defaults.iter() {
+            let (Some(name), Some(spec)) = (param.inspect(), param.t_spec.as_ref()) else {
+                return feature_error!(
+                    TyCheckErrors,
+                    TyCheckError,
+                    self,
+                    param.loc(),
+                    "polymorphic class parameters without type specification"
+                );
+            };
+            let t =
+                self.instantiate_typespec(&spec.t_spec, None, &mut tv_cache, PreRegister, false)?;
+            let constr = Constraint::new_type_of(t.clone());
+            let tp = if constr.get_sub_sup().is_none() {
+                let tp = mono_q_tp(name.clone(), constr);
+                tv_cache.push_or_init_typaram(name, &tp, self);
+                tp
+            } else {
+                let tv = mono_q(name.clone(), constr);
+                tv_cache.push_or_init_tyvar(name, &tv, self);
+                TyParam::t(tv)
+            };
+            params.push(ParamSpec::new(
+                Some(name.clone()),
+                t,
+                false,
+                DefaultInfo::NonDefault,
+                self.absolutize(param.loc()),
+            ));
+            tps.push(tp);
+        }
+        let ast::Expr::Call(call) = body.block.first().unwrap() else {
+            unreachable!()
+        };
+        let base = if let Some(base) = call.args.get_left_or_key("Base") {
+            let spec = Parser::expr_to_type_spec(base.clone()).map_err(|_| {
+                TyCheckErrors::from(TyCheckError::not_const_expr(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    base.loc(),
+                    self.caused_by(),
+                ))
+            })?;
+            let t = self.instantiate_typespec(&spec, None, &mut tv_cache, PreRegister, false)?;
+            Some(TypeObj::builtin_type(t))
+        } else {
+            None
+        };
+        let impls = if let Some(impls) = call
+            .args
+            .kw_args()
+            .iter()
+            .find_map(|arg| (&arg.keyword.inspect()[..] == "Impl").then_some(&arg.expr))
+        {
+            let impls = self.eval_const_expr(impls)?;
+            impls.as_type()
+        } else {
+            None
+        };
+        let vis = self.instantiate_vis_modifier(sig.vis())?;
+        let qual_name = if vis.is_public() {
+            format!("{}.{}", self.name, sig.ident.inspect())
+        } else {
+            format!("{}::{}", self.name, sig.ident.inspect())
+        };
+        let gen = GenTypeObj::class(poly(qual_name, tps), base, impls);
+        self.register_gen_poly_class(&sig.ident, gen, params)
+    }
+
+    /// ```erg
+    /// List T, 0 = Nil T
+    /// List T, N = Cons T, N
+    /// ```
+    /// The cases are registered as a `UserConstSubr` and evaluated when the type is applied.
+    fn preregister_type_func(
+        &mut self,
+        sig: &ast::SubrSignature,
+        body: &ast::DefBody,
+    ) -> TyCheckResult<()> {
+        let block = Parser::validate_const_block(body.block.clone()).map_err(|_| {
+            TyCheckErrors::from(TyCheckError::not_const_expr(
+                self.cfg.input.clone(),
+                line!() as usize,
+                body.loc(),
+                self.caused_by(),
+            ))
+        })?;
+        if let Some(ValueObj::Subr(ConstSubr::User(subr))) =
+            self.consts.get_mut(sig.ident.inspect())
+        {
+            subr.push_case(sig.params.clone(), block);
+            return Ok(());
+        }
+        let params = sig
+            .params
+            .non_defaults
+            .iter()
+            .map(|param| ParamTy::pos_or_kw(param.inspect().cloned(), Type::Obj))
+            .collect();
+        let sig_t = subr_t(SubrKind::Func, params, None, vec![], None, Type::Type);
+        let subr = UserConstSubr::new(
+            sig.ident.inspect().clone(),
+            sig.params.clone(),
+            block,
+            sig_t,
+        );
+        self.register_gen_const(&sig.ident, ValueObj::Subr(ConstSubr::User(subr)), false)
+    }
+
     /// e.g. .new
     fn register_auto_impl(
         &mut self,
@@ -1143,8 +1404,18 @@ impl Context {
             match obj {
                 ValueObj::Type(t) => match t {
                     TypeObj::Generated(gen) if alias => {
+                        let patch = if let GenTypeObj::Patch(_) = &gen {
+                            self.get_patch_of_type(gen.typ()).cloned()
+                        } else {
+                            None
+                        };
                         let meta_t = gen.meta_type();
-                        self.register_type_alias(ident, gen.into_typ(), meta_t)
+                        self.register_type_alias(ident, gen.into_typ(), meta_t)?;
+                        // e.g. `{StrReverse;} = import "foo"`
+                        if let Some(patch) = patch {
+                            self.register_patch_alias(ident, patch)?;
+                        }
+                        Ok(())
                     }
                     TypeObj::Generated(gen) => self.register_gen_type(ident, gen),
                     TypeObj::Builtin { t, meta_t } => self.register_type_alias(ident, t, meta_t),
@@ -1187,58 +1458,8 @@ impl Context {
                         2,
                         self.level,
                     );
-                    let mut methods =
-                        Self::methods(None, self.cfg.clone(), self.shared.clone(), 2, self.level);
-                    let new_t = if let Some(base) = gen.base_or_sup() {
-                        match base {
-                            TypeObj::Builtin {
-                                t: Type::Record(rec),
-                                ..
-                            } => {
-                                for (field, t) in rec.iter() {
-                                    let varname = VarName::from_str(field.symbol.clone());
-                                    let vi = VarInfo::instance_attr(
-                                        field.clone(),
-                                        t.clone(),
-                                        self.impl_of(),
-                                        ctx.name.clone(),
-                                    );
-                                    ctx.decls.insert(varname, vi);
-                                }
-                            }
-                            other => {
-                                methods.register_fixed_auto_impl(
-                                    "base",
-                                    other.typ().clone(),
-                                    Immutable,
-                                    Visibility::BUILTIN_PRIVATE,
-                                    None,
-                                )?;
-                            }
-                        }
-                        func1(base.typ().clone(), gen.typ().clone())
-                    } else {
-                        func0(gen.typ().clone())
-                    };
-                    methods.register_fixed_auto_impl(
-                        "__new__",
-                        new_t.clone(),
-                        Immutable,
-                        Visibility::BUILTIN_PRIVATE,
-                        Some("__call__".into()),
-                    )?;
-                    // 必要なら、ユーザーが独自に上書きする
-                    // users can override this if necessary
-                    methods.register_auto_impl(
-                        "new",
-                        new_t,
-                        Immutable,
-                        Visibility::BUILTIN_PUBLIC,
-                        None,
-                    )?;
-                    ctx.methods_list
-                        .push((ClassDefType::Simple(gen.typ().clone()), methods));
-                    self.register_gen_mono_type(ident, gen, ctx, Const)
+                    self.register_gen_class_attrs(&gen, &mut ctx)?;
+                    self.register_gen_type_ctx(ident, gen, ctx, Const)
                 } else {
                     feature_error!(
                         CompileErrors,
@@ -1349,7 +1570,7 @@ impl Context {
                         )?;
                         ctx.methods_list
                             .push((ClassDefType::Simple(gen.typ().clone()), methods));
-                        self.register_gen_mono_type(ident, gen, ctx, Const)
+                        self.register_gen_type_ctx(ident, gen, ctx, Const)
                     } else {
                         let class_name = gen.base_or_sup().unwrap().typ().local_name();
                         Err(CompileErrors::from(CompileError::no_type_error(
@@ -1371,6 +1592,52 @@ impl Context {
                     )
                 }
             }
+            GenTypeObj::Enum(_) => {
+                if gen.typ().is_monomorphic() {
+                    let GenTypeObj::Enum(enum_) = &gen else { unreachable!() };
+                    let mut ctx = Self::mono_class(
+                        gen.typ().qual_name(),
+                        self.cfg.clone(),
+                        self.shared.clone(),
+                        2,
+                        self.level,
+                    );
+                    // variants are registered as class attributes
+                    let mut methods =
+                        Self::methods(None, self.cfg.clone(), self.shared.clone(), 2, self.level);
+                    for (name, payload) in enum_.variants.iter() {
+                        let variant_t = enum_.variant_type(name);
+                        let variant_ctx =
+                            self.gen_enum_variant_ctx(gen.typ(), &ctx, variant_t.clone(), payload)?;
+                        let val = ValueObj::builtin_class(variant_t.clone());
+                        let vi = VarInfo::new(
+                            v_enum(set! { val.clone() }),
+                            Const,
+                            Visibility::new(VisibilityModifier::Public, ctx.name.clone()),
+                            VarKind::FixedAuto,
+                            None,
+                            self.impl_of(),
+                            None,
+                            AbsLocation::unknown(),
+                        );
+                        let varname = VarName::from_str(name.clone());
+                        methods.locals.insert(varname.clone(), vi);
+                        methods.consts.insert(varname.clone(), val);
+                        ctx.mono_types.insert(varname, (variant_t, variant_ctx));
+                    }
+                    ctx.methods_list
+                        .push((ClassDefType::Simple(gen.typ().clone()), methods));
+                    self.register_gen_type_ctx(ident, gen, ctx, Const)
+                } else {
+                    feature_error!(
+                        CompileErrors,
+                        CompileError,
+                        self,
+                        ident.loc(),
+                        "polymorphic enum definition"
+                    )
+                }
+            }
             GenTypeObj::Trait(_) => {
                 if gen.typ().is_monomorphic() {
                     let mut ctx = Self::mono_trait(
@@ -1391,7 +1658,7 @@ impl Context {
                         ctx.decls
                             .insert(VarName::from_str(field.symbol.clone()), vi);
                     }
-                    self.register_gen_mono_type(ident, gen, ctx, Const)
+                    self.register_gen_type_ctx(ident, gen, ctx, Const)
                 } else {
                     feature_error!(
                         CompileErrors,
@@ -1441,7 +1708,7 @@ impl Context {
                             log!(err "{sup} not found");
                         }
                     }
-                    self.register_gen_mono_type(ident, gen, ctx, Const)
+                    self.register_gen_type_ctx(ident, gen, ctx, Const)
                 } else {
                     feature_error!(
                         CompileErrors,
@@ -1454,15 +1721,28 @@ impl Context {
             }
             GenTypeObj::Patch(_) => {
                 if gen.typ().is_monomorphic() {
-                    let Some(TypeObj::Builtin{ t: base, .. }) = gen.base_or_sup() else { todo!("{gen}") };
-                    let ctx = Self::mono_patch(
-                        gen.typ().qual_name(),
-                        base.clone(),
-                        self.cfg.clone(),
-                        self.shared.clone(),
-                        2,
-                        self.level,
-                    );
+                    let Some(base) = gen.base_or_sup().map(|base| base.typ().clone()) else { todo!("{gen}") };
+                    // `Patch(Base, Impl := Trait)` is a glue patch
+                    let ctx = if let Some(impls) = gen.impls() {
+                        Self::mono_glue_patch(
+                            gen.typ().qual_name(),
+                            base,
+                            impls.typ().clone(),
+                            self.cfg.clone(),
+                            self.shared.clone(),
+                            2,
+                            self.level,
+                        )
+                    } else {
+                        Self::mono_patch(
+                            gen.typ().qual_name(),
+                            base,
+                            self.cfg.clone(),
+                            self.shared.clone(),
+                            2,
+                            self.level,
+                        )
+                    };
                     self.register_gen_mono_patch(ident, gen, ctx, Const)
                 } else {
                     feature_error!(
@@ -1484,6 +1764,70 @@ impl Context {
         }
     }
 
+    /// e.g. `Expr.Lit` (payload: `Int`), `Expr.Add` (payload: `{.lhs = Expr; .rhs = Expr}`)
+    ///
+    /// The payload is stored in `base`, and the fields of a record payload are also instance attributes.
+    fn gen_enum_variant_ctx(
+        &self,
+        enum_t: &Type,
+        enum_ctx: &Context,
+        variant_t: Type,
+        payload: &TypeObj,
+    ) -> CompileResult<Self> {
+        let mut ctx = Self::mono_class(
+            variant_t.qual_name(),
+            self.cfg.clone(),
+            self.shared.clone(),
+            2,
+            self.level,
+        );
+        ctx.register_superclass(enum_t.clone(), enum_ctx);
+        if let TypeObj::Builtin {
+            t: Type::Record(rec),
+            ..
+        } = payload
+        {
+            // the fields of a payload can be accessed wherever the variant is matched
+            for (field, t) in rec.iter() {
+                let varname = VarName::from_str(field.symbol.clone());
+                let vi = VarInfo::instance_attr(
+                    Field::public(field.symbol.clone()),
+                    t.clone(),
+                    self.impl_of(),
+                    ctx.name.clone(),
+                );
+                ctx.decls.insert(varname, vi);
+            }
+        }
+        let mut methods = Self::methods(None, self.cfg.clone(), self.shared.clone(), 2, self.level);
+        methods.register_fixed_auto_impl(
+            "base",
+            payload.typ().clone(),
+            Immutable,
+            Visibility::BUILTIN_PRIVATE,
+            None,
+        )?;
+        let new_t = func1(payload.typ().clone(), variant_t.clone());
+        methods.register_fixed_auto_impl(
+            "__new__",
+            new_t.clone(),
+            Immutable,
+            Visibility::BUILTIN_PRIVATE,
+            Some("__call__".into()),
+        )?;
+        // `Expr.Lit(1)`
+        methods.register_fixed_auto_impl(
+            "__call__",
+            new_t,
+            Immutable,
+            Visibility::BUILTIN_PUBLIC,
+            None,
+        )?;
+        ctx.methods_list
+            .push((ClassDefType::Simple(variant_t), methods));
+        Ok(ctx)
+    }
+
     pub(crate) fn register_type_alias(
         &mut self,
         ident: &Identifier,
@@ -1530,7 +1874,7 @@ impl Context {
         }
     }
 
-    fn register_gen_mono_type(
+    fn register_gen_type_ctx(
         &mut self,
         ident: &Identifier,
         gen: GenTypeObj,
@@ -1539,7 +1883,9 @@ impl Context {
     ) -> CompileResult<()> {
         let vis = self.instantiate_vis_modifier(&ident.vis)?;
         // FIXME: recursive search
-        if self.mono_types.contains_key(ident.inspect()) {
+        if self.mono_types.contains_key(ident.inspect())
+            || self.poly_types.contains_key(ident.inspect())
+        {
             Err(CompileErrors::from(CompileError::reassign_error(
                 self.cfg.input.clone(),
                 line!() as usize,
@@ -1604,7 +1950,11 @@ impl Context {
                     );
                 }
             }
-            self.mono_types.insert(name.clone(), (t, ctx));
+            if t.is_monomorphic() {
+                self.mono_types.insert(name.clone(), (t, ctx));
+            } else {
+                self.poly_types.insert(name.clone(), (t, ctx));
+            }
             Ok(())
         }
     }
@@ -1613,7 +1963,7 @@ impl Context {
         &mut self,
         ident: &Identifier,
         gen: GenTypeObj,
-        ctx: Self,
+        mut ctx: Self,
         muty: Mutability,
     ) -> CompileResult<()> {
         let vis = self.instantiate_vis_modifier(&ident.vis)?;
@@ -1635,6 +1985,11 @@ impl Context {
                 ident.inspect(),
             )))
         } else {
+            let coherence = self.check_glue_patch_coherence(ident, &ctx);
+            // a conflicting glue patch is registered as a normal patch (the methods are still available)
+            if let (Err(_), ContextKind::GluePatch(tr_impl)) = (&coherence, &ctx.kind) {
+                ctx.kind = ContextKind::Patch(tr_impl.sub_type.clone());
+            }
             let t = gen.typ().clone();
             let meta_t = gen.meta_type();
             let name = &ident.name;
@@ -1684,11 +2039,82 @@ impl Context {
                     );
                 }
             }
+            self.register_glue_patch_impl(&ctx);
             self.patches.insert(name.clone(), ctx);
+            coherence.map_err(CompileErrors::from)
+        }
+    }
+
+    /// Patch methods are compiled into module-level functions.
+    /// Methods of glue patches are also set as attributes of the base class and are called by dynamic dispatch,
+    /// so they don't have `py_name`s (they may be called from other modules).
+    fn patch_method_py_name(&self, ident: &Identifier) -> Option<Str> {
+        if !matches!(&self.kind, ContextKind::PatchMethodDefs(_)) {
+            return None;
+        }
+        let patch_name = *self.name.split_with(&["::", "."]).last().unwrap();
+        let is_glue = self
+            .get_outer()
+            .and_then(|outer| outer.rec_get_patch(patch_name))
+            .is_some_and(|patch| matches!(patch.kind, ContextKind::GluePatch(_)));
+        (!is_glue).then(|| Str::from(format!("::{}{}", self.name, ident)))
+    }
+
+    /// Only one glue patch can be visible per type/trait pair
+    fn check_glue_patch_coherence(
+        &self,
+        ident: &Identifier,
+        patch: &Context,
+    ) -> SingleCompileResult<()> {
+        let ContextKind::GluePatch(tr_impl) = &patch.kind else {
+            return Ok(());
+        };
+        if let Some((other, other_patch)) = self.get_conflicting_glue_patch(tr_impl) {
+            let other_name = other_patch.name.split_with(&["::", "."]);
+            Err(CompileError::duplicate_glue_patch_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                ident.loc(),
+                self.caused_by(),
+                &tr_impl.sub_type,
+                &tr_impl.sup_trait,
+                &other.sub_type,
+                other_name.last().unwrap(),
+            ))
+        } else {
             Ok(())
         }
     }
 
+    fn register_glue_patch_impl(&self, patch: &Context) {
+        if let ContextKind::GluePatch(tr_impl) = &patch.kind {
+            if let Some(impls) = self.trait_impls().get_mut(&tr_impl.sup_trait.qual_name()) {
+                impls.insert(tr_impl.clone());
+            } else {
+                self.trait_impls()
+                    .register(tr_impl.sup_trait.qual_name(), set![tr_impl.clone()]);
+            }
+        }
+    }
+
+    /// Registers a patch imported from another module (e.g. `{StrReverse;} = import "foo"`).
+    /// The methods and the trait implementation of the patch become available in this scope.
+    fn register_patch_alias(
+        &mut self,
+        ident: &Identifier,
+        patch: Context,
+    ) -> CompileResult<()> {
+        self.check_glue_patch_coherence(ident, &patch)
+            .map_err(CompileErrors::from)?;
+        self.register_glue_patch_impl(&patch);
+        self.patches.insert(ident.name.clone(), patch);
+        // a patch takes effect just by being imported
+        if let Some(vi) = self.decls.get(&ident.name) {
+            self.inc_ref(vi, &ident.name, self);
+        }
+        Ok(())
+    }
+
     pub(crate) fn import_mod(
         &mut self,
         kind: OperationKind,
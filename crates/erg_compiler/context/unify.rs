@@ -12,8 +12,9 @@ use crate::ty::constructors::*;
 use crate::ty::free::{Constraint, FreeKind, HasLevel, GENERIC_LEVEL};
 use crate::ty::typaram::{OpKind, TyParam};
 use crate::ty::value::ValueObj;
-use crate::ty::{Predicate, SubrType, Type};
+use crate::ty::{ConstSubr, Predicate, SubrType, Type};
 
+use crate::context::instantiate::TyVarCache;
 use crate::context::{Context, Variance};
 use crate::error::{TyCheckError, TyCheckErrors, TyCheckResult};
 use crate::{feature_error, type_feature_error};
@@ -50,6 +51,9 @@ impl Context {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(var_params.typ(), maybe_sup, loc)?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(kw_var_params.typ(), maybe_sup, loc)?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(non_default_t, maybe_sup, loc)?;
                 }
@@ -63,6 +67,9 @@ impl Context {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(maybe_sub, var_params.typ(), loc)?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(maybe_sub, kw_var_params.typ(), loc)?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(maybe_sub, non_default_t, loc)?;
                 }
@@ -83,6 +90,11 @@ impl Context {
                         self.occur(lhs.typ(), rhs.typ(), loc)?;
                     }
                 }
+                if let Some(lhs) = lhs.kw_var_params.as_ref() {
+                    if let Some(rhs) = rhs.kw_var_params.as_ref() {
+                        self.occur(lhs.typ(), rhs.typ(), loc)?;
+                    }
+                }
                 for (lhs, rhs) in lhs
                     .non_default_params
                     .iter()
@@ -164,6 +176,9 @@ impl Context {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(var_params.typ(), maybe_sup, loc)?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(kw_var_params.typ(), maybe_sup, loc)?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(non_default_t, maybe_sup, loc)?;
                 }
@@ -177,6 +192,9 @@ impl Context {
                 if let Some(var_params) = subr.var_params.as_ref() {
                     self.occur_inner(maybe_sub, var_params.typ(), loc)?;
                 }
+                if let Some(kw_var_params) = subr.kw_var_params.as_ref() {
+                    self.occur_inner(maybe_sub, kw_var_params.typ(), loc)?;
+                }
                 for non_default_t in subr.non_default_params.iter().map(|pt| pt.typ()) {
                     self.occur_inner(maybe_sub, non_default_t, loc)?;
                 }
@@ -197,6 +215,11 @@ impl Context {
                         self.occur_inner(lhs.typ(), rhs.typ(), loc)?;
                     }
                 }
+                if let Some(lhs) = lhs.kw_var_params.as_ref() {
+                    if let Some(rhs) = rhs.kw_var_params.as_ref() {
+                        self.occur_inner(lhs.typ(), rhs.typ(), loc)?;
+                    }
+                }
                 for (lhs, rhs) in lhs
                     .non_default_params
                     .iter()
@@ -261,8 +284,13 @@ impl Context {
         }
         match (maybe_sub, maybe_sup) {
             (TyParam::Type(sub), TyParam::Type(sup)) => self.sub_unify(sub, sup, loc, None),
+            // e.g. {Array!(Int, 3)} <: {Array!(?T, ?N)} (singleton types are invariant)
             (TyParam::Value(ValueObj::Type(sub)), TyParam::Type(sup)) => {
-                self.sub_unify(sub.typ(), sup, loc, None)
+                self.sub_unify(sub.typ(), sup, loc, None)?;
+                if sup.has_unbound_var() {
+                    self.sub_unify(sup, sub.typ(), loc, None)?;
+                }
+                Ok(())
             }
             (TyParam::Type(sub), TyParam::Value(ValueObj::Type(sup))) => {
                 self.sub_unify(sub, sup.typ(), loc, None)
@@ -270,9 +298,15 @@ impl Context {
             (TyParam::Value(ValueObj::Type(sub)), TyParam::Value(ValueObj::Type(sup))) => {
                 self.sub_unify(sub.typ(), sup.typ(), loc, None)
             }
+            // e.g. `1: Nat` and `1: Int`
+            (TyParam::Value(_), TyParam::Value(_)) if self.eq_tp(maybe_sub, maybe_sup) => Ok(()),
             (TyParam::FreeVar(sub_fv), TyParam::FreeVar(sup_fv))
                 if sub_fv.is_unbound() && sup_fv.is_unbound() =>
             {
+                // `?N <: ?N` (linking `?N` to itself makes a cycle)
+                if sub_fv.as_ptr() == sup_fv.as_ptr() {
+                    return Ok(());
+                }
                 if sub_fv.level().unwrap() > sup_fv.level().unwrap() {
                     if !sub_fv.is_generalized() {
                         sub_fv.link(maybe_sup);
@@ -356,6 +390,23 @@ impl Context {
                     )))
                 }
             }
+            // e.g. `1 <: ?N - 1` => `?N := 2`
+            (
+                TyParam::Value(_),
+                TyParam::BinOp {
+                    op: op @ (OpKind::Add | OpKind::Sub),
+                    lhs,
+                    rhs,
+                },
+            ) if lhs.has_unbound_var() && !rhs.has_unbound_var() => {
+                let inverse = if *op == OpKind::Add {
+                    OpKind::Sub
+                } else {
+                    OpKind::Add
+                };
+                let value = self.eval_tp(TyParam::bin(inverse, maybe_sub.clone(), *rhs.clone()))?;
+                self.sub_unify_tp(&value, lhs, _variance, loc, allow_divergence)
+            }
             (TyParam::UnaryOp { op: lop, val: lval }, TyParam::UnaryOp { op: rop, val: rval })
                 if lop == rop =>
             {
@@ -573,6 +624,40 @@ impl Context {
         }
     }
 
+    /// Select the case of a type-level function application that `sub` matches,
+    /// and fix the arguments that have not been determined yet.
+    /// e.g. `Nil(Int) <: List(?T, ?N-1)`: `?N-1 == 0`, so `?N --> 1` and `Nil(?T)` is returned
+    fn select_type_app_case(
+        &self,
+        sub: &Type,
+        sup: &Type,
+        loc: &impl Locational,
+    ) -> TyCheckResult<Option<Type>> {
+        let Type::Poly { name, params: args } = sup else {
+            return Ok(None);
+        };
+        let Some(ValueObj::Subr(ConstSubr::User(subr))) = self.rec_get_const_obj(name) else {
+            return Ok(None);
+        };
+        for (params, block) in subr.cases() {
+            let mut tv_cache = TyVarCache::new(self.level, self);
+            let Some(undetermined) = self.bind_type_app_case(params, args, &mut tv_cache)? else {
+                continue;
+            };
+            let t = self.instantiate_type_app_body(block, &mut tv_cache)?;
+            if t.qual_name() != sub.qual_name() {
+                continue;
+            }
+            for (spec_t, arg) in undetermined.iter() {
+                if let Some(value) = spec_t.singleton_value() {
+                    self.sub_unify_tp(value, arg, None, loc, false)?;
+                }
+            }
+            return Ok(Some(t));
+        }
+        Ok(None)
+    }
+
     /// Assuming that `sub` is a subtype of `sup`, fill in the type variable to satisfy the assumption
     ///
     /// When comparing arguments and parameter, the left side (`sub`) is the argument (found) and the right side (`sup`) is the parameter (expected)
@@ -602,6 +687,10 @@ impl Context {
         if maybe_sub == &Type::Failure || maybe_sup == &Type::Failure {
             return Ok(());
         }
+        // e.g. sub_unify(Cons(Int, 1), List(?T, ?N-1)): (?T --> Int, ?N --> 2)
+        if let Some(sup) = self.select_type_app_case(maybe_sub, maybe_sup, loc)? {
+            return self.sub_unify(maybe_sub, &sup, loc, param_name);
+        }
         self.occur(maybe_sub, maybe_sup, loc)?;
         let maybe_sub_is_sub = self.subtype_of(maybe_sub, maybe_sup);
         if !maybe_sub_is_sub {
@@ -908,6 +997,14 @@ impl Context {
                         // contravariant
                         self.sub_unify(sup.typ(), sub.typ(), loc, param_name)
                     })?;
+                sub_subr
+                    .kw_var_params
+                    .iter()
+                    .zip(sup_subr.kw_var_params.iter())
+                    .try_for_each(|(sub, sup)| {
+                        // contravariant
+                        self.sub_unify(sup.typ(), sub.typ(), loc, param_name)
+                    })?;
                 for sup_pt in sup_subr.default_params.iter() {
                     if let Some(sub_pt) = sub_subr
                         .default_params
@@ -1083,6 +1180,60 @@ impl Context {
                 self.sub_unify(l, r, loc, param_name)
             }
             (_, RefMut { before, .. }) => self.sub_unify(maybe_sub, before, loc, param_name),
+            // e.g. sub_unify(?G(Int), ?F(?T)): (?F --> ?G, ?T --> Int)
+            (
+                ProjCall {
+                    lhs: l_ctor,
+                    attr_name: l_attr,
+                    args: l_args,
+                },
+                ProjCall {
+                    lhs: r_ctor,
+                    attr_name: r_attr,
+                    args: r_args,
+                },
+            ) if &l_attr[..] == "__call__" && &r_attr[..] == "__call__" => {
+                if l_args.len() != r_args.len() {
+                    return Err(TyCheckErrors::from(TyCheckError::type_mismatch_error(
+                        self.cfg.input.clone(),
+                        line!() as usize,
+                        loc.loc(),
+                        self.caused_by(),
+                        param_name.unwrap_or(&Str::ever("_")),
+                        None,
+                        maybe_sup,
+                        maybe_sub,
+                        None,
+                        None,
+                    )));
+                }
+                self.sub_unify_tp(l_ctor, r_ctor, None, loc, false)?;
+                for (l_arg, r_arg) in l_args.iter().zip(r_args.iter()) {
+                    self.sub_unify_tp(l_arg, r_arg, None, loc, false)?;
+                }
+                Ok(())
+            }
+            // e.g. sub_unify(Array(Int, 2), ?F(?T)): (?F --> Array(_: Type, 2), ?T --> Int)
+            (
+                _,
+                ProjCall {
+                    lhs: ctor,
+                    attr_name,
+                    args,
+                },
+            ) if &attr_name[..] == "__call__" => {
+                self.sub_unify_type_ctor_app(maybe_sub, ctor, args, true, loc, param_name)
+            }
+            (
+                ProjCall {
+                    lhs: ctor,
+                    attr_name,
+                    args,
+                },
+                _,
+            ) if &attr_name[..] == "__call__" => {
+                self.sub_unify_type_ctor_app(maybe_sup, ctor, args, false, loc, param_name)
+            }
             (_, Proj { lhs, rhs }) => {
                 if let Ok(evaled) = self.eval_proj(*lhs.clone(), rhs.clone(), self.level, loc) {
                     if maybe_sup != &evaled {
@@ -1139,6 +1290,67 @@ impl Context {
         }
     }
 
+    /// Unify `t` with an application of a higher-kinded type variable (`ctor(args)`).
+    /// `t_is_sub`: `t <: ctor(args)` or `ctor(args) <: t`
+    ///
+    /// e.g. `Array(Int, 2) <: ?F(?T)`: (?F --> Array(_: Type, 2), ?T --> Int)
+    fn sub_unify_type_ctor_app(
+        &self,
+        t: &Type,
+        ctor: &TyParam,
+        args: &[TyParam],
+        t_is_sub: bool,
+        loc: &impl Locational,
+        param_name: Option<&Str>,
+    ) -> TyCheckResult<()> {
+        if let Some(app) = <&Type>::try_from(ctor)
+            .ok()
+            .and_then(|ctor| ctor.apply_type_ctor(args))
+        {
+            return if t_is_sub {
+                self.sub_unify(t, &app, loc, param_name)
+            } else {
+                self.sub_unify(&app, t, loc, param_name)
+            };
+        }
+        let unification_error = || {
+            let app = proj_call(ctor.clone(), "__call__", args.to_vec());
+            let (sub, sup) = if t_is_sub { (t, &app) } else { (&app, t) };
+            TyCheckErrors::from(TyCheckError::unification_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                sub,
+                sup,
+                loc.loc(),
+                self.caused_by(),
+            ))
+        };
+        let Some((t_ctor, t_args)) = t.split_type_ctor(args.len()) else {
+            return Err(unification_error());
+        };
+        match ctor {
+            // the kind of `?F` must be `Type -> ... -> Type` taking as many types as `args`
+            TyParam::FreeVar(fv)
+                if fv.is_unbound()
+                    && !fv.is_generalized()
+                    && fv.constraint().and_then(|c| c.type_ctor_arity()) == Some(args.len()) =>
+            {
+                fv.link(&TyParam::t(t_ctor));
+            }
+            _ => {
+                return Err(unification_error());
+            }
+        }
+        for (t_arg, arg) in t_args.iter().zip(args.iter()) {
+            if t_is_sub {
+                self.sub_unify_tp(t_arg, arg, None, loc, false)?;
+            } else {
+                self.sub_unify_tp(arg, t_arg, None, loc, false)?;
+            }
+        }
+        Ok(())
+    }
+
     // TODO: Current implementation is inefficient because coercion is performed twice with `subtype_of` in `sub_unify`
     fn nominal_sub_unify(
         &self,
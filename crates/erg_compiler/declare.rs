@@ -169,7 +169,7 @@ impl ASTLowerer {
     }
 
     fn fake_lower_args(&self, args: ast::Args) -> LowerResult<hir::Args> {
-        let (pos_args_, var_args_, kw_args_, paren) = args.deconstruct();
+        let (pos_args_, var_args_, kw_args_, kw_var_args_, paren) = args.deconstruct();
         let mut pos_args = vec![];
         for arg in pos_args_.into_iter() {
             let arg = self.fake_lower_expr(arg.expr)?;
@@ -187,7 +187,14 @@ impl ASTLowerer {
             let expr = self.fake_lower_expr(kw_arg.expr)?;
             kw_args.push(hir::KwArg::new(kw_arg.keyword, expr));
         }
-        let args = hir::Args::new(pos_args, var_args, kw_args, paren);
+        let kw_var_args = match kw_var_args_ {
+            Some(kw_var_args) => {
+                let kw_var_args = self.fake_lower_expr(kw_var_args.expr)?;
+                Some(hir::PosArg::new(kw_var_args))
+            }
+            None => None,
+        };
+        let args = hir::Args::new(pos_args, var_args, kw_args, kw_var_args, paren);
         Ok(args)
     }
 
@@ -225,7 +232,7 @@ impl ASTLowerer {
                     let elem = self.fake_lower_expr(elem.expr)?;
                     elems.push(hir::PosArg::new(elem));
                 }
-                let elems = hir::Args::new(elems, None, vec![], None);
+                let elems = hir::Args::new(elems, None, vec![], None, None);
                 Ok(hir::Array::Normal(hir::NormalArray::new(
                     arr.l_sqbr,
                     arr.r_sqbr,
@@ -246,7 +253,7 @@ impl ASTLowerer {
         match tup {
             ast::Tuple::Normal(tup) => {
                 let mut elems = Vec::new();
-                let (elems_, _, _, paren) = tup.elems.deconstruct();
+                let (elems_, _, _, _, paren) = tup.elems.deconstruct();
                 for elem in elems_.into_iter() {
                     let elem = self.fake_lower_expr(elem.expr)?;
                     elems.push(hir::PosArg::new(elem));
@@ -286,6 +293,14 @@ impl ASTLowerer {
             ast::Record::Normal(rec) => rec,
             ast::Record::Mixed(_mixed) => unreachable!(),
         };
+        if let Some(spread) = rec.spreads.first() {
+            return Err(LowerErrors::from(LowerError::declare_error(
+                self.cfg().input.clone(),
+                line!() as usize,
+                spread.loc(),
+                self.module.context.caused_by(),
+            )));
+        }
         let mut elems = Vec::new();
         for elem in rec.attrs.into_iter() {
             let elem = self.fake_lower_def(elem)?;
@@ -323,7 +338,7 @@ impl ASTLowerer {
 
     fn fake_lower_dict(&self, dict: ast::Dict) -> LowerResult<hir::Dict> {
         match dict {
-            ast::Dict::Normal(dict) => {
+            ast::Dict::Normal(dict) if dict.spreads.is_empty() => {
                 let mut kvs = Vec::new();
                 for elem in dict.kvs.into_iter() {
                     let key = self.fake_lower_expr(elem.key)?;
@@ -348,7 +363,7 @@ impl ASTLowerer {
     }
 
     fn fake_lower_params(&self, params: ast::Params) -> LowerResult<hir::Params> {
-        let (non_defaults_, var_params_, defaults_, parens) = params.deconstruct();
+        let (non_defaults_, var_params_, defaults_, kw_var_params_, parens) = params.deconstruct();
         let mut non_defaults = vec![];
         for non_default_ in non_defaults_.into_iter() {
             let non_default =
@@ -369,7 +384,20 @@ impl ASTLowerer {
             let default = hir::DefaultParamSignature::new(sig, default_val);
             defaults.push(default);
         }
-        Ok(hir::Params::new(non_defaults, var_args, defaults, parens))
+        let kw_var_args = kw_var_params_.map(|kw_var_args| {
+            Box::new(hir::NonDefaultParamSignature::new(
+                *kw_var_args,
+                VarInfo::default(),
+                None,
+            ))
+        });
+        Ok(hir::Params::new(
+            non_defaults,
+            var_args,
+            defaults,
+            kw_var_args,
+            parens,
+        ))
     }
 
     fn fake_lower_block(&self, block: ast::Block) -> LowerResult<hir::Block> {
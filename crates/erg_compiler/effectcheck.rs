@@ -101,6 +101,9 @@ impl SideEffectChecker {
                 }
                 Expr::PatchDef(patch_def) => {
                     self.check_expr(patch_def.base.as_ref());
+                    if let Some(impls) = &patch_def.impls {
+                        self.check_expr(impls);
+                    }
                     // TODO: grow
                     for def in patch_def.methods.iter() {
                         self.check_expr(def);
@@ -113,6 +116,14 @@ impl SideEffectChecker {
                     for kwarg in call.args.kw_args.iter() {
                         self.check_expr(&kwarg.expr);
                     }
+                    for spread in call
+                        .args
+                        .var_args
+                        .iter()
+                        .chain(call.args.kw_var_args.iter())
+                    {
+                        self.check_expr(&spread.expr);
+                    }
                 }
                 Expr::BinOp(bin) => {
                     self.check_expr(&bin.lhs);
@@ -167,6 +178,9 @@ impl SideEffectChecker {
                 },
                 Expr::Dict(dict) => match dict {
                     Dict::Normal(dict) => {
+                        for spread in dict.spreads.iter() {
+                            self.check_expr(spread);
+                        }
                         for kv in dict.kvs.iter() {
                             self.check_expr(&kv.key);
                             self.check_expr(&kv.value);
@@ -228,6 +242,16 @@ impl SideEffectChecker {
                 ));
             }
         }
+        if let Some(kw_var_arg) = params.kw_var_params.as_deref() {
+            if kw_var_arg.vi.t.is_procedure() && !kw_var_arg.inspect().unwrap().ends_with('!') {
+                self.errs.push(EffectError::proc_assign_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    kw_var_arg.raw.pat.loc(),
+                    self.full_path(),
+                ));
+            }
+        }
         for d_param in params.defaults.iter() {
             if d_param.sig.vi.t.is_procedure() && !d_param.inspect().unwrap().ends_with('!') {
                 self.errs.push(EffectError::proc_assign_error(
@@ -328,6 +352,9 @@ impl SideEffectChecker {
             }
             Expr::PatchDef(patch_def) => {
                 self.check_expr(patch_def.base.as_ref());
+                if let Some(impls) = &patch_def.impls {
+                    self.check_expr(impls);
+                }
                 for def in patch_def.methods.iter() {
                     self.check_expr(def);
                 }
@@ -377,6 +404,9 @@ impl SideEffectChecker {
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dict) => {
+                    for spread in dict.spreads.iter() {
+                        self.check_expr(spread);
+                    }
                     for kv in dict.kvs.iter() {
                         self.check_expr(&kv.key);
                         self.check_expr(&kv.value);
@@ -408,6 +438,11 @@ impl SideEffectChecker {
                     .kw_args
                     .iter()
                     .for_each(|kwarg| self.check_expr(&kwarg.expr));
+                call.args
+                    .var_args
+                    .iter()
+                    .chain(call.args.kw_var_args.iter())
+                    .for_each(|spread| self.check_expr(&spread.expr));
             }
             Expr::UnaryOp(unary) => {
                 self.check_expr(&unary.expr);
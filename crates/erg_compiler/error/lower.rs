@@ -148,6 +148,45 @@ impl LowerError {
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn duplicate_glue_patch_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        class: &Type,
+        trait_: &Type,
+        other_class: &Type,
+        other_patch: &str,
+    ) -> Self {
+        let class = format!("{class}").with_color_and_attr(ERR, ATTR);
+        let trait_ = format!("{trait_}").with_color_and_attr(WARN, ATTR);
+        let other_class = format!("{other_class}").with_color_and_attr(HINT, ATTR);
+        let other_patch = other_patch.with_color_and_attr(HINT, ATTR);
+        let hint = switch_lang!(
+            "japanese" => format!("{other_class}は{other_patch}によって{trait_}と関連付けられています"),
+            "simplified_chinese" => format!("{other_class}已通过{other_patch}与{trait_}关联"),
+            "traditional_chinese" => format!("{other_class}已通過{other_patch}與{trait_}關聯"),
+            "english" => format!("{other_class} is associated with {trait_} by {other_patch}"),
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("{class}は既に{trait_}と関連付けられています"),
+                    "simplified_chinese" => format!("{class}已与{trait_}关联"),
+                    "traditional_chinese" => format!("{class}已與{trait_}關聯"),
+                    "english" => format!("{class} is already associated with {trait_}"),
+                ),
+                errno,
+                TypeError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn violate_decl_error(
         input: Input,
         errno: usize,
@@ -958,6 +997,38 @@ impl LowerError {
         )
     }
 
+    pub fn await_outside_async_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        caused_by: String,
+        name: &str,
+    ) -> Self {
+        let name = StyledString::new(name, Some(ERR), Some(ATTR));
+        let hint = switch_lang!(
+            "japanese" => "プロシージャを`@Async`で修飾してください",
+            "simplified_chinese" => "请用`@Async`修饰过程",
+            "traditional_chinese" => "請用`@Async`修飾過程",
+            "english" => "decorate the procedure with `@Async`",
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, vec![], Some(hint.into()))],
+                switch_lang!(
+                    "japanese" => format!("{name}は非同期プロシージャの中でのみ使用できます"),
+                    "simplified_chinese" => format!("{name}只能在异步过程中使用"),
+                    "traditional_chinese" => format!("{name}只能在異步過程中使用"),
+                    "english" => format!("{name} can only be used inside an async procedure"),
+                ),
+                errno,
+                SyntaxError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn invalid_type_cast_error(
         input: Input,
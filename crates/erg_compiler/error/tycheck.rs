@@ -1193,6 +1193,93 @@ passed keyword args:    {kw_args_len}"
         )
     }
 
+    pub fn no_matching_overload_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        callee_name: &str,
+        arg_ts: &[Type],
+        candidates: &[Type],
+        caused_by: String,
+    ) -> Self {
+        let name = readable_name(callee_name);
+        let args = format!("({})", fmt_iter(arg_ts.iter())).with_color_and_attr(ERR, ATTR);
+        let mut msgs = vec![switch_lang!(
+            "japanese" => "候補:",
+            "simplified_chinese" => "候选:",
+            "traditional_chinese" => "候選:",
+            "english" => "candidates:",
+        )
+        .to_string()];
+        msgs.extend(
+            candidates
+                .iter()
+                .map(|t| format!("    {}", t.to_string().with_color_and_attr(WARN, ATTR))),
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, msgs, None)],
+                switch_lang!(
+                    "japanese" => format!("引数{args}に適合する{name}のオーバーロードがありません"),
+                    "simplified_chinese" => format!("没有与参数{args}匹配的{name}重载"),
+                    "traditional_chinese" => format!("沒有與參數{args}匹配的{name}重載"),
+                    "english" => format!("no overload of {name} matches the arguments {args}"),
+                ),
+                errno,
+                TypeError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
+    pub fn ambiguous_overload_error(
+        input: Input,
+        errno: usize,
+        loc: Location,
+        callee_name: &str,
+        candidates: &[Type],
+        caused_by: String,
+    ) -> Self {
+        let name = readable_name(callee_name);
+        let hint = switch_lang!(
+            "japanese" => "引数の型を指定してください",
+            "simplified_chinese" => "请指定参数的类型",
+            "traditional_chinese" => "請指定參數的類型",
+            "english" => "specify the types of the arguments",
+        )
+        .to_string();
+        let mut msgs = vec![switch_lang!(
+            "japanese" => "候補:",
+            "simplified_chinese" => "候选:",
+            "traditional_chinese" => "候選:",
+            "english" => "candidates:",
+        )
+        .to_string()];
+        msgs.extend(
+            candidates
+                .iter()
+                .map(|t| format!("    {}", t.to_string().with_color_and_attr(WARN, ATTR))),
+        );
+        Self::new(
+            ErrorCore::new(
+                vec![SubMessage::ambiguous_new(loc, msgs, Some(hint))],
+                switch_lang!(
+                    "japanese" => format!("{name}のオーバーロードを一意に決定できませんでした"),
+                    "simplified_chinese" => format!("无法确定{name}的重载"),
+                    "traditional_chinese" => format!("無法確定{name}的重載"),
+                    "english" => format!("cannot determine which overload of {name} to call"),
+                ),
+                errno,
+                TypeError,
+                loc,
+            ),
+            input,
+            caused_by,
+        )
+    }
+
     pub fn tp_to_type_error(
         input: Input,
         errno: usize,
@@ -167,6 +167,7 @@ pub struct Args {
     pub pos_args: Vec<PosArg>,
     pub var_args: Option<Box<PosArg>>,
     pub kw_args: Vec<KwArg>,
+    pub kw_var_args: Option<Box<PosArg>>,
     pub paren: Option<(Token, Token)>,
 }
 
@@ -182,6 +183,10 @@ impl NestedDisplay for Args {
         if !self.kw_args.is_empty() {
             fmt_lines(self.kw_args.iter(), f, level)?;
         }
+        if let Some(kw_var_args) = &self.kw_var_args {
+            writeln!(f, "**")?;
+            kw_var_args.fmt_nest(f, level)?;
+        }
         Ok(())
     }
 }
@@ -206,6 +211,9 @@ impl NoTypeDisplay for Args {
                 .map(|x| x.to_string_notype())
                 .fold("".to_string(), |acc, s| acc + &s + ", ");
         }
+        if let Some(kw_var_args) = &self.kw_var_args {
+            s += &format!(", **{}", kw_var_args.to_string_notype());
+        }
         s
     }
 }
@@ -222,6 +230,16 @@ impl Locational for Args {
                 return loc;
             }
         }
+        if let Some(kw_var_args) = &self.kw_var_args {
+            let first = self
+                .pos_args
+                .first()
+                .map(|a| a.loc())
+                .or_else(|| self.var_args.as_ref().map(|a| a.loc()))
+                .or_else(|| self.kw_args.first().map(|a| a.loc()))
+                .unwrap_or(kw_var_args.loc());
+            return Location::concat(&first, kw_var_args.as_ref());
+        }
         match (
             self.pos_args.first(),
             self.var_args.as_ref(),
@@ -242,12 +260,14 @@ impl Args {
         pos_args: Vec<PosArg>,
         var_args: Option<PosArg>,
         kw_args: Vec<KwArg>,
+        kw_var_args: Option<PosArg>,
         paren: Option<(Token, Token)>,
     ) -> Self {
         Self {
             pos_args,
             var_args: var_args.map(Box::new),
             kw_args,
+            kw_var_args: kw_var_args.map(Box::new),
             paren,
         }
     }
@@ -261,23 +281,34 @@ impl Args {
     }
 
     pub fn pos_only(pos_args: Vec<PosArg>, paren: Option<(Token, Token)>) -> Self {
-        Self::new(pos_args, None, vec![], paren)
+        Self::new(pos_args, None, vec![], None, paren)
     }
 
     pub fn empty() -> Self {
-        Self::new(vec![], None, vec![], None)
+        Self::new(vec![], None, vec![], None, None)
     }
 
     #[inline]
     pub fn len(&self) -> usize {
         #[allow(clippy::bool_to_int_with_if)]
         let var_argc = if self.var_args.is_none() { 0 } else { 1 };
-        self.pos_args.len() + var_argc + self.kw_args.len()
+        #[allow(clippy::bool_to_int_with_if)]
+        let kw_var_argc = if self.kw_var_args.is_none() { 0 } else { 1 };
+        self.pos_args.len() + var_argc + self.kw_args.len() + kw_var_argc
     }
 
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.pos_args.is_empty() && self.var_args.is_none() && self.kw_args.is_empty()
+        self.pos_args.is_empty()
+            && self.var_args.is_none()
+            && self.kw_args.is_empty()
+            && self.kw_var_args.is_none()
+    }
+
+    /// `f(*xs)` or `f(**kw)`
+    #[inline]
+    pub fn has_spread(&self) -> bool {
+        self.var_args.is_some() || self.kw_var_args.is_some()
     }
 
     #[inline]
@@ -876,6 +907,8 @@ pub struct NormalDict {
     pub l_brace: Token,
     pub r_brace: Token,
     pub t: Type,
+    /// operands of `**`, these are merged before `kvs`
+    pub spreads: Vec<Expr>,
     pub kvs: Vec<KeyValue>,
 }
 
@@ -883,20 +916,25 @@ impl_t!(NormalDict);
 
 impl NestedDisplay for NormalDict {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
-        write!(f, "{{{}}}(: {})", fmt_vec(&self.kvs), self.t)
+        let spreads = self.spreads.iter().map(|spread| format!("**{spread}"));
+        let kvs = self.kvs.iter().map(|kv| kv.to_string());
+        write!(
+            f,
+            "{{{}}}(: {})",
+            spreads.chain(kvs).collect::<Vec<_>>().join(", "),
+            self.t
+        )
     }
 }
 
 impl NoTypeDisplay for NormalDict {
     fn to_string_notype(&self) -> String {
-        format!(
-            "{{{}}}",
-            self.kvs
-                .iter()
-                .map(|kv| kv.to_string_notype())
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
+        let spreads = self
+            .spreads
+            .iter()
+            .map(|spread| format!("**{}", spread.to_string_notype()));
+        let kvs = self.kvs.iter().map(|kv| kv.to_string_notype());
+        format!("{{{}}}", spreads.chain(kvs).collect::<Vec<_>>().join(", "))
     }
 }
 
@@ -914,9 +952,15 @@ impl NormalDict {
             l_brace,
             r_brace,
             t: dict_t(TyParam::Dict(kv_ts)),
+            spreads: vec![],
             kvs,
         }
     }
+
+    pub fn with_spreads(mut self, spreads: Vec<Expr>) -> Self {
+        self.spreads = spreads;
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -1408,6 +1452,7 @@ impl Call {
             "pyimport" | "py" | "__import__" => Some(OperationKind::PyImport),
             "Del" => Some(OperationKind::Del),
             "assert" => Some(OperationKind::Assert),
+            "await" | "async_for!" | "async_with!" => Some(OperationKind::Await),
             _ => {
                 if self.obj.qual_name() == Some("typing")
                     && self
@@ -1686,6 +1731,7 @@ pub struct Params {
     pub non_defaults: Vec<NonDefaultParamSignature>,
     pub var_params: Option<Box<NonDefaultParamSignature>>,
     pub defaults: Vec<DefaultParamSignature>,
+    pub kw_var_params: Option<Box<NonDefaultParamSignature>>,
     pub parens: Option<(Token, Token)>,
 }
 
@@ -1693,10 +1739,11 @@ impl fmt::Display for Params {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "({}, {}, {})",
+            "({}, {}, {}, {})",
             fmt_vec(&self.non_defaults),
             fmt_option!(pre "*", &self.var_params),
-            fmt_vec(&self.defaults)
+            fmt_vec(&self.defaults),
+            fmt_option!(pre "**", &self.kw_var_params),
         )
     }
 }
@@ -1704,13 +1751,14 @@ impl fmt::Display for Params {
 impl NoTypeDisplay for Params {
     fn to_string_notype(&self) -> String {
         format!(
-            "({}, {}, {})",
+            "({}, {}, {}, {})",
             fmt_vec(&self.non_defaults),
             fmt_option!(pre "*", &self.var_params),
             self.defaults
                 .iter()
                 .map(|p| p.to_string_notype())
-                .fold("".to_string(), |acc, e| acc + &e + ", ")
+                .fold("".to_string(), |acc, e| acc + &e + ", "),
+            fmt_option!(pre "**", &self.kw_var_params),
         )
     }
 }
@@ -1743,6 +1791,7 @@ type RawParams = (
     Vec<NonDefaultParamSignature>,
     Option<Box<NonDefaultParamSignature>>,
     Vec<DefaultParamSignature>,
+    Option<Box<NonDefaultParamSignature>>,
     Option<(Token, Token)>,
 );
 
@@ -1750,6 +1799,7 @@ type RefRawParams<'a> = (
     &'a Vec<NonDefaultParamSignature>,
     &'a Option<Box<NonDefaultParamSignature>>,
     &'a Vec<DefaultParamSignature>,
+    &'a Option<Box<NonDefaultParamSignature>>,
     &'a Option<(Token, Token)>,
 );
 
@@ -1758,22 +1808,24 @@ impl Params {
         non_defaults: Vec<NonDefaultParamSignature>,
         var_params: Option<Box<NonDefaultParamSignature>>,
         defaults: Vec<DefaultParamSignature>,
+        kw_var_params: Option<Box<NonDefaultParamSignature>>,
         parens: Option<(Token, Token)>,
     ) -> Self {
         Self {
             non_defaults,
             var_params,
             defaults,
+            kw_var_params,
             parens,
         }
     }
 
     pub fn empty() -> Self {
-        Self::new(vec![], None, vec![], None)
+        Self::new(vec![], None, vec![], None, None)
     }
 
     pub fn single(sig: NonDefaultParamSignature) -> Self {
-        Self::new(vec![sig], None, vec![], None)
+        Self::new(vec![sig], None, vec![], None, None)
     }
 
     pub const fn ref_deconstruct(&self) -> RefRawParams {
@@ -1781,6 +1833,7 @@ impl Params {
             &self.non_defaults,
             &self.var_params,
             &self.defaults,
+            &self.kw_var_params,
             &self.parens,
         )
     }
@@ -1790,6 +1843,7 @@ impl Params {
             self.non_defaults,
             self.var_params,
             self.defaults,
+            self.kw_var_params,
             self.parens,
         )
     }
@@ -2091,6 +2145,7 @@ impl Def {
             Expr::Call(call) => match call.obj.show_acc().as_ref().map(|n| &n[..]) {
                 Some("Class") => DefKind::Class,
                 Some("Inherit") => DefKind::Inherit,
+                Some("Enum") => DefKind::Enum,
                 Some("Trait") => DefKind::Trait,
                 Some("Subsume") => DefKind::Subsume,
                 Some("Inheritable") => {
@@ -2242,6 +2297,8 @@ impl ClassDef {
 pub struct PatchDef {
     pub sig: Signature,
     pub base: Box<Expr>,
+    /// the trait implemented by the glue patch
+    pub impls: Option<Box<Expr>>,
     pub methods: Block,
 }
 
@@ -2249,6 +2306,10 @@ impl NestedDisplay for PatchDef {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
         write!(f, "{} = Patch ", self.sig)?;
         self.base.fmt_nest(f, level)?;
+        if let Some(impls) = &self.impls {
+            write!(f, ", Impl := ")?;
+            impls.fmt_nest(f, level)?;
+        }
         writeln!(f, ":")?;
         self.methods.fmt_nest(f, level + 1)
     }
@@ -2289,13 +2350,18 @@ impl HasType for PatchDef {
 }
 
 impl PatchDef {
-    pub fn new(sig: Signature, base: Expr, methods: Block) -> Self {
+    pub fn new(sig: Signature, base: Expr, impls: Option<Expr>, methods: Block) -> Self {
         Self {
             sig,
             base: Box::new(base),
+            impls: impls.map(Box::new),
             methods,
         }
     }
+
+    pub const fn is_glue(&self) -> bool {
+        self.impls.is_some()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
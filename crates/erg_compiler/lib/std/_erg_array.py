@@ -17,6 +17,14 @@ class Array(list):
         self.append(value)
         return self
 
+    # `t` is an array type such as `[Int!; 3]`, i.e. a list of element types
+    def into(self, t):
+        return Array(t[0](x) for x in self)
+
+    def map_mut(self, f):
+        for i, x in enumerate(self):
+            self[i] = f(x)
+
     def partition(self, f):
         return Array(list(filter(f, self))), Array(
             list(filter(lambda x: not f(x), self))
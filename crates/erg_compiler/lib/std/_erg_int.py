@@ -69,6 +69,9 @@ class IntMut:  # inherits Int
     def __int__(self):
         return self.value.__int__()
 
+    def __index__(self):
+        return self.value.__index__()
+
     def __repr__(self):
         return self.value.__repr__()
 
@@ -0,0 +1,3 @@
+def rename_kwargs(kwargs, names, escaped):
+    renames = dict(zip(names, escaped))
+    return {renames.get(key, key): value for key, value in kwargs.items()}
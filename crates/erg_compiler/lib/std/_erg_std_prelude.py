@@ -18,6 +18,7 @@ from _erg_dict import Dict
 from _erg_set import Set
 from _erg_in_operator import in_operator
 from _erg_mutate_operator import mutate_operator
+from _erg_rename_kwargs import rename_kwargs
 
 
 class Never:
@@ -41,7 +41,7 @@ impl ASTLinker {
                         Expr::Call(call) => {
                             match call.obj.get_name().map(|s| &s[..]) {
                                 // TODO: decorator
-                                Some("Class" | "Inherit" | "Inheritable") => {
+                                Some("Class" | "Inherit" | "Inheritable" | "Enum") => {
                                     self.def_root_pos_map.insert(
                                         def.sig.ident().unwrap().inspect().clone(),
                                         new.len(),
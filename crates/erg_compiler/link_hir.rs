@@ -98,6 +98,9 @@ impl<'a> HIRLinker<'a> {
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dic) => {
+                    for spread in dic.spreads.iter_mut() {
+                        Self::resolve_pymod_path(spread);
+                    }
                     for elem in dic.kvs.iter_mut() {
                         Self::resolve_pymod_path(&mut elem.key);
                         Self::resolve_pymod_path(&mut elem.value);
@@ -127,6 +130,14 @@ impl<'a> HIRLinker<'a> {
                 for arg in call.args.kw_args.iter_mut() {
                     Self::resolve_pymod_path(&mut arg.expr);
                 }
+                for spread in call
+                    .args
+                    .var_args
+                    .iter_mut()
+                    .chain(call.args.kw_var_args.iter_mut())
+                {
+                    Self::resolve_pymod_path(&mut spread.expr);
+                }
             }
             Expr::Def(def) => {
                 for chunk in def.body.block.iter_mut() {
@@ -212,6 +223,9 @@ impl<'a> HIRLinker<'a> {
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dic) => {
+                    for spread in dic.spreads.iter_mut() {
+                        self.replace_import(spread);
+                    }
                     for elem in dic.kvs.iter_mut() {
                         self.replace_import(&mut elem.key);
                         self.replace_import(&mut elem.value);
@@ -248,6 +262,14 @@ impl<'a> HIRLinker<'a> {
                     for arg in call.args.kw_args.iter_mut() {
                         self.replace_import(&mut arg.expr);
                     }
+                    for spread in call
+                        .args
+                        .var_args
+                        .iter_mut()
+                        .chain(call.args.kw_var_args.iter_mut())
+                    {
+                        self.replace_import(&mut spread.expr);
+                    }
                 }
             },
             Expr::Def(def) => {
@@ -149,6 +149,11 @@ impl ASTLowerer {
                         warns.extend(ws);
                     }
                 }
+                if let Some(kw_var_args) = &call.args.kw_var_args {
+                    if let Err(ws) = self.block_use_check(&kw_var_args.expr) {
+                        warns.extend(ws);
+                    }
+                }
             }
             // TODO: unary, binary, array, ...
             _ => {}
@@ -22,14 +22,17 @@ use erg_parser::Parser;
 
 use crate::artifact::{CompleteArtifact, IncompleteArtifact};
 use crate::context::instantiate::TyVarCache;
+use crate::context::register::is_type_func_def;
 use crate::module::SharedCompilerResource;
 use crate::ty::constructors::{
-    array_t, free_var, func, guard, mono, poly, proc, refinement, set_t, ty_tp, v_enum,
+    array_t, free_var, func1, guard, mono, poly, refinement, set_t, subr_t, ty_tp, v_enum,
 };
-use crate::ty::free::Constraint;
+use crate::ty::free::{Constraint, HasLevel};
 use crate::ty::typaram::TyParam;
 use crate::ty::value::{GenTypeObj, TypeObj, ValueObj};
-use crate::ty::{GuardType, HasType, ParamTy, Predicate, Type, Variable, VisibilityModifier};
+use crate::ty::{
+    GuardType, HasType, ParamTy, Predicate, SubrKind, Type, Variable, VisibilityModifier,
+};
 
 use crate::context::{
     ClassDefType, Context, ContextKind, ContextProvider, ControlKind, ModuleContext,
@@ -271,8 +274,22 @@ impl ASTLowerer {
         let mut new_array = vec![];
         let (elems, ..) = array.elems.deconstruct();
         let mut union = Type::Never;
+        let mut has_spread = false;
+        // None if the length is unknown
+        let mut len = Some(0);
         for elem in elems.into_iter() {
-            let elem = self.lower_expr(elem.expr)?;
+            let elem = match elem.expr {
+                ast::Expr::UnaryOp(unary) if unary.op.is(TokenKind::PreStar) => {
+                    let (spread, spread_len) = self.lower_spread_elem(unary)?;
+                    has_spread = true;
+                    len = len.zip(spread_len).map(|(l, n)| l + n);
+                    spread
+                }
+                other => {
+                    len = len.map(|l| l + 1);
+                    self.lower_expr(other)?
+                }
+            };
             let union_ = self.module.context.union(&union, elem.ref_t());
             if let Some((l, r)) = union_.union_pair() {
                 match (l.is_unbound_var(), r.is_unbound_var()) {
@@ -309,12 +326,42 @@ impl ASTLowerer {
         } else {
             union
         };
-        Ok(hir::NormalArray::new(
+        let mut hir_array = hir::NormalArray::new(
             array.l_sqbr,
             array.r_sqbr,
-            elem_t,
+            elem_t.clone(),
             hir::Args::values(new_array, None),
-        ))
+        );
+        if has_spread {
+            let len = len.map_or(TyParam::erased(Type::Nat), TyParam::value);
+            hir_array.t = array_t(elem_t, len);
+        }
+        Ok(hir_array)
+    }
+
+    /// Lowers `*expr` in array/set literals.
+    /// Returns the number of elements of `expr` as well, if it is statically known.
+    fn lower_spread_elem(
+        &mut self,
+        unary: ast::UnaryOp,
+    ) -> LowerResult<(hir::Expr, Option<usize>)> {
+        log!(info "entered {}({unary})", fn_name!());
+        let (op, expr) = unary.deconstruct();
+        let expr = self.lower_expr(expr)?;
+        let len = self
+            .module
+            .context
+            .get_sized_spread_ts(expr.ref_t())
+            .map(|ts| ts.len());
+        let elem_t = self
+            .module
+            .context
+            .get_iterable_elem_t(expr.ref_t(), &expr)?;
+        let vi = VarInfo {
+            t: func1(expr.t(), elem_t),
+            ..VarInfo::default()
+        };
+        Ok((hir::Expr::UnaryOp(hir::UnaryOp::new(op, expr, vi)), len))
     }
 
     fn lower_array_with_length(
@@ -322,8 +369,18 @@ impl ASTLowerer {
         array: ast::ArrayWithLength,
     ) -> LowerResult<hir::ArrayWithLength> {
         log!(info "entered {}({array})", fn_name!());
+        // e.g. `[Int!; 3]` is an array type
+        let array_type = self
+            .module
+            .context
+            .eval_const_array_with_length(&array)
+            .ok();
         let elem = self.lower_expr(array.elem.expr)?;
-        let array_t = self.gen_array_with_length_type(&elem, &array.len);
+        let array_t = if let Some(array_type) = array_type {
+            v_enum(set! { array_type })
+        } else {
+            self.gen_array_with_length_type(&elem, &array.len)
+        };
         let len = self.lower_expr(*array.len)?;
         let hir_array = hir::ArrayWithLength::new(array.l_sqbr, array.r_sqbr, array_t, elem, len);
         Ok(hir_array)
@@ -367,12 +424,13 @@ impl ASTLowerer {
 
     fn lower_normal_record(&mut self, record: ast::NormalRecord) -> LowerResult<hir::Record> {
         log!(info "entered {}({record})", fn_name!());
+        let attrs = self.expand_record_spreads(record.spreads, record.attrs)?;
         let mut hir_record =
             hir::Record::new(record.l_brace, record.r_brace, hir::RecordAttrs::empty());
         self.module
             .context
             .grow("<record>", ContextKind::Dummy, Private, None);
-        for attr in record.attrs.into_iter() {
+        for attr in attrs.into_iter() {
             let attr = self.lower_def(attr).map_err(|errs| {
                 self.pop_append_errs();
                 errs
@@ -383,6 +441,82 @@ impl ASTLowerer {
         Ok(hir_record)
     }
 
+    /// ```erg
+    /// {**r; z = 3} # r: {x = Int; y = Int}
+    /// # => {x = r.x; y = r.y; z = 3}
+    /// ```
+    fn expand_record_spreads(
+        &mut self,
+        spreads: Vec<ast::UnaryOp>,
+        attrs: ast::RecordAttrs,
+    ) -> LowerResult<ast::RecordAttrs> {
+        if spreads.is_empty() {
+            return Ok(attrs);
+        }
+        let explicit_fields = attrs
+            .iter()
+            .filter_map(|def| def.sig.ident().map(|ident| ident.inspect().clone()))
+            .collect::<Set<_>>();
+        let mut expanded = Vec::<ast::Def>::new();
+        for spread in spreads {
+            let (op, expr) = spread.deconstruct();
+            let ast::Expr::Accessor(acc) = expr else {
+                return feature_error!(
+                    LowerErrors,
+                    LowerError,
+                    self.module.context,
+                    expr.loc(),
+                    "spreading records other than variables"
+                );
+            };
+            let operand = self.lower_acc(acc.clone())?;
+            let Some(fields) = self.module.context.get_record_fields(operand.ref_t()) else {
+                return Err(LowerErrors::from(LowerError::type_mismatch_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    operand.loc(),
+                    self.module.context.caused_by(),
+                    &operand.to_string_notype(),
+                    None,
+                    &mono("Record"),
+                    operand.ref_t(),
+                    None,
+                    None,
+                )));
+            };
+            let line = op.ln_begin().unwrap_or(0);
+            for field in fields.keys() {
+                if explicit_fields.contains(&field.symbol) {
+                    continue;
+                }
+                // the latter spread takes precedence
+                expanded.retain(|def| {
+                    !def.sig
+                        .ident()
+                        .is_some_and(|ident| ident.inspect() == &field.symbol)
+                });
+                let ident = if field.vis.is_public() {
+                    let dot = Token::new(TokenKind::Dot, ".", line, op.col_begin().unwrap_or(0));
+                    ast::Identifier::public_with_line(dot, field.symbol.clone(), line)
+                } else {
+                    ast::Identifier::private_with_line(field.symbol.clone(), line)
+                };
+                let attr = ast::Identifier::new(
+                    VisModifierSpec::Auto,
+                    VarName::from_str_and_line(field.symbol.clone(), line),
+                );
+                let body = ast::Expr::Accessor(acc.clone()).attr_expr(attr);
+                let sig = ast::Signature::Var(ast::VarSignature::new(
+                    ast::VarPattern::Ident(ident),
+                    None,
+                ));
+                expanded.push(ast::Def::new(sig, ast::DefBody::new_single(body)));
+            }
+        }
+        expanded.extend(attrs);
+        Ok(ast::RecordAttrs::new(expanded))
+    }
+
     fn lower_set(&mut self, set: ast::Set) -> LowerResult<hir::Set> {
         log!(info "enter {}({set})", fn_name!());
         match set {
@@ -396,8 +530,15 @@ impl ASTLowerer {
         let (elems, ..) = set.elems.deconstruct();
         let mut union = Type::Never;
         let mut new_set = vec![];
+        let mut has_spread = false;
         for elem in elems {
-            let elem = self.lower_expr(elem.expr)?;
+            let elem = match elem.expr {
+                ast::Expr::UnaryOp(unary) if unary.op.is(TokenKind::PreStar) => {
+                    has_spread = true;
+                    self.lower_spread_elem(unary)?.0
+                }
+                other => self.lower_expr(other)?,
+            };
             union = self.module.context.union(&union, elem.ref_t());
             if !cfg!(feature = "py_compat") && union.is_union_type() {
                 return Err(LowerErrors::from(LowerError::syntax_error(
@@ -461,7 +602,11 @@ impl ASTLowerer {
         {
             self.errs.extend(errs);
         }
-        Ok(hir::NormalSet::new(set.l_brace, set.r_brace, elem_t, elems))
+        let mut hir_set = hir::NormalSet::new(set.l_brace, set.r_brace, elem_t.clone(), elems);
+        if has_spread {
+            hir_set.t = set_t(elem_t, TyParam::erased(Type::Nat));
+        }
+        Ok(hir_set)
     }
 
     /// This (e.g. {"a"; 3}) is meaningless as an object, but makes sense as a type (e.g. {Int; 3}).
@@ -510,39 +655,24 @@ impl ASTLowerer {
     fn lower_normal_dict(&mut self, dict: ast::NormalDict) -> LowerResult<hir::NormalDict> {
         log!(info "enter {}({dict})", fn_name!());
         let mut union = dict! {};
+        let mut spreads = vec![];
+        for spread in dict.spreads {
+            let (_op, expr) = spread.deconstruct();
+            let expr = self.lower_expr(expr)?;
+            let (key_t, val_t) = self.module.context.get_mapping_kv_t(expr.ref_t(), &expr)?;
+            self.insert_dict_kv_t(&mut union, key_t, val_t, expr.loc())?;
+            spreads.push(expr);
+        }
         let mut new_kvs = vec![];
         for kv in dict.kvs {
             let key = self.lower_expr(kv.key)?;
             let value = self.lower_expr(kv.value)?;
-            if let Some(popped_val_t) = union.insert(key.t(), value.t()) {
-                if cfg!(feature = "py_compat") {
-                    let val_t = union.get_mut(key.ref_t()).unwrap();
-                    *val_t = self.module.context.union(&mem::take(val_t), &popped_val_t);
-                } else {
-                    return Err(LowerErrors::from(LowerError::syntax_error(
-                        self.cfg.input.clone(),
-                        line!() as usize,
-                        Location::concat(&key, &value),
-                        String::from(&self.module.context.name[..]),
-                        switch_lang!(
-                            "japanese" => "Dictの値は全て同じ型である必要があります",
-                            "simplified_chinese" => "Dict的值必须是同一类型",
-                            "traditional_chinese" => "Dict的值必須是同一類型",
-                            "english" => "Values of Dict must be the same type",
-                        )
-                        .to_owned(),
-                        Some(
-                            switch_lang!(
-                                "japanese" => "Int or Strなど明示的に型を指定してください",
-                                "simplified_chinese" => "明确指定类型，例如: Int or Str",
-                                "traditional_chinese" => "明確指定類型，例如: Int or Str",
-                                "english" => "please specify the type explicitly, e.g. Int or Str",
-                            )
-                            .to_owned(),
-                        ),
-                    )));
-                }
-            }
+            self.insert_dict_kv_t(
+                &mut union,
+                key.t(),
+                value.t(),
+                Location::concat(&key, &value),
+            )?;
             new_kvs.push(hir::KeyValue::new(key, value));
         }
         for key_t in union.keys() {
@@ -582,12 +712,49 @@ impl ASTLowerer {
         }
         Ok(normal_set)
         */
-        Ok(hir::NormalDict::new(
-            dict.l_brace,
-            dict.r_brace,
-            kv_ts,
-            new_kvs,
-        ))
+        Ok(hir::NormalDict::new(dict.l_brace, dict.r_brace, kv_ts, new_kvs).with_spreads(spreads))
+    }
+
+    fn insert_dict_kv_t(
+        &self,
+        union: &mut Dict<Type, Type>,
+        key_t: Type,
+        val_t: Type,
+        loc: Location,
+    ) -> LowerResult<()> {
+        if let Some(popped_val_t) = union.insert(key_t.clone(), val_t) {
+            let val_t = union.get_mut(&key_t).unwrap();
+            if &popped_val_t == val_t {
+                return Ok(());
+            }
+            if cfg!(feature = "py_compat") {
+                *val_t = self.module.context.union(&mem::take(val_t), &popped_val_t);
+            } else {
+                return Err(LowerErrors::from(LowerError::syntax_error(
+                    self.cfg.input.clone(),
+                    line!() as usize,
+                    loc,
+                    String::from(&self.module.context.name[..]),
+                    switch_lang!(
+                        "japanese" => "Dictの値は全て同じ型である必要があります",
+                        "simplified_chinese" => "Dict的值必须是同一类型",
+                        "traditional_chinese" => "Dict的值必須是同一類型",
+                        "english" => "Values of Dict must be the same type",
+                    )
+                    .to_owned(),
+                    Some(
+                        switch_lang!(
+                            "japanese" => "Int or Strなど明示的に型を指定してください",
+                            "simplified_chinese" => "明确指定类型，例如: Int or Str",
+                            "traditional_chinese" => "明確指定類型，例如: Int or Str",
+                            "english" => "please specify the type explicitly, e.g. Int or Str",
+                        )
+                        .to_owned(),
+                    ),
+                )));
+            }
+        }
+        Ok(())
     }
 
     fn lower_acc(&mut self, acc: ast::Accessor) -> LowerResult<hir::Accessor> {
@@ -777,6 +944,10 @@ impl ASTLowerer {
             self.errs.extend(errs);
             hir::Expr::Dummy(hir::Dummy::new(vec![]))
         });
+        // mutable contents may change later, so they cannot be narrowed to a value
+        // e.g. `arr = [1, 2].into [Int!; 2]; assert arr == [1, 2]`
+        let guard =
+            guard.filter(|_| !bin.op.is(TokenKind::DblEq) || !lhs.ref_t().has_mut_content());
         let lhs = hir::PosArg::new(lhs);
         let rhs = self.lower_expr(rhs).unwrap_or_else(|errs| {
             self.errs.extend(errs);
@@ -826,18 +997,29 @@ impl ASTLowerer {
     }
 
     fn lower_args(&mut self, args: ast::Args, errs: &mut LowerErrors) -> hir::Args {
-        let (pos_args, var_args, kw_args, paren) = args.deconstruct();
+        let (pos_args, var_args, kw_args, kw_var_args, paren) = args.deconstruct();
         let mut hir_args = hir::Args::new(
             Vec::with_capacity(pos_args.len()),
             None,
             Vec::with_capacity(kw_args.len()),
+            None,
             paren,
         );
+        let mut scrutinee_t = None;
         for (nth, arg) in pos_args.into_iter().enumerate() {
-            match self.lower_expr(arg.expr) {
+            let expr = match (arg.expr, scrutinee_t.as_ref()) {
+                (ast::Expr::Lambda(lambda), Some(t)) => {
+                    self.lower_lambda(lambda, Some(t)).map(hir::Expr::Lambda)
+                }
+                (expr, _) => self.lower_expr(expr),
+            };
+            match expr {
                 Ok(expr) => {
                     if let Some(kind) = self.module.context.control_kind() {
                         self.push_guard(nth, kind, expr.ref_t());
+                        if nth == 0 && kind.is_match() {
+                            scrutinee_t = Some(expr.t());
+                        }
                     }
                     hir_args.pos_args.push(hir::PosArg::new(expr))
                 }
@@ -869,6 +1051,16 @@ impl ASTLowerer {
                 }
             }
         }
+        if let Some(kw_var_args) = kw_var_args {
+            match self.lower_expr(kw_var_args.expr) {
+                Ok(expr) => hir_args.kw_var_args = Some(Box::new(hir::PosArg::new(expr))),
+                Err(es) => {
+                    errs.extend(es);
+                    let dummy = hir::Expr::Dummy(hir::Dummy::empty());
+                    hir_args.kw_var_args = Some(Box::new(hir::PosArg::new(dummy)));
+                }
+            }
+        }
         hir_args
     }
 
@@ -899,9 +1091,14 @@ impl ASTLowerer {
     /// `ASTLowerer` is designed to cause as little information loss in HIR as possible
     pub(crate) fn lower_call(&mut self, call: ast::Call) -> LowerResult<hir::Call> {
         log!(info "entered {}({}{}(...))", fn_name!(), call.obj, fmt_option!(call.attr_name));
-        if let (Some(name), None) = (call.obj.get_name(), &call.attr_name) {
-            self.module.context.higher_order_caller.push(name.clone());
-        }
+        // method calls are not pushed, so they must not pop the caller either
+        let is_higher_order_caller =
+            if let (Some(name), None) = (call.obj.get_name(), &call.attr_name) {
+                self.module.context.higher_order_caller.push(name.clone());
+                true
+            } else {
+                false
+            };
         let mut errs = LowerErrors::empty();
         let guard = if let (
             ast::Expr::Accessor(ast::Accessor::Ident(ident)),
@@ -922,7 +1119,9 @@ impl ASTLowerer {
         let mut obj = match self.lower_expr(*call.obj) {
             Ok(obj) => obj,
             Err(es) => {
-                self.module.context.higher_order_caller.pop();
+                if is_higher_order_caller {
+                    self.module.context.higher_order_caller.pop();
+                }
                 errs.extend(es);
                 return Err(errs);
             }
@@ -931,13 +1130,14 @@ impl ASTLowerer {
             &obj,
             &call.attr_name,
             &hir_args.pos_args,
+            hir_args.var_args.as_deref(),
             &hir_args.kw_args,
+            hir_args.kw_var_args.as_deref(),
             &self.cfg.input,
             &self.module.context,
         ) {
             Ok(vi) => vi,
             Err((vi, es)) => {
-                self.module.context.higher_order_caller.pop();
                 errs.extend(es);
                 vi.unwrap_or(VarInfo::ILLEGAL.clone())
             }
@@ -968,7 +1168,9 @@ impl ASTLowerer {
             None
         };
         let mut call = hir::Call::new(obj, attr_name, hir_args);
-        self.module.context.higher_order_caller.pop();
+        if is_higher_order_caller {
+            self.module.context.higher_order_caller.pop();
+        }
         if errs.is_empty() {
             self.exec_additional_op(&mut call)?;
         }
@@ -995,7 +1197,7 @@ impl ASTLowerer {
                     )))
                 }
             },
-            Some(OperationKind::Return | OperationKind::Yield) => {
+            Some(op @ (OperationKind::Return | OperationKind::Yield)) => {
                 // (f: ?T -> ?U).return: (self: GenericCallable, arg: Obj) -> Never
                 let callable_t = call.obj.ref_t();
                 let ret_t = match callable_t {
@@ -1008,8 +1210,27 @@ impl ASTLowerer {
                         return unreachable_error!(LowerErrors, LowerError, self.module.context);
                     }
                 };
+                // `f.yield x` turns `f: () => ?T` into `f: () => Generator(?Y, NoneType, ?R)`
+                if op == OperationKind::Yield {
+                    if let Type::FreeVar(fv) = &ret_t {
+                        if fv.is_unbound()
+                            && matches!(fv.get_sub(), None | Some(Type::Never))
+                            && matches!(fv.get_super(), None | Some(Type::Obj))
+                        {
+                            let level = fv.level().unwrap_or(self.module.context.level);
+                            fv.link(&Self::generator_t(level));
+                        }
+                    }
+                }
+                let expect_t = match (op, Self::generator_yield_and_return_t(&ret_t)) {
+                    (OperationKind::Yield, Some((yield_t, _))) => yield_t,
+                    (OperationKind::Return, Some((_, return_t))) => return_t,
+                    _ => ret_t,
+                };
                 let arg_t = call.args.get(0).unwrap().ref_t();
-                self.module.context.sub_unify(arg_t, &ret_t, call, None)?;
+                self.module
+                    .context
+                    .sub_unify(arg_t, &expect_t, call, None)?;
                 Ok(())
             }
             Some(OperationKind::Assert) => {
@@ -1020,6 +1241,20 @@ impl ASTLowerer {
                 }
                 Ok(())
             }
+            Some(OperationKind::Await) => {
+                if self.module.context.in_async_subr() {
+                    Ok(())
+                } else {
+                    let name = call.obj.show_acc().unwrap_or_default();
+                    Err(LowerErrors::from(LowerError::await_outside_async_error(
+                        self.input().clone(),
+                        line!() as usize,
+                        call.loc(),
+                        self.module.context.caused_by(),
+                        &name,
+                    )))
+                }
+            }
             Some(OperationKind::Cast) => {
                 self.warns.push(LowerWarning::use_cast_warning(
                     self.input().clone(),
@@ -1056,7 +1291,9 @@ impl ASTLowerer {
             &class,
             &Some(attr_name.clone()),
             &args,
+            None,
             &[],
+            None,
             &self.cfg.input,
             &self.module.context,
         ) {
@@ -1078,7 +1315,10 @@ impl ASTLowerer {
         let t_spec_as_expr = non_default
             .t_spec
             .as_ref()
-            .map(|t_spec_op| self.fake_lower_expr(*t_spec_op.t_spec_as_expr.clone()))
+            .map(|t_spec_op| {
+                let expr = self.replace_self_in_t_spec_expr(*t_spec_op.t_spec_as_expr.clone());
+                self.fake_lower_expr(expr)
+            })
             .transpose()?;
         // TODO: define here (not assign_params)
         let vi = VarInfo::default();
@@ -1100,6 +1340,32 @@ impl ASTLowerer {
         ))
     }
 
+    /// `Self` cannot be referred to at runtime
+    /// e.g. `Self.Lit` => `Expr.Lit`
+    fn replace_self_in_t_spec_expr(&self, expr: ast::Expr) -> ast::Expr {
+        match expr {
+            ast::Expr::Accessor(ast::Accessor::Ident(ident)) if &ident.inspect()[..] == "Self" => {
+                let Some(self_t) = self.module.context.rec_get_self_t() else {
+                    return ast::Expr::Accessor(ast::Accessor::Ident(ident));
+                };
+                let symbol = Token::new(
+                    TokenKind::Symbol,
+                    self_t.local_name(),
+                    ident.ln_begin().unwrap_or(0),
+                    ident.col_begin().unwrap_or(0),
+                );
+                ast::Expr::Accessor(ast::Accessor::Ident(ast::Identifier::private_from_token(
+                    symbol,
+                )))
+            }
+            ast::Expr::Accessor(ast::Accessor::Attr(attr)) => {
+                let obj = self.replace_self_in_t_spec_expr(*attr.obj);
+                obj.attr_expr(attr.ident)
+            }
+            other => other,
+        }
+    }
+
     fn lower_params(&mut self, params: ast::Params) -> LowerResult<hir::Params> {
         log!(info "entered {}({})", fn_name!(), params);
         let mut errs = LowerErrors::empty();
@@ -1130,6 +1396,16 @@ impl ASTLowerer {
                 Err(es) => errs.extend(es),
             }
         }
+        let hir_kw_var_params = match params.kw_var_params {
+            Some(kw_var_params) => match self.lower_non_default_param(*kw_var_params) {
+                Ok(sig) => Some(Box::new(sig)),
+                Err(es) => {
+                    errs.extend(es);
+                    None
+                }
+            },
+            None => None,
+        };
         if !errs.is_empty() {
             Err(errs)
         } else {
@@ -1137,13 +1413,19 @@ impl ASTLowerer {
                 hir_non_defaults,
                 hir_var_params,
                 hir_defaults,
+                hir_kw_var_params,
                 params.parens,
             );
             Ok(hir_params)
         }
     }
 
-    fn lower_lambda(&mut self, lambda: ast::Lambda) -> LowerResult<hir::Lambda> {
+    /// `scrutinee_t`: the type of the object to be matched (if `lambda` is an arm of `match`)
+    fn lower_lambda(
+        &mut self,
+        lambda: ast::Lambda,
+        scrutinee_t: Option<&Type>,
+    ) -> LowerResult<hir::Lambda> {
         log!(info "entered {}({lambda})", fn_name!());
         let in_statement = cfg!(feature = "py_compat")
             && self
@@ -1177,6 +1459,21 @@ impl ASTLowerer {
         if let Err(errs) = self.module.context.assign_params(&mut params, None) {
             self.errs.extend(errs);
         }
+        // e.g. `match Cons(Int, 2): Cons{head; rest} -> ...`: the parameter is `Cons(Int, 2)` (not `Cons(T, N)`)
+        if let (Some(scrutinee_t), Some(param)) = (scrutinee_t, params.non_defaults.first_mut()) {
+            if param.vi.t.has_qvar()
+                && param.vi.t.qual_name() == scrutinee_t.qual_name()
+                && self.module.context.subtype_of(scrutinee_t, &param.vi.t)
+            {
+                param.vi.t = scrutinee_t.clone();
+                if let Some(vi) = param
+                    .name()
+                    .and_then(|name| self.module.context.get_mut_current_scope_var(name))
+                {
+                    vi.t = scrutinee_t.clone();
+                }
+            }
+        }
         let overwritten = {
             let mut overwritten = vec![];
             let guards = if in_statement {
@@ -1221,6 +1518,9 @@ impl ASTLowerer {
             for default in params.defaults.iter() {
                 self.inc_ref(&default.sig.vi, &default.sig);
             }
+            if let Some(kw_var_param) = params.kw_var_params.as_deref() {
+                self.inc_ref(&kw_var_param.vi, kw_var_param);
+            }
         }
         let (non_default_params, default_params): (Vec<_>, Vec<_>) = self
             .module
@@ -1228,6 +1528,24 @@ impl ASTLowerer {
             .params
             .iter()
             .partition(|(_, vi)| !vi.kind.has_default());
+        let (kw_var_params, non_default_params): (Vec<_>, Vec<_>) = non_default_params
+            .into_iter()
+            .partition(|(_, vi)| vi.kind.is_kw_var_params());
+        // vi.t: `{Str: T}`
+        // pt: `name: T`
+        let kw_var_params = kw_var_params.first().map(|(name, vi)| {
+            let value_t = match vi.t.typarams().first() {
+                Some(TyParam::Dict(dict)) => dict
+                    .values()
+                    .next()
+                    .and_then(|tp| Type::try_from(tp.clone()).ok()),
+                _ => None,
+            };
+            ParamTy::pos_or_kw(
+                name.as_ref().map(|n| n.inspect().clone()),
+                value_t.unwrap_or(Type::Obj),
+            )
+        });
         #[cfg(not(feature = "py_compat"))]
         let (var_params, non_default_params) = {
             let (var_params, non_default_params): (Vec<_>, Vec<_>) = non_default_params
@@ -1296,21 +1614,19 @@ impl ASTLowerer {
         } else {
             self.pop_append_errs();
         }
-        let ty = if is_procedural {
-            proc(
-                non_default_param_tys,
-                var_params,
-                default_param_tys,
-                body.t(),
-            )
+        let kind = if is_procedural {
+            SubrKind::Proc
         } else {
-            func(
-                non_default_param_tys,
-                var_params,
-                default_param_tys,
-                body.t(),
-            )
+            SubrKind::Func
         };
+        let ty = subr_t(
+            kind,
+            non_default_param_tys,
+            var_params,
+            default_param_tys,
+            kw_var_params,
+            body.t(),
+        );
         let t = if ty.has_qvar() { ty.quantify() } else { ty };
         Ok(hir::Lambda::new(id, params, lambda.op, body, t))
     }
@@ -1463,6 +1779,54 @@ impl ASTLowerer {
         }
     }
 
+    /// `Generator(?Y, NoneType, ?R)`
+    fn generator_t(level: usize) -> Type {
+        let yield_t = free_var(level, Constraint::new_type_of(Type::Type));
+        let return_t = free_var(level, Constraint::new_type_of(Type::Type));
+        poly(
+            "Generator",
+            vec![ty_tp(yield_t), ty_tp(Type::NoneType), ty_tp(return_t)],
+        )
+    }
+
+    /// `Generator(Y, S, R)` -> `Some((Y, R))`
+    fn generator_yield_and_return_t(t: &Type) -> Option<(Type, Type)> {
+        if &t.qual_name()[..] != "Generator" {
+            return None;
+        }
+        let into_type = |tp: &TyParam| match tp {
+            TyParam::Type(t) => Some(*t.clone()),
+            TyParam::Value(ValueObj::Type(t)) => Some(t.typ().clone()),
+            _ => None,
+        };
+        let tps = t.typarams();
+        Some((into_type(tps.first()?)?, into_type(tps.get(2)?)?))
+    }
+
+    /// `Coroutine(T)` -> `Some(T)`
+    fn coroutine_return_t(t: &Type) -> Option<Type> {
+        if &t.qual_name()[..] != "Coroutine" {
+            return None;
+        }
+        match t.typarams().first()? {
+            TyParam::Type(t) => Some(*t.clone()),
+            TyParam::Value(ValueObj::Type(t)) => Some(t.typ().clone()),
+            _ => None,
+        }
+    }
+
+    fn coerce_generator_t(&mut self, t: Type, sig: &ast::SubrSignature) {
+        if let Type::FreeVar(fv) = &t {
+            if fv.is_unbound() {
+                let outer = self.module.context.outer.as_ref().unwrap();
+                match outer.coerce(t.clone(), sig) {
+                    Ok(coerced) => fv.link(&coerced),
+                    Err(errs) => self.errs.extend(errs),
+                }
+            }
+        }
+    }
+
     // NOTE: Note that this is in the inner scope while being called.
     fn lower_subr_def(
         &mut self,
@@ -1481,6 +1845,7 @@ impl ASTLowerer {
             .unwrap_or(Type::Failure);
         match registered_t {
             Type::Subr(subr_t) => {
+                let return_t = *subr_t.return_t.clone();
                 let mut params = self.lower_params(sig.params.clone())?;
                 if let Err(errs) = self.module.context.assign_params(&mut params, Some(subr_t)) {
                     self.errs.extend(errs);
@@ -1490,7 +1855,44 @@ impl ASTLowerer {
                 }
                 match self.lower_block(body.block) {
                     Ok(block) => {
-                        let found_body_t = self.module.context.squash_tyvar(block.t());
+                        let mut found_body_t = self.module.context.squash_tyvar(block.t());
+                        // the body of a generator is the `R` of `Generator(Y, S, R)`
+                        if let Some((yield_t, gen_return_t)) =
+                            Self::generator_yield_and_return_t(&return_t)
+                        {
+                            if let Err(errs) = self.module.context.sub_unify(
+                                &found_body_t,
+                                &gen_return_t,
+                                block.last().unwrap(),
+                                None,
+                            ) {
+                                self.errs.extend(errs);
+                            }
+                            // fix the inferred `Y` and `R` (e.g. `?Y(:> Nat)` -> `Nat`)
+                            if sig.return_t_spec.is_none() {
+                                self.coerce_generator_t(yield_t, &sig);
+                                self.coerce_generator_t(gen_return_t, &sig);
+                            }
+                            found_body_t = return_t;
+                        } else if let Some(coro_return_t) = sig
+                            .is_async()
+                            .then(|| Self::coroutine_return_t(&return_t))
+                            .flatten()
+                        {
+                            // the body of an async procedure is the `T` of `Coroutine(T)`
+                            if let Err(errs) = self.module.context.sub_unify(
+                                &found_body_t,
+                                &coro_return_t,
+                                block.last().unwrap(),
+                                None,
+                            ) {
+                                self.errs.extend(errs);
+                            }
+                            if sig.return_t_spec.is_none() {
+                                self.coerce_generator_t(coro_return_t, &sig);
+                            }
+                            found_body_t = return_t;
+                        }
                         let vi = match self.module.context.outer.as_mut().unwrap().assign_subr(
                             &sig,
                             body.id,
@@ -1557,12 +1959,41 @@ impl ASTLowerer {
         }
     }
 
+    /// Type-level functions are evaluated at compile time (in `Context::eval_type_app`),
+    /// so they have no runtime representation.
+    fn lower_type_func_def(&mut self, def: ast::Def) -> hir::Dummy {
+        log!(info "entered {}({})", fn_name!(), def.sig);
+        let name = def.sig.ident().unwrap().inspect();
+        if let Some((name, vi)) = self.module.context.decls.remove_entry(name) {
+            self.module.context.locals.insert(name, vi);
+        }
+        hir::Dummy::new(vec![])
+    }
+
     fn lower_class_def(&mut self, class_def: ast::ClassDef) -> LowerResult<hir::ClassDef> {
         log!(info "entered {}({class_def})", fn_name!());
-        let mut hir_def = self.lower_def(class_def.def)?;
+        let mut hir_def = if let ast::Signature::Subr(_) = &class_def.def.sig {
+            self.lower_poly_class_sig(class_def.def)?
+        } else {
+            self.lower_def(class_def.def)?
+        };
         let mut hir_methods = hir::Block::empty();
+        let poly_class_t = match self
+            .module
+            .context
+            .rec_get_const_obj(hir_def.sig.ident().inspect())
+        {
+            Some(ValueObj::Type(t)) if !t.typ().is_monomorphic() => Some(t.typ().clone()),
+            _ => None,
+        };
         for mut methods in class_def.methods_list.into_iter() {
-            let (class, impl_trait) = self.get_class_and_impl_trait(&methods.class)?;
+            let (class, impl_trait, class_params) = if let Some(class) = &poly_class_t {
+                let params = self.get_poly_class_params(&methods.class, class)?;
+                (class.clone(), None, params)
+            } else {
+                let (class, impl_trait) = self.get_class_and_impl_trait(&methods.class)?;
+                (class, impl_trait, vec![])
+            };
             // assume the class has implemented the trait, regardless of whether the implementation is correct
             if let Some((trait_, trait_loc)) = &impl_trait {
                 self.register_trait_impl(&class, trait_, *trait_loc)?;
@@ -1592,6 +2023,7 @@ impl ASTLowerer {
             self.module
                 .context
                 .grow(&class.local_name(), kind, hir_def.sig.vis().clone(), None);
+            self.module.context.params = class_params;
             for attr in methods.attrs.iter_mut() {
                 match attr {
                     ast::ClassAttr::Def(def) => {
@@ -1644,37 +2076,57 @@ impl ASTLowerer {
             }
             self.check_collision_and_push(class);
         }
-        let class = self.module.context.gen_type(&hir_def.sig.ident().raw);
-        let Some((_, class_ctx)) = self.module.context.get_nominal_type_ctx(&class) else {
+        let Some(class_type) = self
+            .module
+            .context
+            .rec_get_const_obj(hir_def.sig.ident().inspect())
+        else {
+            return unreachable_error!(LowerErrors, LowerError, self);
+        };
+        let ValueObj::Type(TypeObj::Generated(type_obj)) = class_type else {
+            return unreachable_error!(LowerErrors, LowerError, self);
+        };
+        let Some((_, class_ctx)) = self.module.context.get_nominal_type_ctx(type_obj.typ()) else {
             return Err(LowerErrors::from(LowerError::type_not_found(
                 self.cfg.input.clone(),
                 line!() as usize,
                 hir_def.sig.loc(),
                 self.module.context.caused_by(),
-                &class,
+                type_obj.typ(),
             )));
         };
-        let Some(class_type) = self.module.context.rec_get_const_obj(hir_def.sig.ident().inspect()) else {
-            return unreachable_error!(LowerErrors, LowerError, self);
-        };
-        let ValueObj::Type(TypeObj::Generated(type_obj)) = class_type else {
-            return unreachable_error!(LowerErrors, LowerError, self);
-        };
-        let Some(hir::Expr::Call(call)) = hir_def.body.block.first() else {
-            return unreachable_error!(LowerErrors, LowerError, self);
-        };
-        if let Some(sup_type) = call.args.get_left_or_key("Super") {
-            Self::check_inheritable(&self.cfg, &mut self.errs, type_obj, sup_type, &hir_def.sig);
+        if let Some(hir::Expr::Call(call)) = hir_def.body.block.first() {
+            if let Some(sup_type) = call.args.get_left_or_key("Super") {
+                Self::check_inheritable(
+                    &self.cfg,
+                    &mut self.errs,
+                    type_obj,
+                    sup_type,
+                    &hir_def.sig,
+                );
+            }
         }
         let (__new__, need_to_gen_new) = if let (Some(dunder_new_vi), Some(new_vi)) = (
             class_ctx.get_current_scope_var(&VarName::from_static("__new__")),
             class_ctx.get_current_scope_var(&VarName::from_static("new")),
         ) {
-            (dunder_new_vi.t.clone(), new_vi.kind == VarKind::Auto)
+            // the constructor of a polymorphic class is emitted from the unquantified type
+            let __new__ = match &dunder_new_vi.t {
+                Type::Quantified(quant) => quant.as_ref().clone(),
+                other => other.clone(),
+            };
+            (__new__, new_vi.kind == VarKind::Auto)
+        } else if let GenTypeObj::Enum(_) = type_obj {
+            // an enum has no constructor (only its variants have)
+            (Type::Never, false)
         } else {
             return unreachable_error!(LowerErrors, LowerError, self);
         };
-        let require_or_sup = Self::get_require_or_sup_or_base(hir_def.body.block.remove(0));
+        let require_or_sup = if hir_def.body.block.is_empty() {
+            None
+        } else {
+            Self::get_require_or_sup_or_base(hir_def.body.block.remove(0))
+        };
         Ok(hir::ClassDef::new(
             type_obj.clone(),
             hir_def.sig,
@@ -1685,6 +2137,73 @@ impl ASTLowerer {
         ))
     }
 
+    /// `C T: Type = Class { .x = T }`
+    /// The body refers to the type parameters, so it has already been evaluated on preregistration
+    /// and only the class name is lowered here.
+    fn lower_poly_class_sig(&mut self, def: ast::Def) -> LowerResult<hir::Def> {
+        log!(info "entered {}({})", fn_name!(), def.sig);
+        let ast::Signature::Subr(sig) = def.sig else {
+            return unreachable_error!(LowerErrors, LowerError, self);
+        };
+        if self.module.context.kind != ContextKind::Module {
+            self.module.context.decls.remove(sig.ident.inspect());
+            return Err(LowerErrors::from(LowerError::inner_typedef_error(
+                self.cfg.input.clone(),
+                line!() as usize,
+                sig.loc(),
+                self.module.context.caused_by(),
+            )));
+        }
+        let var_sig = ast::VarSignature::new(ast::VarPattern::Ident(sig.ident.clone()), None);
+        let vi = self
+            .module
+            .context
+            .assign_var_sig(&var_sig, &Type::Type, def.body.id, None)?;
+        let ident = hir::Identifier::new(sig.ident, None, vi);
+        let sig = hir::VarSignature::new(ident, None);
+        let body = hir::DefBody::new(def.body.op, hir::Block::empty(), def.body.id);
+        Ok(hir::Def::new(hir::Signature::Var(sig), body))
+    }
+
+    /// `C(T, N).` -> `[(T, Type), (N, Nat)]`
+    /// The names are bound to the type parameters of `C` in order.
+    fn get_poly_class_params(
+        &self,
+        class_spec: &ast::TypeSpec,
+        class: &Type,
+    ) -> LowerResult<Vec<(Option<VarName>, VarInfo)>> {
+        let Some((_, class_ctx)) = self.module.context.get_nominal_type_ctx(class) else {
+            return unreachable_error!(LowerErrors, LowerError, self);
+        };
+        let names = match class_spec {
+            ast::TypeSpec::PreDeclTy(ast::PreDeclTypeSpec::Simple(simple)) => simple
+                .args
+                .pos_args()
+                .map(|arg| match &arg.expr {
+                    ast::ConstExpr::Accessor(ast::ConstAccessor::Local(ident)) => {
+                        Some(ident.name.clone())
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>(),
+            _ => None,
+        };
+        match names {
+            Some(names) if names.len() == class_ctx.params.len() => Ok(names
+                .into_iter()
+                .zip(class_ctx.params.iter())
+                .map(|(name, (_, vi))| (Some(name), vi.clone()))
+                .collect()),
+            _ => feature_error!(
+                LowerErrors,
+                LowerError,
+                self.module.context,
+                class_spec.loc(),
+                "methods of a specialized polymorphic class"
+            ),
+        }
+    }
+
     fn get_class_and_impl_trait<'c>(
         &mut self,
         class_spec: &'c ast::TypeSpec,
@@ -1781,7 +2300,11 @@ impl ASTLowerer {
             )?
         };
         let mut hir_def = self.lower_def(class_def.def)?;
-        let base = Self::get_require_or_sup_or_base(hir_def.body.block.remove(0)).unwrap();
+        let hir::Expr::Call(mut call) = hir_def.body.block.remove(0) else {
+            return unreachable_error!(LowerErrors, LowerError, self);
+        };
+        let base = call.args.remove_left_or_key("Base").unwrap();
+        let impls = call.args.remove_left_or_key("Impl");
         let mut hir_methods = hir::Block::empty();
         for mut methods in class_def.methods_list.into_iter() {
             let kind = ContextKind::PatchMethodDefs(base_t.clone());
@@ -1841,9 +2364,12 @@ impl ASTLowerer {
             if let Err(errs) = self.module.context.check_decls() {
                 self.errs.extend(errs);
             }
+            if let Some(impls) = impls.as_ref() {
+                self.check_glue_patch_impl(hir_def.sig.ident().inspect(), impls);
+            }
             self.push_patch();
         }
-        Ok(hir::PatchDef::new(hir_def.sig, base, hir_methods))
+        Ok(hir::PatchDef::new(hir_def.sig, base, impls, hir_methods))
     }
 
     fn lower_redef(&mut self, redef: ast::ReDef) -> LowerResult<hir::ReDef> {
@@ -2022,7 +2548,7 @@ impl ASTLowerer {
         impl_trait: &Type,
         class: &Type,
         (trait_type, trait_ctx): (&Type, &Context),
-        t_spec: &TypeSpecWithOp,
+        impl_loc: &impl Locational,
     ) -> (Set<&VarName>, CompileErrors) {
         let mut errors = CompileErrors::empty();
         let mut unverified_names = self.module.context.locals.keys().collect::<Set<_>>();
@@ -2057,13 +2583,35 @@ impl ASTLowerer {
                     impl_trait,
                     class,
                     None,
-                    t_spec.loc(),
+                    impl_loc.loc(),
                 ));
             }
         }
         (unverified_names, errors)
     }
 
+    /// Inspect that the glue patch defines all the attributes required by the trait
+    /// (unlike `check_trait_impl`, the patch may define extra attributes)
+    fn check_glue_patch_impl(&mut self, patch_name: &str, impls: &hir::Expr) {
+        let outer = self.module.context.get_outer().unwrap();
+        let Some(patch) = outer.rec_get_patch(patch_name) else {
+            return;
+        };
+        let ContextKind::GluePatch(tr_impl) = &patch.kind else {
+            return;
+        };
+        let Some(trait_ctx) = outer.get_nominal_type_ctx(&tr_impl.sup_trait) else {
+            return;
+        };
+        let (_, errors) = self.check_methods_compatibility(
+            &tr_impl.sup_trait,
+            &tr_impl.sub_type,
+            trait_ctx,
+            impls,
+        );
+        self.errs.extend(errors);
+    }
+
     fn check_collision_and_push(&mut self, class: Type) {
         let methods = self.module.context.pop();
         let Some((_, class_root)) = self
@@ -2149,6 +2697,7 @@ impl ASTLowerer {
             hir::Expr::Call(mut call) => match call.obj.show_acc().as_ref().map(|s| &s[..]) {
                 Some("Class" | "Trait") => call.args.remove_left_or_key("Requirement"),
                 Some("Inherit") => call.args.remove_left_or_key("Super"),
+                Some("Enum") => call.args.remove_left_or_key("Variants"),
                 Some("Inheritable") => {
                     Self::get_require_or_sup_or_base(call.args.remove_left_or_key("Class").unwrap())
                 }
@@ -2309,7 +2858,7 @@ impl ASTLowerer {
             ast::Expr::UnaryOp(unary) => Ok(hir::Expr::UnaryOp(self.lower_unary(unary))),
             ast::Expr::Call(call) => Ok(hir::Expr::Call(self.lower_call(call)?)),
             ast::Expr::DataPack(pack) => Ok(hir::Expr::Call(self.lower_pack(pack)?)),
-            ast::Expr::Lambda(lambda) => Ok(hir::Expr::Lambda(self.lower_lambda(lambda)?)),
+            ast::Expr::Lambda(lambda) => Ok(hir::Expr::Lambda(self.lower_lambda(lambda, None)?)),
             ast::Expr::TypeAscription(tasc) => Ok(hir::Expr::TypeAsc(self.lower_type_asc(tasc)?)),
             // Checking is also performed for expressions in Dummy. However, it has no meaning in code generation
             ast::Expr::Dummy(dummy) => Ok(hir::Expr::Dummy(self.lower_dummy(dummy)?)),
@@ -2326,6 +2875,9 @@ impl ASTLowerer {
     pub(crate) fn lower_chunk(&mut self, chunk: ast::Expr) -> LowerResult<hir::Expr> {
         log!(info "entered {}", fn_name!());
         match chunk {
+            ast::Expr::Def(def) if is_type_func_def(&def) => {
+                Ok(hir::Expr::Dummy(self.lower_type_func_def(def)))
+            }
             ast::Expr::Def(def) => Ok(hir::Expr::Def(self.lower_def(def)?)),
             ast::Expr::ClassDef(defs) => Ok(hir::Expr::ClassDef(self.lower_class_def(defs)?)),
             ast::Expr::PatchDef(defs) => Ok(hir::Expr::PatchDef(self.lower_patch_def(defs)?)),
@@ -9,6 +9,7 @@ use erg_common::traits::{ExitStatus, Runnable};
 use erg_compiler::build_hir::HIRBuilder;
 use erg_compiler::lower::ASTLowerer;
 use erg_compiler::transpile::Transpiler;
+use erg_compiler::ty::asm::Assembler;
 use erg_compiler::ty::deserialize::Deserializer;
 use erg_compiler::Compiler;
 
@@ -25,6 +26,7 @@ fn run() {
         Transpile => Transpiler::run(cfg),
         Compile | Execute => Compiler::run(cfg),
         Read => Deserializer::run(cfg),
+        Assemble => Assembler::run(cfg),
         other => {
             println!("invalid mode: {other}");
             ExitStatus::ERR1
@@ -70,9 +70,10 @@ impl ModuleGraph {
         tsort(self.0).map(Self)
     }
 
+    /// If the graph is cyclic, `self` is left unchanged.
     #[allow(clippy::result_unit_err)]
     pub fn sort(&mut self) -> Result<(), TopoSortError> {
-        *self = std::mem::take(self).sorted()?;
+        *self = self.clone().sorted()?;
         Ok(())
     }
 
@@ -73,9 +73,16 @@ impl ModuleIndex {
         self.members.clear();
     }
 
+    /// Removes the definitions in `path` and the references from `path`.
     pub fn remove_path(&mut self, path: &Path) {
         self.members
             .retain(|loc, _| loc.module.as_deref() != Some(path));
+        for value in self.members.values_mut() {
+            value.referrers = std::mem::take(&mut value.referrers)
+                .into_iter()
+                .filter(|loc| loc.module.as_deref() != Some(path))
+                .collect();
+        }
     }
 }
 
@@ -107,7 +107,8 @@ impl OwnershipChecker {
                 self.dict
                     .insert(Str::from(self.full_path()), LocalVars::default());
                 if let Signature::Subr(subr) = &def.sig {
-                    let (nd_params, var_params, d_params, _) = subr.params.ref_deconstruct();
+                    let (nd_params, var_params, d_params, kw_var_params, _) =
+                        subr.params.ref_deconstruct();
                     for param in nd_params {
                         if let ParamPattern::VarName(name) = &param.raw.pat {
                             self.define_param(name);
@@ -123,6 +124,11 @@ impl OwnershipChecker {
                             self.define_param(name);
                         }
                     }
+                    if let Some(kw_var) = kw_var_params {
+                        if let ParamPattern::VarName(name) = &kw_var.raw.pat {
+                            self.define_param(name);
+                        }
+                    }
                 }
                 self.check_block(&def.body.block);
                 self.path_stack.pop();
@@ -137,6 +143,9 @@ impl OwnershipChecker {
             }
             Expr::PatchDef(patch_def) => {
                 self.check_expr(&patch_def.base, Ownership::Owned, false);
+                if let Some(impls) = &patch_def.impls {
+                    self.check_expr(impls, Ownership::Owned, false);
+                }
                 for def in patch_def.methods.iter() {
                     self.check_expr(def, Ownership::Owned, true);
                 }
@@ -183,10 +192,21 @@ impl OwnershipChecker {
                         .find(|(k, _)| k.as_ref() == Some(kw_arg.keyword.inspect()))
                     {
                         self.check_expr(&kw_arg.expr, *ownership, false);
+                    } else if let Some((_, ownership)) = args_owns.kw_var_params.as_ref() {
+                        self.check_expr(&kw_arg.expr, *ownership, false);
                     } else {
                         todo!()
                     }
                 }
+                // spread operands are only iterated over
+                for spread in call
+                    .args
+                    .var_args
+                    .iter()
+                    .chain(call.args.kw_var_args.iter())
+                {
+                    self.check_expr(&spread.expr, Ownership::Ref, false);
+                }
             }
             // TODO: referenced
             Expr::BinOp(binop) => {
@@ -217,6 +237,9 @@ impl OwnershipChecker {
             },
             Expr::Dict(dict) => match dict {
                 hir::Dict::Normal(dic) => {
+                    for spread in dic.spreads.iter() {
+                        self.check_expr(spread, ownership, false);
+                    }
                     for kv in dic.kvs.iter() {
                         self.check_expr(&kv.key, ownership, false);
                         self.check_expr(&kv.value, ownership, false);
@@ -1,10 +1,16 @@
+use std::env::temp_dir;
+
 use erg_common::config::ErgConfig;
 use erg_common::error::MultiErrorDisplay;
+use erg_common::serialize::PycInvalidationMode;
 use erg_common::traits::Runnable;
 
 use erg_compiler::context::{Context, ModuleContext};
 use erg_compiler::error::CompileErrors;
 use erg_compiler::lower::ASTLowerer;
+use erg_compiler::ty::asm::{disassemble, Assembler};
+use erg_compiler::ty::deserialize::Deserializer;
+use erg_compiler::Compiler;
 
 use erg_compiler::ty::constructors::{
     func0, func1, func2, kw, mono, nd_func, nd_proc, or, poly, proc1, subtype_q, ty_tp, type_q,
@@ -88,6 +94,71 @@ fn test_intersection() -> Result<(), ()> {
     Ok(())
 }
 
+#[test]
+fn test_hash_based_pyc_is_reproducible() -> Result<(), ()> {
+    let src = "x = \"hello world\"\nf a = a + 1\nprint! x, f(1), 2.5, [1, 2]\n";
+    let compile = |name: &str| {
+        let cfg = ErgConfig {
+            py_magic_num: Some(3495),
+            pyc_invalidation_mode: PycInvalidationMode::CheckedHash,
+            ..ErgConfig::string(src.to_string())
+        };
+        let path = temp_dir().join(name);
+        Compiler::new(cfg)
+            .compile_and_dump_as_pyc(&path, src.to_string(), "exec")
+            .map_err(|eart| eart.errors.fmt_all_stderr())?;
+        Ok(path)
+    };
+    let (a, b) = (
+        compile("erg_reproducible_a.pyc")?,
+        compile("erg_reproducible_b.pyc")?,
+    );
+    let (bytes_a, bytes_b) = (std::fs::read(a).unwrap(), std::fs::read(b).unwrap());
+    assert_eq!(bytes_a, bytes_b);
+    // flags: hash-based + check_source
+    assert_eq!(&bytes_a[4..8], &[0b11, 0, 0, 0]);
+    let header = Deserializer::deserialize_pyc_header(&mut bytes_a.clone()).unwrap();
+    assert_eq!(
+        (header.magic_num, header.mode),
+        (3495, PycInvalidationMode::CheckedHash)
+    );
+    let mut broken = bytes_a;
+    broken[4] = 0b100;
+    assert!(Deserializer::deserialize_pyc_header(&mut broken).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_disassemble_and_assemble_pyc() -> Result<(), ()> {
+    let src = "\
+g! n =
+    i = !0
+    while! do! i < n, do!:
+        i.inc!()
+    i
+h x =
+    y = x
+    z -> y + z
+print! \"hello\", g!(3), h(1)(2), (1, 2.5)
+";
+    let cfg = ErgConfig {
+        py_magic_num: Some(3495),
+        ..ErgConfig::string(src.to_string())
+    };
+    let path = temp_dir().join("erg_disassemble.pyc");
+    Compiler::new(cfg)
+        .compile_and_dump_as_pyc(&path, src.to_string(), "exec")
+        .map_err(|eart| eart.errors.fmt_all_stderr())?;
+    let bytes = std::fs::read(path).unwrap();
+    let (header, code) = Deserializer::new()
+        .deserialize_pyc(&mut bytes.clone())
+        .unwrap();
+    let listing = disassemble(&header, &code);
+    assert!(listing.contains("POP_JUMP_BACKWARD_IF_TRUE L"));
+    assert_eq!(Assembler::assemble(&listing).unwrap(), bytes);
+    Ok(())
+}
+
 /*
 #[test]
 fn test_patch() -> Result<(), ()> {
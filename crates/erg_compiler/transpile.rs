@@ -1,14 +1,15 @@
 use std::fs::File;
 use std::io::Write;
 
-use erg_common::config::ErgConfig;
+use erg_common::config::{ErgConfig, TranspileTarget};
 use erg_common::dict::Dict as HashMap;
 use erg_common::error::MultiErrorDisplay;
 use erg_common::log;
+use erg_common::python_util::{env_python_version, PythonVersion};
 use erg_common::traits::{ExitStatus, Runnable, Stream};
 use erg_common::Str;
 
-use erg_parser::ast::{ParamPattern, TypeSpec, VarName};
+use erg_parser::ast::{OperationKind, ParamPattern, TypeSpec, VarName, AST};
 use erg_parser::token::TokenKind;
 
 use crate::artifact::{
@@ -21,12 +22,13 @@ use crate::desugar_hir::HIRDesugarer;
 use crate::error::{CompileError, CompileErrors};
 use crate::hir::{
     Accessor, Args, Array, BinOp, Block, Call, ClassDef, Def, Dict, Expr, Identifier, Lambda,
-    Literal, Params, PatchDef, ReDef, Record, Set, Signature, Tuple, UnaryOp, HIR,
+    Literal, NonDefaultParamSignature, Params, PatchDef, ReDef, Record, Set, Signature, Tuple,
+    UnaryOp, HIR,
 };
 use crate::link_hir::HIRLinker;
 use crate::module::SharedCompilerResource;
-use crate::ty::value::ValueObj;
-use crate::ty::Type;
+use crate::ty::value::{EnumTypeObj, GenTypeObj, ValueObj};
+use crate::ty::{HasType, Type};
 use crate::varinfo::VarInfo;
 
 /// patch method -> function
@@ -66,7 +68,118 @@ fn replace_non_symbolic(name: &str) -> String {
         .replace('$', "erg_shared__")
 }
 
-#[derive(Debug)]
+/// Methods that Python's builtin classes have natively.
+/// Other methods of Erg's builtin types are provided by the runtime classes (`_erg_int.py`, etc.).
+fn has_native_method(class: &str, method: &str) -> bool {
+    if method.starts_with("__") {
+        return true;
+    }
+    let methods: &[&str] = match class {
+        "Int" | "Nat" | "Bool" => &[
+            "as_integer_ratio",
+            "bit_count",
+            "bit_length",
+            "conjugate",
+            "denominator",
+            "from_bytes",
+            "imag",
+            "numerator",
+            "real",
+            "to_bytes",
+        ],
+        "Float" => &[
+            "as_integer_ratio",
+            "conjugate",
+            "fromhex",
+            "hex",
+            "imag",
+            "is_integer",
+            "real",
+        ],
+        "Str" => &[
+            "capitalize",
+            "casefold",
+            "center",
+            "count",
+            "encode",
+            "endswith",
+            "expandtabs",
+            "find",
+            "format",
+            "format_map",
+            "index",
+            "isalnum",
+            "isalpha",
+            "isascii",
+            "isdecimal",
+            "isdigit",
+            "isidentifier",
+            "islower",
+            "isnumeric",
+            "isprintable",
+            "isspace",
+            "istitle",
+            "isupper",
+            "join",
+            "ljust",
+            "lower",
+            "lstrip",
+            "maketrans",
+            "partition",
+            "removeprefix",
+            "removesuffix",
+            "replace",
+            "rfind",
+            "rindex",
+            "rjust",
+            "rpartition",
+            "rsplit",
+            "rstrip",
+            "split",
+            "splitlines",
+            "startswith",
+            "strip",
+            "swapcase",
+            "title",
+            "translate",
+            "upper",
+            "zfill",
+        ],
+        "Array" => &[
+            "append", "clear", "copy", "count", "extend", "index", "insert", "pop", "remove",
+            "reverse", "sort",
+        ],
+        _ => return true,
+    };
+    methods.contains(&method)
+}
+
+/// Erg builtin types whose values are represented by Python builtin values in native mode.
+fn is_builtin_scalar(t: &Type) -> bool {
+    matches!(&t.qual_name()[..], "Int" | "Nat" | "Float" | "Str" | "Bool")
+}
+
+fn is_int_like(t: &Type) -> bool {
+    matches!(&t.qual_name()[..], "Int" | "Nat" | "Bool")
+}
+
+fn is_type_obj(t: &Type) -> bool {
+    matches!(&t.qual_name()[..], "Type" | "ClassType" | "TraitType")
+}
+
+fn python_binop(kind: TokenKind, content: &str) -> &str {
+    match kind {
+        TokenKind::BitAnd => "&",
+        TokenKind::BitOr => "|",
+        TokenKind::BitXor => "^",
+        TokenKind::IsOp => "is",
+        TokenKind::IsNotOp => "is not",
+        TokenKind::NotInOp => "not in",
+        _ => content,
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum LastLineOperation {
     Discard,
     Return,
@@ -116,7 +229,10 @@ impl Runnable for Transpiler {
         Self {
             shared: shared.clone(),
             builder: HIRBuilder::new_with_cache(cfg.copy(), "<module>", shared),
-            script_generator: ScriptGenerator::new(),
+            script_generator: ScriptGenerator::new(
+                cfg.transpile_target,
+                cfg.target_version.unwrap_or_else(env_python_version),
+            ),
             cfg,
         }
     }
@@ -187,7 +303,10 @@ impl Buildable<PyScript> for Transpiler {
         Self {
             shared: shared.clone(),
             builder: HIRBuilder::new_with_cache(cfg.copy(), mod_name, shared),
-            script_generator: ScriptGenerator::new(),
+            script_generator: ScriptGenerator::new(
+                cfg.transpile_target,
+                cfg.target_version.unwrap_or_else(env_python_version),
+            ),
             cfg,
         }
     }
@@ -199,6 +318,14 @@ impl Buildable<PyScript> for Transpiler {
         self.transpile(src, mode)
             .map_err(|err| IncompleteArtifact::new(None, err.errors, err.warns))
     }
+    fn build_from_ast(
+        &mut self,
+        ast: AST,
+        mode: &str,
+    ) -> Result<CompleteArtifact<PyScript>, IncompleteArtifact<PyScript>> {
+        self.transpile_ast(ast, mode)
+            .map_err(|err| IncompleteArtifact::new(None, err.errors, err.warns))
+    }
     fn pop_context(&mut self) -> Option<ModuleContext> {
         self.builder.pop_context()
     }
@@ -223,16 +350,34 @@ impl Transpiler {
         Ok(CompleteArtifact::new(script, artifact.warns))
     }
 
+    pub fn transpile_ast(
+        &mut self,
+        ast: AST,
+        mode: &str,
+    ) -> Result<CompleteArtifact<PyScript>, ErrorArtifact> {
+        log!(info "the transpiling process has started.");
+        let artifact = self.builder.check(ast, mode)?;
+        let artifact = self.link_desugar(artifact);
+        let script = self.script_generator.transpile(artifact.object);
+        log!(info "code:\n{}", script.code);
+        log!(info "the transpiling process has completed");
+        Ok(CompleteArtifact::new(script, artifact.warns))
+    }
+
     fn build_link_desugar(
         &mut self,
         src: String,
         mode: &str,
     ) -> Result<CompleteArtifact, ErrorArtifact> {
         let artifact = self.builder.build(src, mode)?;
+        Ok(self.link_desugar(artifact))
+    }
+
+    fn link_desugar(&mut self, artifact: CompleteArtifact) -> CompleteArtifact {
         let linker = HIRLinker::new(&self.cfg, &self.shared.mod_cache);
         let hir = linker.link(artifact.object);
         let desugared = HIRDesugarer::desugar(hir);
-        Ok(CompleteArtifact::new(desugared, artifact.warns))
+        CompleteArtifact::new(desugared, artifact.warns)
     }
 
     pub fn pop_mod_ctx(&mut self) -> Option<ModuleContext> {
@@ -254,6 +399,8 @@ impl Transpiler {
 
 #[derive(Debug, Default)]
 pub struct ScriptGenerator {
+    target: TranspileTarget,
+    py_version: PythonVersion,
     level: usize,
     fresh_var_n: usize,
     namedtuple_loaded: bool,
@@ -263,12 +410,18 @@ pub struct ScriptGenerator {
     builtin_types_loaded: bool,
     builtin_control_loaded: bool,
     convertors_loaded: bool,
+    /// whether the current subroutine is an `async def` (native mode only)
+    in_async_subr: bool,
     prelude: String,
+    /// helper functions that must be defined just before the current statement (native mode only)
+    hoisted: String,
 }
 
 impl ScriptGenerator {
-    pub const fn new() -> Self {
+    pub const fn new(target: TranspileTarget, py_version: PythonVersion) -> Self {
         Self {
+            target,
+            py_version,
             level: 0,
             fresh_var_n: 0,
             namedtuple_loaded: false,
@@ -278,15 +431,21 @@ impl ScriptGenerator {
             builtin_types_loaded: false,
             builtin_control_loaded: false,
             convertors_loaded: false,
+            in_async_subr: false,
             prelude: String::new(),
+            hoisted: String::new(),
         }
     }
 
     pub fn transpile(&mut self, hir: HIR) -> PyScript {
         let mut code = String::new();
         for chunk in hir.module.into_iter() {
-            code += &self.transpile_expr(chunk);
-            code.push('\n');
+            if self.target.is_native() {
+                code += &self.transpile_chunk(chunk, Discard);
+            } else {
+                code += &self.transpile_expr(chunk);
+                code.push('\n');
+            }
         }
         code = std::mem::take(&mut self.prelude) + &code;
         PyScript {
@@ -404,6 +563,10 @@ impl ScriptGenerator {
             Expr::BinOp(bin) => self.transpile_binop(bin),
             Expr::UnaryOp(unary) => self.transpile_unaryop(unary),
             Expr::Array(array) => match array {
+                Array::Normal(arr) if self.target.is_native() => {
+                    let elems = self.transpile_native_args(arr.elems, false);
+                    format!("[{}]", elems.join(", "))
+                }
                 Array::Normal(arr) => {
                     let mut code = "[".to_string();
                     for elem in arr.elems.pos_args {
@@ -412,9 +575,23 @@ impl ScriptGenerator {
                     code += "]";
                     code
                 }
+                // same as the codegen: `Array([elem]) * len`
+                Array::WithLength(arr) => {
+                    self.load_builtin_types_if_not();
+                    let elem = self.transpile_expr(*arr.elem);
+                    let len = self.transpile_expr(*arr.len);
+                    format!("(Array([{elem}]) * ({len}))")
+                }
                 other => todo!("transpiling {other}"),
             },
             Expr::Set(set) => match set {
+                Set::Normal(st) if self.target.is_native() && st.elems.is_empty() => {
+                    "set()".to_string()
+                }
+                Set::Normal(st) if self.target.is_native() => {
+                    let elems = self.transpile_native_args(st.elems, false);
+                    format!("{{{}}}", elems.join(", "))
+                }
                 Set::Normal(st) => {
                     let mut code = "{".to_string();
                     for elem in st.elems.pos_args {
@@ -427,6 +604,14 @@ impl ScriptGenerator {
             },
             Expr::Record(rec) => self.transpile_record(rec),
             Expr::Tuple(tuple) => match tuple {
+                Tuple::Normal(tup) if self.target.is_native() && tup.elems.len() == 1 => {
+                    let elems = self.transpile_native_args(tup.elems, false);
+                    format!("({},)", elems[0])
+                }
+                Tuple::Normal(tup) if self.target.is_native() => {
+                    let elems = self.transpile_native_args(tup.elems, false);
+                    format!("({})", elems.join(", "))
+                }
                 Tuple::Normal(tup) => {
                     let mut code = "(".to_string();
                     for elem in tup.elems.pos_args {
@@ -437,8 +622,23 @@ impl ScriptGenerator {
                 }
             },
             Expr::Dict(dict) => match dict {
+                Dict::Normal(dic) if self.target.is_native() => {
+                    let mut kvs = vec![];
+                    for spread in dic.spreads {
+                        kvs.push(format!("**{}", self.transpile_expr(spread)));
+                    }
+                    for kv in dic.kvs {
+                        let key = self.transpile_expr(kv.key);
+                        let value = self.transpile_expr(kv.value);
+                        kvs.push(format!("{key}: {value}"));
+                    }
+                    format!("{{{}}}", kvs.join(", "))
+                }
                 Dict::Normal(dic) => {
                     let mut code = "{".to_string();
+                    for spread in dic.spreads {
+                        code += &format!("**({}),", self.transpile_expr(spread));
+                    }
                     for kv in dic.kvs {
                         code += &format!(
                             "({}): ({}),",
@@ -457,6 +657,9 @@ impl ScriptGenerator {
             Expr::ClassDef(classdef) => self.transpile_classdef(classdef),
             Expr::PatchDef(patchdef) => self.transpile_patchdef(patchdef),
             Expr::ReDef(redef) => self.transpile_attrdef(redef),
+            Expr::Compound(comp) if self.target.is_native() => {
+                self.transpile_stmts(comp.into_iter().collect(), Discard)
+            }
             // TODO:
             Expr::Compound(comp) => {
                 let mut code = "".to_string();
@@ -469,10 +672,16 @@ impl ScriptGenerator {
             Expr::Import(acc) => {
                 let full_name = Str::from(acc.show());
                 let root = PyCodeGenerator::get_root(&acc);
-                self.prelude += &format!(
-                    "{} = __import__(\"{full_name}\")\n",
-                    Self::transpile_ident(root)
-                );
+                let root = Self::transpile_ident(root);
+                if self.target.is_native() && !full_name.contains('.') {
+                    if root == full_name[..] {
+                        self.prelude += &format!("import {full_name}\n");
+                    } else {
+                        self.prelude += &format!("import {full_name} as {root}\n");
+                    }
+                } else {
+                    self.prelude += &format!("{root} = __import__(\"{full_name}\")\n");
+                }
                 String::new()
             }
             Expr::TypeAsc(tasc) => self.transpile_expr(*tasc.expr),
@@ -482,7 +691,17 @@ impl ScriptGenerator {
     }
 
     fn transpile_lit(&mut self, lit: Literal) -> String {
+        // e.g. b"\xff\n"
+        if let ValueObj::Bytes(_) = &lit.value {
+            return lit.value.to_string();
+        }
         let escaped = Self::escape_str(&lit.token.content);
+        if self.target.is_native() {
+            return match lit.token.kind {
+                TokenKind::InfLit => "float('inf')".to_string(),
+                _ => escaped,
+            };
+        }
         if matches!(
             &lit.value,
             ValueObj::Bool(_) | ValueObj::Int(_) | ValueObj::Nat(_) | ValueObj::Str(_)
@@ -500,7 +719,10 @@ impl ScriptGenerator {
         let mut values = "(".to_string();
         for mut attr in rec.attrs.into_iter() {
             attrs += &format!("'{}',", Self::transpile_ident(attr.sig.into_ident()));
-            if attr.body.block.len() > 1 {
+            if attr.body.block.len() > 1 && self.target.is_native() {
+                let name = self.hoist_block("instant_block", String::new(), "", attr.body.block);
+                values += &format!("{},", self.call_hoisted(&name));
+            } else if attr.body.block.len() > 1 {
                 let name = format!("instant_block_{}__", self.fresh_var_n);
                 self.fresh_var_n += 1;
                 let mut code = format!("def {name}():\n");
@@ -519,6 +741,38 @@ impl ScriptGenerator {
 
     fn transpile_binop(&mut self, bin: BinOp) -> String {
         match bin.op.kind {
+            TokenKind::Closed | TokenKind::LeftOpen | TokenKind::RightOpen | TokenKind::Open
+                if self.target.is_native()
+                    && is_int_like(bin.lhs.ref_t())
+                    && is_int_like(bin.rhs.ref_t()) =>
+            {
+                let lhs = self.transpile_expr(*bin.lhs);
+                let rhs = self.transpile_expr(*bin.rhs);
+                match bin.op.kind {
+                    TokenKind::Closed => format!("range({lhs}, {rhs} + 1)"),
+                    TokenKind::LeftOpen => format!("range({lhs} + 1, {rhs} + 1)"),
+                    TokenKind::RightOpen => format!("range({lhs}, {rhs})"),
+                    _ => format!("range({lhs} + 1, {rhs})"),
+                }
+            }
+            TokenKind::InOp | TokenKind::NotInOp
+                if self.target.is_native() && !is_type_obj(bin.rhs.ref_t()) =>
+            {
+                let op = python_binop(bin.op.kind, &bin.op.content);
+                format!(
+                    "({} {op} {})",
+                    self.transpile_expr(*bin.lhs),
+                    self.transpile_expr(*bin.rhs)
+                )
+            }
+            TokenKind::NotInOp if self.target.is_native() => {
+                self.load_in_op_if_not();
+                format!(
+                    "(not in_operator({}, {}))",
+                    self.transpile_expr(*bin.lhs),
+                    self.transpile_expr(*bin.rhs)
+                )
+            }
             TokenKind::Closed | TokenKind::LeftOpen | TokenKind::RightOpen | TokenKind::Open => {
                 self.load_range_ops_if_not();
                 let mut code = match bin.op.kind {
@@ -548,7 +802,7 @@ impl ScriptGenerator {
                 let mut code = "(".to_string();
                 code += &self.transpile_expr(*bin.lhs);
                 code.push(' ');
-                code += &bin.op.content;
+                code += python_binop(bin.op.kind, &bin.op.content);
                 code.push(' ');
                 code += &self.transpile_expr(*bin.rhs);
                 code += ")";
@@ -558,6 +812,23 @@ impl ScriptGenerator {
     }
 
     fn transpile_unaryop(&mut self, unary: UnaryOp) -> String {
+        // Python's containers are mutable from the beginning
+        if self.target.is_native() && unary.op.kind == TokenKind::Mutate {
+            let class = unary.expr.ref_t().qual_name();
+            if !is_builtin_scalar(unary.expr.ref_t()) {
+                return self.transpile_expr(*unary.expr);
+            }
+            self.load_mutate_op_if_not();
+            self.load_builtin_types_if_not();
+            return format!(
+                "mutate_operator({class}({}))",
+                self.transpile_expr(*unary.expr)
+            );
+        }
+        // e.g. `[*xs]`, this cannot be parenthesized
+        if unary.op.kind == TokenKind::PreStar {
+            return format!("*{}", self.transpile_expr(*unary.expr));
+        }
         let mut code = "".to_string();
         if unary.op.kind == TokenKind::Mutate {
             self.load_mutate_op_if_not();
@@ -573,6 +844,21 @@ impl ScriptGenerator {
 
     fn transpile_acc(&mut self, acc: Accessor) -> String {
         match acc {
+            Accessor::Ident(ident) if self.target.is_native() => {
+                match &ident.inspect()[..] {
+                    // Python's `str` never fails
+                    "str" => return "str".to_string(),
+                    "int" | "nat" | "float" => {
+                        self.load_convertors_if_not();
+                        self.load_builtin_types_if_not();
+                    }
+                    "if" | "if!" | "for!" | "while" | "discard" => {
+                        self.load_builtin_controls_if_not();
+                    }
+                    _ => {}
+                }
+                Self::transpile_ident(ident)
+            }
             Accessor::Ident(ident) => {
                 match &ident.inspect()[..] {
                     "Str" | "Bool" | "Nat" | "Array" => {
@@ -591,6 +877,14 @@ impl ScriptGenerator {
             Accessor::Attr(attr) => {
                 if let Some(name) = debind(&attr.ident) {
                     demangle(&name)
+                } else if self.target.is_native()
+                    && matches!(attr.obj.as_ref(), Expr::Accessor(Accessor::Ident(_)))
+                {
+                    format!(
+                        "{}.{}",
+                        self.transpile_expr(*attr.obj),
+                        Self::transpile_ident(attr.ident)
+                    )
                 } else {
                     format!(
                         "({}).{}",
@@ -603,6 +897,36 @@ impl ScriptGenerator {
     }
 
     fn transpile_call(&mut self, mut call: Call) -> String {
+        if self.target.is_native() {
+            match call.obj.local_name() {
+                Some("if" | "if!") if Self::is_block_call(&call) => {
+                    return self.transpile_if_expr(call);
+                }
+                Some("for" | "for!" | "while" | "while!" | "async_for!")
+                    if Self::is_block_call(&call) =>
+                {
+                    return self.hoist_stmt("loop_tmp_func", Expr::Call(call));
+                }
+                Some("async_with!") if Self::is_block_call(&call) => {
+                    return self.hoist_stmt("with_tmp_func", Expr::Call(call));
+                }
+                Some("match" | "match!") if Self::is_block_call(&call) => {
+                    return self.hoist_stmt("match_tmp_func", Expr::Call(call));
+                }
+                Some("if" | "if!" | "for" | "for!" | "while" | "while!" | "match" | "match!") => {
+                    return self.transpile_simple_call(call);
+                }
+                _ => {}
+            }
+        }
+        if call.additional_operation() == Some(OperationKind::Yield) {
+            let value = if let Some(arg) = call.args.try_remove(0) {
+                format!(" {}", self.transpile_expr(arg))
+            } else {
+                "".to_string()
+            };
+            return format!("(yield{value})");
+        }
         match call.obj.local_name() {
             Some("assert") => {
                 let mut code = format!("assert {}", self.transpile_expr(call.args.remove(0)));
@@ -612,29 +936,198 @@ impl ScriptGenerator {
                 code
             }
             Some("not") => format!("(not ({}))", self.transpile_expr(call.args.remove(0))),
+            Some("await") => format!("(await {})", self.transpile_expr(call.args.remove(0))),
             Some("if" | "if!") => self.transpile_if(call),
-            Some("for" | "for!") => {
-                let mut code = "for ".to_string();
-                let iter = call.args.remove(0);
-                let Expr::Lambda(block) = call.args.remove(0) else { todo!() };
-                let non_default = block.params.non_defaults.get(0).unwrap();
-                let ParamPattern::VarName(param) = &non_default.raw.pat else { todo!() };
-                code += &format!("{}__ ", &param.token().content);
+            Some("for" | "for!" | "async_for!") => self.transpile_for(call),
+            Some("async_with!") => self.transpile_async_with(call, Discard),
+            Some("while" | "while!") => self.transpile_while(call),
+            Some("match" | "match!") => self.transpile_match(call),
+            _ => self.transpile_simple_call(call),
+        }
+    }
+
+    /// Whether the bodies of the control call are given as blocks (e.g. `if! c, do!: ...`),
+    /// so that the call can be expanded into Python statements.
+    fn is_block_call(call: &Call) -> bool {
+        match call.obj.local_name() {
+            Some("if" | "if!") => {
+                matches!(call.args.get(1), Some(Expr::Lambda(_)))
+                    && matches!(call.args.get(2), None | Some(Expr::Lambda(_)))
+            }
+            Some("for" | "for!" | "while" | "while!" | "async_for!" | "async_with!") => {
+                matches!(call.args.get(1), Some(Expr::Lambda(_)))
+            }
+            Some("match" | "match!") => {
+                call.args.len() > 1
+                    && (1..call.args.len()).all(|i| {
+                        matches!(
+                            call.args.get(i),
+                            Some(Expr::Lambda(arm)) if Self::block_param_bind(arm).is_some()
+                        )
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// The name bound by a parameter (`Some(None)` if the value is discarded).
+    /// `None` if the pattern cannot be bound by a Python statement.
+    fn param_bind(param: &NonDefaultParamSignature) -> Option<Option<String>> {
+        match &param.raw.pat {
+            ParamPattern::VarName(name) if &name.token().content[..] == "_" => Some(None),
+            // e.g. `%v1` (desugared from `{x; y}`)
+            ParamPattern::VarName(name) => Some(Some(format!(
+                "{}__",
+                replace_non_symbolic(&name.token().content)
+            ))),
+            ParamPattern::Discard(_) => Some(None),
+            _ => None,
+        }
+    }
+
+    fn block_param_bind(block: &Lambda) -> Option<Option<String>> {
+        block
+            .params
+            .non_defaults
+            .first()
+            .map_or(Some(None), Self::param_bind)
+    }
+
+    /// The body of a control call which is not a block (e.g. `for! xs, f!`) is called with the bound value.
+    fn transpile_body_call(&mut self, body: Expr, arg: &str) -> String {
+        let indent = "    ".repeat(self.level + 1);
+        format!("{indent}{}({arg})\n", self.transpile_expr(body))
+    }
+
+    fn transpile_for(&mut self, mut call: Call) -> String {
+        let mut code = if call.obj.local_name() == Some("async_for!") {
+            "async for ".to_string()
+        } else {
+            "for ".to_string()
+        };
+        let iter = call.args.remove(0);
+        match call.args.remove(0) {
+            Expr::Lambda(block) if Self::block_param_bind(&block).is_some() => {
+                let bind = Self::block_param_bind(&block).flatten();
+                code += &format!("{} ", bind.as_deref().unwrap_or("_"));
                 code += &format!("in {}:\n", self.transpile_expr(iter));
                 code += &self.transpile_block(block.body, Discard);
-                code
             }
-            Some("while" | "while!") => {
-                let mut code = "while ".to_string();
-                let cond = call.args.remove(0);
-                let Expr::Lambda(block) = call.args.remove(0) else { todo!() };
-                code += &format!("{}:\n", self.transpile_expr(cond));
+            body => {
+                let tmp = format!("for_tmp_{}__", self.fresh_var_n);
+                self.fresh_var_n += 1;
+                code += &format!("{tmp} in {}:\n", self.transpile_expr(iter));
+                code += &self.transpile_body_call(body, &tmp);
+            }
+        }
+        code
+    }
+
+    /// `async_with! obj, x => ...` -> `async with obj as x__: ...`
+    fn transpile_async_with(&mut self, mut call: Call, last_op: LastLineOperation) -> String {
+        let mut code = format!("async with {}", self.transpile_expr(call.args.remove(0)));
+        match call.args.remove(0) {
+            Expr::Lambda(block) if Self::block_param_bind(&block).is_some() => {
+                match Self::block_param_bind(&block).flatten() {
+                    Some(bind) => {
+                        code += &format!(" as {bind}:\n");
+                    }
+                    None => {
+                        code += ":\n";
+                    }
+                }
+                code += &self.transpile_block(block.body, last_op);
+            }
+            body => {
+                let tmp = format!("with_tmp_{}__", self.fresh_var_n);
+                self.fresh_var_n += 1;
+                code += &format!(" as {tmp}:\n");
+                let indent = "    ".repeat(self.level + 1);
+                let call = self.transpile_body_call(body, &tmp);
+                let call = call.trim_start();
+                code += &match last_op {
+                    Return => format!("{indent}return {call}"),
+                    StoreTmp(tmp) => format!("{indent}{tmp} = {call}"),
+                    Discard => format!("{indent}{call}"),
+                };
+            }
+        }
+        code
+    }
+
+    fn transpile_while(&mut self, mut call: Call) -> String {
+        let mut code = "while ".to_string();
+        // e.g. is_foo!: () => Bool, do!(is_bar)
+        let cond = match call.args.remove(0) {
+            Expr::Lambda(mut lambda) => lambda.body.remove(0),
+            Expr::Accessor(acc) => Expr::Accessor(acc).call_expr(Args::empty()),
+            other => other,
+        };
+        code += &format!("{}:\n", self.transpile_expr(cond));
+        match call.args.remove(0) {
+            Expr::Lambda(block) => {
                 code += &self.transpile_block(block.body, Discard);
-                code
             }
-            Some("match" | "match!") => self.transpile_match(call),
-            _ => self.transpile_simple_call(call),
+            body => {
+                code += &self.transpile_body_call(body, "");
+            }
+        }
+        code
+    }
+
+    /// `if` in an expression position
+    fn transpile_if_expr(&mut self, mut call: Call) -> String {
+        let is_simple = |blk: &Lambda| blk.body.len() == 1 && !Self::is_stmt(&blk.body[0]);
+        let then_is_simple = matches!(call.args.get(1), Some(Expr::Lambda(blk)) if is_simple(blk));
+        let else_is_simple = match call.args.get(2) {
+            Some(Expr::Lambda(blk)) => is_simple(blk),
+            _ => true,
+        };
+        if !then_is_simple || !else_is_simple {
+            return self.hoist_stmt("if_tmp_func", Expr::Call(call));
+        }
+        let cond = self.transpile_expr(call.args.remove(0));
+        let Some(Expr::Lambda(mut then_block)) = call.args.try_remove(0) else { unreachable!() };
+        let then = self.transpile_expr(then_block.body.remove(0));
+        let els = if let Some(Expr::Lambda(mut else_block)) = call.args.try_remove(0) {
+            self.transpile_expr(else_block.body.remove(0))
+        } else {
+            "None".to_string()
+        };
+        format!("({then} if {cond} else {els})")
+    }
+
+    /// `if` in a statement position
+    fn transpile_if_stmt(&mut self, mut call: Call, last_op: LastLineOperation) -> String {
+        let cond = self.transpile_expr(call.args.remove(0));
+        let Expr::Lambda(then_block) = call.args.remove(0) else { unreachable!() };
+        let mut code = format!("if {cond}:\n");
+        code += &self.transpile_block(then_block.body, last_op.clone());
+        let indent = "    ".repeat(self.level);
+        match call.args.try_remove(0) {
+            Some(Expr::Lambda(mut else_block)) => {
+                let is_elif = else_block.body.len() == 1
+                    && matches!(
+                        &else_block.body[0],
+                        Expr::Call(call) if matches!(call.obj.local_name(), Some("if" | "if!"))
+                            && Self::is_block_call(call)
+                    );
+                if is_elif {
+                    let Some(Expr::Call(elif)) = else_block.body.pop() else { unreachable!() };
+                    code += &format!("{indent}el{}", self.transpile_if_stmt(elif, last_op));
+                } else {
+                    code += &format!("{indent}else:\n");
+                    code += &self.transpile_block(else_block.body, last_op);
+                }
+            }
+            Some(_) => unreachable!(),
+            None => {
+                if let StoreTmp(tmp) = last_op {
+                    code += &format!("{indent}else:\n{indent}    {tmp} = None\n");
+                }
+            }
         }
+        code
     }
 
     fn transpile_if(&mut self, mut call: Call) -> String {
@@ -699,14 +1192,16 @@ impl ScriptGenerator {
         code += &"    ".repeat(self.level);
         code += "match ";
         let cond = call.args.remove(0);
+        let mut_subject = Self::is_mut_scalar(cond.ref_t());
         code += &format!("{}:\n", self.transpile_expr(cond));
         while let Some(Expr::Lambda(arm)) = call.args.try_remove(0) {
             self.level += 1;
             code += &"    ".repeat(self.level);
             let target = arm.params.non_defaults.get(0).unwrap();
             match &target.raw.pat {
-                ParamPattern::VarName(param) => {
-                    code += &format!("case {}__:\n", &param.token().content);
+                ParamPattern::VarName(_) => {
+                    let pat = self.transpile_case_pattern(target.clone(), mut_subject);
+                    code += &format!("case {pat}:\n");
                     code += &self.transpile_block(arm.body, StoreTmp(tmp.clone()));
                     self.level -= 1;
                 }
@@ -738,11 +1233,205 @@ impl ScriptGenerator {
         format!("{tmp_func}()")
     }
 
+    /// `match` in a statement position
+    /// (Python 3.9 or earlier does not have `match` statements, so they are lowered to `if`/`elif`)
+    fn transpile_match_stmt(&mut self, mut call: Call, last_op: LastLineOperation) -> String {
+        let subject_t = call.args.get(0).unwrap().ref_t().clone();
+        let mut_subject = Self::is_mut_scalar(&subject_t);
+        let subject = self.transpile_expr(call.args.remove(0));
+        let has_match_stmt = self.py_version.major > 3
+            || (self.py_version.major == 3 && self.py_version.minor.unwrap_or(0) >= 10);
+        if !has_match_stmt {
+            return self.transpile_match_stmt_as_if(subject, mut_subject, call, last_op);
+        }
+        let mut code = format!("match {subject}:\n");
+        self.level += 1;
+        let indent = "    ".repeat(self.level);
+        while let Some(Expr::Lambda(mut arm)) = call.args.try_remove(0) {
+            let target = arm.params.non_defaults.remove(0);
+            let pat = self.transpile_case_pattern(target, mut_subject);
+            code += &format!("{indent}case {pat}:\n");
+            code += &self.transpile_block(arm.body, last_op.clone());
+        }
+        self.level -= 1;
+        code
+    }
+
+    fn transpile_match_stmt_as_if(
+        &mut self,
+        subject: String,
+        mut_subject: bool,
+        mut call: Call,
+        last_op: LastLineOperation,
+    ) -> String {
+        let tmp = format!("match_subject_{}__", self.fresh_var_n);
+        self.fresh_var_n += 1;
+        let indent = "    ".repeat(self.level);
+        let mut code = format!("{tmp} = {subject}\n");
+        let mut first = true;
+        while let Some(Expr::Lambda(mut arm)) = call.args.try_remove(0) {
+            let target = arm.params.non_defaults.remove(0);
+            let (cond, bind) = self.transpile_case_cond(target, &tmp, mut_subject);
+            code += &indent;
+            code += &match (first, cond) {
+                (true, None) => "if True:\n".to_string(),
+                (false, None) => "else:\n".to_string(),
+                (true, Some(cond)) => format!("if {cond}:\n"),
+                (false, Some(cond)) => format!("elif {cond}:\n"),
+            };
+            first = false;
+            if let Some(bind) = bind {
+                code += &format!("{indent}    {bind} = {tmp}\n");
+            }
+            code += &self.transpile_block(arm.body, last_op.clone());
+        }
+        code
+    }
+
+    /// Mutable scalars (`IntMut`, etc.) are not subclasses of the builtin types, so their `value`s are checked
+    fn is_mut_scalar(t: &Type) -> bool {
+        t.is_mut_type()
+            && matches!(
+                &t.qual_name()[..],
+                "Int!" | "Nat!" | "Float!" | "Str!" | "Bool!"
+            )
+    }
+
+    /// The binding name of a `match` arm (`None` if the value is discarded).
+    /// The patterns of the arms have been checked by `is_block_call`.
+    fn case_bind(target: &NonDefaultParamSignature) -> Option<String> {
+        Self::param_bind(target).flatten()
+    }
+
+    /// The Python class to be checked for the type of a `match` arm
+    fn case_class(t: &Type) -> Option<&'static str> {
+        match &t.qual_name()[..] {
+            "Int" | "Nat" => Some("int"),
+            "Float" => Some("float"),
+            "Str" => Some("str"),
+            "Bool" => Some("bool"),
+            _ => None,
+        }
+    }
+
+    fn transpile_case_pattern(
+        &mut self,
+        target: NonDefaultParamSignature,
+        mut_subject: bool,
+    ) -> String {
+        let bind = Self::case_bind(&target);
+        let capture = bind.clone().unwrap_or_else(|| "_".to_string());
+        let with_bind = |pat: String| match &bind {
+            Some(bind) => format!("{pat} as {bind}"),
+            None => pat,
+        };
+        let Some(t_spec) = target.raw.t_spec.as_ref().map(|t| &t.t_spec) else {
+            return capture;
+        };
+        if let TypeSpec::Enum(enum_t) = t_spec {
+            let values = ValueObj::vec_from_const_args(enum_t.clone());
+            let pats = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            return with_bind(pats.join(" | "));
+        }
+        // type variables cannot be checked at runtime
+        if target.vi.t.is_unbound_var() || target.vi.t.has_qvar() {
+            return capture;
+        }
+        match &target.vi.t.qual_name()[..] {
+            "Obj" => return capture,
+            "NoneType" => return with_bind("None".to_string()),
+            _ => {}
+        }
+        let bind = bind.unwrap_or_else(|| {
+            let name = format!("match_arg_{}__", self.fresh_var_n);
+            self.fresh_var_n += 1;
+            name
+        });
+        let class = Self::case_class(&target.vi.t);
+        let is_nat = &target.vi.t.qual_name()[..] == "Nat";
+        match class {
+            Some(class) if mut_subject => {
+                let nat_check = if is_nat {
+                    format!(" and {bind}.value >= 0")
+                } else {
+                    String::new()
+                };
+                format!("{bind} if isinstance({bind}.value, {class}){nat_check}")
+            }
+            Some(_) if is_nat => format!("int() as {bind} if {bind} >= 0"),
+            Some(class) => format!("{class}() as {bind}"),
+            None => {
+                if let Some(t_spec) = target.t_spec_as_expr {
+                    self.load_in_op_if_not();
+                    format!(
+                        "{bind} if in_operator({bind}, {})",
+                        self.transpile_expr(t_spec)
+                    )
+                } else {
+                    bind
+                }
+            }
+        }
+    }
+
+    /// Returns the condition of a `match` arm (`None` if it always matches) and the binding name.
+    fn transpile_case_cond(
+        &mut self,
+        target: NonDefaultParamSignature,
+        subject: &str,
+        mut_subject: bool,
+    ) -> (Option<String>, Option<String>) {
+        let bind = Self::case_bind(&target);
+        let Some(t_spec) = target.raw.t_spec.as_ref().map(|t| &t.t_spec) else {
+            return (None, bind);
+        };
+        if let TypeSpec::Enum(enum_t) = t_spec {
+            let values = ValueObj::vec_from_const_args(enum_t.clone());
+            let conds = values
+                .iter()
+                .map(|v| format!("{subject} == {v}"))
+                .collect::<Vec<_>>();
+            return (Some(conds.join(" or ")), bind);
+        }
+        if target.vi.t.is_unbound_var() || target.vi.t.has_qvar() {
+            return (None, bind);
+        }
+        match &target.vi.t.qual_name()[..] {
+            "Obj" => return (None, bind),
+            "NoneType" => return (Some(format!("{subject} is None")), bind),
+            _ => {}
+        }
+        let value = if mut_subject {
+            format!("{subject}.value")
+        } else {
+            subject.to_string()
+        };
+        let cond = match Self::case_class(&target.vi.t) {
+            Some(class) if &target.vi.t.qual_name()[..] == "Nat" => {
+                format!("isinstance({value}, {class}) and {value} >= 0")
+            }
+            Some(class) => format!("isinstance({value}, {class})"),
+            None => {
+                let Some(t_spec) = target.t_spec_as_expr else {
+                    return (None, bind);
+                };
+                self.load_in_op_if_not();
+                format!("in_operator({subject}, {})", self.transpile_expr(t_spec))
+            }
+        };
+        (Some(cond), bind)
+    }
+
     fn transpile_simple_call(&mut self, call: Call) -> String {
         let is_py_api = if let Some(attr) = &call.attr_name {
             let is_py_api = attr.is_py_api();
             if let Some(name) = debind(attr) {
                 let name = demangle(&name);
+                if self.target.is_native() {
+                    let mut args = vec![self.transpile_expr(*call.obj)];
+                    args.extend(self.transpile_native_args(call.args, is_py_api));
+                    return format!("{name}({})", args.join(", "));
+                }
                 return format!(
                     "{name}({}, {})",
                     self.transpile_expr(*call.obj),
@@ -753,6 +1442,9 @@ impl ScriptGenerator {
         } else {
             call.obj.is_py_api()
         };
+        if self.target.is_native() {
+            return self.transpile_native_simple_call(call, is_py_api);
+        }
         let mut code = format!("({})", self.transpile_expr(*call.obj));
         if let Some(attr) = call.attr_name {
             code += &format!(".{}", Self::transpile_ident(attr));
@@ -761,6 +1453,72 @@ impl ScriptGenerator {
         code
     }
 
+    fn transpile_native_simple_call(&mut self, mut call: Call, is_py_api: bool) -> String {
+        let class = call.obj.ref_t().qual_name();
+        let is_ident = matches!(call.obj.as_ref(), Expr::Accessor(Accessor::Ident(_)));
+        let is_subscript = call
+            .attr_name
+            .as_ref()
+            // e.g. `__Tuple_getitem__` is also `__getitem__` in Python
+            .is_some_and(|attr| Self::transpile_ident(attr.clone()) == "__getitem__")
+            && call.args.pos_args.len() == 1
+            && call.args.var_args.is_none()
+            && call.args.kw_args.is_empty()
+            && call.args.kw_var_args.is_none();
+        let obj = self.transpile_expr(*call.obj);
+        // `x.__getitem__(i)` -> `x[i]`
+        if is_subscript {
+            let index = self.transpile_expr(call.args.pos_args.remove(0).expr);
+            return if is_ident {
+                format!("{obj}[{index}]")
+            } else {
+                format!("({obj})[{index}]")
+            };
+        }
+        let mut code = if let Some(attr) = call.attr_name {
+            let method = Self::transpile_ident(attr);
+            // Erg-specific methods (e.g. `Nat.times!`) are provided by the runtime classes
+            let receiver = if !has_native_method(&class, &method) {
+                self.load_builtin_types_if_not();
+                format!("{class}({obj})")
+            } else if is_ident {
+                obj
+            } else {
+                format!("({obj})")
+            };
+            format!("{receiver}.{method}")
+        } else if is_ident {
+            obj
+        } else {
+            format!("({obj})")
+        };
+        let args = self.transpile_native_args(call.args, is_py_api);
+        code += &format!("({})", args.join(", "));
+        code
+    }
+
+    fn transpile_native_args(&mut self, mut args: Args, is_py_api: bool) -> Vec<String> {
+        let mut codes = vec![];
+        while let Some(arg) = args.try_remove_pos(0) {
+            codes.push(self.transpile_expr(arg.expr));
+        }
+        if let Some(var_args) = args.var_args.take() {
+            codes.push(format!("*{}", self.transpile_expr(var_args.expr)));
+        }
+        while let Some(arg) = args.try_remove_kw(0) {
+            let escape = if is_py_api { "" } else { "__" };
+            codes.push(format!(
+                "{}{escape}={}",
+                arg.keyword.content,
+                self.transpile_expr(arg.expr)
+            ));
+        }
+        if let Some(kw_var_args) = args.kw_var_args.take() {
+            codes.push(self.transpile_kw_spread(kw_var_args.expr, is_py_api));
+        }
+        codes
+    }
+
     fn transpile_args(&mut self, mut args: Args, is_py_api: bool, paren: bool) -> String {
         let mut code = String::new();
         if paren {
@@ -770,6 +1528,9 @@ impl ScriptGenerator {
             code += &self.transpile_expr(arg.expr);
             code.push(',');
         }
+        if let Some(var_args) = args.var_args.take() {
+            code += &format!("*({}),", self.transpile_expr(var_args.expr));
+        }
         while let Some(arg) = args.try_remove_kw(0) {
             let escape = if is_py_api { "" } else { "__" };
             code += &format!(
@@ -778,12 +1539,26 @@ impl ScriptGenerator {
                 self.transpile_expr(arg.expr)
             );
         }
+        if let Some(kw_var_args) = args.kw_var_args.take() {
+            code += &self.transpile_kw_spread(kw_var_args.expr, is_py_api);
+            code.push(',');
+        }
         if paren {
             code.push(')');
         }
         code
     }
 
+    /// Keyword arguments of Erg subroutines are escaped in the same way as `transpile_args`
+    fn transpile_kw_spread(&mut self, kwargs: Expr, is_py_api: bool) -> String {
+        let kwargs = self.transpile_expr(kwargs);
+        if is_py_api {
+            format!("**({kwargs})")
+        } else {
+            format!("**{{f\"{{k}}__\": v for k, v in ({kwargs}).items()}}")
+        }
+    }
+
     fn transpile_ident(ident: Identifier) -> String {
         if let Some(py_name) = ident.vi.py_name {
             return demangle(&py_name);
@@ -797,37 +1572,83 @@ impl ScriptGenerator {
         }
     }
 
+    fn transpile_non_default_param(&mut self, param: NonDefaultParamSignature) -> String {
+        match param.raw.pat {
+            ParamPattern::VarName(param) => {
+                format!("{}__", replace_non_symbolic(&param.into_token().content))
+            }
+            ParamPattern::Discard(_) => {
+                let name = format!("_{}", self.fresh_var_n);
+                self.fresh_var_n += 1;
+                name
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// The parameters are ordered in the same way as Python: `a, *args, b=1, **kwargs`
     fn transpile_params(&mut self, params: Params) -> String {
-        let mut code = String::new();
+        let mut codes = vec![];
         for non_default in params.non_defaults {
-            match non_default.raw.pat {
-                ParamPattern::VarName(param) => {
-                    code += &format!("{}__,", replace_non_symbolic(&param.into_token().content));
-                }
-                ParamPattern::Discard(_) => {
-                    code += &format!("_{},", self.fresh_var_n);
-                    self.fresh_var_n += 1;
-                }
-                _ => unreachable!(),
-            }
+            let code = self.transpile_non_default_param(non_default);
+            codes.push(code);
+        }
+        if let Some(var_params) = params.var_params {
+            let code = self.transpile_non_default_param(*var_params);
+            codes.push(format!("*{code}"));
         }
         for default in params.defaults {
             let ParamPattern::VarName(param) = default.sig.raw.pat else { todo!() };
-            code += &format!(
-                "{}__ = {},",
+            codes.push(format!(
+                "{}__ = {}",
                 replace_non_symbolic(&param.into_token().content),
                 self.transpile_expr(default.default_val)
-            );
+            ));
         }
-        code
+        if let Some(kw_var_params) = params.kw_var_params {
+            let code = self.transpile_non_default_param(*kw_var_params);
+            codes.push(format!("**{code}"));
+        }
+        if self.target.is_native() {
+            codes.join(", ")
+        } else {
+            codes.into_iter().map(|code| code + ",").collect()
+        }
+    }
+
+    /// The keywords are escaped by the caller (see `transpile_args`), so they are unescaped on entry.
+    fn transpile_kw_var_params_prologue(&self, params: &Params) -> String {
+        let Some(ParamPattern::VarName(name)) = params.kw_var_params.as_ref().map(|p| &p.raw.pat)
+        else {
+            return String::new();
+        };
+        let name = format!("{}__", replace_non_symbolic(name.inspect()));
+        format!(
+            "{}{name} = {{(k[:-2] if k.endswith(\"__\") else k): v for k, v in {name}.items()}}\n",
+            "    ".repeat(self.level + 1)
+        )
     }
 
     fn transpile_block(&mut self, block: Block, last_op: LastLineOperation) -> String {
         self.level += 1;
         let mut code = String::new();
         let last = block.len().saturating_sub(1);
+        if self.target.is_native() {
+            for (i, chunk) in block.into_iter().enumerate() {
+                let op = if i == last { last_op.clone() } else { Discard };
+                code += &self.transpile_chunk(chunk, op);
+            }
+            self.level -= 1;
+            return code;
+        }
         for (i, chunk) in block.into_iter().enumerate() {
             code += &"    ".repeat(self.level);
+            if Self::is_async_with_stmt(&chunk) {
+                let Expr::Call(call) = chunk else { unreachable!() };
+                let op = if i == last { last_op.clone() } else { Discard };
+                code += &self.transpile_async_with(call, op);
+                continue;
+            }
             if i == last {
                 match last_op {
                     Return => {
@@ -846,11 +1667,145 @@ impl ScriptGenerator {
         code
     }
 
+    /// Transpiles `chunk` as a statement at the current level,
+    /// preceded by the helper functions it requires.
+    fn transpile_chunk(&mut self, chunk: Expr, last_op: LastLineOperation) -> String {
+        let outer = std::mem::take(&mut self.hoisted);
+        let stmt = self.transpile_stmt(chunk, last_op);
+        let hoisted = std::mem::replace(&mut self.hoisted, outer);
+        let stmt = stmt.trim_end_matches('\n');
+        let stmt = if stmt.is_empty() { "pass" } else { stmt };
+        format!("{hoisted}{}{stmt}\n", "    ".repeat(self.level))
+    }
+
+    /// Transpiles statements at the current level (without the indentation of the first line).
+    fn transpile_stmts(&mut self, stmts: Vec<Expr>, last_op: LastLineOperation) -> String {
+        let mut code = String::new();
+        let last = stmts.len().saturating_sub(1);
+        for (i, chunk) in stmts.into_iter().enumerate() {
+            let op = if i == last { last_op.clone() } else { Discard };
+            code += &self.transpile_chunk(chunk, op);
+        }
+        code.trim().to_string()
+    }
+
+    /// Transpiles `expr` into a Python statement (native mode only).
+    /// The first line is not indented.
+    fn transpile_stmt(&mut self, expr: Expr, last_op: LastLineOperation) -> String {
+        let tail = match &last_op {
+            StoreTmp(tmp) => format!("\n{}{tmp} = None", "    ".repeat(self.level)),
+            _ => String::new(),
+        };
+        match expr {
+            Expr::Call(call) if Self::is_block_call(&call) => match call.obj.local_name() {
+                Some("if" | "if!") => self.transpile_if_stmt(call, last_op),
+                Some("for" | "for!" | "async_for!") => self.transpile_for(call) + &tail,
+                Some("async_with!") => self.transpile_async_with(call, last_op),
+                Some("while" | "while!") => self.transpile_while(call) + &tail,
+                _ => self.transpile_match_stmt(call, last_op),
+            },
+            Expr::Call(call) if call.obj.local_name() == Some("Del") => {
+                let mut targets = vec![];
+                for arg in call.args.pos_args {
+                    targets.push(self.transpile_expr(arg.expr));
+                }
+                format!("del {}", targets.join(", ")) + &tail
+            }
+            Expr::Call(mut call) if call.obj.local_name() == Some("discard") => {
+                let expr = self.transpile_expr(call.args.remove(0));
+                expr + &tail
+            }
+            Expr::Compound(comp) => self.transpile_stmts(comp.into_iter().collect(), last_op),
+            Expr::Def(_) | Expr::ClassDef(_) | Expr::PatchDef(_) | Expr::ReDef(_) => {
+                self.transpile_expr(expr) + &tail
+            }
+            other => {
+                let code = self.transpile_expr(other);
+                match last_op {
+                    Return => format!("return {code}"),
+                    StoreTmp(tmp) => format!("{tmp} = {code}"),
+                    Discard => code,
+                }
+            }
+        }
+    }
+
+    /// `async with` cannot be an expression, so its result is stored by the body (compatible mode only).
+    fn is_async_with_stmt(expr: &Expr) -> bool {
+        matches!(
+            expr,
+            Expr::Call(call) if call.obj.local_name() == Some("async_with!") && Self::is_block_call(call)
+        )
+    }
+
+    fn is_stmt(expr: &Expr) -> bool {
+        matches!(
+            expr,
+            Expr::Def(_)
+                | Expr::ClassDef(_)
+                | Expr::PatchDef(_)
+                | Expr::ReDef(_)
+                | Expr::Compound(_)
+        )
+    }
+
+    /// Defines a helper function just before the current statement (native mode only).
+    /// Unlike the prelude functions, the helper can refer to the local variables.
+    /// In an `async def`, the helper is also an `async def` (so that it can contain `await`),
+    /// and must be called with [`Self::call_hoisted`].
+    fn hoist_block(
+        &mut self,
+        prefix: &str,
+        params: String,
+        prologue: &str,
+        block: Block,
+    ) -> String {
+        let name = format!("{prefix}_{}__", self.fresh_var_n);
+        self.fresh_var_n += 1;
+        let def = if self.in_async_subr {
+            "async def"
+        } else {
+            "def"
+        };
+        let mut code = format!("{}{def} {name}({params}):\n", "    ".repeat(self.level));
+        code += prologue;
+        code += &self.transpile_block(block, Return);
+        self.hoisted += &code;
+        name
+    }
+
+    fn call_hoisted(&self, name: &str) -> String {
+        if self.in_async_subr {
+            format!("(await {name}())")
+        } else {
+            format!("{name}()")
+        }
+    }
+
+    fn hoist_stmt(&mut self, prefix: &str, stmt: Expr) -> String {
+        let name = self.hoist_block(prefix, String::new(), "", Block::new(vec![stmt]));
+        self.call_hoisted(&name)
+    }
+
     fn transpile_lambda(&mut self, lambda: Lambda) -> String {
-        if lambda.body.len() > 1 {
+        let prologue = self.transpile_kw_var_params_prologue(&lambda.params);
+        if self.target.is_native() {
+            let params = self.transpile_params(lambda.params);
+            if lambda.body.len() > 1 || Self::is_stmt(&lambda.body[0]) || !prologue.is_empty() {
+                // lambdas are passed as values, so they cannot be `async def`
+                let in_async_subr = std::mem::take(&mut self.in_async_subr);
+                let name = self.hoist_block("lambda", params, &prologue, lambda.body);
+                self.in_async_subr = in_async_subr;
+                name
+            } else {
+                let mut body = lambda.body;
+                format!("(lambda {params}: {})", self.transpile_expr(body.remove(0)))
+            }
+        } else if lambda.body.len() > 1 || !prologue.is_empty() {
             let name = format!("lambda_{}__", self.fresh_var_n);
             self.fresh_var_n += 1;
             let mut code = format!("def {name}({}):\n", self.transpile_params(lambda.params));
+            code += &prologue;
             code += &self.transpile_block(lambda.body, Return);
             self.prelude += &code;
             name
@@ -866,7 +1821,8 @@ impl ScriptGenerator {
     // TODO: trait definition
     fn transpile_def(&mut self, mut def: Def) -> String {
         // HACK: allow reference to local variables in tmp functions
-        let mut code = if self.level == 0 {
+        // (in native mode, helper functions are defined locally, so this is not necessary)
+        let mut code = if self.level == 0 || self.target.is_native() {
             "".to_string()
         } else {
             format!(
@@ -876,6 +1832,28 @@ impl ScriptGenerator {
             )
         };
         match def.sig {
+            Signature::Var(var) if self.target.is_native() => {
+                let name = Self::transpile_ident(var.ident);
+                let is_block_call = matches!(
+                    def.body.block.first(),
+                    Some(Expr::Call(call)) if Self::is_block_call(call)
+                );
+                if def.body.block.len() > 1 || is_block_call {
+                    let stmts = def.body.block.into_iter().collect();
+                    code += &self.transpile_stmts(stmts, StoreTmp(Str::from(name)));
+                } else {
+                    code += &format!("{name} = {}", self.transpile_expr(def.body.block.remove(0)));
+                }
+                code
+            }
+            Signature::Var(var)
+                if def.body.block.len() == 1 && Self::is_async_with_stmt(&def.body.block[0]) =>
+            {
+                let name = Str::from(Self::transpile_ident(var.ident));
+                let Some(Expr::Call(call)) = def.body.block.pop() else { unreachable!() };
+                code += &self.transpile_async_with(call, StoreTmp(name));
+                code
+            }
             Signature::Var(var) => {
                 code += &format!("{} = ", Self::transpile_ident(var.ident));
                 if def.body.block.len() > 1 {
@@ -892,18 +1870,28 @@ impl ScriptGenerator {
                 }
             }
             Signature::Subr(subr) => {
+                let is_async = subr.ident.vi.is_async();
+                let prologue = self.transpile_kw_var_params_prologue(&subr.params);
                 code += &format!(
-                    "def {}({}):\n",
+                    "{}def {}({}):\n",
+                    if is_async { "async " } else { "" },
                     Self::transpile_ident(subr.ident),
                     self.transpile_params(subr.params)
                 );
+                code += &prologue;
+                let in_async_subr = std::mem::replace(&mut self.in_async_subr, is_async);
                 code += &self.transpile_block(def.body.block, Return);
+                self.in_async_subr = in_async_subr;
                 code
             }
         }
     }
 
     fn transpile_classdef(&mut self, classdef: ClassDef) -> String {
+        if let GenTypeObj::Enum(enum_) = &classdef.obj {
+            let enum_ = enum_.clone();
+            return self.transpile_enumdef(enum_, classdef);
+        }
         let class_name = Self::transpile_ident(classdef.sig.into_ident());
         let mut code = format!("class {class_name}():\n");
         let mut init_method = format!(
@@ -933,25 +1921,79 @@ impl ScriptGenerator {
         code
     }
 
+    /// ```python
+    /// class Expr__():
+    ///     ...
+    /// class Expr__Lit(Expr__):
+    ///     __tag__ = 0
+    ///     def __init__(self, param__):
+    ///         self.base__ = param__
+    /// Expr__.Lit = Expr__Lit
+    /// ```
+    fn transpile_enumdef(&mut self, enum_: EnumTypeObj, classdef: ClassDef) -> String {
+        let class_name = Self::transpile_ident(classdef.sig.into_ident());
+        let indent = "    ".repeat(self.level);
+        let mut code = format!("class {class_name}():\n");
+        if classdef.methods.is_empty() {
+            code += &format!("{indent}    pass\n");
+        } else {
+            code += &self.transpile_block(classdef.methods, Discard);
+        }
+        for (tag, (name, payload)) in enum_.variants.iter().enumerate() {
+            let variant_name = format!("{class_name}{name}");
+            code += &format!("\n{indent}class {variant_name}({class_name}):\n");
+            code += &format!("{indent}    __tag__ = {tag}\n");
+            code += &format!("{indent}    def __init__(self, param__):\n");
+            code += &format!("{indent}        self.base__ = param__\n");
+            if let Type::Record(rec) = payload.typ() {
+                for field in rec.keys() {
+                    let vis = if field.vis.is_private() { "__" } else { "" };
+                    code += &format!(
+                        "{indent}        self.{} = param__.{}{vis}\n",
+                        field.symbol, field.symbol,
+                    );
+                }
+            }
+            code += &format!("{indent}{class_name}.{name} = {variant_name}\n");
+        }
+        code
+    }
+
     fn transpile_patchdef(&mut self, patch_def: PatchDef) -> String {
         let mut code = String::new();
+        let base = self.transpile_expr(*patch_def.base);
         for chunk in patch_def.methods.into_iter() {
             let Expr::Def(mut def) = chunk else { todo!() };
+            let method = def.sig.ident().inspect().clone();
             let name = format!(
                 "{}{}",
                 demangle(&patch_def.sig.ident().to_string_notype()),
                 demangle(&def.sig.ident().to_string_notype()),
             );
-            def.sig.ident_mut().raw.name = VarName::from_str(Str::from(name));
+            def.sig.ident_mut().raw.name = VarName::from_str(Str::from(name.clone()));
             code += &"    ".repeat(self.level);
             code += &self.transpile_def(def);
             code.push('\n');
+            // glue patch methods are called by dynamic dispatch
+            if patch_def.impls.is_some() {
+                code += &"    ".repeat(self.level);
+                code += &format!("{base}.{method} = {name}\n");
+            }
         }
+        // a patch has no runtime representation, so the name is bound to the base
+        let patch = Self::transpile_ident(patch_def.sig.ident().clone());
+        code += &"    ".repeat(self.level);
+        code += &format!("{patch} = {base}\n");
         code
     }
 
     fn transpile_attrdef(&mut self, mut redef: ReDef) -> String {
-        let mut code = format!("{} = ", self.transpile_expr(Expr::Accessor(redef.attr)));
+        let attr = self.transpile_expr(Expr::Accessor(redef.attr));
+        if self.target.is_native() && redef.block.len() > 1 {
+            let stmts = redef.block.into_iter().collect();
+            return self.transpile_stmts(stmts, StoreTmp(Str::from(attr)));
+        }
+        let mut code = format!("{attr} = ");
         if redef.block.len() > 1 {
             let name = format!("instant_block_{}__", self.fresh_var_n);
             self.fresh_var_n += 1;
@@ -0,0 +1,970 @@
+//! defines the listing format of .pyc files, and its disassembler and assembler.
+//!
+//! .pycファイルのリスティング形式と、その逆アセンブラ・アセンブラを定義する
+//!
+//! ```text
+//! ; Python 3.11 (magic number: 3495)
+//! .magic 3495
+//! .mode timestamp
+//! .validation 3b6d2a6423000000
+//! .code
+//!     .name "<module>"
+//!     ...
+//!     .consts
+//!         "hello" ; 0
+//!         .code ; 1
+//!             ...
+//!         .end
+//!     .names
+//!         "print" ; 0
+//!     .linetable 0201
+//!     .exceptiontable
+//!     .instructions
+//!         ; line 1
+//!              0 RESUME 0
+//!     L2:
+//!              2 LOAD_CONST 0 ; "hello"
+//!              4 JUMP_BACKWARD L2
+//! .end
+//! ```
+//!
+//! * `;` starts a comment, and offsets at the beginning of instructions are ignored by the assembler.
+//! * Jump arguments are labels (`L{offset}`), other arguments are the full values.
+//!   `EXTENDED_ARG` prefixes are rewritten by the assembler to match the following instruction.
+//! * The line table and the exception table are kept as raw bytes (hex), decoded entries are shown as comments.
+use std::fmt::Write as _;
+
+use erg_common::config::ErgConfig;
+use erg_common::dict::Dict;
+use erg_common::opcode308::Opcode308;
+use erg_common::opcode310::Opcode310;
+use erg_common::opcode311::{BinOpCode, Opcode311};
+use erg_common::python_util::PythonVersion;
+use erg_common::serialize::PycInvalidationMode;
+use erg_common::traits::ExitStatus;
+use erg_common::{fn_name, switch_lang, Str};
+
+use super::codeobj::{CodeObj, CodeObjFlags};
+use super::deserialize::{DeserializeError, DeserializeResult, PycHeader};
+use super::value::ValueObj;
+
+const EXTENDED_ARG: u8 = 144;
+const CMP_OPS: [&str; 6] = ["<", "<=", "==", "!=", ">", ">="];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JumpKind {
+    Absolute,
+    Forward,
+    Backward,
+}
+
+fn jump_kind(python_ver: PythonVersion, op: u8) -> Option<JumpKind> {
+    match python_ver.minor {
+        Some(minor) if minor >= 11 => match op {
+            93 | 110 | 111 | 112 | 114 | 115 | 123 | 128 | 129 => Some(JumpKind::Forward),
+            134 | 140 | 173 | 174 | 175 | 176 => Some(JumpKind::Backward),
+            _ => None,
+        },
+        minor => match op {
+            111..=115 => Some(JumpKind::Absolute),
+            // SETUP_EXCEPT (3.7) / JUMP_IF_NOT_EXC_MATCH (3.9, 3.10)
+            121 if minor < Some(8) => Some(JumpKind::Forward),
+            121 if minor > Some(8) => Some(JumpKind::Absolute),
+            // FOR_ITER, JUMP_FORWARD, SETUP_LOOP, SETUP_FINALLY, SETUP_WITH, SETUP_ASYNC_WITH, CALL_FINALLY
+            93 | 110 | 120 | 122 | 143 | 154 | 162 => Some(JumpKind::Forward),
+            _ => None,
+        },
+    }
+}
+
+/// Python 3.10+ counts jumps in code units, not bytes
+fn jump_unit(python_ver: PythonVersion) -> usize {
+    if python_ver.minor >= Some(10) {
+        2
+    } else {
+        1
+    }
+}
+
+fn jump_target(python_ver: PythonVersion, op: u8, arg: usize, offset: usize) -> Option<usize> {
+    let delta = arg * jump_unit(python_ver);
+    match jump_kind(python_ver, op)? {
+        JumpKind::Absolute => Some(delta),
+        JumpKind::Forward => Some(offset + 2 + delta),
+        JumpKind::Backward => (offset + 2).checked_sub(delta),
+    }
+}
+
+fn jump_arg(python_ver: PythonVersion, op: u8, target: usize, offset: usize) -> Option<usize> {
+    let delta = match jump_kind(python_ver, op)? {
+        JumpKind::Absolute => target,
+        JumpKind::Forward => target.checked_sub(offset + 2)?,
+        JumpKind::Backward => (offset + 2).checked_sub(target)?,
+    };
+    let unit = jump_unit(python_ver);
+    (delta % unit == 0).then_some(delta / unit)
+}
+
+fn opname(python_ver: PythonVersion, op: u8) -> String {
+    let name = match python_ver.minor {
+        Some(minor) if minor >= 11 => Opcode311::try_from_byte(op).map(|op| op.to_string()),
+        Some(9 | 10) => Opcode310::try_from_byte(op).map(|op| op.to_string()),
+        _ => Opcode308::try_from_byte(op).map(|op| op.to_string()),
+    };
+    name.unwrap_or_else(|| format!("<{op}>"))
+}
+
+fn opcode_from_name(python_ver: PythonVersion, name: &str) -> Option<u8> {
+    if let Some(op) = name.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+        return op.parse().ok();
+    }
+    match python_ver.minor {
+        Some(minor) if minor >= 11 => Opcode311::try_from_name(name).map(u8::from),
+        Some(9 | 10) => Opcode310::try_from_name(name).map(u8::from),
+        _ => Opcode308::try_from_name(name).map(u8::from),
+    }
+}
+
+fn literal(obj: &ValueObj) -> Option<String> {
+    match obj {
+        ValueObj::Int(i) => Some(i.to_string()),
+        ValueObj::Nat(n) => Some(n.to_string()),
+        ValueObj::Float(f) => Some(format!("{f:?}")),
+        ValueObj::Str(s) => Some(format!("{:?}", &s[..])),
+        ValueObj::Bytes(_) => Some(obj.to_string()),
+        ValueObj::Bool(true) => Some("True".into()),
+        ValueObj::Bool(false) => Some("False".into()),
+        ValueObj::None => Some("None".into()),
+        ValueObj::Ellipsis => Some("...".into()),
+        ValueObj::Array(elems) | ValueObj::Tuple(elems) => {
+            let elems = elems.iter().map(literal).collect::<Option<Vec<_>>>()?;
+            if elems.len() == 1 {
+                Some(format!("({},)", elems[0]))
+            } else {
+                Some(format!("({})", elems.join(", ")))
+            }
+        }
+        _ => None,
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut s, b| {
+        write!(s, "{b:02x}").unwrap();
+        s
+    })
+}
+
+/// Returns (start offset, line number) pairs. `None` means the instructions have no line number.
+fn line_starts(code: &CodeObj, python_ver: PythonVersion) -> Vec<(usize, Option<u32>)> {
+    let table = &code.lnotab;
+    // Erg emits lnotab even for 3.11+ (location table entries always start with `1xxxxxxx`)
+    let is_lnotab = !matches!(table.first(), Some(b) if b & 0x80 != 0);
+    if python_ver.minor >= Some(11) && !is_lnotab {
+        decode_location_table(table, code.firstlineno)
+    } else if python_ver.minor == Some(10) {
+        decode_linetable_310(table, code.firstlineno)
+    } else {
+        decode_lnotab(table, code.firstlineno)
+    }
+}
+
+fn decode_lnotab(table: &[u8], firstlineno: u32) -> Vec<(usize, Option<u32>)> {
+    let mut starts = vec![(0, Some(firstlineno))];
+    let (mut addr, mut line) = (0, firstlineno as i64);
+    for pair in table.chunks_exact(2) {
+        addr += pair[0] as usize;
+        line += pair[1] as i8 as i64;
+        starts.push((addr, Some(line as u32)));
+    }
+    starts
+}
+
+fn decode_linetable_310(table: &[u8], firstlineno: u32) -> Vec<(usize, Option<u32>)> {
+    let mut starts = vec![];
+    let (mut addr, mut line) = (0, firstlineno as i64);
+    for pair in table.chunks_exact(2) {
+        let ldelta = pair[1] as i8;
+        if ldelta == -128 {
+            starts.push((addr, None));
+        } else {
+            line += ldelta as i64;
+            starts.push((addr, Some(line as u32)));
+        }
+        addr += pair[0] as usize;
+    }
+    starts
+}
+
+fn read_varint(iter: &mut impl Iterator<Item = u8>) -> u64 {
+    let mut val = 0;
+    let mut shift = 0;
+    for b in iter {
+        val |= ((b & 63) as u64) << shift;
+        shift += 6;
+        if b & 64 == 0 {
+            break;
+        }
+    }
+    val
+}
+
+fn read_svarint(iter: &mut impl Iterator<Item = u8>) -> i64 {
+    let uval = read_varint(iter);
+    if uval & 1 != 0 {
+        -((uval >> 1) as i64)
+    } else {
+        (uval >> 1) as i64
+    }
+}
+
+/// see Objects/locations.md in CPython
+fn decode_location_table(table: &[u8], firstlineno: u32) -> Vec<(usize, Option<u32>)> {
+    let mut starts = vec![];
+    let (mut addr, mut line) = (0, firstlineno as i64);
+    let mut iter = table.iter().copied();
+    while let Some(first) = iter.next() {
+        if first & 0x80 == 0 {
+            break;
+        }
+        let code = (first >> 3) & 15;
+        let len = (first & 7) as usize + 1;
+        match code {
+            15 => {
+                starts.push((addr, None));
+            }
+            14 => {
+                line += read_svarint(&mut iter);
+                for _ in 0..3 {
+                    read_varint(&mut iter);
+                }
+                starts.push((addr, Some(line as u32)));
+            }
+            13 => {
+                line += read_svarint(&mut iter);
+                starts.push((addr, Some(line as u32)));
+            }
+            10..=12 => {
+                line += (code - 10) as i64;
+                iter.next();
+                iter.next();
+                starts.push((addr, Some(line as u32)));
+            }
+            _ => {
+                iter.next();
+                starts.push((addr, Some(line as u32)));
+            }
+        }
+        addr += len * 2;
+    }
+    starts
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ExceptionTableEntry {
+    start: usize,
+    end: usize,
+    target: usize,
+    depth: usize,
+    lasti: bool,
+}
+
+/// see Objects/exception_handling_notes.txt in CPython
+fn decode_exception_table(table: &[u8]) -> Vec<ExceptionTableEntry> {
+    let mut entries = vec![];
+    let mut iter = table.iter().copied();
+    let mut varint = || {
+        let mut b = iter.next()?;
+        let mut val = (b & 63) as usize;
+        while b & 64 != 0 {
+            b = iter.next()?;
+            val = (val << 6) | (b & 63) as usize;
+        }
+        Some(val)
+    };
+    while let Some(start) = varint() {
+        let (Some(len), Some(target), Some(depth_lasti)) = (varint(), varint(), varint()) else {
+            break;
+        };
+        entries.push(ExceptionTableEntry {
+            start: start * 2,
+            end: (start + len) * 2,
+            target: target * 2,
+            depth: depth_lasti >> 1,
+            lasti: depth_lasti & 1 != 0,
+        });
+    }
+    entries
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Instr {
+    offset: usize,
+    op: u8,
+    /// including `EXTENDED_ARG`s
+    arg: usize,
+}
+
+fn decode_instrs(code: &[u8]) -> Vec<Instr> {
+    let mut instrs = vec![];
+    let mut ext = 0;
+    for (i, pair) in code.chunks(2).enumerate() {
+        let (op, raw) = (pair[0], pair.get(1).copied().unwrap_or(0));
+        if op == EXTENDED_ARG {
+            instrs.push(Instr {
+                offset: i * 2,
+                op,
+                arg: raw as usize,
+            });
+            ext = (ext | raw as usize) << 8;
+        } else {
+            instrs.push(Instr {
+                offset: i * 2,
+                op,
+                arg: ext | raw as usize,
+            });
+            ext = 0;
+        }
+    }
+    instrs
+}
+
+struct Disassembler {
+    python_ver: PythonVersion,
+    out: String,
+}
+
+impl Disassembler {
+    const fn new(python_ver: PythonVersion) -> Self {
+        Self {
+            python_ver,
+            out: String::new(),
+        }
+    }
+
+    fn line(&mut self, depth: usize, line: &str) {
+        let line = format!("{}{line}", "    ".repeat(depth));
+        writeln!(self.out, "{}", line.trim_end()).unwrap();
+    }
+
+    fn strs(&mut self, depth: usize, directive: &str, strs: &[Str]) {
+        self.line(depth, directive);
+        for (i, s) in strs.iter().enumerate() {
+            self.line(depth + 1, &format!("{:?} ; {i}", &s[..]));
+        }
+    }
+
+    fn code(&mut self, code: &CodeObj, depth: usize) {
+        self.line(depth + 1, &format!(".name {:?}", &code.name[..]));
+        self.line(depth + 1, &format!(".qualname {:?}", &code.qualname[..]));
+        self.line(depth + 1, &format!(".filename {:?}", &code.filename[..]));
+        self.line(depth + 1, &format!(".firstlineno {}", code.firstlineno));
+        self.line(depth + 1, &format!(".argcount {}", code.argcount));
+        self.line(
+            depth + 1,
+            &format!(".posonlyargcount {}", code.posonlyargcount),
+        );
+        self.line(
+            depth + 1,
+            &format!(".kwonlyargcount {}", code.kwonlyargcount),
+        );
+        self.line(depth + 1, &format!(".nlocals {}", code.nlocals));
+        self.line(depth + 1, &format!(".stacksize {}", code.stacksize));
+        let flags = (0..32)
+            .filter(|i| code.flags & (1 << i) != 0)
+            .map(|i| format!("{:?}", CodeObjFlags::from(1u32 << i)))
+            .collect::<Vec<_>>();
+        if flags.is_empty() {
+            self.line(depth + 1, &format!(".flags {:#010x}", code.flags));
+        } else {
+            self.line(
+                depth + 1,
+                &format!(".flags {:#010x} ; {}", code.flags, flags.join(", ")),
+            );
+        }
+        self.line(depth + 1, ".consts");
+        for (i, obj) in code.consts.iter().enumerate() {
+            if let ValueObj::Code(inner) = obj {
+                self.line(depth + 2, &format!(".code ; {i}"));
+                self.code(inner, depth + 2);
+            } else if let Some(lit) = literal(obj) {
+                self.line(depth + 2, &format!("{lit} ; {i}"));
+            } else {
+                self.line(depth + 2, &format!("; {i}: {obj} (not supported)"));
+            }
+        }
+        self.strs(depth + 1, ".names", &code.names);
+        self.strs(depth + 1, ".varnames", &code.varnames);
+        self.strs(depth + 1, ".freevars", &code.freevars);
+        self.strs(depth + 1, ".cellvars", &code.cellvars);
+        if !code.localspluskinds.is_empty() {
+            self.line(
+                depth + 1,
+                &format!(".localspluskinds {}", hex(&code.localspluskinds)),
+            );
+        }
+        self.line(depth + 1, &format!(".linetable {}", hex(&code.lnotab)));
+        self.line(
+            depth + 1,
+            &format!(".exceptiontable {}", hex(&code.exceptiontable)),
+        );
+        let entries = decode_exception_table(&code.exceptiontable);
+        for entry in entries.iter() {
+            self.line(
+                depth + 2,
+                &format!(
+                    "; {} to {} -> L{} [{}]{}",
+                    entry.start,
+                    entry.end,
+                    entry.target,
+                    entry.depth,
+                    if entry.lasti { " lasti" } else { "" }
+                ),
+            );
+        }
+        self.line(depth + 1, ".instructions");
+        self.instrs(code, &entries, depth + 2);
+        self.line(depth, ".end");
+    }
+
+    fn instrs(&mut self, code: &CodeObj, entries: &[ExceptionTableEntry], depth: usize) {
+        let instrs = decode_instrs(&code.code);
+        let python_ver = self.python_ver;
+        let target_of = |instr: &Instr| {
+            jump_target(python_ver, instr.op, instr.arg, instr.offset)
+                .filter(|t| *t < code.code.len() && t % 2 == 0)
+        };
+        let mut labels = instrs.iter().filter_map(target_of).collect::<Vec<_>>();
+        labels.extend(entries.iter().map(|entry| entry.target));
+        let localsplus = if self.python_ver.minor >= Some(11) {
+            code.localsplus().0
+        } else {
+            [&code.cellvars[..], &code.freevars[..]].concat()
+        };
+        let mut starts = line_starts(code, self.python_ver).into_iter().peekable();
+        let mut cur_line = None;
+        for instr in instrs.iter() {
+            let mut new_line = None;
+            while let Some((_, line)) = starts.next_if(|(start, _)| *start <= instr.offset) {
+                new_line = Some(line);
+            }
+            if let Some(line) = new_line.filter(|line| *line != cur_line) {
+                match line {
+                    Some(line) => self.line(depth, &format!("; line {line}")),
+                    None => self.line(depth, "; line -"),
+                }
+                cur_line = line;
+            }
+            if labels.contains(&instr.offset) {
+                self.line(depth - 1, &format!("L{}:", instr.offset));
+            }
+            let name = opname(self.python_ver, instr.op);
+            let mut line = format!("{:>6} {name}", instr.offset);
+            if let Some(target) = target_of(instr) {
+                write!(line, " L{target}").unwrap();
+            } else if instr.op >= 90 || instr.arg != 0 {
+                write!(line, " {}", instr.arg).unwrap();
+            }
+            if let Some(comment) = self.comment(code, &localsplus, instr) {
+                write!(line, " ; {comment}").unwrap();
+            }
+            self.line(depth, &line);
+        }
+    }
+
+    fn comment(&self, code: &CodeObj, localsplus: &[Str], instr: &Instr) -> Option<String> {
+        let py311 = self.python_ver.minor >= Some(11);
+        let arg = instr.arg;
+        match instr.op {
+            100 => code
+                .consts
+                .get(arg)
+                .map(|c| literal(c).unwrap_or(c.to_string())),
+            172 if py311 => code.consts.get(arg).and_then(literal),
+            116 if py311 => {
+                let name = code.names.get(arg >> 1)?;
+                Some(if arg & 1 != 0 {
+                    format!("NULL + {name}")
+                } else {
+                    name.to_string()
+                })
+            }
+            90 | 91 | 95 | 96 | 97 | 98 | 101 | 106 | 108 | 109 | 116 | 160 => {
+                code.names.get(arg).map(|n| n.to_string())
+            }
+            124..=126 if py311 => localsplus.get(arg).map(|n| n.to_string()),
+            124..=126 => code.varnames.get(arg).map(|n| n.to_string()),
+            135 if py311 => localsplus.get(arg).map(|n| n.to_string()),
+            136..=139 | 148 => localsplus.get(arg).map(|n| n.to_string()),
+            107 => CMP_OPS.get(arg).map(|op| op.to_string()),
+            122 if py311 => BinOpCode::try_from_byte(arg as u8).map(|op| op.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns a listing of the .pyc file, which can be assembled by `Assembler`.
+pub fn disassemble(header: &PycHeader, code: &CodeObj) -> String {
+    let python_ver = header.python_version();
+    let mut dis = Disassembler::new(python_ver);
+    dis.line(
+        0,
+        &format!(
+            "; Python {}.{} (magic number: {})",
+            python_ver.major,
+            python_ver.minor.unwrap_or(0),
+            header.magic_num
+        ),
+    );
+    dis.line(0, &format!(".magic {}", header.magic_num));
+    dis.line(0, &format!(".mode {}", header.mode));
+    dis.line(0, &format!(".validation {}", hex(&header.validation)));
+    dis.line(0, ".code");
+    dis.code(code, 0);
+    dis.out
+}
+
+fn asm_syntax_error(lineno: usize, line: &str) -> DeserializeError {
+    DeserializeError::new(
+        0,
+        fn_name!(),
+        switch_lang!(
+            "japanese" => format!("{lineno}行目の構文が不正です: {line}"),
+            "simplified_chinese" => format!("第{lineno}行语法无效: {line}"),
+            "traditional_chinese" => format!("第{lineno}行語法無效: {line}"),
+            "english" => format!("invalid syntax at line {lineno}: {line}"),
+        ),
+    )
+}
+
+fn asm_unknown_opcode_error(lineno: usize, name: &str) -> DeserializeError {
+    DeserializeError::new(
+        0,
+        fn_name!(),
+        switch_lang!(
+            "japanese" => format!("{lineno}行目: 不明なオペコードです: {name}"),
+            "simplified_chinese" => format!("第{lineno}行: 未知的操作码: {name}"),
+            "traditional_chinese" => format!("第{lineno}行: 未知的操作碼: {name}"),
+            "english" => format!("line {lineno}: unknown opcode: {name}"),
+        ),
+    )
+}
+
+fn asm_undefined_label_error(lineno: usize, label: &str) -> DeserializeError {
+    DeserializeError::new(
+        0,
+        fn_name!(),
+        switch_lang!(
+            "japanese" => format!("{lineno}行目: ラベル{label}は定義されていません"),
+            "simplified_chinese" => format!("第{lineno}行: 标签{label}未定义"),
+            "traditional_chinese" => format!("第{lineno}行: 標籤{label}未定義"),
+            "english" => format!("line {lineno}: label {label} is not defined"),
+        ),
+    )
+}
+
+fn asm_invalid_arg_error(lineno: usize) -> DeserializeError {
+    DeserializeError::new(
+        0,
+        fn_name!(),
+        switch_lang!(
+            "japanese" => format!("{lineno}行目: 引数を表現できません(ジャンプ先またはEXTENDED_ARGを確認してください)"),
+            "simplified_chinese" => format!("第{lineno}行: 无法表示参数(请检查跳转目标或EXTENDED_ARG)"),
+            "traditional_chinese" => format!("第{lineno}行: 無法表示參數(請檢查跳轉目標或EXTENDED_ARG)"),
+            "english" => format!("line {lineno}: the argument cannot be encoded (check the jump target or EXTENDED_ARG)"),
+        ),
+    )
+}
+
+fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.split_whitespace().collect::<String>();
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn parse_u32(s: &str) -> Option<u32> {
+    if let Some(hex) = s.strip_prefix("0x") {
+        u32::from_str_radix(hex, 16).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// Parses a literal printed by `literal`, returns the rest of the input.
+fn parse_literal(s: &str) -> Option<(ValueObj, &str)> {
+    let s = s.trim_start();
+    if let Some(rest) = s.strip_prefix('"') {
+        let (string, rest) = parse_str_body(rest)?;
+        return Some((ValueObj::Str(Str::from(string)), rest));
+    }
+    if let Some(rest) = s.strip_prefix("b\"") {
+        let (bytes, rest) = parse_bytes_body(rest)?;
+        return Some((ValueObj::Bytes(bytes.into()), rest));
+    }
+    if let Some(mut rest) = s.strip_prefix('(') {
+        let mut elems = vec![];
+        loop {
+            rest = rest.trim_start();
+            if let Some(rest) = rest.strip_prefix(')') {
+                return Some((ValueObj::Array(elems.into()), rest));
+            }
+            let (elem, r) = parse_literal(rest)?;
+            elems.push(elem);
+            rest = r.trim_start();
+            if let Some(r) = rest.strip_prefix(',') {
+                rest = r;
+            } else if !rest.starts_with(')') {
+                return None;
+            }
+        }
+    }
+    let end = s
+        .find(|c: char| c == ',' || c == ')' || c.is_whitespace())
+        .unwrap_or(s.len());
+    let (token, rest) = s.split_at(end);
+    let obj = match token {
+        "True" => ValueObj::Bool(true),
+        "False" => ValueObj::Bool(false),
+        "None" => ValueObj::None,
+        "..." => ValueObj::Ellipsis,
+        _ => {
+            if let Ok(i) = token.parse::<i32>() {
+                ValueObj::Int(i)
+            } else if let Ok(n) = token.parse::<u64>() {
+                ValueObj::Nat(n)
+            } else {
+                ValueObj::Float(token.parse::<f64>().ok()?)
+            }
+        }
+    };
+    Some((obj, rest))
+}
+
+/// Parses the rest of a string literal (after `"`) escaped by `{:?}`.
+fn parse_str_body(s: &str) -> Option<(String, &str)> {
+    let mut string = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((string, &s[i + 1..])),
+            '\\' => {
+                let c = match chars.next()?.1 {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '0' => '\0',
+                    'u' => {
+                        let (start, _) = chars.next().filter(|(_, c)| *c == '{')?;
+                        let end = s[start..].find('}')? + start;
+                        let code = u32::from_str_radix(&s[start + 1..end], 16).ok()?;
+                        while chars.next()?.0 < end {}
+                        char::from_u32(code)?
+                    }
+                    c => c,
+                };
+                string.push(c);
+            }
+            c => string.push(c),
+        }
+    }
+    None
+}
+
+/// Parses the rest of a bytes literal (after `b"`) escaped by `escape_ascii`.
+fn parse_bytes_body(s: &str) -> Option<(Vec<u8>, &str)> {
+    let mut bytes = vec![];
+    let mut iter = s.bytes().enumerate();
+    while let Some((i, b)) = iter.next() {
+        match b {
+            b'"' => return Some((bytes, &s[i + 1..])),
+            b'\\' => {
+                let b = match iter.next()?.1 {
+                    b'n' => b'\n',
+                    b't' => b'\t',
+                    b'r' => b'\r',
+                    b'x' => {
+                        let (h, l) = (iter.next()?.0, iter.next()?.0);
+                        u8::from_str_radix(s.get(h..=l)?, 16).ok()?
+                    }
+                    b => b,
+                };
+                bytes.push(b);
+            }
+            b => bytes.push(b),
+        }
+    }
+    None
+}
+
+/// Strips a comment (`; ...`) outside string literals.
+fn strip_comment(line: &str) -> &str {
+    let mut in_str = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_str => escaped = true,
+            '"' => in_str = !in_str,
+            ';' if !in_str => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    None,
+    Consts,
+    Names,
+    Varnames,
+    Freevars,
+    Cellvars,
+    Instructions,
+}
+
+#[derive(Debug, Clone)]
+enum AsmArg {
+    Num(usize),
+    Label(String),
+}
+
+/// Assembles a listing generated by `disassemble` into a .pyc file.
+pub struct Assembler {
+    lines: Vec<(usize, String)>,
+    pos: usize,
+    python_ver: PythonVersion,
+}
+
+impl Assembler {
+    pub fn new(src: &str) -> Self {
+        let lines = src
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, strip_comment(line).trim().to_string()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+        Self {
+            lines,
+            pos: 0,
+            python_ver: PythonVersion::default(),
+        }
+    }
+
+    pub fn run(mut cfg: ErgConfig) -> ExitStatus {
+        let src = cfg.input.read();
+        let path = cfg.dump_pyc_path();
+        match Self::assemble(&src) {
+            Ok(bytes) => {
+                if let Err(e) = std::fs::write(&path, bytes) {
+                    eprintln!("failed to write {}: {e}", path.display());
+                    return ExitStatus::ERR1;
+                }
+                ExitStatus::OK
+            }
+            Err(e) => {
+                eprintln!(
+                    "failed to assemble {}: {}",
+                    cfg.input.unescaped_filename(),
+                    e.desc
+                );
+                ExitStatus::ERR1
+            }
+        }
+    }
+
+    pub fn assemble(src: &str) -> DeserializeResult<Vec<u8>> {
+        let mut asm = Self::new(src);
+        let (header, code) = asm.parse_pyc()?;
+        Ok([header.into_bytes(), code.into_bytes(asm.python_ver)].concat())
+    }
+
+    fn next_line(&mut self) -> Option<(usize, String)> {
+        let line = self.lines.get(self.pos).cloned();
+        self.pos += 1;
+        line
+    }
+
+    fn eof_error(&self) -> DeserializeError {
+        let lineno = self.lines.last().map_or(0, |(lineno, _)| *lineno);
+        asm_syntax_error(lineno, "<EOF>")
+    }
+
+    fn parse_pyc(&mut self) -> DeserializeResult<(PycHeader, CodeObj)> {
+        let mut header = PycHeader::new(0, PycInvalidationMode::Timestamp, [0; 8]);
+        while let Some((lineno, line)) = self.next_line() {
+            let (directive, value) = line.split_once(' ').unwrap_or((&line, ""));
+            let value = value.trim();
+            match directive {
+                ".magic" => {
+                    header.magic_num =
+                        parse_u32(value).ok_or_else(|| asm_syntax_error(lineno, &line))?;
+                    self.python_ver = header.python_version();
+                }
+                ".mode" => {
+                    header.mode = PycInvalidationMode::try_from(value)
+                        .map_err(|_| asm_syntax_error(lineno, &line))?;
+                }
+                ".validation" => {
+                    let bytes = parse_hex(value).filter(|bytes| bytes.len() == 8);
+                    let bytes = bytes.ok_or_else(|| asm_syntax_error(lineno, &line))?;
+                    header.validation.copy_from_slice(&bytes);
+                }
+                ".code" if header.magic_num != 0 => {
+                    let code = self.parse_code()?;
+                    if let Some((lineno, line)) = self.next_line() {
+                        return Err(asm_syntax_error(lineno, &line));
+                    }
+                    return Ok((header, code));
+                }
+                _ => return Err(asm_syntax_error(lineno, &line)),
+            }
+        }
+        Err(self.eof_error())
+    }
+
+    fn parse_code(&mut self) -> DeserializeResult<CodeObj> {
+        let mut code = CodeObj::default();
+        let mut section = Section::None;
+        let mut instrs = vec![];
+        let mut labels = Dict::new();
+        while let Some((lineno, line)) = self.next_line() {
+            let (directive, value) = line.split_once(' ').unwrap_or((&line, ""));
+            let value = value.trim();
+            let syntax_error = || asm_syntax_error(lineno, &line);
+            let parse_str = |s: &str| {
+                let (s, rest) = parse_str_body(s.strip_prefix('"')?)?;
+                rest.trim().is_empty().then(|| Str::from(s))
+            };
+            match directive {
+                ".end" => {
+                    code.code = self.encode_instrs(instrs, &labels)?;
+                    return Ok(code);
+                }
+                ".code" if section == Section::Consts => {
+                    let inner = self.parse_code()?;
+                    code.consts.push(ValueObj::from(inner));
+                }
+                ".name" => code.name = parse_str(value).ok_or_else(syntax_error)?,
+                ".qualname" => code.qualname = parse_str(value).ok_or_else(syntax_error)?,
+                ".filename" => code.filename = parse_str(value).ok_or_else(syntax_error)?,
+                ".firstlineno" => code.firstlineno = parse_u32(value).ok_or_else(syntax_error)?,
+                ".argcount" => code.argcount = parse_u32(value).ok_or_else(syntax_error)?,
+                ".posonlyargcount" => {
+                    code.posonlyargcount = parse_u32(value).ok_or_else(syntax_error)?
+                }
+                ".kwonlyargcount" => {
+                    code.kwonlyargcount = parse_u32(value).ok_or_else(syntax_error)?
+                }
+                ".nlocals" => code.nlocals = parse_u32(value).ok_or_else(syntax_error)?,
+                ".stacksize" => code.stacksize = parse_u32(value).ok_or_else(syntax_error)?,
+                ".flags" => code.flags = parse_u32(value).ok_or_else(syntax_error)?,
+                ".localspluskinds" => {
+                    code.localspluskinds = parse_hex(value).ok_or_else(syntax_error)?
+                }
+                ".linetable" => code.lnotab = parse_hex(value).ok_or_else(syntax_error)?,
+                ".exceptiontable" => {
+                    code.exceptiontable = parse_hex(value).ok_or_else(syntax_error)?
+                }
+                ".consts" => section = Section::Consts,
+                ".names" => section = Section::Names,
+                ".varnames" => section = Section::Varnames,
+                ".freevars" => section = Section::Freevars,
+                ".cellvars" => section = Section::Cellvars,
+                ".instructions" => section = Section::Instructions,
+                _ if directive.starts_with('.') => return Err(syntax_error()),
+                _ => match section {
+                    Section::Consts => {
+                        let (obj, rest) = parse_literal(&line).ok_or_else(syntax_error)?;
+                        if !rest.trim().is_empty() {
+                            return Err(syntax_error());
+                        }
+                        code.consts.push(obj);
+                    }
+                    Section::Names => code.names.push(parse_str(&line).ok_or_else(syntax_error)?),
+                    Section::Varnames => code
+                        .varnames
+                        .push(parse_str(&line).ok_or_else(syntax_error)?),
+                    Section::Freevars => code
+                        .freevars
+                        .push(parse_str(&line).ok_or_else(syntax_error)?),
+                    Section::Cellvars => code
+                        .cellvars
+                        .push(parse_str(&line).ok_or_else(syntax_error)?),
+                    Section::Instructions => {
+                        if let Some(label) = line.strip_suffix(':') {
+                            labels.insert(label.to_string(), instrs.len());
+                        } else {
+                            instrs.push((lineno, self.parse_instr(lineno, &line)?));
+                        }
+                    }
+                    Section::None => return Err(syntax_error()),
+                },
+            }
+        }
+        Err(self.eof_error())
+    }
+
+    fn parse_instr(&self, lineno: usize, line: &str) -> DeserializeResult<(u8, AsmArg)> {
+        let mut tokens = line.split_whitespace().peekable();
+        // offset (ignored)
+        tokens.next_if(|token| token.chars().all(|c| c.is_ascii_digit()));
+        let name = tokens
+            .next()
+            .ok_or_else(|| asm_syntax_error(lineno, line))?;
+        let op = opcode_from_name(self.python_ver, name)
+            .ok_or_else(|| asm_unknown_opcode_error(lineno, name))?;
+        let arg = match tokens.next() {
+            Some(label) if label.starts_with('L') => AsmArg::Label(label.to_string()),
+            Some(arg) => AsmArg::Num(arg.parse().map_err(|_| asm_syntax_error(lineno, line))?),
+            None => AsmArg::Num(0),
+        };
+        if tokens.next().is_some() {
+            return Err(asm_syntax_error(lineno, line));
+        }
+        Ok((op, arg))
+    }
+
+    fn encode_instrs(
+        &self,
+        instrs: Vec<(usize, (u8, AsmArg))>,
+        labels: &Dict<String, usize>,
+    ) -> DeserializeResult<Vec<u8>> {
+        let mut code = Vec::with_capacity(instrs.len() * 2);
+        for (i, (lineno, (op, arg))) in instrs.into_iter().enumerate() {
+            let offset = i * 2;
+            let arg = match arg {
+                AsmArg::Num(n) => n,
+                AsmArg::Label(label) => {
+                    let target = labels
+                        .get(&label)
+                        .ok_or_else(|| asm_undefined_label_error(lineno, &label))?;
+                    jump_arg(self.python_ver, op, target * 2, offset)
+                        .ok_or_else(|| asm_invalid_arg_error(lineno))?
+                }
+            };
+            code.push(op);
+            code.push((arg & 0xff) as u8);
+            if op == EXTENDED_ARG {
+                continue;
+            }
+            // fill the preceding `EXTENDED_ARG`s with the upper bytes
+            let mut rest = arg >> 8;
+            let mut idx = offset;
+            while idx >= 2 && code[idx - 2] == EXTENDED_ARG {
+                idx -= 2;
+                code[idx + 1] = (rest & 0xff) as u8;
+                rest >>= 8;
+            }
+            if rest != 0 {
+                return Err(asm_invalid_arg_error(lineno));
+            }
+        }
+        Ok(code)
+    }
+}
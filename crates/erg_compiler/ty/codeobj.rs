@@ -156,6 +156,9 @@ pub struct CodeObj {
     // if delta > 255 -> [255, 0, 255-delta, ...]
     pub lnotab: Vec<u8>,
     pub exceptiontable: Vec<u8>,
+    // Python 3.11+ only: kinds of `varnames` (as `co_localsplusnames`), see `FastKind`
+    // if empty, `varnames`, `freevars` and `cellvars` are laid out by `dump_locals`
+    pub localspluskinds: Vec<u8>,
 }
 
 impl HasType for CodeObj {
@@ -206,6 +209,7 @@ impl Default for CodeObj {
             firstlineno: 1,
             lnotab: Vec::new(),
             exceptiontable: Vec::new(),
+            localspluskinds: Vec::new(),
         }
     }
 }
@@ -220,8 +224,9 @@ impl CodeObj {
     ) -> Self {
         let name = name.into();
         let var_args_defined = (flags & CodeObjFlags::VarArgs as u32 != 0) as u32;
+        let kw_var_args_defined = (flags & CodeObjFlags::VarKeywords as u32 != 0) as u32;
         Self {
-            argcount: params.len() as u32 - var_args_defined,
+            argcount: params.len() as u32 - var_args_defined - kw_var_args_defined,
             posonlyargcount: 0,
             kwonlyargcount: 0,
             nlocals: params.len() as u32,
@@ -239,6 +244,7 @@ impl CodeObj {
             firstlineno,
             lnotab: Vec::with_capacity(4),
             exceptiontable: Vec::with_capacity(0),
+            localspluskinds: Vec::new(),
         }
     }
 
@@ -246,68 +252,13 @@ impl CodeObj {
         let mut f = BufReader::new(File::open(path)?);
         let v = &mut Vec::with_capacity(16);
         f.read_to_end(v)?;
-        let magic_num = get_magic_num_from_bytes(&Deserializer::consume::<4>(v));
-        let python_ver = get_ver_from_magic_num(magic_num);
-        let _padding = Deserializer::deserialize_u32(v);
-        let _timestamp = Deserializer::deserialize_u32(v);
-        let _padding = Deserializer::deserialize_u32(v);
-        let code = Self::from_bytes(v, python_ver)?;
+        let (_header, code) = Deserializer::new().deserialize_pyc(v)?;
         Ok(code)
     }
 
+    /// `v` does not include the prefix (`DataTypePrefix::Code`)
     pub fn from_bytes(v: &mut Vec<u8>, python_ver: PythonVersion) -> DeserializeResult<Self> {
-        let mut des = Deserializer::new();
-        let argcount = Deserializer::deserialize_u32(v);
-        let posonlyargcount = if python_ver.minor >= Some(8) {
-            Deserializer::deserialize_u32(v)
-        } else {
-            0
-        };
-        let kwonlyargcount = Deserializer::deserialize_u32(v);
-        let nlocals = if python_ver.minor >= Some(11) {
-            0
-        } else {
-            Deserializer::deserialize_u32(v)
-        };
-        let stacksize = Deserializer::deserialize_u32(v);
-        let flags = Deserializer::deserialize_u32(v);
-        let code = des.deserialize_bytes(v)?;
-        let consts = des.deserialize_const_vec(v, python_ver)?;
-        let names = des.deserialize_str_vec(v, python_ver)?;
-        // TODO: localplusnames
-        let varnames = des.deserialize_str_vec(v, python_ver)?;
-        let freevars = des.deserialize_str_vec(v, python_ver)?;
-        let cellvars = des.deserialize_str_vec(v, python_ver)?;
-        let filename = des.deserialize_str(v, python_ver)?;
-        let name = des.deserialize_str(v, python_ver)?;
-        let qualname = des.deserialize_str(v, python_ver)?;
-        let firstlineno = Deserializer::deserialize_u32(v);
-        let lnotab = des.deserialize_bytes(v)?;
-        let exceptiontable = if python_ver.minor >= Some(11) {
-            des.deserialize_bytes(v)?
-        } else {
-            vec![]
-        };
-        Ok(CodeObj {
-            argcount,
-            posonlyargcount,
-            kwonlyargcount,
-            nlocals,
-            stacksize,
-            flags,
-            code,
-            consts,
-            names,
-            varnames,
-            freevars,
-            cellvars,
-            filename,
-            name,
-            qualname,
-            firstlineno,
-            lnotab,
-            exceptiontable,
-        })
+        Deserializer::new().deserialize_code(v, python_ver)
     }
 
     pub fn into_bytes(self, python_ver: PythonVersion) -> Vec<u8> {
@@ -330,6 +281,7 @@ impl CodeObj {
             self.varnames,
             self.freevars,
             self.cellvars,
+            self.localspluskinds,
             &mut bytes,
             python_ver,
         );
@@ -347,25 +299,50 @@ impl CodeObj {
         bytes
     }
 
+    /// Returns `co_localsplusnames` and `co_localspluskinds` (Python 3.11+).
+    pub fn localsplus(&self) -> (Vec<Str>, Vec<u8>) {
+        Self::layout_localsplus(
+            self.varnames.clone(),
+            self.freevars.clone(),
+            self.cellvars.clone(),
+            self.localspluskinds.clone(),
+        )
+    }
+
+    fn layout_localsplus(
+        varnames: Vec<Str>,
+        freevars: Vec<Str>,
+        cellvars: Vec<Str>,
+        localspluskinds: Vec<u8>,
+    ) -> (Vec<Str>, Vec<u8>) {
+        if !localspluskinds.is_empty() {
+            return (varnames, localspluskinds);
+        }
+        let varnames = varnames
+            .into_iter()
+            .filter(|n| !freevars.contains(n) && !cellvars.contains(n))
+            .collect::<Vec<_>>();
+        let localspluskinds = [
+            vec![FastKind::Local as u8; varnames.len()],
+            vec![FastKind::Free as u8; freevars.len()],
+            vec![FastKind::Cell as u8 + FastKind::Local as u8; cellvars.len()],
+        ]
+        .concat();
+        let localsplusnames = [varnames, freevars, cellvars].concat();
+        (localsplusnames, localspluskinds)
+    }
+
     fn dump_locals(
         varnames: Vec<Str>,
         freevars: Vec<Str>,
         cellvars: Vec<Str>,
+        localspluskinds: Vec<u8>,
         bytes: &mut Vec<u8>,
         python_ver: PythonVersion,
     ) {
         if python_ver.minor >= Some(11) {
-            let varnames = varnames
-                .into_iter()
-                .filter(|n| !freevars.contains(n) && !cellvars.contains(n))
-                .collect::<Vec<_>>();
-            let localspluskinds = [
-                vec![FastKind::Local as u8; varnames.len()],
-                vec![FastKind::Free as u8; freevars.len()],
-                vec![FastKind::Cell as u8 + FastKind::Local as u8; cellvars.len()],
-            ]
-            .concat();
-            let localsplusnames = [varnames, freevars, cellvars].concat();
+            let (localsplusnames, localspluskinds) =
+                Self::layout_localsplus(varnames, freevars, cellvars, localspluskinds);
             bytes.append(&mut strs_into_bytes(localsplusnames));
             bytes.append(&mut raw_string_into_bytes(localspluskinds));
         } else {
@@ -375,19 +352,21 @@ impl CodeObj {
         }
     }
 
+    /// `src` is the source code, which is used to generate the header (size or hash).
+    /// The output is deterministic unless `mode` is `PycInvalidationMode::Timestamp`.
     pub fn dump_as_pyc<P: AsRef<Path>>(
         self,
         path: P,
+        src: &str,
         py_magic_num: Option<u32>,
+        mode: PycInvalidationMode,
     ) -> std::io::Result<()> {
         let mut file = File::create(path)?;
         let mut bytes = Vec::with_capacity(16);
         let py_magic_num = py_magic_num.unwrap_or_else(env_magic_number);
         let python_ver = get_ver_from_magic_num(py_magic_num);
         bytes.append(&mut get_magic_num_bytes(py_magic_num).to_vec());
-        bytes.append(&mut vec![0; 4]); // padding
-        bytes.append(&mut get_timestamp_bytes().to_vec());
-        bytes.append(&mut vec![0; 4]); // padding
+        bytes.append(&mut get_pyc_header_bytes(py_magic_num, src.as_bytes(), mode).to_vec());
         bytes.append(&mut self.into_bytes(python_ver));
         file.write_all(&bytes[..])?;
         Ok(())
@@ -13,23 +13,36 @@ use super::{Predicate, Type};
 
 use crate::context::Context;
 
+/// A user-defined const subroutine can be defined by cases.
+/// The first case whose parameters match the arguments is applied.
+/// e.g. `List T, 0 = Nil T` and `List T, N = Cons T, N`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct UserConstSubr {
     name: Str,
-    params: Params,
-    block: ConstBlock,
+    cases: Vec<(Params, ConstBlock)>,
     sig_t: Type,
 }
 
 impl UserConstSubr {
-    pub const fn new(name: Str, params: Params, block: ConstBlock, sig_t: Type) -> Self {
+    pub fn new(name: Str, params: Params, block: ConstBlock, sig_t: Type) -> Self {
         Self {
             name,
-            params,
-            block,
+            cases: vec![(params, block)],
             sig_t,
         }
     }
+
+    pub fn name(&self) -> &Str {
+        &self.name
+    }
+
+    pub fn cases(&self) -> &[(Params, ConstBlock)] {
+        &self.cases
+    }
+
+    pub fn push_case(&mut self, params: Params, block: ConstBlock) {
+        self.cases.push((params, block));
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -162,6 +175,7 @@ impl ConstSubr {
                             subr.non_default_params.clone(),
                             var_params.cloned(),
                             subr.default_params.clone(),
+                            subr.kw_var_params.as_deref().cloned(),
                             return_t,
                         );
                         return Some(subr_t);
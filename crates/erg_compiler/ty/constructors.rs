@@ -1,5 +1,7 @@
 use std::convert::TryInto;
 
+use erg_common::dict;
+
 use crate::ty::*;
 
 #[inline]
@@ -68,6 +70,13 @@ pub fn dict_t(dict: TyParam) -> Type {
     poly("Dict", vec![dict])
 }
 
+/// `{Str: T}`, the type of `**kwargs: T`
+pub fn str_dict_t(value: Type) -> Type {
+    dict_t(TyParam::Dict(
+        dict! { TyParam::t(Type::Str) => TyParam::t(value) },
+    ))
+}
+
 #[inline]
 pub fn range(t: Type) -> Type {
     poly("Range", vec![TyParam::t(t)])
@@ -181,6 +190,7 @@ pub fn subr_t(
     non_default_params: Vec<ParamTy>,
     var_params: Option<ParamTy>,
     default_params: Vec<ParamTy>,
+    kw_var_params: Option<ParamTy>,
     return_t: Type,
 ) -> Type {
     Type::Subr(SubrType::new(
@@ -188,6 +198,7 @@ pub fn subr_t(
         non_default_params,
         var_params,
         default_params,
+        kw_var_params,
         return_t,
     ))
 }
@@ -203,6 +214,7 @@ pub fn func(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
@@ -250,6 +262,7 @@ pub fn proc(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
@@ -284,6 +297,7 @@ pub fn fn_met(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
@@ -313,6 +327,7 @@ pub fn pr_met(
         non_default_params,
         var_params,
         default_params,
+        None,
         return_t,
     ))
 }
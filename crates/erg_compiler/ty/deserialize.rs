@@ -6,12 +6,16 @@ use erg_common::config::ErgConfig;
 use erg_common::dict::Dict;
 use erg_common::error::{ErrorCore, ErrorKind, Location, SubMessage};
 use erg_common::python_util::PythonVersion;
-use erg_common::serialize::DataTypePrefix;
+use erg_common::serialize::{
+    get_magic_num_bytes, get_magic_num_from_bytes, get_ver_from_magic_num, DataTypePrefix,
+    PycInvalidationMode, FLAG_REF,
+};
 use erg_common::traits::ExitStatus;
 use erg_common::{fn_name, switch_lang};
 use erg_common::{RcArray, Str};
 
-use super::codeobj::CodeObj;
+use super::asm::disassemble;
+use super::codeobj::{CodeObj, FastKind};
 use super::constructors::array_t;
 use super::typaram::TyParam;
 use super::value::ValueObj;
@@ -90,15 +94,68 @@ impl DeserializeError {
             ),
         )
     }
+
+    pub fn invalid_pyc_flags_error(flags: u32) -> Self {
+        Self::new(
+            0,
+            fn_name!(),
+            switch_lang!(
+                "japanese" => format!("不正な.pycヘッダーのフラグです: {flags:#b}"),
+                "simplified_chinese" => format!("无效的.pyc头标志: {flags:#b}"),
+                "traditional_chinese" => format!("無效的.pyc頭標誌: {flags:#b}"),
+                "english" => format!("invalid .pyc header flags: {flags:#b}"),
+            ),
+        )
+    }
 }
 
 pub type DeserializeResult<T> = Result<T, DeserializeError>;
 
+/// The first 16 bytes of a .pyc file (PEP 552)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PycHeader {
+    pub magic_num: u32,
+    pub mode: PycInvalidationMode,
+    /// timestamp + source size, or source hash
+    pub validation: [u8; 8],
+}
+
+impl PycHeader {
+    pub const fn new(magic_num: u32, mode: PycInvalidationMode, validation: [u8; 8]) -> Self {
+        Self {
+            magic_num,
+            mode,
+            validation,
+        }
+    }
+
+    pub const fn python_version(&self) -> PythonVersion {
+        get_ver_from_magic_num(self.magic_num)
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        [
+            get_magic_num_bytes(self.magic_num).to_vec(),
+            self.mode.flags().to_le_bytes().to_vec(),
+            self.validation.to_vec(),
+        ]
+        .concat()
+    }
+}
+
+/// An entry of the reference table (see `FLAG_REF`)
+#[derive(Debug, Clone)]
+enum RefObj {
+    Value(ValueObj),
+    Bytes(Vec<u8>),
+}
+
 #[derive(Default)]
 pub struct Deserializer {
     str_cache: CacheSet<str>,
     arr_cache: CacheSet<[ValueObj]>,
     _dict_cache: CacheSet<Dict<ValueObj, ValueObj>>,
+    refs: Vec<RefObj>,
 }
 
 impl Deserializer {
@@ -107,6 +164,7 @@ impl Deserializer {
             str_cache: CacheSet::new(),
             arr_cache: CacheSet::new(),
             _dict_cache: CacheSet::new(),
+            refs: vec![],
         }
     }
 
@@ -115,9 +173,12 @@ impl Deserializer {
             eprintln!("{:?} is not a filename", cfg.input);
             return ExitStatus::ERR1;
         };
-        match CodeObj::from_pyc(filename) {
-            Ok(codeobj) => {
-                println!("{}", codeobj.code_info(None));
+        let res = std::fs::read(filename)
+            .map_err(DeserializeError::from)
+            .and_then(|mut v| Self::new().deserialize_pyc(&mut v));
+        match res {
+            Ok((header, codeobj)) => {
+                print!("{}", disassemble(&header, &codeobj));
                 ExitStatus::OK
             }
             Err(e) => {
@@ -155,39 +216,132 @@ impl Deserializer {
         u32::from_le_bytes(Self::consume::<4>(v))
     }
 
+    /// Consumes the header of a .pyc file (PEP 552).
+    /// The contents of the validation field (timestamp + size or source hash) are not checked.
+    pub fn deserialize_pyc_header(v: &mut Vec<u8>) -> DeserializeResult<PycHeader> {
+        if v.len() < 16 {
+            return Err(DeserializeError::file_broken_error());
+        }
+        let magic_num = get_magic_num_from_bytes(&Self::consume::<4>(v));
+        let flags = Self::deserialize_u32(v);
+        let mode = PycInvalidationMode::from_flags(flags)
+            .ok_or_else(|| DeserializeError::invalid_pyc_flags_error(flags))?;
+        let validation = Self::consume::<8>(v);
+        Ok(PycHeader::new(magic_num, mode, validation))
+    }
+
+    pub fn deserialize_pyc(&mut self, v: &mut Vec<u8>) -> DeserializeResult<(PycHeader, CodeObj)> {
+        let header = Self::deserialize_pyc_header(v)?;
+        match self.deserialize_const(v, header.python_version())? {
+            ValueObj::Code(code) => Ok((header, *code)),
+            other => Err(DeserializeError::type_error(&Type::Code, other.ref_t())),
+        }
+    }
+
+    /// returns the prefix and whether the object should be registered in the reference table
+    fn deserialize_prefix(v: &mut Vec<u8>) -> DeserializeResult<(DataTypePrefix, bool)> {
+        if v.is_empty() {
+            return Err(DeserializeError::file_broken_error());
+        }
+        let byte = v.remove(0);
+        Ok((DataTypePrefix::from(byte & !FLAG_REF), byte & FLAG_REF != 0))
+    }
+
+    fn deserialize_ref(&self, v: &mut Vec<u8>) -> DeserializeResult<RefObj> {
+        let idx = Self::deserialize_u32(v) as usize;
+        self.refs
+            .get(idx)
+            .cloned()
+            .ok_or_else(DeserializeError::file_broken_error)
+    }
+
+    /// Python's `long` is represented as 15-bit digits (little endian)
+    /// Only the values that fit in `Int` (i32) or `Nat` (u64) can be restored.
+    fn deserialize_long(v: &mut Vec<u8>) -> DeserializeResult<ValueObj> {
+        let len = Self::deserialize_u32(v) as i32;
+        let mut n = 0u128;
+        let mut overflow = false;
+        for i in 0..len.unsigned_abs() {
+            let digit = u16::from_le_bytes(Self::consume::<2>(v)) as u128;
+            if i < 5 {
+                n |= digit << (15 * i);
+            } else {
+                overflow |= digit != 0;
+            }
+        }
+        let obj = if overflow {
+            None
+        } else if len < 0 {
+            i32::try_from(-(n as i128)).ok().map(ValueObj::Int)
+        } else if let Ok(i) = i32::try_from(n) {
+            Some(ValueObj::Int(i))
+        } else {
+            u64::try_from(n).ok().map(ValueObj::Nat)
+        };
+        obj.ok_or_else(|| Self::cannot_deserialize_error(DataTypePrefix::Long))
+    }
+
+    fn cannot_deserialize_error(prefix: DataTypePrefix) -> DeserializeError {
+        DeserializeError::new(
+            0,
+            fn_name!(),
+            switch_lang!(
+                "japanese" => format!("このオブジェクトは復元できません: {prefix}"),
+                "simplified_chinese" => format!("无法反序列化此对象: {prefix}"),
+                "traditional_chinese" => format!("無法反序列化此對象: {prefix}"),
+                "english" => format!("cannot deserialize this object: {prefix}"),
+            ),
+        )
+    }
+
     pub fn deserialize_const(
         &mut self,
         v: &mut Vec<u8>,
         python_ver: PythonVersion,
     ) -> DeserializeResult<ValueObj> {
-        match DataTypePrefix::from(v.remove(0)) {
+        let (prefix, flagged) = Self::deserialize_prefix(v)?;
+        // the index is reserved before the contents are read, as CPython does
+        let ref_idx = flagged.then(|| {
+            self.refs.push(RefObj::Value(ValueObj::None));
+            self.refs.len() - 1
+        });
+        let obj = match prefix {
             DataTypePrefix::Int32 => {
                 let bytes = Self::consume::<4>(v);
-                Ok(ValueObj::Int(i32::from_le_bytes(bytes)))
+                ValueObj::Int(i32::from_le_bytes(bytes))
             }
+            DataTypePrefix::Long => Self::deserialize_long(v)?,
             DataTypePrefix::BinFloat => {
                 let bytes = Self::consume::<8>(v);
-                Ok(ValueObj::Float(f64::from_le_bytes(bytes)))
+                ValueObj::Float(f64::from_le_bytes(bytes))
             }
             DataTypePrefix::ShortAscii | DataTypePrefix::ShortAsciiInterned => {
                 let len = v.remove(0);
                 let bytes = v.drain(..len as usize).collect();
-                Ok(self.get_cached_str(&String::from_utf8(bytes)?))
+                self.get_cached_str(&String::from_utf8(bytes)?)
             }
-            DataTypePrefix::Str | DataTypePrefix::Unicode => {
+            // `s` is `bytes` in Python
+            DataTypePrefix::Str => {
+                let len = Self::deserialize_u32(v);
+                ValueObj::Bytes(v.drain(..len as usize).collect())
+            }
+            DataTypePrefix::Unicode
+            | DataTypePrefix::Interned
+            | DataTypePrefix::Ascii
+            | DataTypePrefix::AsciiInterned => {
                 let len = Self::deserialize_u32(v);
                 let bytes = v.drain(..len as usize).collect();
-                Ok(self.get_cached_str(&String::from_utf8(bytes)?))
+                self.get_cached_str(&String::from_utf8(bytes)?)
             }
-            DataTypePrefix::True => Ok(ValueObj::Bool(true)),
-            DataTypePrefix::False => Ok(ValueObj::Bool(false)),
+            DataTypePrefix::True => ValueObj::Bool(true),
+            DataTypePrefix::False => ValueObj::Bool(false),
             DataTypePrefix::SmallTuple => {
                 let len = v.remove(0);
                 let mut arr = Vec::with_capacity(len as usize);
                 for _ in 0..len {
                     arr.push(self.deserialize_const(v, python_ver)?);
                 }
-                Ok(self.get_cached_arr(&arr))
+                self.get_cached_arr(&arr)
             }
             DataTypePrefix::Tuple => {
                 let len = Self::deserialize_u32(v);
@@ -195,76 +349,108 @@ impl Deserializer {
                 for _ in 0..len {
                     arr.push(self.deserialize_const(v, python_ver)?);
                 }
-                Ok(self.get_cached_arr(&arr))
+                self.get_cached_arr(&arr)
             }
-            DataTypePrefix::Code => {
-                let argcount = Self::deserialize_u32(v);
-                let posonlyargcount = if python_ver.minor >= Some(8) {
-                    Self::deserialize_u32(v)
-                } else {
-                    0
-                };
-                let kwonlyargcount = Self::deserialize_u32(v);
-                let nlocals = if python_ver.minor < Some(11) {
-                    Self::deserialize_u32(v)
-                } else {
-                    0
-                };
-                let stacksize = Self::deserialize_u32(v);
-                let flags = Self::deserialize_u32(v);
-                let code = self.deserialize_bytes(v)?;
-                let consts = self.deserialize_const_vec(v, python_ver)?;
-                let names = self.deserialize_str_vec(v, python_ver)?;
-                let varnames = self.deserialize_str_vec(v, python_ver)?;
-                let freevars = self.deserialize_str_vec(v, python_ver)?;
-                let cellvars = self.deserialize_str_vec(v, python_ver)?;
-                let filename = self.deserialize_str(v, python_ver)?;
-                let name = self.deserialize_str(v, python_ver)?;
-                let qualname = if python_ver.minor >= Some(11) {
-                    self.deserialize_str(v, python_ver)?
-                } else {
-                    name.clone()
-                };
-                let firstlineno = Self::deserialize_u32(v);
-                let lnotab = self.deserialize_bytes(v)?;
-                let exceptiontable = if python_ver.minor >= Some(11) {
-                    self.deserialize_bytes(v)?
-                } else {
-                    vec![]
-                };
-                Ok(ValueObj::from(CodeObj {
-                    argcount,
-                    posonlyargcount,
-                    kwonlyargcount,
-                    nlocals,
-                    stacksize,
-                    flags,
-                    code,
-                    consts,
-                    names,
-                    varnames,
-                    freevars,
-                    cellvars,
-                    filename,
-                    name,
-                    qualname,
-                    firstlineno,
-                    lnotab,
-                    exceptiontable,
-                }))
-            }
-            DataTypePrefix::None => Ok(ValueObj::None),
-            other => Err(DeserializeError::new(
-                0,
-                fn_name!(),
-                switch_lang!(
-                    "japanese" => format!("このオブジェクトは復元できません: {other}"),
-                    "simplified_chinese" => format!("无法反序列化此对象: {other}"),
-                    "traditional_chinese" => format!("無法反序列化此對象: {other}"),
-                    "english" => format!("cannot deserialize this object: {other}"),
-                ),
-            )),
+            DataTypePrefix::Code => ValueObj::from(self.deserialize_code(v, python_ver)?),
+            DataTypePrefix::None => ValueObj::None,
+            DataTypePrefix::Ellipsis => ValueObj::Ellipsis,
+            DataTypePrefix::Ref => match self.deserialize_ref(v)? {
+                RefObj::Value(obj) => obj,
+                RefObj::Bytes(bytes) => ValueObj::Bytes(bytes.into()),
+            },
+            other => return Err(Self::cannot_deserialize_error(other)),
+        };
+        if let Some(idx) = ref_idx {
+            self.refs[idx] = RefObj::Value(obj.clone());
         }
+        Ok(obj)
+    }
+
+    /// `v` does not include the prefix (`DataTypePrefix::Code`)
+    pub fn deserialize_code(
+        &mut self,
+        v: &mut Vec<u8>,
+        python_ver: PythonVersion,
+    ) -> DeserializeResult<CodeObj> {
+        let argcount = Self::deserialize_u32(v);
+        let posonlyargcount = if python_ver.minor >= Some(8) {
+            Self::deserialize_u32(v)
+        } else {
+            0
+        };
+        let kwonlyargcount = Self::deserialize_u32(v);
+        let nlocals = if python_ver.minor < Some(11) {
+            Self::deserialize_u32(v)
+        } else {
+            0
+        };
+        let stacksize = Self::deserialize_u32(v);
+        let flags = Self::deserialize_u32(v);
+        let code = self.deserialize_bytes(v)?;
+        let consts = self.deserialize_const_vec(v, python_ver)?;
+        let names = self.deserialize_str_vec(v, python_ver)?;
+        let (varnames, freevars, cellvars, localspluskinds) = if python_ver.minor >= Some(11) {
+            let localsplusnames = self.deserialize_str_vec(v, python_ver)?;
+            let localspluskinds = self.deserialize_bytes(v)?;
+            let filter_by = |kind: FastKind| {
+                localsplusnames
+                    .iter()
+                    .zip(localspluskinds.iter())
+                    .filter(|(_, k)| *k & kind as u8 != 0)
+                    .map(|(name, _)| name.clone())
+                    .collect::<Vec<_>>()
+            };
+            let (freevars, cellvars) = (filter_by(FastKind::Free), filter_by(FastKind::Cell));
+            (localsplusnames, freevars, cellvars, localspluskinds)
+        } else {
+            let varnames = self.deserialize_str_vec(v, python_ver)?;
+            let freevars = self.deserialize_str_vec(v, python_ver)?;
+            let cellvars = self.deserialize_str_vec(v, python_ver)?;
+            (varnames, freevars, cellvars, vec![])
+        };
+        let nlocals = if python_ver.minor >= Some(11) {
+            localspluskinds
+                .iter()
+                .filter(|k| *k & FastKind::Local as u8 != 0)
+                .count() as u32
+        } else {
+            nlocals
+        };
+        let filename = self.deserialize_str(v, python_ver)?;
+        let name = self.deserialize_str(v, python_ver)?;
+        let qualname = if python_ver.minor >= Some(11) {
+            self.deserialize_str(v, python_ver)?
+        } else {
+            name.clone()
+        };
+        let firstlineno = Self::deserialize_u32(v);
+        let lnotab = self.deserialize_bytes(v)?;
+        let exceptiontable = if python_ver.minor >= Some(11) {
+            self.deserialize_bytes(v)?
+        } else {
+            vec![]
+        };
+        Ok(CodeObj {
+            argcount,
+            posonlyargcount,
+            kwonlyargcount,
+            nlocals,
+            stacksize,
+            flags,
+            code,
+            consts,
+            names,
+            varnames,
+            freevars,
+            cellvars,
+            filename,
+            name,
+            qualname,
+            firstlineno,
+            lnotab,
+            exceptiontable,
+            localspluskinds,
+        })
     }
 
     pub fn deserialize_const_vec(
@@ -331,9 +517,24 @@ impl Deserializer {
         }
     }
 
-    pub fn deserialize_bytes(&self, v: &mut Vec<u8>) -> DeserializeResult<Vec<u8>> {
-        if DataTypePrefix::from(v.remove(0)) != DataTypePrefix::Str {
-            return Err(DeserializeError::new(
+    pub fn deserialize_bytes(&mut self, v: &mut Vec<u8>) -> DeserializeResult<Vec<u8>> {
+        match Self::deserialize_prefix(v)? {
+            (DataTypePrefix::Str, flagged) => {
+                let len = Self::deserialize_u32(v);
+                let bytes = v.drain(0..len as usize).collect::<Vec<_>>();
+                if flagged {
+                    self.refs.push(RefObj::Bytes(bytes.clone()));
+                }
+                Ok(bytes)
+            }
+            (DataTypePrefix::Ref, _) => match self.deserialize_ref(v)? {
+                RefObj::Bytes(bytes) => Ok(bytes),
+                RefObj::Value(ValueObj::Bytes(bytes)) => Ok(bytes.to_vec()),
+                RefObj::Value(other) => {
+                    Err(DeserializeError::type_error(&Type::Str, other.ref_t()))
+                }
+            },
+            _ => Err(DeserializeError::new(
                 0,
                 fn_name!(),
                 switch_lang!(
@@ -342,9 +543,7 @@ impl Deserializer {
                     "traditional_chinese" => "未能加載字節",
                     "english" => "failed to load bytes",
                 ),
-            ));
+            )),
         }
-        let len = Self::deserialize_u32(v);
-        Ok(v.drain(0..len as usize).collect())
     }
 }
@@ -201,6 +201,25 @@ impl Constraint {
         }
     }
 
+    /// The number of type arguments that a higher-kinded type variable takes.
+    /// e.g. `F: Type -> Type` => `Some(1)`, `T: Type` => `None`
+    pub fn type_ctor_arity(&self) -> Option<usize> {
+        match self.get_type()? {
+            Type::Subr(subr)
+                if subr.return_t.as_ref() == &Type::Type
+                    && subr.var_params.is_none()
+                    && subr.default_params.is_empty()
+                    && subr
+                        .non_default_params
+                        .iter()
+                        .all(|pt| pt.typ() == &Type::Type) =>
+            {
+                Some(subr.non_default_params.len())
+            }
+            _ => None,
+        }
+    }
+
     /// :> Sub
     pub fn get_sub(&self) -> Option<&Type> {
         match self {
@@ -3,6 +3,7 @@
 //! Type(コンパイラ等で使われる「型」を表現する)を定義する
 #![allow(clippy::derived_hash_with_manual_eq)]
 #![allow(clippy::large_enum_variant)]
+pub mod asm;
 pub mod codeobj;
 pub mod const_subr;
 pub mod constructors;
@@ -29,7 +30,7 @@ use erg_common::{enum_unwrap, fmt_option, ref_addr_eq, set, Str};
 use erg_parser::token::TokenKind;
 
 pub use const_subr::*;
-use constructors::{dict_t, int_interval, mono};
+use constructors::{dict_t, int_interval, mono, poly};
 use free::{CanbeFree, Constraint, Free, FreeKind, FreeTyVar, HasLevel, Level, GENERIC_LEVEL};
 pub use predicate::Predicate;
 pub use typaram::{IntervalOp, TyParam};
@@ -245,7 +246,7 @@ pub struct SubrType {
     pub non_default_params: Vec<ParamTy>,
     pub var_params: Option<Box<ParamTy>>, // TODO: need to have a position (var_params can be specified after default_params)
     pub default_params: Vec<ParamTy>,
-    // var_kw_params: Option<(Str, Box<Type>)>,
+    pub kw_var_params: Option<Box<ParamTy>>,
     pub return_t: Box<Type>,
 }
 
@@ -305,6 +306,16 @@ impl LimitedDisplay for SubrType {
             write!(f, ", {} := ", pt.name().unwrap())?;
             pt.typ().limited_fmt(f, limit - 1)?;
         }
+        if let Some(kw_var_params) = &self.kw_var_params {
+            if !self.non_default_params.is_empty()
+                || self.var_params.is_some()
+                || !self.default_params.is_empty()
+            {
+                write!(f, ", ")?;
+            }
+            write!(f, "**")?;
+            kw_var_params.typ().limited_fmt(f, limit - 1)?;
+        }
         write!(f, ") {} ", self.kind.arrow())?;
         self.return_t.limited_fmt(f, limit - 1)
     }
@@ -338,8 +349,17 @@ impl StructuralEq for SubrType {
             .iter()
             .zip(other.var_params.iter())
             .all(|(l, r)| l.typ().structural_eq(r.typ()));
+        let kw_var_params_judge = self
+            .kw_var_params
+            .iter()
+            .zip(other.kw_var_params.iter())
+            .all(|(l, r)| l.typ().structural_eq(r.typ()));
         let return_t_judge = self.return_t.structural_eq(&other.return_t);
-        non_defaults_judge && var_params_judge && return_t_judge && kw_check()
+        non_defaults_judge
+            && var_params_judge
+            && kw_var_params_judge
+            && return_t_judge
+            && kw_check()
     }
 }
 
@@ -349,6 +369,7 @@ impl SubrType {
         non_default_params: Vec<ParamTy>,
         var_params: Option<ParamTy>,
         default_params: Vec<ParamTy>,
+        kw_var_params: Option<ParamTy>,
         return_t: Type,
     ) -> Self {
         Self {
@@ -356,6 +377,7 @@ impl SubrType {
             non_default_params,
             var_params: var_params.map(Box::new),
             default_params,
+            kw_var_params: kw_var_params.map(Box::new),
             return_t: Box::new(return_t),
         }
     }
@@ -373,6 +395,11 @@ impl SubrType {
                 .default_params
                 .iter()
                 .any(|pt| pt.typ().contains_tvar(target))
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().contains_tvar(target))
+                .unwrap_or(false)
             || self.return_t.contains_tvar(target)
     }
 
@@ -389,6 +416,11 @@ impl SubrType {
                 .default_params
                 .iter()
                 .any(|pt| pt.typ().contains(target))
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().contains(target))
+                .unwrap_or(false)
             || self.return_t.contains(target)
     }
 
@@ -403,6 +435,9 @@ impl SubrType {
         for pt in self.default_params.iter() {
             qvars.extend(pt.typ().qvars());
         }
+        if let Some(kw_var_params) = &self.kw_var_params {
+            qvars.extend(kw_var_params.typ().qvars());
+        }
         qvars.extend(self.return_t.qvars());
         qvars
     }
@@ -426,6 +461,7 @@ impl SubrType {
             .map(|pt| pt.typ().qnames())
             .chain(self.var_params.iter().map(|pt| pt.typ().qnames()))
             .chain(self.default_params.iter().map(|pt| pt.typ().qnames()))
+            .chain(self.kw_var_params.iter().map(|pt| pt.typ().qnames()))
             .chain([self.return_t.qnames()]);
         Set::multi_intersection(qnames_sets).extended(structural_qname)
     }
@@ -438,6 +474,11 @@ impl SubrType {
                 .map(|pt| pt.typ().has_qvar())
                 .unwrap_or(false)
             || self.default_params.iter().any(|pt| pt.typ().has_qvar())
+            || self
+                .kw_var_params
+                .as_ref()
+                .map(|pt| pt.typ().has_qvar())
+                .unwrap_or(false)
             || self.return_t.has_qvar()
     }
 
@@ -456,6 +497,11 @@ impl SubrType {
                 .iter()
                 .map(|pt| TyParam::t(pt.typ().clone()))
                 .collect(),
+            self.kw_var_params
+                .as_ref()
+                .map(|pt| TyParam::t(pt.typ().clone()))
+                .into_iter()
+                .collect(),
         ]
         .concat()
     }
@@ -505,6 +551,7 @@ impl SubrType {
             .iter()
             .chain(self.var_params.as_deref().into_iter())
             .chain(self.default_params.iter())
+            .chain(self.kw_var_params.as_deref())
             .map(|pt| pt.name().map_or("_", |s| &s[..]))
     }
 }
@@ -648,6 +695,7 @@ pub struct ArgsOwnership {
     pub non_defaults: Vec<(Option<Str>, Ownership)>,
     pub var_params: Option<(Option<Str>, Ownership)>,
     pub defaults: Vec<(Str, Ownership)>,
+    pub kw_var_params: Option<(Option<Str>, Ownership)>,
 }
 
 impl fmt::Display for ArgsOwnership {
@@ -674,6 +722,14 @@ impl fmt::Display for ArgsOwnership {
         for (name, o) in self.defaults.iter() {
             write!(f, ", {name} := {o:?}")?;
         }
+        if let Some((name, o)) = self.kw_var_params.as_ref() {
+            write!(f, ", **")?;
+            if let Some(name) = name {
+                write!(f, "{name}: {o:?}")?;
+            } else {
+                write!(f, "{o:?}")?;
+            }
+        }
         write!(f, ")")?;
         Ok(())
     }
@@ -684,11 +740,13 @@ impl ArgsOwnership {
         non_defaults: Vec<(Option<Str>, Ownership)>,
         var_params: Option<(Option<Str>, Ownership)>,
         defaults: Vec<(Str, Ownership)>,
+        kw_var_params: Option<(Option<Str>, Ownership)>,
     ) -> Self {
         Self {
             non_defaults,
             var_params,
             defaults,
+            kw_var_params,
         }
     }
 }
@@ -807,7 +865,6 @@ pub enum Type {
         param_ts: Vec<Type>,
         return_t: Box<Type>,
     },
-    // Overloaded(Vec<Type>),
     Record(Dict<Field, Type>), // e.g. {x = Int}
     // e.g. {T -> T | T: Type}, {I: Int | I > 0}, {S | N: Nat; S: Str N; N > 1}
     // 区間型と列挙型は篩型に変換される
@@ -818,6 +875,8 @@ pub enum Type {
     Refinement(RefinementType),
     // e.g. |T: Type| T -> T
     Quantified(Box<Type>),
+    // overloaded subroutines are also represented as intersection types
+    // e.g. ((x: Int) -> Int) and ((s: Str) -> Str)
     And(Box<Type>, Box<Type>),
     Or(Box<Type>, Box<Type>),
     Not(Box<Type>),
@@ -1052,9 +1111,21 @@ impl LimitedDisplay for Type {
                 quantified.limited_fmt(f, limit - 1)
             }
             Self::And(lhs, rhs) => {
-                lhs.limited_fmt(f, limit - 1)?;
+                if lhs.is_subr() {
+                    write!(f, "(")?;
+                    lhs.limited_fmt(f, limit - 1)?;
+                    write!(f, ")")?;
+                } else {
+                    lhs.limited_fmt(f, limit - 1)?;
+                }
                 write!(f, " and ")?;
-                rhs.limited_fmt(f, limit - 1)
+                if rhs.is_subr() {
+                    write!(f, "(")?;
+                    rhs.limited_fmt(f, limit - 1)?;
+                    write!(f, ")")
+                } else {
+                    rhs.limited_fmt(f, limit - 1)
+                }
             }
             Self::Not(ty) => {
                 write!(f, "not ")?;
@@ -1090,8 +1161,21 @@ impl LimitedDisplay for Type {
                 attr_name,
                 args,
             } => {
+                // e.g. `F(Int)` (`F` is a higher-kinded type variable, `F := Array(_: Type, 2)`) => `Array(Int, 2)`
+                if &attr_name[..] == "__call__" {
+                    if let Some(t) = <&Type>::try_from(lhs.as_ref())
+                        .ok()
+                        .and_then(|ctor| ctor.apply_type_ctor(args))
+                    {
+                        return t.limited_fmt(f, limit);
+                    }
+                }
                 lhs.limited_fmt(f, limit - 1)?;
-                write!(f, ".{attr_name}(")?;
+                if &attr_name[..] == "__call__" {
+                    write!(f, "(")?;
+                } else {
+                    write!(f, ".{attr_name}(")?;
+                }
                 for (i, arg) in args.iter().enumerate() {
                     if i != 0 {
                         write!(f, ", ")?;
@@ -1279,6 +1363,7 @@ impl HasType for Type {
                         .into_iter(),
                 )
                 .chain(sub.non_default_params.iter().map(|pt| pt.typ().clone()))
+                .chain(sub.kw_var_params.as_deref().map(|pt| pt.typ().clone()))
                 .chain([*sub.return_t.clone()].into_iter())
                 .collect(),
             Self::Callable { param_ts, .. } => param_ts.clone(),
@@ -1332,8 +1417,13 @@ impl HasLevel for Type {
                     .iter()
                     .filter_map(|p| p.typ().level())
                     .min();
+                let kv_min = subr
+                    .kw_var_params
+                    .iter()
+                    .filter_map(|p| p.typ().level())
+                    .min();
                 let ret_min = subr.return_t.level();
-                [nd_min, v_min, d_min, ret_min]
+                [nd_min, v_min, d_min, kv_min, ret_min]
                     .iter()
                     .filter_map(|o| *o)
                     .min()
@@ -1420,6 +1510,9 @@ impl HasLevel for Type {
                 for pt in subr.default_params.iter() {
                     pt.typ().set_level(level);
                 }
+                if let Some(pt) = subr.kw_var_params.as_ref() {
+                    pt.typ().set_level(level);
+                }
                 subr.return_t.set_level(level);
             }
             Self::Quantified(quant) => {
@@ -1676,6 +1769,13 @@ impl Type {
         }
     }
 
+    /// e.g. `[Int!; 3]` (`Array(Int!, 3)`) => true, `[Int; 3]` => false
+    pub fn has_mut_content(&self) -> bool {
+        self.typarams()
+            .iter()
+            .any(|tp| matches!(tp, TyParam::Type(t) if t.is_mut_type()))
+    }
+
     pub fn is_nonelike(&self) -> bool {
         match self {
             Self::Never | Self::Failure => true,
@@ -1710,6 +1810,11 @@ impl Type {
         }
     }
 
+    /// e.g. `((x: Int) -> Int) and ((s: Str) -> Str)`
+    pub fn is_overloaded(&self) -> bool {
+        self.is_intersection_type() && self.intersection_types().iter().all(|t| t.is_subr())
+    }
+
     pub fn has_union_type(&self) -> bool {
         match self {
             Self::FreeVar(fv) if fv.is_linked() => fv.crack().has_union_type(),
@@ -1745,6 +1850,11 @@ impl Type {
                         .default_params
                         .iter()
                         .any(|pt| pt.typ().has_union_type())
+                    || subr
+                        .kw_var_params
+                        .as_ref()
+                        .map(|pt| pt.typ().has_union_type())
+                        .unwrap_or(false)
                     || subr.return_t.has_union_type()
             }
             Self::Record(r) => r.values().any(|t| t.has_union_type()),
@@ -1986,7 +2096,11 @@ impl Type {
                     };
                     d_args.push((d_param.name().unwrap().clone(), ownership));
                 }
-                ArgsOwnership::new(nd_args, var_args, d_args)
+                let kw_var_args = subr
+                    .kw_var_params
+                    .as_ref()
+                    .map(|t| (t.name().cloned(), t.typ().ownership()));
+                ArgsOwnership::new(nd_args, var_args, d_args, kw_var_args)
             }
             Self::Quantified(quant) => quant.args_ownership(),
             other => todo!("{other}"),
@@ -2149,6 +2263,19 @@ impl Type {
         }
     }
 
+    pub fn intersection_types(&self) -> Vec<Type> {
+        match self {
+            Type::FreeVar(fv) if fv.is_linked() => fv.crack().intersection_types(),
+            Type::Refinement(refine) => refine.t.intersection_types(),
+            Type::And(t1, t2) => {
+                let mut types = t1.intersection_types();
+                types.extend(t2.intersection_types());
+                types
+            }
+            _ => vec![self.clone()],
+        }
+    }
+
     /// assert!((A or B).contains_union(B))
     pub fn contains_union(&self, typ: &Type) -> bool {
         match self {
@@ -2443,6 +2570,11 @@ impl Type {
                         .default_params
                         .iter()
                         .any(|pt| pt.typ().has_unbound_var())
+                    || subr
+                        .kw_var_params
+                        .as_ref()
+                        .map(|pt| pt.typ().has_unbound_var())
+                        .unwrap_or(false)
                     || subr.return_t.has_unbound_var()
             }
             Self::Record(r) => r.values().any(|t| t.has_unbound_var()),
@@ -2476,6 +2608,7 @@ impl Type {
                 subr.non_default_params.len()
                     + subr.var_params.as_ref().map(|_| 1).unwrap_or(0)
                     + subr.default_params.len()
+                    + subr.kw_var_params.as_ref().map(|_| 1).unwrap_or(0)
                     + 1,
             ),
             Self::Callable { param_ts, .. } => Some(param_ts.len() + 1),
@@ -2544,6 +2677,49 @@ impl Type {
         }
     }
 
+    /// Splits a polymorphic type into a type constructor and its first `arity` type arguments.
+    /// The type arguments are replaced with holes (`_: Type`).
+    /// e.g. `Array(Int, 2)`, `1` => `(Array(_: Type, 2), [Int])`
+    pub fn split_type_ctor(&self, arity: usize) -> Option<(Type, Vec<TyParam>)> {
+        match self {
+            Self::FreeVar(fv) if fv.is_linked() => fv.crack().split_type_ctor(arity),
+            Self::Poly { name, params }
+                if params.len() >= arity
+                    && params[..arity]
+                        .iter()
+                        .all(|tp| <&Type>::try_from(tp).is_ok()) =>
+            {
+                let (args, rest) = params.split_at(arity);
+                let holes = std::iter::repeat_n(TyParam::erased(Type::Type), arity);
+                let ctor = poly(name.clone(), holes.chain(rest.iter().cloned()).collect());
+                Some((ctor, args.to_vec()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Fills the holes (`_: Type`) of a type constructor with `args` in order.
+    /// e.g. `Array(_: Type, 2)`, `[Int]` => `Array(Int, 2)`
+    pub fn apply_type_ctor(&self, args: &[TyParam]) -> Option<Type> {
+        match self {
+            Self::FreeVar(fv) if fv.is_linked() => fv.crack().apply_type_ctor(args),
+            Self::Poly { name, params } => {
+                let mut args = args.iter();
+                let params = params
+                    .iter()
+                    .map(|param| match param {
+                        TyParam::Erased(t) if t.as_ref() == &Type::Type => {
+                            args.next().unwrap_or(param).clone()
+                        }
+                        _ => param.clone(),
+                    })
+                    .collect();
+                args.next().is_none().then(|| poly(name.clone(), params))
+            }
+            _ => None,
+        }
+    }
+
     pub fn self_t(&self) -> Option<&Type> {
         match self {
             Self::FreeVar(fv) if fv.is_linked() => unsafe { fv.as_ptr().as_ref() }
@@ -2603,6 +2779,19 @@ impl Type {
         }
     }
 
+    pub fn kw_var_params(&self) -> Option<&ParamTy> {
+        match self {
+            Self::FreeVar(fv) if fv.is_linked() => unsafe { fv.as_ptr().as_ref() }
+                .unwrap()
+                .linked()
+                .and_then(|t| t.kw_var_params()),
+            Self::Refinement(refine) => refine.t.kw_var_params(),
+            Self::Subr(SubrType { kw_var_params, .. }) => kw_var_params.as_deref(),
+            Self::Quantified(quant) => quant.kw_var_params(),
+            _ => None,
+        }
+    }
+
     pub fn non_var_params(&self) -> Option<impl Iterator<Item = &ParamTy> + Clone> {
         match self {
             Self::FreeVar(fv) if fv.is_linked() => unsafe { fv.as_ptr().as_ref() }
@@ -2777,6 +2966,10 @@ impl Type {
                 for d in subr.default_params.iter_mut() {
                     *d.typ_mut() = std::mem::take(d.typ_mut())._replace(target, to);
                 }
+                if let Some(kw_var) = subr.kw_var_params.as_mut() {
+                    *kw_var.as_mut().typ_mut() =
+                        std::mem::take(kw_var.as_mut().typ_mut())._replace(target, to);
+                }
                 subr.return_t = Box::new(subr.return_t._replace(target, to));
                 Self::Subr(subr)
             }
@@ -2844,6 +3037,10 @@ impl Type {
                 for d in subr.default_params.iter_mut() {
                     *d.typ_mut() = std::mem::take(d.typ_mut()).normalize();
                 }
+                if let Some(kw_var) = subr.kw_var_params.as_mut() {
+                    *kw_var.as_mut().typ_mut() =
+                        std::mem::take(kw_var.as_mut().typ_mut()).normalize();
+                }
                 subr.return_t = Box::new(subr.return_t.normalize());
                 Self::Subr(subr)
             }
@@ -2920,6 +3117,9 @@ impl<'t> ReplaceTable<'t> {
                 for (ld, rd) in lsub.default_params.iter().zip(rsub.default_params.iter()) {
                     self.iterate(ld.typ(), rd.typ());
                 }
+                for (lkv, rkv) in lsub.kw_var_params.iter().zip(rsub.kw_var_params.iter()) {
+                    self.iterate(lkv.typ(), rkv.typ());
+                }
                 self.iterate(lsub.return_t.as_ref(), rsub.return_t.as_ref());
             }
             (Type::Quantified(quant), Type::Quantified(quant2)) => {
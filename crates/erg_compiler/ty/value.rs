@@ -23,7 +23,7 @@ use crate::context::eval::type_from_token_kind;
 use self::value_set::inner_class;
 
 use super::codeobj::CodeObj;
-use super::constructors::{array_t, dict_t, refinement, set_t, tuple_t};
+use super::constructors::{array_t, dict_t, mono, refinement, set_t, tuple_t};
 use super::typaram::TyParam;
 use super::{ConstSubr, Field, HasType, Predicate, Type};
 
@@ -91,6 +91,31 @@ impl InheritedTypeObj {
     }
 }
 
+/// e.g. `Expr = Enum {.Lit = Int; .Add = {lhs = Expr; rhs = Expr}}`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EnumTypeObj {
+    pub t: Type,
+    /// (variant name, payload type), sorted by name (the index is used as the tag)
+    pub variants: Vec<(Str, TypeObj)>,
+    pub impls: Option<Box<TypeObj>>,
+}
+
+impl EnumTypeObj {
+    pub fn new(t: Type, mut variants: Vec<(Str, TypeObj)>, impls: Option<TypeObj>) -> Self {
+        variants.sort_by(|(l, _), (r, _)| l.cmp(r));
+        Self {
+            t,
+            variants,
+            impls: impls.map(Box::new),
+        }
+    }
+
+    /// `Expr.variant_type("Lit") == mono("<module>::Expr.Lit")`
+    pub fn variant_type(&self, name: &str) -> Type {
+        mono(format!("{}.{name}", self.t.qual_name()))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct TraitTypeObj {
     pub t: Type,
@@ -197,6 +222,7 @@ impl PatchObj {
 pub enum GenTypeObj {
     Class(ClassTypeObj),
     Subclass(InheritedTypeObj),
+    Enum(EnumTypeObj),
     Trait(TraitTypeObj),
     Subtrait(SubsumedTypeObj),
     Structural(StructuralTypeObj),
@@ -225,6 +251,10 @@ impl GenTypeObj {
         GenTypeObj::Subclass(InheritedTypeObj::new(t, sup, impls, additional))
     }
 
+    pub fn enum_(t: Type, variants: Vec<(Str, TypeObj)>, impls: Option<TypeObj>) -> Self {
+        GenTypeObj::Enum(EnumTypeObj::new(t, variants, impls))
+    }
+
     pub fn trait_(t: Type, require: TypeObj, impls: Option<TypeObj>) -> Self {
         GenTypeObj::Trait(TraitTypeObj::new(t, require, impls))
     }
@@ -270,6 +300,7 @@ impl GenTypeObj {
         match self {
             Self::Class(class) => class.impls.as_ref().map(|x| x.as_ref()),
             Self::Subclass(subclass) => subclass.impls.as_ref().map(|x| x.as_ref()),
+            Self::Enum(enum_) => enum_.impls.as_ref().map(|x| x.as_ref()),
             Self::Subtrait(subtrait) => subtrait.impls.as_ref().map(|x| x.as_ref()),
             Self::Patch(patch) => patch.impls.as_ref().map(|x| x.as_ref()),
             _ => None,
@@ -280,6 +311,7 @@ impl GenTypeObj {
         match self {
             Self::Class(class) => Some(&mut class.impls),
             Self::Subclass(subclass) => Some(&mut subclass.impls),
+            Self::Enum(enum_) => Some(&mut enum_.impls),
             Self::Subtrait(subtrait) => Some(&mut subtrait.impls),
             Self::Patch(patch) => Some(&mut patch.impls),
             _ => None,
@@ -296,7 +328,7 @@ impl GenTypeObj {
 
     pub fn meta_type(&self) -> Type {
         match self {
-            Self::Class(_) | Self::Subclass(_) => Type::ClassType,
+            Self::Class(_) | Self::Subclass(_) | Self::Enum(_) => Type::ClassType,
             Self::Trait(_) | Self::Subtrait(_) => Type::TraitType,
             Self::Patch(_) => Type::Patch,
             Self::Structural(_) => Type::Type,
@@ -308,6 +340,7 @@ impl GenTypeObj {
         match self {
             Self::Class(class) => &class.t,
             Self::Subclass(subclass) => &subclass.t,
+            Self::Enum(enum_) => &enum_.t,
             Self::Trait(trait_) => &trait_.t,
             Self::Subtrait(subtrait) => &subtrait.t,
             Self::Structural(struct_) => &struct_.t,
@@ -321,6 +354,7 @@ impl GenTypeObj {
         match self {
             Self::Class(class) => &mut class.t,
             Self::Subclass(subclass) => &mut subclass.t,
+            Self::Enum(enum_) => &mut enum_.t,
             Self::Trait(trait_) => &mut trait_.t,
             Self::Subtrait(subtrait) => &mut subtrait.t,
             Self::Structural(struct_) => &mut struct_.t,
@@ -334,6 +368,7 @@ impl GenTypeObj {
         match self {
             Self::Class(class) => class.t,
             Self::Subclass(subclass) => subclass.t,
+            Self::Enum(enum_) => enum_.t,
             Self::Trait(trait_) => trait_.t,
             Self::Subtrait(subtrait) => subtrait.t,
             Self::Structural(struct_) => struct_.t,
@@ -427,6 +462,22 @@ impl TypeObj {
     }
 }
 
+/// Parses an integer literal (e.g. `-1_000`, `0xff`, `0o777`, `0b1010`).
+fn parse_int_lit(content: &str) -> Option<i128> {
+    let content = content.replace('_', "");
+    let (sign, abs) = match content.strip_prefix('-') {
+        Some(abs) => (-1, abs),
+        None => (1, &content[..]),
+    };
+    let (radix, digits) = match abs.get(..2) {
+        Some("0x" | "0X") => (16, &abs[2..]),
+        Some("0o" | "0O") => (8, &abs[2..]),
+        Some("0b" | "0B") => (2, &abs[2..]),
+        _ => (10, abs),
+    };
+    i128::from_str_radix(digits, radix).ok().map(|n| sign * n)
+}
+
 /// 値オブジェクト
 /// コンパイル時評価ができ、シリアライズも可能
 #[derive(Clone, PartialEq, Default)]
@@ -435,6 +486,7 @@ pub enum ValueObj {
     Nat(u64),
     Float(f64),
     Str(Str),
+    Bytes(Rc<[u8]>),
     Bool(bool),
     Array(Rc<[ValueObj]>),
     Set(Set<ValueObj>),
@@ -487,6 +539,7 @@ impl fmt::Debug for ValueObj {
                 Ok(())
             }
             Self::Str(s) => write!(f, "\"{s}\""),
+            Self::Bytes(bytes) => write!(f, "b\"{}\"", bytes.escape_ascii()),
             Self::Bool(b) => {
                 if *b {
                     write!(f, "True")
@@ -568,6 +621,7 @@ impl Hash for ValueObj {
             // TODO:
             Self::Float(f) => f.to_bits().hash(state),
             Self::Str(s) => s.hash(state),
+            Self::Bytes(bytes) => bytes.hash(state),
             Self::Bool(b) => b.hash(state),
             Self::Array(arr) => arr.hash(state),
             Self::Dict(dict) => dict.hash(state),
@@ -812,12 +866,11 @@ impl ValueObj {
 
     pub fn from_str(t: Type, content: Str) -> Option<Self> {
         match t {
-            Type::Int => content.replace('_', "").parse::<i32>().ok().map(Self::Int),
-            Type::Nat => content
-                .trim_start_matches('-') // -0 -> 0
-                .replace('_', "")
-                .parse::<u64>()
-                .ok()
+            Type::Int => parse_int_lit(&content)
+                .and_then(|i| i32::try_from(i).ok())
+                .map(Self::Int),
+            Type::Nat => parse_int_lit(content.trim_start_matches('-')) // -0 -> 0
+                .and_then(|n| u64::try_from(n).ok())
                 .map(Self::Nat),
             Type::Float => content
                 .replace('_', "")
@@ -840,6 +893,17 @@ impl ValueObj {
                     Some(Self::Str(Str::rc(replaced)))
                 }
             }
+            Type::Mono(name) if &name[..] == "Bytes" => {
+                // `\xhh` is stored as `U+00hh` by the lexer
+                let bytes = content
+                    .trim_start_matches('b')
+                    .trim_start_matches('\"')
+                    .trim_end_matches('\"')
+                    .chars()
+                    .map(|c| u8::try_from(c).ok())
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Self::Bytes(Rc::from(bytes)))
+            }
             Type::Bool => Some(Self::Bool(&content[..] == "True")),
             Type::NoneType => Some(Self::None),
             Type::Ellipsis => Some(Self::Ellipsis),
@@ -853,7 +917,21 @@ impl ValueObj {
     pub fn into_bytes(self, python_ver: PythonVersion) -> Vec<u8> {
         match self {
             Self::Int(i) => [vec![DataTypePrefix::Int32 as u8], i.to_le_bytes().to_vec()].concat(),
-            // TODO: Natとしてシリアライズ
+            // Python's `long` (15-bit digits, little endian)
+            Self::Nat(n) if n > i32::MAX as u64 => {
+                let mut digits = vec![];
+                let mut rest = n;
+                while rest > 0 {
+                    digits.push((rest & 0x7fff) as u16);
+                    rest >>= 15;
+                }
+                let mut bytes = vec![DataTypePrefix::Long as u8];
+                bytes.append(&mut (digits.len() as i32).to_le_bytes().to_vec());
+                for digit in digits {
+                    bytes.append(&mut digit.to_le_bytes().to_vec());
+                }
+                bytes
+            }
             Self::Nat(n) => [
                 vec![DataTypePrefix::Int32 as u8],
                 (n as i32).to_le_bytes().to_vec(),
@@ -864,7 +942,11 @@ impl ValueObj {
                 f.to_le_bytes().to_vec(),
             ]
             .concat(),
-            Self::Str(s) => str_into_bytes(s, false),
+            Self::Str(s) => {
+                let is_interned = is_interned_str(&s);
+                str_into_bytes(s, is_interned)
+            }
+            Self::Bytes(bytes) => raw_string_into_bytes(bytes.to_vec()),
             Self::Bool(true) => vec![DataTypePrefix::True as u8],
             Self::Bool(false) => vec![DataTypePrefix::False as u8],
             // TODO: SmallTuple
@@ -889,6 +971,9 @@ impl ValueObj {
             Self::None => {
                 vec![DataTypePrefix::None as u8]
             }
+            Self::Ellipsis => {
+                vec![DataTypePrefix::Ellipsis as u8]
+            }
             Self::Code(c) => c.into_bytes(python_ver),
             // Dict
             other => {
@@ -929,6 +1014,7 @@ impl ValueObj {
             Self::Nat(_) => Type::Nat,
             Self::Float(_) => Type::Float,
             Self::Str(_) => Type::Str,
+            Self::Bytes(_) => mono("Bytes"),
             Self::Bool(_) => Type::Bool,
             Self::Array(arr) => array_t(
                 // REVIEW: Never?
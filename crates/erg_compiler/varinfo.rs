@@ -42,6 +42,7 @@ pub enum VarKind {
     Parameter {
         def_id: DefId,
         var: bool,
+        kw_var: bool,
         default: DefaultInfo,
     },
     Auto,
@@ -55,10 +56,20 @@ impl VarKind {
         Self::Parameter {
             def_id,
             var,
+            kw_var: false,
             default,
         }
     }
 
+    pub const fn kw_var_parameter(def_id: DefId) -> Self {
+        Self::Parameter {
+            def_id,
+            var: false,
+            kw_var: true,
+            default: DefaultInfo::NonDefault,
+        }
+    }
+
     pub const fn has_default(&self) -> bool {
         match self {
             Self::Parameter { default, .. } => default.has_default(),
@@ -77,6 +88,13 @@ impl VarKind {
         }
     }
 
+    pub const fn is_kw_var_params(&self) -> bool {
+        match self {
+            Self::Parameter { kw_var, .. } => *kw_var,
+            _ => false,
+        }
+    }
+
     pub const fn is_defined(&self) -> bool {
         matches!(self, Self::Defined(_))
     }
@@ -261,10 +279,18 @@ impl VarInfo {
         }
     }
 
+    /// decorated with `@Async`
+    pub fn is_async(&self) -> bool {
+        self.comptime_decos
+            .as_ref()
+            .is_some_and(|decos| decos.contains("Async"))
+    }
+
     pub fn nd_parameter(t: Type, def_loc: AbsLocation, namespace: Str) -> Self {
         let kind = VarKind::Parameter {
             def_id: DefId(0),
             var: false,
+            kw_var: false,
             default: DefaultInfo::NonDefault,
         };
         Self::new(
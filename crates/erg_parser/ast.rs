@@ -26,11 +26,13 @@ pub enum OperationKind {
     Assert,
     Class,
     Inherit,
+    Enum,
     Trait,
     Subsume,
     Return,
     Yield,
     Cast,
+    Await,
 }
 
 impl OperationKind {
@@ -161,6 +163,7 @@ pub struct Args {
     pos_args: Vec<PosArg>,
     pub(crate) var_args: Option<Box<PosArg>>,
     kw_args: Vec<KwArg>,
+    pub(crate) kw_var_args: Option<Box<PosArg>>,
     // these are for ELS
     pub paren: Option<(Token, Token)>,
 }
@@ -199,18 +202,20 @@ impl Args {
         pos_args: Vec<PosArg>,
         var_args: Option<PosArg>,
         kw_args: Vec<KwArg>,
+        kw_var_args: Option<PosArg>,
         paren: Option<(Token, Token)>,
     ) -> Self {
         Self {
             pos_args,
             var_args: var_args.map(Box::new),
             kw_args,
+            kw_var_args: kw_var_args.map(Box::new),
             paren,
         }
     }
 
     pub fn pos_only(pos_arg: Vec<PosArg>, paren: Option<(Token, Token)>) -> Self {
-        Self::new(pos_arg, None, vec![], paren)
+        Self::new(pos_arg, None, vec![], None, paren)
     }
 
     pub fn single(pos_args: PosArg) -> Self {
@@ -218,7 +223,7 @@ impl Args {
     }
 
     pub fn empty() -> Self {
-        Self::new(vec![], None, vec![], None)
+        Self::new(vec![], None, vec![], None, None)
     }
 
     // for replacing to hir::Args
@@ -229,12 +234,14 @@ impl Args {
         Vec<PosArg>,
         Option<PosArg>,
         Vec<KwArg>,
+        Option<PosArg>,
         Option<(Token, Token)>,
     ) {
         (
             self.pos_args,
             self.var_args.map(|x| *x),
             self.kw_args,
+            self.kw_var_args.map(|x| *x),
             self.paren,
         )
     }
@@ -299,6 +306,10 @@ impl Args {
         self.kw_args.push(arg);
     }
 
+    pub fn set_kw_var_args(&mut self, arg: PosArg) {
+        self.kw_var_args = Some(Box::new(arg));
+    }
+
     pub fn set_parens(&mut self, paren: (Token, Token)) {
         self.paren = Some(paren);
     }
@@ -752,12 +763,23 @@ impl KeyValue {
 pub struct NormalDict {
     pub l_brace: Token,
     pub r_brace: Token,
+    /// `**expr` entries, these precede `kvs`
+    pub spreads: Vec<UnaryOp>,
     pub kvs: Vec<KeyValue>,
 }
 
 impl NestedDisplay for NormalDict {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
-        write!(f, "{{{}}}", fmt_vec(&self.kvs))
+        let spreads = self
+            .spreads
+            .iter()
+            .map(|spread| format!("{}{}", spread.op.content, spread.args[0]));
+        let kvs = self.kvs.iter().map(|kv| kv.to_string());
+        write!(
+            f,
+            "{{{}}}",
+            spreads.chain(kvs).collect::<Vec<_>>().join(", ")
+        )
     }
 }
 
@@ -769,9 +791,15 @@ impl NormalDict {
         Self {
             l_brace,
             r_brace,
+            spreads: Vec::new(),
             kvs,
         }
     }
+
+    pub fn with_spreads(mut self, spreads: Vec<UnaryOp>) -> Self {
+        self.spreads = spreads;
+        self
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -896,12 +924,23 @@ impl From<Vec<Def>> for RecordAttrs {
 pub struct NormalRecord {
     pub l_brace: Token,
     pub r_brace: Token,
+    /// `**expr` entries, these precede `attrs`
+    pub spreads: Vec<UnaryOp>,
     pub attrs: RecordAttrs,
 }
 
 impl NestedDisplay for NormalRecord {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
         writeln!(f, "{{")?;
+        for spread in self.spreads.iter() {
+            writeln!(
+                f,
+                "{}{}{}",
+                "    ".repeat(level + 1),
+                spread.op.content,
+                spread.args[0]
+            )?;
+        }
         self.attrs.fmt_nest(f, level + 1)?;
         writeln!(f, "{}}}", "    ".repeat(level))
     }
@@ -921,9 +960,15 @@ impl NormalRecord {
         Self {
             l_brace,
             r_brace,
+            spreads: Vec::new(),
             attrs,
         }
     }
+
+    pub fn with_spreads(mut self, spreads: Vec<UnaryOp>) -> Self {
+        self.spreads = spreads;
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -941,6 +986,7 @@ impl Record {
         Self::Mixed(MixedRecord {
             l_brace,
             r_brace,
+            spreads: Vec::new(),
             attrs,
         })
     }
@@ -949,6 +995,7 @@ impl Record {
         Self::Normal(NormalRecord {
             l_brace,
             r_brace,
+            spreads: Vec::new(),
             attrs,
         })
     }
@@ -957,6 +1004,7 @@ impl Record {
         Self::Normal(NormalRecord {
             l_brace,
             r_brace,
+            spreads: Vec::new(),
             attrs: RecordAttrs::new(Vec::with_capacity(0)),
         })
     }
@@ -967,12 +1015,17 @@ impl Record {
 pub struct MixedRecord {
     pub l_brace: Token,
     pub r_brace: Token,
+    /// `**expr` entries, these precede `attrs`
+    pub spreads: Vec<UnaryOp>,
     pub attrs: Vec<RecordAttrOrIdent>,
 }
 
 impl NestedDisplay for MixedRecord {
     fn fmt_nest(&self, f: &mut fmt::Formatter<'_>, _level: usize) -> fmt::Result {
         write!(f, "{{")?;
+        for spread in self.spreads.iter() {
+            write!(f, "{}{}; ", spread.op.content, spread.args[0])?;
+        }
         for attr in self.attrs.iter() {
             write!(f, "{attr}; ")?;
         }
@@ -988,9 +1041,15 @@ impl MixedRecord {
         Self {
             l_brace,
             r_brace,
+            spreads: Vec::new(),
             attrs,
         }
     }
+
+    pub fn with_spreads(mut self, spreads: Vec<UnaryOp>) -> Self {
+        self.spreads = spreads;
+        self
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -1224,6 +1283,7 @@ impl Call {
             "Del" => Some(OperationKind::Del),
             "Class" => Some(OperationKind::Class),
             "Inherit" => Some(OperationKind::Inherit),
+            "Enum" => Some(OperationKind::Enum),
             "Trait" => Some(OperationKind::Trait),
             "Subsume" => Some(OperationKind::Subsume),
             _ => None,
@@ -2108,6 +2168,7 @@ impl ConstArgs {
                 // TODO t_spec
                 .map(|arg| KwArg::new(arg.keyword, None, arg.expr.downcast()))
                 .collect(),
+            None,
             paren,
         )
     }
@@ -2258,6 +2319,7 @@ pub struct SubrTypeSpec {
     pub non_defaults: Vec<ParamTySpec>,
     pub var_params: Option<Box<ParamTySpec>>,
     pub defaults: Vec<DefaultParamTySpec>,
+    pub kw_var_params: Option<Box<ParamTySpec>>,
     pub arrow: Token,
     pub return_t: Box<TypeSpec>,
 }
@@ -2269,10 +2331,11 @@ impl fmt::Display for SubrTypeSpec {
         }
         write!(
             f,
-            "({}, {}, {}) {} {}",
+            "({}, {}, {}, {}) {} {}",
             fmt_vec(&self.non_defaults),
             fmt_option!(pre "*", &self.var_params),
             fmt_vec(&self.defaults),
+            fmt_option!(pre "**", &self.kw_var_params),
             self.arrow.content,
             self.return_t
         )
@@ -2293,12 +2356,14 @@ impl Locational for SubrTypeSpec {
 }
 
 impl SubrTypeSpec {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         bounds: TypeBoundSpecs,
         lparen: Option<Token>,
         non_defaults: Vec<ParamTySpec>,
         var_params: Option<ParamTySpec>,
         defaults: Vec<DefaultParamTySpec>,
+        kw_var_params: Option<ParamTySpec>,
         arrow: Token,
         return_t: TypeSpec,
     ) -> Self {
@@ -2308,6 +2373,7 @@ impl SubrTypeSpec {
             non_defaults,
             var_params: var_params.map(Box::new),
             defaults,
+            kw_var_params: kw_var_params.map(Box::new),
             arrow,
             return_t: Box::new(return_t),
         }
@@ -3523,6 +3589,7 @@ pub struct Params {
     pub non_defaults: Vec<NonDefaultParamSignature>,
     pub var_params: Option<Box<NonDefaultParamSignature>>,
     pub defaults: Vec<DefaultParamSignature>,
+    pub kw_var_params: Option<Box<NonDefaultParamSignature>>,
     pub parens: Option<(Token, Token)>,
 }
 
@@ -3530,10 +3597,11 @@ impl fmt::Display for Params {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "({}, {}, {})",
+            "({}, {}, {}, {})",
             fmt_vec(&self.non_defaults),
             fmt_option!(pre "*", &self.var_params),
-            fmt_vec(&self.defaults)
+            fmt_vec(&self.defaults),
+            fmt_option!(pre "**", &self.kw_var_params),
         )
     }
 }
@@ -3566,6 +3634,7 @@ type RawParams = (
     Vec<NonDefaultParamSignature>,
     Option<Box<NonDefaultParamSignature>>,
     Vec<DefaultParamSignature>,
+    Option<Box<NonDefaultParamSignature>>,
     Option<(Token, Token)>,
 );
 
@@ -3574,18 +3643,20 @@ impl Params {
         non_defaults: Vec<NonDefaultParamSignature>,
         var_params: Option<NonDefaultParamSignature>,
         defaults: Vec<DefaultParamSignature>,
+        kw_var_params: Option<NonDefaultParamSignature>,
         parens: Option<(Token, Token)>,
     ) -> Self {
         Self {
             non_defaults,
             var_params: var_params.map(Box::new),
             defaults,
+            kw_var_params: kw_var_params.map(Box::new),
             parens,
         }
     }
 
     pub fn single(non_default: NonDefaultParamSignature) -> Self {
-        Self::new(vec![non_default], None, vec![], None)
+        Self::new(vec![non_default], None, vec![], None, None)
     }
 
     pub fn deconstruct(self) -> RawParams {
@@ -3593,6 +3664,7 @@ impl Params {
             self.non_defaults,
             self.var_params,
             self.defaults,
+            self.kw_var_params,
             self.parens,
         )
     }
@@ -3679,6 +3751,13 @@ impl SubrSignature {
     pub fn vis(&self) -> &VisModifierSpec {
         &self.ident.vis
     }
+
+    /// decorated with `@Async`
+    pub fn is_async(&self) -> bool {
+        self.decorators.iter().any(|deco| {
+            matches!(&deco.0, Expr::Accessor(Accessor::Ident(ident)) if &ident.inspect()[..] == "Async")
+        })
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -3739,7 +3818,7 @@ impl LambdaSignature {
     pub fn do_sig(do_symbol: &Token) -> Self {
         let parens = Some((do_symbol.clone(), do_symbol.clone()));
         Self::new(
-            Params::new(vec![], None, vec![], parens),
+            Params::new(vec![], None, vec![], None, parens),
             None,
             TypeBoundSpecs::empty(),
         )
@@ -3923,6 +4002,7 @@ impl TypeAscription {
 pub enum DefKind {
     Class,
     Inherit,
+    Enum,
     Trait,
     Subsume,
     StructuralTrait,
@@ -3939,7 +4019,11 @@ impl DefKind {
     }
 
     pub const fn is_class(&self) -> bool {
-        matches!(self, Self::Class | Self::Inherit)
+        matches!(self, Self::Class | Self::Inherit | Self::Enum)
+    }
+
+    pub const fn is_enum(&self) -> bool {
+        matches!(self, Self::Enum)
     }
 
     pub const fn is_class_or_trait(&self) -> bool {
@@ -3986,6 +4070,7 @@ impl DefBody {
             Expr::Call(call) => match call.obj.get_name().map(|n| &n[..]) {
                 Some("Class") => DefKind::Class,
                 Some("Inherit") => DefKind::Inherit,
+                Some("Enum") => DefKind::Enum,
                 Some("Trait") => DefKind::Trait,
                 Some("Subsume") => DefKind::Subsume,
                 Some("Inheritable") => {
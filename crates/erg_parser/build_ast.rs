@@ -5,7 +5,8 @@ use erg_common::Str;
 use crate::ast::AST;
 use crate::desugar::Desugarer;
 use crate::error::{ParserRunnerError, ParserRunnerErrors};
-use crate::parse::ParserRunner;
+use crate::parse::{ParsedModule, ParserRunner};
+use crate::token::TokenStream;
 
 /// Summarize parsing and desugaring
 #[derive(Debug, Default)]
@@ -66,6 +67,21 @@ impl ASTBuilder {
         Ok(ast)
     }
 
+    /// Builds an AST from `ts`, re-parsing only the top-level chunks changed from `prev`.
+    /// The returned `ParsedModule` can be passed as `prev` on the next build.
+    pub fn build_incrementally(
+        &mut self,
+        prev: Option<&ParsedModule>,
+        ts: TokenStream,
+    ) -> Result<(AST, ParsedModule), ParserRunnerErrors> {
+        let parsed = self.runner.parse_incrementally(prev, ts)?;
+        let mut desugarer = Desugarer::new();
+        let module = desugarer.desugar(parsed.module().clone());
+        let name = self.runner.cfg().input.unescaped_filename();
+        let ast = AST::new(Str::rc(name), module);
+        Ok((ast, parsed))
+    }
+
     pub fn build_without_desugaring(&mut self, src: String) -> Result<AST, ParserRunnerErrors> {
         let module = self.runner.parse(src)?;
         let name = self.runner.cfg().input.unescaped_filename();
@@ -161,6 +161,16 @@ impl Parser {
 
     fn convert_record_to_record_pat(&mut self, record: Record) -> ParseResult<VarRecordPattern> {
         debug_call_info!(self);
+        let spread = match &record {
+            Record::Normal(rec) => rec.spreads.first(),
+            Record::Mixed(rec) => rec.spreads.first(),
+        };
+        if let Some(spread) = spread {
+            let err = ParseError::feature_error(line!() as usize, spread.loc(), "spread pattern");
+            self.errs.push(err);
+            debug_exit_info!(self);
+            return Err(());
+        }
         match record {
             Record::Normal(rec) => {
                 let pats = rec
@@ -211,7 +221,7 @@ impl Parser {
         let mut vars = Vars::empty();
         match tuple {
             Tuple::Normal(tup) => {
-                let (pos_args, _var_args, _kw_args, paren) = tup.elems.deconstruct();
+                let (pos_args, _var_args, _kw_args, _kw_var_args, paren) = tup.elems.deconstruct();
                 for arg in pos_args {
                     let sig = self
                         .convert_rhs_to_sig(arg.expr)
@@ -325,7 +335,7 @@ impl Parser {
             return Ok(TypeBoundSpecs::empty());
         };
         let mut bounds = vec![];
-        let (pos_args, _var_args, _kw_args, _paren) = args.deconstruct();
+        let (pos_args, _var_args, _kw_args, _kw_var_args, _paren) = args.deconstruct();
         for arg in pos_args.into_iter() {
             let bound = self
                 .convert_type_arg_to_bound(arg)
@@ -365,8 +375,8 @@ impl Parser {
 
     pub(crate) fn convert_args_to_params(&mut self, args: Args) -> ParseResult<Params> {
         debug_call_info!(self);
-        let (pos_args, var_args, kw_args, parens) = args.deconstruct();
-        let mut params = Params::new(vec![], None, vec![], parens);
+        let (pos_args, var_args, kw_args, kw_var_args, parens) = args.deconstruct();
+        let mut params = Params::new(vec![], None, vec![], None, parens);
         for (i, arg) in pos_args.into_iter().enumerate() {
             let nd_param = self
                 .convert_pos_arg_to_non_default_param(arg, i == 0)
@@ -379,13 +389,18 @@ impl Parser {
                 .map_err(|_| self.stack_dec(fn_name!()))?;
             params.var_params = Some(Box::new(var_args));
         }
-        // TODO: varargs
         for arg in kw_args.into_iter() {
             let d_param = self
                 .convert_kw_arg_to_default_param(arg)
                 .map_err(|_| self.stack_dec(fn_name!()))?;
             params.defaults.push(d_param);
         }
+        if let Some(kw_var_args) = kw_var_args {
+            let kw_var_args = self
+                .convert_pos_arg_to_non_default_param(kw_var_args, false)
+                .map_err(|_| self.stack_dec(fn_name!()))?;
+            params.kw_var_params = Some(Box::new(kw_var_args));
+        }
         debug_exit_info!(self);
         Ok(params)
     }
@@ -403,6 +418,54 @@ impl Parser {
         Ok(param)
     }
 
+    /// `self(T, N ~> N+1)` -> `self: Self(T, N ~> N+1)`
+    fn convert_call_to_receiver_param(
+        &mut self,
+        mut call: Call,
+        allow_self: bool,
+    ) -> ParseResult<NonDefaultParamSignature> {
+        debug_call_info!(self);
+        let self_ident = match call.obj.as_ref() {
+            Expr::Accessor(Accessor::Ident(ident))
+                if allow_self && call.attr_name.is_none() && &ident.inspect()[..] == "self" =>
+            {
+                ident.clone()
+            }
+            _ => {
+                let err = ParseError::simple_syntax_error(line!() as usize, call.loc());
+                self.errs.push(err);
+                debug_exit_info!(self);
+                return Err(());
+            }
+        };
+        let self_t = Token::new(
+            TokenKind::Symbol,
+            "Self",
+            self_ident.ln_begin().unwrap_or(0),
+            self_ident.col_begin().unwrap_or(0),
+        );
+        let self_t = Expr::Accessor(Accessor::Ident(Identifier::private_from_token(self_t)));
+        call.obj = Box::new(self_t.clone());
+        let t_spec = match Parser::call_to_predecl_type_spec(call) {
+            Ok(t_spec) => TypeSpec::PreDeclTy(t_spec),
+            Err(err) => {
+                self.errs.push(err);
+                debug_exit_info!(self);
+                return Err(());
+            }
+        };
+        let colon = Token::new(
+            TokenKind::Colon,
+            ":",
+            self_ident.ln_begin().unwrap_or(0),
+            self_ident.col_end().unwrap_or(0),
+        );
+        let t_spec = TypeSpecWithOp::new(colon, t_spec, self_t);
+        let pat = ParamPattern::RefMut(self_ident.name);
+        debug_exit_info!(self);
+        Ok(NonDefaultParamSignature::new(pat, Some(t_spec)))
+    }
+
     fn convert_rhs_to_param(
         &mut self,
         expr: Expr,
@@ -479,6 +542,14 @@ impl Parser {
                 }
                 TokenKind::RefMutOp => {
                     let var = unary.args.into_iter().next().unwrap();
+                    // e.g. `ref! self(T, N ~> N+1)`
+                    if let Expr::Call(call) = *var {
+                        let param = self
+                            .convert_call_to_receiver_param(call, allow_self)
+                            .map_err(|_| self.stack_dec(fn_name!()))?;
+                        debug_exit_info!(self);
+                        return Ok(param);
+                    }
                     let Expr::Accessor(Accessor::Ident(var)) = *var else {
                         let err = ParseError::simple_syntax_error(line!() as usize, var.loc());
                         self.errs.push(err);
@@ -490,7 +561,13 @@ impl Parser {
                     debug_exit_info!(self);
                     Ok(param)
                 }
-                // TODO: Spread
+                TokenKind::PreStar | TokenKind::PreDblStar => {
+                    let err =
+                        ParseError::feature_error(line!() as usize, unary.loc(), "spread pattern");
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    Err(())
+                }
                 _other => {
                     let err = ParseError::simple_syntax_error(line!() as usize, unary.loc());
                     self.errs.push(err);
@@ -527,7 +604,7 @@ impl Parser {
                 for arg in arr.elems.into_iters().0 {
                     params.push(self.convert_pos_arg_to_non_default_param(arg, false)?);
                 }
-                let params = Params::new(params, None, vec![], None);
+                let params = Params::new(params, None, vec![], None, None);
                 debug_exit_info!(self);
                 Ok(ParamArrayPattern::new(arr.l_sqbr, params, arr.r_sqbr))
             }
@@ -549,6 +626,21 @@ impl Parser {
         };
         assert_eq!(attr.body.block.len(), 1);
         let first = attr.body.block.remove(0);
+        match first {
+            Expr::Call(call) => {
+                let rhs = self
+                    .convert_nested_variant_pattern_to_param_sig(call)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                return Ok(ParamRecordAttr::new(lhs, rhs));
+            }
+            Expr::DataPack(pack) => {
+                let rhs = self
+                    .convert_data_pack_to_param_sig(pack)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                return Ok(ParamRecordAttr::new(lhs, rhs));
+            }
+            _ => {}
+        }
         let Expr::Accessor(rhs) = first else {
             let err = ParseError::simple_syntax_error(line!() as usize, first.loc());
             self.errs.push(err);
@@ -559,11 +651,110 @@ impl Parser {
         Ok(ParamRecordAttr::new(lhs, rhs))
     }
 
+    /// `Cons{head; rest}` (in `{rest = Cons{head; rest}}`) -> `{head; rest}: Cons`
+    fn convert_nested_variant_pattern_to_param_sig(
+        &mut self,
+        mut call: Call,
+    ) -> ParseResult<NonDefaultParamSignature> {
+        debug_call_info!(self);
+        // `Expr.Add {...}` is parsed as a method call
+        if let Some(attr_name) = call.attr_name.take() {
+            *call.obj = call.obj.attr_expr(attr_name);
+        }
+        let is_variant = match call.obj.as_ref() {
+            Expr::Accessor(Accessor::Attr(_)) => true,
+            Expr::Accessor(Accessor::Ident(ident)) => ident.is_const(),
+            _ => false,
+        } && call.args.len() == 1
+            && matches!(
+                call.args.pos_args().first().map(|arg| &arg.expr),
+                Some(Expr::Record(_))
+            );
+        if !is_variant {
+            let err = ParseError::simple_syntax_error(line!() as usize, call.loc());
+            self.errs.push(err);
+            debug_exit_info!(self);
+            return Err(());
+        }
+        let Expr::Record(record) = call.args.remove_pos(0).expr else {
+            unreachable!()
+        };
+        let param = self
+            .convert_variant_record_to_param_sig(*call.obj, record)
+            .map_err(|_| self.stack_dec(fn_name!()))?;
+        debug_exit_info!(self);
+        Ok(param)
+    }
+
+    /// `Cons(_, _)::{head; rest}` -> `{head; rest}: Cons(_, _)`
+    fn convert_data_pack_to_param_sig(
+        &mut self,
+        pack: DataPack,
+    ) -> ParseResult<NonDefaultParamSignature> {
+        debug_call_info!(self);
+        let param = self
+            .convert_variant_record_to_param_sig(*pack.class, pack.args)
+            .map_err(|_| self.stack_dec(fn_name!()))?;
+        debug_exit_info!(self);
+        Ok(param)
+    }
+
+    fn convert_variant_record_to_param_sig(
+        &mut self,
+        class: Expr,
+        record: Record,
+    ) -> ParseResult<NonDefaultParamSignature> {
+        debug_call_info!(self);
+        // `Cons(_, _)` matches any `Cons`
+        let class = match class {
+            Expr::Call(call)
+                if call.attr_name.is_none()
+                    && call.args.kw_is_empty()
+                    && call.args.pos_args().iter().all(|arg| {
+                        matches!(
+                            &arg.expr,
+                            Expr::Accessor(Accessor::Ident(ident)) if &ident.inspect()[..] == "_"
+                        )
+                    }) =>
+            {
+                *call.obj
+            }
+            other => other,
+        };
+        let record_pat = self
+            .convert_record_to_param_record_pat(record)
+            .map_err(|_| self.stack_dec(fn_name!()))?;
+        let t_spec = match Self::expr_to_type_spec(class.clone()) {
+            Ok(t_spec) => t_spec,
+            Err(err) => {
+                self.errs.push(err);
+                debug_exit_info!(self);
+                return Err(());
+            }
+        };
+        let t_spec = TypeSpecWithOp::new(Token::DUMMY, t_spec, class);
+        debug_exit_info!(self);
+        Ok(NonDefaultParamSignature::new(
+            ParamPattern::Record(record_pat),
+            Some(t_spec),
+        ))
+    }
+
     fn convert_record_to_param_record_pat(
         &mut self,
         record: Record,
     ) -> ParseResult<ParamRecordPattern> {
         debug_call_info!(self);
+        let spread = match &record {
+            Record::Normal(rec) => rec.spreads.first(),
+            Record::Mixed(rec) => rec.spreads.first(),
+        };
+        if let Some(spread) = spread {
+            let err = ParseError::feature_error(line!() as usize, spread.loc(), "spread pattern");
+            self.errs.push(err);
+            debug_exit_info!(self);
+            return Err(());
+        }
         match record {
             Record::Normal(rec) => {
                 let pats = rec
@@ -606,7 +797,7 @@ impl Parser {
         match tuple {
             Tuple::Normal(tup) => {
                 let mut params = vec![];
-                let (elems, var_args, _, parens) = tup.elems.deconstruct();
+                let (elems, var_args, _, _, parens) = tup.elems.deconstruct();
                 for arg in elems.into_iter() {
                     params.push(self.convert_pos_arg_to_non_default_param(arg, false)?);
                 }
@@ -616,7 +807,7 @@ impl Parser {
                 } else {
                     None
                 };
-                let params = Params::new(params, var_params, vec![], parens);
+                let params = Params::new(params, var_params, vec![], None, parens);
                 debug_exit_info!(self);
                 Ok(ParamTuplePattern::new(params))
             }
@@ -686,6 +877,14 @@ impl Parser {
                 debug_exit_info!(self);
                 Ok(LambdaSignature::new(params, None, TypeBoundSpecs::empty()))
             }
+            Expr::DataPack(pack) => {
+                let param = self
+                    .convert_data_pack_to_param_sig(pack)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                let params = Params::single(param);
+                debug_exit_info!(self);
+                Ok(LambdaSignature::new(params, None, TypeBoundSpecs::empty()))
+            }
             Expr::TypeAscription(tasc) => {
                 let sig = self
                     .convert_type_asc_to_lambda_sig(tasc)
@@ -727,6 +926,11 @@ impl Parser {
     }
 
     fn convert_call_to_param_sig(&mut self, call: Call) -> ParseResult<NonDefaultParamSignature> {
+        if call.attr_name.is_some()
+            || matches!(call.obj.as_ref(), Expr::Accessor(Accessor::Attr(_)))
+        {
+            return self.convert_variant_pattern_to_param_sig(call);
+        }
         let predecl = Self::call_to_predecl_type_spec(call.clone()).map_err(|_| ())?;
         let t_spec =
             TypeSpecWithOp::new(Token::DUMMY, TypeSpec::PreDeclTy(predecl), Expr::Call(call));
@@ -736,12 +940,51 @@ impl Parser {
         ))
     }
 
+    /// `Expr.Lit(i)` -> `{base = i}: Expr.Lit`
+    fn convert_variant_pattern_to_param_sig(
+        &mut self,
+        call: Call,
+    ) -> ParseResult<NonDefaultParamSignature> {
+        debug_call_info!(self);
+        let loc = call.loc();
+        let obj = match call.attr_name {
+            Some(attr_name) => call.obj.attr_expr(attr_name),
+            None => *call.obj,
+        };
+        let (mut pos_args, var_args, kw_args, kw_var_args, paren) = call.args.deconstruct();
+        if pos_args.len() != 1 || var_args.is_some() || !kw_args.is_empty() || kw_var_args.is_some()
+        {
+            let err = ParseError::simple_syntax_error(line!() as usize, loc);
+            self.errs.push(err);
+            debug_exit_info!(self);
+            return Err(());
+        }
+        let payload = self
+            .convert_rhs_to_param(pos_args.remove(0).expr, false)
+            .map_err(|_| self.stack_dec(fn_name!()))?;
+        let t_spec = match Self::expr_to_type_spec(obj.clone()) {
+            Ok(t_spec) => t_spec,
+            Err(err) => {
+                self.errs.push(err);
+                debug_exit_info!(self);
+                return Err(());
+            }
+        };
+        let base = Identifier::private_with_line("base".into(), loc.ln_begin().unwrap_or(0));
+        let attrs = ParamRecordAttrs::new(vec![ParamRecordAttr::new(base, payload)]);
+        let (l_brace, r_brace) = paren.unwrap_or((Token::DUMMY, Token::DUMMY));
+        let pat = ParamPattern::Record(ParamRecordPattern::new(l_brace, attrs, r_brace));
+        let t_spec = TypeSpecWithOp::new(Token::DUMMY, t_spec, obj);
+        debug_exit_info!(self);
+        Ok(NonDefaultParamSignature::new(pat, Some(t_spec)))
+    }
+
     fn convert_tuple_to_params(&mut self, tuple: Tuple) -> ParseResult<Params> {
         debug_call_info!(self);
         match tuple {
             Tuple::Normal(tup) => {
-                let (pos_args, var_args, kw_args, paren) = tup.elems.deconstruct();
-                let mut params = Params::new(vec![], None, vec![], paren);
+                let (pos_args, var_args, kw_args, kw_var_args, paren) = tup.elems.deconstruct();
+                let mut params = Params::new(vec![], None, vec![], None, paren);
                 for (i, arg) in pos_args.into_iter().enumerate() {
                     let param = self
                         .convert_pos_arg_to_non_default_param(arg, i == 0)
@@ -760,6 +1003,12 @@ impl Parser {
                         .map_err(|_| self.stack_dec(fn_name!()))?;
                     params.defaults.push(param);
                 }
+                if let Some(kw_var_args) = kw_var_args {
+                    let param = self
+                        .convert_pos_arg_to_non_default_param(kw_var_args, false)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    params.kw_var_params = Some(Box::new(param));
+                }
                 debug_exit_info!(self);
                 Ok(params)
             }
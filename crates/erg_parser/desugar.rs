@@ -19,6 +19,7 @@ use crate::ast::{
     VarName, VarPattern, VarRecordAttr, VarSignature, VisModifierSpec,
 };
 use crate::token::{Token, TokenKind, COLON, DOT};
+use crate::Parser;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum BufIndex<'i> {
@@ -43,6 +44,7 @@ impl Desugarer {
     pub fn desugar(&mut self, module: Module) -> Module {
         log!(info "the desugaring process has started.");
         let module = self.desugar_multiple_pattern_def(module);
+        let module = Self::desugar_variant_pattern(module);
         let module = self.desugar_pattern_in_module(module);
         let module = Self::desugar_shortened_record(module);
         let module = Self::desugar_acc(module);
@@ -53,6 +55,7 @@ impl Desugarer {
 
     pub fn desugar_simple_expr(expr: Expr) -> Expr {
         let expr = Self::rec_desugar_shortened_record(expr);
+        let expr = Self::rec_desugar_variant_pattern(expr);
         let expr = Self::rec_desugar_lambda_pattern(&mut Desugarer {}, expr);
         Self::rec_desugar_acc(expr)
     }
@@ -62,7 +65,7 @@ impl Desugarer {
     }
 
     fn desugar_args(mut desugar: impl FnMut(Expr) -> Expr, args: Args) -> Args {
-        let (pos_args, var_args, kw_args, paren) = args.deconstruct();
+        let (pos_args, var_args, kw_args, kw_var_args, paren) = args.deconstruct();
         let pos_args = pos_args
             .into_iter()
             .map(|arg| PosArg::new(desugar(arg.expr)))
@@ -74,7 +77,21 @@ impl Desugarer {
                 KwArg::new(arg.keyword, arg.t_spec, desugar(arg.expr)) // TODO: t_spec
             })
             .collect();
-        Args::new(pos_args, var_args, kw_args, paren)
+        let kw_var_args = kw_var_args.map(|arg| PosArg::new(desugar(arg.expr)));
+        Args::new(pos_args, var_args, kw_args, kw_var_args, paren)
+    }
+
+    fn desugar_spreads(
+        mut desugar: impl FnMut(Expr) -> Expr,
+        spreads: Vec<UnaryOp>,
+    ) -> Vec<UnaryOp> {
+        spreads
+            .into_iter()
+            .map(|spread| {
+                let (op, expr) = spread.deconstruct();
+                UnaryOp::new(op, desugar(expr))
+            })
+            .collect()
     }
 
     fn perform_desugar_acc(mut desugar: impl FnMut(Expr) -> Expr, acc: Accessor) -> Accessor {
@@ -110,17 +127,18 @@ impl Desugarer {
             Expr::Literal(_) => expr,
             Expr::Record(record) => match record {
                 Record::Normal(rec) => {
+                    let spreads = Self::desugar_spreads(&mut desugar, rec.spreads);
                     let mut new_attrs = vec![];
                     for attr in rec.attrs {
                         new_attrs.push(enum_unwrap!(desugar(Expr::Def(attr)), Expr::Def));
                     }
-                    Expr::Record(Record::Normal(NormalRecord::new(
-                        rec.l_brace,
-                        rec.r_brace,
-                        RecordAttrs::new(new_attrs),
-                    )))
+                    Expr::Record(Record::Normal(
+                        NormalRecord::new(rec.l_brace, rec.r_brace, RecordAttrs::new(new_attrs))
+                            .with_spreads(spreads),
+                    ))
                 }
                 Record::Mixed(mixed) => {
+                    let spreads = Self::desugar_spreads(&mut desugar, mixed.spreads);
                     let mut new_attrs = vec![];
                     for attr in mixed.attrs {
                         match attr {
@@ -136,11 +154,10 @@ impl Desugarer {
                             }
                         }
                     }
-                    Expr::Record(Record::Mixed(MixedRecord::new(
-                        mixed.l_brace,
-                        mixed.r_brace,
-                        new_attrs,
-                    )))
+                    Expr::Record(Record::Mixed(
+                        MixedRecord::new(mixed.l_brace, mixed.r_brace, new_attrs)
+                            .with_spreads(spreads),
+                    ))
                 }
             },
             Expr::DataPack(pack) => {
@@ -180,7 +197,7 @@ impl Desugarer {
             },
             Expr::Tuple(tuple) => match tuple {
                 Tuple::Normal(tup) => {
-                    let (elems, _, _, paren) = tup.elems.deconstruct();
+                    let (elems, _, _, _, paren) = tup.elems.deconstruct();
                     let elems = elems
                         .into_iter()
                         .map(|elem| PosArg::new(desugar(elem.expr)))
@@ -210,6 +227,7 @@ impl Desugarer {
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dic) => {
+                    let spreads = Self::desugar_spreads(&mut desugar, dic.spreads);
                     let new_kvs = dic
                         .kvs
                         .into_iter()
@@ -219,7 +237,8 @@ impl Desugarer {
                             KeyValue::new(key, value)
                         })
                         .collect();
-                    let tup = NormalDict::new(dic.l_brace, dic.r_brace, new_kvs);
+                    let tup =
+                        NormalDict::new(dic.l_brace, dic.r_brace, new_kvs).with_spreads(spreads);
                     Expr::Dict(Dict::Normal(tup))
                 }
                 _ => todo!("dict comprehension"),
@@ -335,11 +354,14 @@ impl Desugarer {
 
     /// `fib 0 = 0; fib 1 = 1; fib n = fib(n-1) + fib(n-2)`
     /// -> `fib n = match n, (0 -> 0), (1 -> 1), n -> fib(n-1) + fib(n-2)`
+    ///
+    /// Constant subroutines (e.g. `List T, 0 = ...; List T, N = ...`) are left as they are,
+    /// because the cases are selected at compile time.
     fn desugar_multiple_pattern_def(&self, module: Module) -> Module {
         let mut new = Module::with_capacity(module.len());
         for chunk in module.into_iter() {
             match chunk {
-                Expr::Def(def) if def.is_subr() => {
+                Expr::Def(def) if def.is_subr() && !def.sig.is_const() => {
                     if let Some(Expr::Def(previous)) = new.last() {
                         if previous.is_subr() && previous.sig.name_as_str() == def.sig.name_as_str()
                         {
@@ -376,7 +398,7 @@ impl Desugarer {
                                             _ => unreachable!(),
                                         }
                                     }
-                                    Params::new(params, None, vec![], None)
+                                    Params::new(params, None, vec![], None, None)
                                 }
                                 Expr::Accessor(Accessor::Ident(ident)) => {
                                     let param_name = ident.inspect();
@@ -776,6 +798,78 @@ impl Desugarer {
         }
     }
 
+    /// ```erg
+    /// match x:
+    ///     Expr.Add{lhs; rhs} -> ...
+    ///     Cons{head; rest} -> ...
+    /// ```
+    /// ↓
+    /// ```erg
+    /// match x:
+    ///     ({lhs; rhs}: Expr.Add) -> ...
+    ///     ({head; rest}: Cons) -> ...
+    /// ```
+    fn desugar_variant_pattern(module: Module) -> Module {
+        Self::desugar_all_chunks(module, Self::rec_desugar_variant_pattern)
+    }
+
+    fn rec_desugar_variant_pattern(expr: Expr) -> Expr {
+        match expr {
+            Expr::Call(call)
+                if matches!(
+                    call.obj.get_name().map(|s| &s[..]),
+                    Some("match" | "match!")
+                ) =>
+            {
+                let Call {
+                    obj,
+                    attr_name,
+                    args,
+                } = call;
+                let args = Self::desugar_args(Self::desugar_variant_arm, args);
+                let call = Call::new(*obj, attr_name, args);
+                Self::perform_desugar(Self::rec_desugar_variant_pattern, Expr::Call(call))
+            }
+            expr => Self::perform_desugar(Self::rec_desugar_variant_pattern, expr),
+        }
+    }
+
+    fn desugar_variant_arm(arm: Expr) -> Expr {
+        let Expr::Call(mut call) = arm else {
+            return arm;
+        };
+        // `Self.Add {...}` is parsed as a method call
+        if let Some(attr_name) = call.attr_name.take() {
+            call.obj = Box::new(call.obj.attr_expr(attr_name));
+        }
+        let is_variant_arm = match call.obj.as_ref() {
+            Expr::Accessor(Accessor::Attr(_)) => true,
+            Expr::Accessor(Accessor::Ident(ident)) => ident.is_const(),
+            _ => false,
+        } && call.args.len() == 1
+            && matches!(
+                call.args.pos_args().first().map(|arg| &arg.expr),
+                Some(Expr::Lambda(lambda))
+                    if lambda.sig.params.len() == 1
+                        && matches!(
+                            lambda.sig.params.non_defaults.first(),
+                            Some(NonDefaultParamSignature { pat: ParamPattern::Record(_), t_spec: None })
+                        )
+            );
+        if !is_variant_arm {
+            return Expr::Call(call);
+        }
+        let Ok(t_spec) = Parser::expr_to_type_spec(*call.obj.clone()) else {
+            return Expr::Call(call);
+        };
+        let Expr::Lambda(mut lambda) = call.args.remove_pos(0).expr else {
+            unreachable!()
+        };
+        let t_spec = TypeSpecWithOp::new(COLON, t_spec, *call.obj);
+        lambda.sig.params.non_defaults[0].t_spec = Some(t_spec);
+        Expr::Lambda(lambda)
+    }
+
     /// `{x; y}` -> `{x = x; y = y}`
     fn desugar_shortened_record(module: Module) -> Module {
         Self::desugar_all_chunks(module, Self::rec_desugar_shortened_record)
@@ -785,7 +879,11 @@ impl Desugarer {
         match expr {
             Expr::Record(Record::Mixed(record)) => {
                 let rec = Self::desugar_shortened_record_inner(record);
-                Expr::Record(Record::Normal(rec))
+                // e.g. `{.A = Int; .B = {x = Int; y}}`
+                Self::perform_desugar(
+                    Self::rec_desugar_shortened_record,
+                    Expr::Record(Record::Normal(rec)),
+                )
             }
             Expr::DataPack(pack) => {
                 if let Record::Mixed(rec) = pack.args {
@@ -824,7 +922,7 @@ impl Desugarer {
             })
             .collect();
         let attrs = RecordAttrs::new(attrs);
-        NormalRecord::new(record.l_brace, record.r_brace, attrs)
+        NormalRecord::new(record.l_brace, record.r_brace, attrs).with_spreads(record.spreads)
     }
 
     fn dummy_array_expr(len: Literal) -> Expr {
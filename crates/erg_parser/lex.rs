@@ -214,6 +214,119 @@ impl Lexer /*<'a>*/ {
         }
     }
 
+    /// Re-lexes `new_code` (an edited version of `old_code`) and splices the result into `tokens` (the tokens of `old_code`) in place.
+    ///
+    /// Lexing restarts from the last top-level line before the edited lines
+    /// and stops at the first top-level line after them which was also a top-level line of `old_code`;
+    /// the following tokens are reused (only their line numbers are shifted).
+    /// If `new_code` has lexical errors, `tokens` is not changed.
+    pub fn relex(
+        tokens: &mut TokenStream,
+        old_code: &str,
+        new_code: &str,
+    ) -> Result<(), LexErrors> {
+        let old_code = normalize_newline(old_code);
+        let new_code = normalize_newline(new_code);
+        let old_lines = old_code.split('\n').collect::<Vec<_>>();
+        let new_lines = new_code.split('\n').collect::<Vec<_>>();
+        let prefix = old_lines
+            .iter()
+            .zip(new_lines.iter())
+            .take_while(|(old, new)| old == new)
+            .count();
+        if prefix == old_lines.len() && prefix == new_lines.len() {
+            return Ok(());
+        }
+        let suffix = old_lines
+            .iter()
+            .rev()
+            .zip(new_lines.iter().rev())
+            .take(old_lines.len().min(new_lines.len()) - prefix)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let line_delta = new_lines.len() as i64 - old_lines.len() as i64;
+        let starts = Self::toplevel_line_starts(tokens);
+        // the line of the beginning is not changed, so the dedents emitted at there are reusable
+        let (start_line, start_idx) = starts
+            .iter()
+            .rev()
+            .find(|(line, _)| *line < prefix)
+            .copied()
+            .unwrap_or((0, 0));
+        let mut lexer = Lexer::from_str(new_code.clone());
+        if start_idx > 0 {
+            lexer.cursor = new_lines[..start_line]
+                .iter()
+                .map(|line| line.chars().count() + 1)
+                .sum();
+            lexer.lineno_token_starts = start_line as u32;
+            lexer.prev_token = tokens[start_idx - 1].clone();
+        }
+        let unchanged_from = new_lines.len() - suffix;
+        let mut region = vec![];
+        let mut end_idx = tokens.len();
+        let mut after_newline = start_idx > 0;
+        while let Some(result) = lexer.next() {
+            let Ok(token) = result else {
+                // report the errors of the entire code
+                *tokens = Lexer::from_str(new_code).lex()?;
+                return Ok(());
+            };
+            match token.kind {
+                Newline => after_newline = true,
+                Dedent => {}
+                Indent => after_newline = false,
+                _ if after_newline
+                    && lexer.indent_stack.is_empty()
+                    && token.lineno as usize > unchanged_from
+                    && lexer.interpol_stack.len() == 1 =>
+                {
+                    let old_line = (token.lineno as i64 - 1 - line_delta) as usize;
+                    let synced = starts
+                        .iter()
+                        .find(|(line, idx)| *line == old_line && tokens[*idx] == token);
+                    if let Some((_, idx)) = synced {
+                        end_idx = *idx;
+                        break;
+                    }
+                    after_newline = false;
+                }
+                _ => after_newline = false,
+            }
+            region.push(token);
+        }
+        tokens.shift_lines(end_idx, line_delta);
+        tokens.splice(start_idx..end_idx, region);
+        Ok(())
+    }
+
+    /// (0-origin line number, index of the token) of the lines which start outside of any indented block.
+    /// The lexer has no indentation at the beginning of such a line, so lexing can be restarted from there.
+    ///
+    /// NOTE: `col_begin` cannot be used for this; e.g. a doc comment in a block may start at column 0.
+    fn toplevel_line_starts(tokens: &TokenStream) -> Vec<(usize, usize)> {
+        let mut starts = vec![];
+        let mut after_newline = true;
+        let mut depth = 0usize;
+        for (i, token) in tokens.iter().enumerate() {
+            match token.kind {
+                Newline => after_newline = true,
+                Dedent => depth = depth.saturating_sub(1),
+                Indent => {
+                    depth += 1;
+                    after_newline = false;
+                }
+                _ => {
+                    if after_newline && depth == 0 && !token.is(EOF) {
+                        starts.push((token.lineno as usize - 1, i));
+                    }
+                    after_newline = false;
+                }
+            }
+        }
+        starts
+    }
+
     fn emit_token(&mut self, kind: TokenKind, cont: &str) -> Token {
         let cont = self.str_cache.get(cont);
         let lineno = self.lineno_token_starts + 2 - cont.lines().count() as u32;
@@ -598,6 +711,11 @@ impl Lexer /*<'a>*/ {
                 n if n.is_ascii_digit() || n == '_' => {
                     num.push(self.consume().unwrap());
                 }
+                // e.g. 0xff, 0o777, 0b1010
+                'x' | 'o' | 'b' | 'X' | 'O' | 'B' if num == "0" || num == "-0" => {
+                    num.push(self.consume().unwrap());
+                    return self.lex_radix_num(num);
+                }
                 c if Self::is_valid_continue_symbol_ch(c) => {
                     // exponent (e.g. 10e+3)
                     if c == 'e'
@@ -627,6 +745,77 @@ impl Lexer /*<'a>*/ {
         Ok(self.emit_token(kind, &num))
     }
 
+    /// `prefix` must be like `0x`, `-0o`
+    fn lex_radix_num(&mut self, prefix: String) -> LexResult<Token> {
+        let mut num = prefix;
+        let radix = match num.chars().last().unwrap().to_ascii_lowercase() {
+            'x' => 16,
+            'o' => 8,
+            _ => 2,
+        };
+        let mut has_digit = false;
+        while let Some(ch) = self.peek_cur_ch() {
+            if ch.is_digit(radix) {
+                has_digit = true;
+                num.push(self.consume().unwrap());
+            } else if ch == '_' {
+                num.push(self.consume().unwrap());
+            } else if ch.is_ascii_alphanumeric() {
+                // e.g. 0b102, 0o8
+                num.push(self.consume().unwrap());
+                has_digit = false;
+                break;
+            } else {
+                break;
+            }
+        }
+        if !has_digit {
+            let token = self.emit_token(Illegal, &num);
+            let kind = match radix {
+                16 => switch_lang!(
+                    "japanese" => "十六進数",
+                    "simplified_chinese" => "十六进制",
+                    "traditional_chinese" => "十六進位",
+                    "english" => "hexadecimal",
+                ),
+                8 => switch_lang!(
+                    "japanese" => "八進数",
+                    "simplified_chinese" => "八进制",
+                    "traditional_chinese" => "八進位",
+                    "english" => "octal",
+                ),
+                _ => switch_lang!(
+                    "japanese" => "二進数",
+                    "simplified_chinese" => "二进制",
+                    "traditional_chinese" => "二進位",
+                    "english" => "binary",
+                ),
+            };
+            return Err(LexError::syntax_error(
+                line!() as usize,
+                token.loc(),
+                switch_lang!(
+                    "japanese" => format!("`{}`は無効な{kind}リテラルです", &token.content),
+                    "simplified_chinese" => format!("`{}`是无效的{kind}字面量", &token.content),
+                    "traditional_chinese" => format!("`{}`是無效的{kind}字面量", &token.content),
+                    "english" => format!("`{}` is invalid {kind} literal", &token.content),
+                ),
+                None,
+            ));
+        }
+        let is_zero = num
+            .chars()
+            .skip_while(|c| !c.is_ascii_alphabetic())
+            .skip(1)
+            .all(|c| c == '0' || c == '_');
+        let kind = if num.starts_with('-') && !is_zero {
+            IntLit
+        } else {
+            NatLit
+        };
+        Ok(self.emit_token(kind, &num))
+    }
+
     /// number '.' ~~
     /// Possibility: RatioLit or Int/NatLit call
     fn lex_num_dot(&mut self, mut num: String) -> LexResult<Token> {
@@ -866,6 +1055,84 @@ impl Lexer /*<'a>*/ {
         Err(Self::unclosed_string_error(token, "\"", line!() as usize))
     }
 
+    /// `b` and `"` must be consumed
+    /// `\xhh` is stored as the character `U+00hh`
+    fn lex_bytes(&mut self) -> LexResult<Token> {
+        let mut s = "b\"".to_string();
+        while let Some(c) = self.peek_cur_ch() {
+            match c {
+                '\n' => {
+                    let token = self.emit_token(Illegal, &s);
+                    return Err(Self::str_line_break_error(token, line!() as usize));
+                }
+                '"' => {
+                    s.push(self.consume().unwrap());
+                    let token = self.emit_token(BytesLit, &s);
+                    return Ok(token);
+                }
+                _ => {
+                    let c = self.consume().unwrap();
+                    if c == '\\' {
+                        let next_c = self.consume().unwrap_or('\\');
+                        match next_c {
+                            '0' => s.push('\0'),
+                            'r' => s.push('\r'),
+                            'n' => s.push('\n'),
+                            't' => s.push('\t'),
+                            '\'' => s.push('\''),
+                            '"' => s.push('"'),
+                            '\\' => s.push('\\'),
+                            'x' => {
+                                let hex = [self.consume(), self.consume()];
+                                let byte = match hex {
+                                    [Some(h), Some(l)] => {
+                                        u8::from_str_radix(&format!("{h}{l}"), 16).ok()
+                                    }
+                                    _ => None,
+                                };
+                                let Some(byte) = byte else {
+                                    let token = self.emit_token(Illegal, &s);
+                                    return Err(Self::invalid_escape_error(next_c, token));
+                                };
+                                s.push(char::from(byte));
+                            }
+                            _ => {
+                                let token = self.emit_token(Illegal, &format!("\\{next_c}"));
+                                return Err(Self::invalid_escape_error(next_c, token));
+                            }
+                        }
+                    } else if c.is_ascii() {
+                        s.push(c);
+                    } else {
+                        s.push(c);
+                        let token = self.emit_token(Illegal, &s);
+                        return Err(LexError::syntax_error(
+                            line!() as usize,
+                            token.loc(),
+                            switch_lang!(
+                                "japanese" => format!("バイト列リテラルにはASCII文字しか使えません: {c}"),
+                                "simplified_chinese" => format!("字节字面量只能包含ASCII字符: {c}"),
+                                "traditional_chinese" => format!("位元組字面量只能包含ASCII字元: {c}"),
+                                "english" => format!("bytes can only contain ASCII literal characters: {c}"),
+                            ),
+                            Some(
+                                switch_lang!(
+                                    "japanese" => "非ASCIIのバイトは\\xhhで表してください",
+                                    "simplified_chinese" => "请用\\xhh表示非ASCII字节",
+                                    "traditional_chinese" => "請用\\xhh表示非ASCII位元組",
+                                    "english" => "use \\xhh for non-ASCII bytes",
+                                )
+                                .into(),
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        let token = self.emit_token(Illegal, &s);
+        Err(Self::unclosed_string_error(token, "\"", line!() as usize))
+    }
+
     fn lex_multi_line_str(&mut self, quote: Quote) -> LexResult<Token> {
         let mut s = quote.quotes().to_string();
         while let Some(c) = self.peek_cur_ch() {
@@ -1257,7 +1524,14 @@ impl Iterator for Lexer /*<'a>*/ {
                     self.accept(Caret, "^")
                 }
             }
-            Some('~') => self.accept(PreBitNot, "~"),
+            Some('~') => {
+                if let Some('>') = self.peek_cur_ch() {
+                    self.consume();
+                    self.accept(Transition, "~>")
+                } else {
+                    self.accept(PreBitNot, "~")
+                }
+            }
             // TODO:
             Some('$') => self.deny_feature("$", "shared variables"),
             Some('@') => self.accept(AtSign, "@"),
@@ -1320,10 +1594,27 @@ impl Iterator for Lexer /*<'a>*/ {
                 }
             },
             Some('*') => match self.peek_cur_ch() {
-                // TODO: infix/prefix
                 Some('*') => {
+                    // `x ** y`, `x**y`: infix, `f **kwargs`, `(**kwargs)`: prefix
+                    let prev_is_operand = matches!(
+                        self.prev_token.category(),
+                        TokenCategory::REnclosure
+                            | TokenCategory::Literal
+                            | TokenCategory::StrInterpRight
+                            | TokenCategory::Symbol
+                    );
+                    let spaced_before = self.peek_prev_prev_ch() == Some(' ');
                     self.consume();
-                    self.accept(Pow, "**")
+                    let is_prefix = if prev_is_operand {
+                        spaced_before && self.peek_cur_ch().is_some_and(|c| c != ' ')
+                    } else {
+                        matches!(self.op_fix(), Some(OpFix::Prefix))
+                    };
+                    if is_prefix {
+                        self.accept(PreDblStar, "**")
+                    } else {
+                        self.accept(Pow, "**")
+                    }
                 }
                 _ => {
                     let kind = match self.op_fix() {
@@ -1520,6 +1811,11 @@ impl Iterator for Lexer /*<'a>*/ {
             }
             // IntLit or RatioLit
             Some(n) if n.is_ascii_digit() => Some(self.lex_num(n)),
+            // BytesLit
+            Some('b') if self.peek_cur_ch() == Some('"') => {
+                self.consume(); // consume '"'
+                Some(self.lex_bytes())
+            }
             // Symbol (includes '_')
             Some(c) if Self::is_valid_start_symbol_ch(c) => Some(self.lex_symbol(c)),
             // Invalid character (e.g. space-like character)
@@ -105,6 +105,7 @@ enum ArgKind {
     Pos(PosArg),
     Var(PosArg),
     Kw(KwArg),
+    KwVar(PosArg),
 }
 
 pub enum ArrayInner {
@@ -149,6 +150,31 @@ impl ArgsStyle {
     }
 }
 
+/// A parsed (not desugared) module which remembers where its top-level chunks start in the token stream.
+/// This is used to re-parse an edited module incrementally (see `Parser::reparse`).
+#[derive(Debug, Clone)]
+pub struct ParsedModule {
+    tokens: TokenStream,
+    /// indices of the first tokens of the top-level chunks
+    chunk_starts: Vec<usize>,
+    module: Module,
+    next_def_id: DefId,
+}
+
+impl ParsedModule {
+    pub const fn module(&self) -> &Module {
+        &self.module
+    }
+
+    pub fn into_module(self) -> Module {
+        self.module
+    }
+
+    pub const fn tokens(&self) -> &TokenStream {
+        &self.tokens
+    }
+}
+
 /// Perform recursive descent parsing.
 ///
 /// `level` is raised by 1 by `debug_call_info!` in each analysis method and lowered by 1 when leaving (`.map_err` is called to lower the level).
@@ -159,6 +185,8 @@ pub struct Parser {
     counter: DefId,
     pub(super) level: usize, // nest level (for debugging)
     tokens: TokenStream,
+    /// the numbers of the remaining tokens when the top-level chunks start
+    chunk_starts: Vec<usize>,
     warns: ParseErrors,
     pub(crate) errs: ParseErrors,
 }
@@ -169,6 +197,7 @@ impl Parser {
             counter: DefId(0),
             level: 0,
             tokens: ts,
+            chunk_starts: vec![],
             warns: ParseErrors::empty(),
             errs: ParseErrors::empty(),
         }
@@ -330,6 +359,19 @@ impl ParserRunner {
             .map_err(|errs| ParserRunnerErrors::convert(self.input(), errs))
     }
 
+    /// Parses `ts`, reusing the unchanged top-level chunks of `prev` (see `Parser::reparse`).
+    pub fn parse_incrementally(
+        &mut self,
+        prev: Option<&ParsedModule>,
+        ts: TokenStream,
+    ) -> Result<ParsedModule, ParserRunnerErrors> {
+        let result = match prev {
+            Some(prev) => Parser::reparse(prev, ts),
+            None => Parser::parse_with_chunks(ts, DefId(0)),
+        };
+        result.map_err(|errs| ParserRunnerErrors::convert(self.input(), errs))
+    }
+
     pub fn parse(&mut self, src: String) -> Result<Module, ParserRunnerErrors> {
         let ts = Lexer::new(Input::new(InputKind::Str(src), self.cfg.input.id()))
             .lex()
@@ -368,6 +410,99 @@ impl Parser {
         }
     }
 
+    fn parse_with_chunks(ts: TokenStream, counter: DefId) -> Result<ParsedModule, ParseErrors> {
+        let len = ts.len();
+        let mut parser = Parser::new(ts.clone());
+        parser.counter = counter;
+        let module = parser.parse()?;
+        let chunk_starts = parser.chunk_starts.iter().map(|rest| len - rest).collect();
+        Ok(ParsedModule {
+            tokens: ts,
+            chunk_starts,
+            module,
+            next_def_id: parser.counter,
+        })
+    }
+
+    /// Parses `ts` (the tokens of an edited version of `prev`), reusing the top-level chunks of `prev` whose tokens are not changed.
+    ///
+    /// Only the chunks between the first and the last changed tokens are re-parsed.
+    /// A chunk is reused only if its tokens (and the surrounding tokens, which the parser may look ahead/behind) are exactly the same,
+    /// including their positions because AST nodes have absolute positions.
+    /// So the chunks after an edit which changes the number of lines are re-parsed.
+    pub fn reparse(prev: &ParsedModule, ts: TokenStream) -> Result<ParsedModule, ParseErrors> {
+        let old = &prev.tokens;
+        let starts = &prev.chunk_starts;
+        if starts.is_empty() {
+            return Self::parse_with_chunks(ts, prev.next_def_id);
+        }
+        let prefix = old
+            .iter()
+            .zip(ts.iter())
+            .take_while(|(old, new)| old.deep_eq(new))
+            .count();
+        if prefix == old.len() && prefix == ts.len() {
+            return Ok(prev.clone());
+        }
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(ts.iter().rev())
+            .take(old.len().min(ts.len()) - prefix)
+            .take_while(|(old, new)| old.deep_eq(new))
+            .count();
+        // the chunk and the first token of the next chunk are not changed
+        let reused_prefix = (0..starts.len())
+            .take_while(|&i| starts.get(i + 1).is_some_and(|next| *next < prefix))
+            .count();
+        // the chunk and the token before it are not changed
+        let reused_suffix = (reused_prefix..starts.len())
+            .find(|&i| starts[i] > old.len() - suffix)
+            .unwrap_or(starts.len());
+        let to_new_idx = |idx: usize| idx + ts.len() - old.len();
+        let mid_start = if reused_prefix == 0 {
+            0
+        } else {
+            starts[reused_prefix]
+        };
+        let mid_end = starts
+            .get(reused_suffix)
+            .map(|&idx| to_new_idx(idx))
+            .unwrap_or(ts.len());
+        let mut mid = ts
+            .iter()
+            .skip(mid_start)
+            .take(mid_end - mid_start)
+            .cloned()
+            .collect::<TokenStream>();
+        if let Some(next) = ts.get(mid_end) {
+            mid.push(Token::new(EOF, "\0", next.lineno, next.col_begin));
+        }
+        let Ok(parsed) = Self::parse_with_chunks(mid, prev.next_def_id) else {
+            // the chunks may not be separable at the boundaries, so the errors are reported by parsing the whole
+            return Self::parse_with_chunks(ts, prev.next_def_id);
+        };
+        let chunks = prev.module.ref_payload();
+        let module = chunks[..reused_prefix]
+            .iter()
+            .cloned()
+            .chain(parsed.module)
+            .chain(chunks[reused_suffix..].iter().cloned())
+            .collect();
+        let chunk_starts = starts[..reused_prefix]
+            .iter()
+            .copied()
+            .chain(parsed.chunk_starts.into_iter().map(|idx| idx + mid_start))
+            .chain(starts[reused_suffix..].iter().map(|&idx| to_new_idx(idx)))
+            .collect();
+        Ok(ParsedModule {
+            tokens: ts,
+            chunk_starts,
+            module,
+            next_def_id: parsed.next_def_id,
+        })
+    }
+
     /// Reduce to the largest unit of syntax, the module (this is called only once)
     /// 構文の最大単位であるモジュールに還元する(これが呼ばれるのは一度きり)
     #[inline]
@@ -383,7 +518,9 @@ impl Parser {
                     break;
                 }
                 Some(_) => {
+                    let rest = self.tokens.len();
                     if let Ok(expr) = self.try_reduce_chunk(true, false) {
+                        self.chunk_starts.push(rest);
                         chunks.push(expr);
                         if !self.cur_is(EOF) && !self.cur_category_is(TC::Separator) {
                             let err = self.skip_and_throw_invalid_chunk_err(
@@ -711,6 +848,14 @@ impl Parser {
     fn try_reduce_elem(&mut self) -> ParseResult<PosArg> {
         debug_call_info!(self);
         match self.peek() {
+            // e.g. [*xs, 1]
+            Some(t) if t.is(PreStar) => {
+                let spread = self
+                    .try_reduce_spread()
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                debug_exit_info!(self);
+                Ok(PosArg::new(Expr::UnaryOp(spread)))
+            }
             Some(_) => {
                 let expr = self
                     .try_reduce_expr(false, false, false, false)
@@ -722,6 +867,17 @@ impl Parser {
         }
     }
 
+    /// `*expr` or `**expr` in container literals
+    fn try_reduce_spread(&mut self) -> ParseResult<UnaryOp> {
+        debug_call_info!(self);
+        let op = self.lpop();
+        let expr = self
+            .try_reduce_expr(false, false, true, false)
+            .map_err(|_| self.stack_dec(fn_name!()))?;
+        debug_exit_info!(self);
+        Ok(UnaryOp::new(op, expr))
+    }
+
     fn opt_reduce_args(&mut self, in_type_args: bool) -> Option<ParseResult<Args>> {
         debug_call_info!(self);
         match self.peek() {
@@ -793,8 +949,9 @@ impl Parser {
             .map_err(|_| self.stack_dec(fn_name!()))?
         {
             ArgKind::Pos(arg) => Args::single(arg),
-            ArgKind::Var(arg) => Args::new(vec![], Some(arg), vec![], None),
-            ArgKind::Kw(arg) => Args::new(vec![], None, vec![arg], None),
+            ArgKind::Var(arg) => Args::new(vec![], Some(arg), vec![], None, None),
+            ArgKind::Kw(arg) => Args::new(vec![], None, vec![arg], None, None),
+            ArgKind::KwVar(arg) => Args::new(vec![], None, vec![], Some(arg), None),
         };
         loop {
             match self.peek_kind() {
@@ -834,26 +991,17 @@ impl Parser {
                         args.set_parens((lp.unwrap(), rp));
                         break;
                     }
-                    if !args.kw_is_empty() {
+                    if !args.kw_is_empty() && !self.cur_is(PreDblStar) {
                         args.push_kw(
                             self.try_reduce_kw_arg(in_type_args)
                                 .map_err(|_| self.stack_dec(fn_name!()))?,
                         );
                     } else {
-                        match self
+                        let arg = self
                             .try_reduce_arg(in_type_args)
-                            .map_err(|_| self.stack_dec(fn_name!()))?
-                        {
-                            ArgKind::Pos(arg) => {
-                                args.push_pos(arg);
-                            }
-                            ArgKind::Var(var) => {
-                                args.set_var_args(var);
-                            }
-                            ArgKind::Kw(arg) => {
-                                args.push_kw(arg);
-                            }
-                        }
+                            .map_err(|_| self.stack_dec(fn_name!()))?;
+                        self.push_arg(&mut args, arg)
+                            .map_err(|_| self.stack_dec(fn_name!()))?;
                     }
                 }
                 Some(RParen) => {
@@ -862,8 +1010,8 @@ impl Parser {
                         args.set_parens((lp, rp));
                     } else {
                         // e.g. f(g 1)
-                        let (pos_args, var_args, kw_args, _) = args.deconstruct();
-                        args = Args::new(pos_args, var_args, kw_args, None);
+                        let (pos_args, var_args, kw_args, kw_var_args, _) = args.deconstruct();
+                        args = Args::new(pos_args, var_args, kw_args, kw_var_args, None);
                     }
                     break;
                 }
@@ -891,26 +1039,17 @@ impl Parser {
                     break;
                 }
                 Some(_) if style.is_colon() => {
-                    if !args.kw_is_empty() {
+                    if !args.kw_is_empty() && !self.cur_is(PreDblStar) {
                         args.push_kw(
                             self.try_reduce_kw_arg(in_type_args)
                                 .map_err(|_| self.stack_dec(fn_name!()))?,
                         );
                     } else {
-                        match self
+                        let arg = self
                             .try_reduce_arg(in_type_args)
-                            .map_err(|_| self.stack_dec(fn_name!()))?
-                        {
-                            ArgKind::Pos(arg) => {
-                                args.push_pos(arg);
-                            }
-                            ArgKind::Var(var) => {
-                                args.set_var_args(var);
-                            }
-                            ArgKind::Kw(arg) => {
-                                args.push_kw(arg);
-                            }
-                        }
+                            .map_err(|_| self.stack_dec(fn_name!()))?;
+                        self.push_arg(&mut args, arg)
+                            .map_err(|_| self.stack_dec(fn_name!()))?;
                     }
                 }
                 _ => {
@@ -922,6 +1061,42 @@ impl Parser {
         Ok(args)
     }
 
+    /// `*`/`**` arguments can be passed only once, and positional arguments cannot follow them
+    fn push_arg(&mut self, args: &mut Args, arg: ArgKind) -> ParseResult<()> {
+        let misplaced = match &arg {
+            ArgKind::Pos(_) => args.var_args.is_some() || args.kw_var_args.is_some(),
+            ArgKind::Var(_) => args.var_args.is_some() || args.kw_var_args.is_some(),
+            ArgKind::KwVar(_) => args.kw_var_args.is_some(),
+            ArgKind::Kw(_) => false,
+        };
+        if misplaced {
+            let loc = match &arg {
+                ArgKind::Pos(arg) | ArgKind::Var(arg) | ArgKind::KwVar(arg) => arg.loc(),
+                ArgKind::Kw(arg) => arg.loc(),
+            };
+            let err = ParseError::syntax_error(
+                line!() as usize,
+                loc,
+                switch_lang!(
+                    "japanese" => "`*`/`**`引数は一度しか渡せず、その後に位置引数を置くことはできません",
+                    "simplified_chinese" => "`*`/`**`参数只能传递一次，并且其后不能有位置参数",
+                    "traditional_chinese" => "`*`/`**`參數只能傳遞一次，並且其後不能有位置參數",
+                    "english" => "`*`/`**` arguments can be passed only once, and positional arguments cannot follow them",
+                ),
+                None,
+            );
+            self.errs.push(err);
+            return Err(());
+        }
+        match arg {
+            ArgKind::Pos(arg) => args.push_pos(arg),
+            ArgKind::Var(var) => args.set_var_args(var),
+            ArgKind::Kw(arg) => args.push_kw(arg),
+            ArgKind::KwVar(arg) => args.set_kw_var_args(arg),
+        }
+        Ok(())
+    }
+
     fn try_reduce_arg(&mut self, in_type_args: bool) -> ParseResult<ArgKind> {
         debug_call_info!(self);
         match self.peek_kind() {
@@ -992,6 +1167,14 @@ impl Parser {
                 debug_exit_info!(self);
                 Ok(ArgKind::Var(PosArg::new(expr)))
             }
+            Some(PreDblStar) => {
+                self.skip();
+                let expr = self
+                    .try_reduce_expr(false, in_type_args, false, false)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                debug_exit_info!(self);
+                Ok(ArgKind::KwVar(PosArg::new(expr)))
+            }
             Some(_) => {
                 let expr = self
                     .try_reduce_expr(false, in_type_args, false, false)
@@ -1769,6 +1952,15 @@ impl Parser {
                 debug_exit_info!(self);
                 Ok(Expr::Tuple(tuple))
             }
+            Some(t) if t.is(PreDblStar) => {
+                let _ = self.lpop();
+                let expr = self.try_reduce_expr(false, in_type_args, in_brace, false)?;
+                let tuple = self
+                    .try_reduce_nonempty_tuple(ArgKind::KwVar(PosArg::new(expr)), false)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                debug_exit_info!(self);
+                Ok(Expr::Tuple(tuple))
+            }
             Some(t) if t.category_is(TC::UnaryOp) => {
                 let unaryop = self
                     .try_reduce_unary()
@@ -1851,9 +2043,16 @@ impl Parser {
                     .map_err(|_| self.stack_dec(fn_name!()))?;
                 let sig = LambdaSignature::new(params, None, bounds);
                 let op = expect_pop!(self, category TC::LambdaOp);
-                let block = self
-                    .try_reduce_block()
-                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                let block = if self.cur_is(Newline) {
+                    self.try_reduce_block()
+                        .map_err(|_| self.stack_dec(fn_name!()))?
+                } else {
+                    // e.g. `(|T|(x: T) -> T) and (...)`
+                    let expr = self
+                        .try_reduce_expr(false, false, false, false)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    Block::new(vec![expr])
+                };
                 self.counter.inc();
                 let lambda = Lambda::new(sig, op, block, self.counter);
                 debug_exit_info!(self);
@@ -2122,6 +2321,31 @@ impl Parser {
                 debug_exit_info!(self);
                 return Err(());
             }
+            // e.g. {*xs, 1}
+            Some(PreStar) => {
+                let spread = self
+                    .try_reduce_spread()
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                if self.cur_is(Semi) {
+                    let err = self.skip_and_throw_syntax_err(caused_by!());
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
+                }
+                let set = self
+                    .try_reduce_set(l_brace, Expr::UnaryOp(spread))
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                debug_exit_info!(self);
+                return Ok(BraceContainer::Set(set));
+            }
+            // e.g. {**d, "a": 1}, {**r; a = 1}
+            Some(PreDblStar) => {
+                let container = self
+                    .try_reduce_spread_container(l_brace)
+                    .map_err(|_| self.stack_dec(fn_name!()))?;
+                debug_exit_info!(self);
+                return Ok(container);
+            }
             _ => {}
         }
 
@@ -2177,6 +2401,140 @@ impl Parser {
         }
     }
 
+    /// Dict or Record beginning with `**expr`
+    /// Whether it is a Dict or a Record is determined by the separator (`,` or `;`)
+    fn try_reduce_spread_container(&mut self, l_brace: Token) -> ParseResult<BraceContainer> {
+        debug_call_info!(self);
+        let mut spreads = vec![self
+            .try_reduce_spread()
+            .map_err(|_| self.stack_dec(fn_name!()))?];
+        // Dict
+        loop {
+            match self.peek_kind() {
+                Some(Comma) => {
+                    self.skip();
+                    while self.cur_is(Newline) {
+                        self.skip();
+                    }
+                    match self.peek_kind() {
+                        Some(PreDblStar) => {
+                            let spread = self
+                                .try_reduce_spread()
+                                .map_err(|_| self.stack_dec(fn_name!()))?;
+                            spreads.push(spread);
+                        }
+                        Some(RBrace | Dedent) => {}
+                        _ => {
+                            let key = self
+                                .try_reduce_expr(false, false, true, false)
+                                .map_err(|_| self.stack_dec(fn_name!()))?;
+                            if !self.cur_is(Colon) {
+                                let err = self.skip_and_throw_syntax_err(caused_by!());
+                                self.errs.push(err);
+                                debug_exit_info!(self);
+                                return Err(());
+                            }
+                            let dict = self
+                                .try_reduce_normal_dict(l_brace, key)
+                                .map_err(|_| self.stack_dec(fn_name!()))?;
+                            debug_exit_info!(self);
+                            return Ok(BraceContainer::Dict(Dict::Normal(
+                                dict.with_spreads(spreads),
+                            )));
+                        }
+                    }
+                }
+                Some(Dedent) => {
+                    self.skip();
+                }
+                Some(RBrace) => {
+                    let r_brace = self.lpop();
+                    let dict = NormalDict::new(l_brace, r_brace, vec![]).with_spreads(spreads);
+                    debug_exit_info!(self);
+                    return Ok(BraceContainer::Dict(Dict::Normal(dict)));
+                }
+                Some(Semi | Newline) if spreads.len() == 1 => {
+                    break;
+                }
+                _ => {
+                    let err = self.skip_and_throw_syntax_err(caused_by!());
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
+                }
+            }
+        }
+        // Record
+        loop {
+            match self.peek_kind() {
+                Some(Newline | Semi) => {
+                    self.skip();
+                }
+                Some(PreDblStar) => {
+                    let spread = self
+                        .try_reduce_spread()
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    spreads.push(spread);
+                }
+                Some(Dedent) => {
+                    self.skip();
+                }
+                Some(RBrace) => {
+                    let r_brace = self.lpop();
+                    let record = MixedRecord::new(l_brace, r_brace, vec![]).with_spreads(spreads);
+                    debug_exit_info!(self);
+                    return Ok(BraceContainer::Record(Record::Mixed(record)));
+                }
+                Some(_) => {
+                    let first = self
+                        .try_reduce_chunk(false, false)
+                        .map_err(|_| self.stack_dec(fn_name!()))?;
+                    let attr = match first {
+                        Expr::Def(def) => RecordAttrOrIdent::Attr(def),
+                        Expr::Accessor(Accessor::Ident(ident)) => RecordAttrOrIdent::Ident(ident),
+                        other => {
+                            let err =
+                                ParseError::simple_syntax_error(line!() as usize, other.loc());
+                            self.errs.push(err);
+                            debug_exit_info!(self);
+                            return Err(());
+                        }
+                    };
+                    let record = match self
+                        .try_reduce_record(l_brace, attr)
+                        .map_err(|_| self.stack_dec(fn_name!()))?
+                    {
+                        Record::Mixed(mixed) => Record::Mixed(mixed.with_spreads(spreads)),
+                        Record::Normal(normal) => Record::Normal(normal.with_spreads(spreads)),
+                    };
+                    debug_exit_info!(self);
+                    return Ok(BraceContainer::Record(record));
+                }
+                None => {
+                    let err = self.skip_and_throw_syntax_err(caused_by!());
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
+                }
+            }
+        }
+    }
+
+    fn misplaced_spread_error(&self) -> ParseError {
+        let loc = self.peek().map_or(Location::Unknown, |t| t.loc());
+        ParseError::syntax_error(
+            line!() as usize,
+            loc,
+            switch_lang!(
+                "japanese" => "展開(`**`)は他の要素より前に置く必要があります",
+                "simplified_chinese" => "展开(`**`)必须放在其他元素之前",
+                "traditional_chinese" => "展開(`**`)必須放在其他元素之前",
+                "english" => "spread entries (`**`) must precede the other entries",
+            ),
+            None,
+        )
+    }
+
     // Note that this accepts:
     //  - {x=expr;y=expr;...}
     //  - {x;y}
@@ -2204,6 +2562,12 @@ impl Parser {
                     debug_exit_info!(self);
                     return Ok(Record::new_mixed(l_brace, r_brace, attrs));
                 }
+                Some(PreDblStar) => {
+                    let err = self.misplaced_spread_error();
+                    self.errs.push(err);
+                    debug_exit_info!(self);
+                    return Err(());
+                }
                 Some(_) => {
                     let next = self
                         .try_reduce_chunk(false, false)
@@ -2277,6 +2641,12 @@ impl Parser {
                         Some(Newline) => {
                             self.skip();
                         }
+                        Some(PreDblStar) => {
+                            let err = self.misplaced_spread_error();
+                            self.errs.push(err);
+                            debug_exit_info!(self);
+                            return Err(());
+                        }
                         _ => {}
                     }
                     let key = self
@@ -2343,6 +2713,13 @@ impl Parser {
                         }
                         _ => {}
                     }
+                    if self.cur_is(PreStar) {
+                        let spread = self
+                            .try_reduce_spread()
+                            .map_err(|_| self.stack_dec(fn_name!()))?;
+                        args.push_pos(PosArg::new(Expr::UnaryOp(spread)));
+                        continue;
+                    }
                     match self
                         .try_reduce_arg(false)
                         .map_err(|_| self.stack_dec(fn_name!()))?
@@ -2358,7 +2735,7 @@ impl Parser {
                                 }
                             }
                         },
-                        ArgKind::Var(var) => {
+                        ArgKind::Var(var) | ArgKind::KwVar(var) => {
                             let err = ParseError::simple_syntax_error(line!() as usize, var.loc());
                             self.errs.push(err);
                             debug_exit_info!(self);
@@ -2397,8 +2774,9 @@ impl Parser {
         debug_call_info!(self);
         let mut args = match first_elem {
             ArgKind::Pos(pos) => Args::single(pos),
-            ArgKind::Var(var) => Args::new(vec![], Some(var), vec![], None),
-            ArgKind::Kw(kw) => Args::new(vec![], None, vec![kw], None),
+            ArgKind::Var(var) => Args::new(vec![], Some(var), vec![], None, None),
+            ArgKind::Kw(kw) => Args::new(vec![], None, vec![kw], None, None),
+            ArgKind::KwVar(var) => Args::new(vec![], None, vec![], Some(var), None),
         };
         #[allow(clippy::while_let_loop)]
         loop {
@@ -2433,6 +2811,9 @@ impl Parser {
                         ArgKind::Var(var) => {
                             args.set_var_args(var);
                         }
+                        ArgKind::KwVar(var) => {
+                            args.set_kw_var_args(var);
+                        }
                         ArgKind::Pos(arg) => {
                             let err = ParseError::syntax_error(
                                 line!() as usize,
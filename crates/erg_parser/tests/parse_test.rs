@@ -1,7 +1,7 @@
 use erg_common::config::{ErgConfig, Input};
 use erg_common::error::MultiErrorDisplay;
 use erg_common::spawn::exec_new_thread;
-use erg_common::traits::{Runnable, Stream};
+use erg_common::traits::{Locational, Runnable, Stream};
 
 use erg_parser::error::ParserRunnerErrors;
 use erg_parser::lex::Lexer;
@@ -67,6 +67,31 @@ fn exec_invalid_chunk_prs_err() -> Result<(), ()> {
     expect_failure("tests/invalid_chunk.er", 62)
 }
 
+#[test]
+fn reparse_edited_module() {
+    let code = std::fs::read_to_string("tests/test2_advanced_syntax.er").unwrap();
+    let mut parser = ParserRunner::new(ErgConfig::default());
+    let ts = Lexer::from_str(code.clone()).lex().unwrap();
+    let prev = parser.parse_incrementally(None, ts).unwrap();
+    let edits = [
+        ("fib(n-1)", "fib(n - 1)"),
+        ("then := 1", "then := 10"),
+        ("assert t == 1", "assert t == 1\nassert t != 2"),
+        ("# keyword arguments\n", ""),
+    ];
+    for (from, to) in edits {
+        let edited = code.replacen(from, to, 1);
+        let ts = Lexer::from_str(edited.clone()).lex().unwrap();
+        let reparsed = parser.parse_incrementally(Some(&prev), ts).unwrap();
+        let expect = parser.parse(edited).unwrap();
+        assert_eq!(reparsed.module().len(), expect.len());
+        for (reparsed, expect) in reparsed.module().iter().zip(expect.iter()) {
+            assert_eq!(reparsed.to_string(), expect.to_string());
+            assert_eq!(reparsed.loc(), expect.loc());
+        }
+    }
+}
+
 fn _parse_test_from_code(file_path: &'static str) -> Result<(), ParserRunnerErrors> {
     let input = Input::file(file_path.into());
     let cfg = ErgConfig {
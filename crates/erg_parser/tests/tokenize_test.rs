@@ -1,6 +1,7 @@
 use std::iter::Iterator;
 
 use erg_common::config::Input;
+use erg_common::traits::DequeStream;
 
 // use erg_compiler::parser;
 
@@ -247,6 +248,16 @@ fn test_lexer_for_literals() -> ParseResult<()> {
         (Newline, newline),
         (Newline, newline),
         (Newline, newline),
+        (NatLit, "0xff"),
+        (Comma, ","),
+        (NatLit, "0o777"),
+        (Comma, ","),
+        (NatLit, "0b1010_1010"),
+        (Comma, ","),
+        (IntLit, "-0x10"),
+        (Newline, newline),
+        (Newline, newline),
+        (Newline, newline),
         (RatioLit, "0.00"),
         (Comma, ","),
         (RatioLit, "-0.0"),
@@ -269,6 +280,14 @@ fn test_lexer_for_literals() -> ParseResult<()> {
         (Newline, newline),
         (Newline, newline),
         (Newline, newline),
+        (BytesLit, "b\"\""),
+        (Comma, ","),
+        (BytesLit, "b\"ab\""),
+        (Comma, ","),
+        (BytesLit, "b\"\0\u{ff}\n\""),
+        (Newline, newline),
+        (Newline, newline),
+        (Newline, newline),
         (BoolLit, "True"),
         (Comma, ","),
         (BoolLit, "False"),
@@ -454,6 +473,56 @@ fn for_loop() -> ParseResult<()> {
     Ok(())
 }
 
+fn assert_relex_eq(old_code: &str, new_code: &str) {
+    let mut tokens = Lexer::from_str(old_code.to_string()).lex().unwrap();
+    Lexer::relex(&mut tokens, old_code, new_code).unwrap();
+    let expect = Lexer::from_str(new_code.to_string()).lex().unwrap();
+    assert_eq!(tokens.len(), expect.len(), "{tokens}\n{expect}");
+    for (relexed, lexed) in tokens.iter().zip(expect.iter()) {
+        assert!(
+            relexed.deep_eq(lexed),
+            "{relexed:?} != {lexed:?} ({old_code:?} -> {new_code:?})"
+        );
+    }
+}
+
+#[test]
+fn relex() {
+    let code = std::fs::read_to_string(FILE2).unwrap();
+    let edits = [
+        // edit in a line
+        ("fib(n-1)", "fib(n - 1)"),
+        // edit in an indented block
+        ("then := 1", "then := 10"),
+        // add lines
+        ("assert t == 1", "assert t == 1\nassert t != 2\n"),
+        // remove lines
+        ("# keyword arguments\n", ""),
+        // the block absorbs the next top-level line
+        ("assert t == 1", "    assert t == 1"),
+        // the beginning and the end
+        ("# Check", "#Check"),
+    ];
+    for (from, to) in edits {
+        assert!(code.contains(from));
+        assert_relex_eq(&code, &code.replacen(from, to, 1));
+    }
+    assert_relex_eq(&code, &format!("{code}\nx = 1"));
+    assert_relex_eq(&code, "");
+    assert_relex_eq("", &code);
+    // a multi-line string swallows the following lines
+    let str_code = std::fs::read_to_string(FILE4).unwrap();
+    assert_relex_eq(&code, &code.replacen("math = import", &str_code, 1));
+    // a doc comment in a block starts at column 0, but is not a top-level line
+    let doc_code = "C = Class()\nC.\n    z = 1\n    '''\n    doc\n    '''\n    f self = 1\n";
+    assert_relex_eq(doc_code, &doc_code.replacen("f self = 1", "f self = 2", 1));
+    // lexical errors leave the tokens unchanged
+    let mut tokens = Lexer::from_str(code.clone()).lex().unwrap();
+    let broken = code.replacen("then := 1", "then := \"1", 1);
+    assert!(Lexer::relex(&mut tokens, &code, &broken).is_err());
+    assert_eq!(tokens.len(), Lexer::from_str(code).lex().unwrap().len());
+}
+
 #[test]
 fn tesop_te_prec() {
     assert_eq!(Mod.precedence(), Some(170));
@@ -27,6 +27,8 @@ pub enum TokenKind {
     RatioLit,
     BoolLit,
     StrLit,
+    /// e.g. b"abc"
+    BytesLit,
     /// e.g. "abc\{
     StrInterpLeft,
     /// e.g. }abc\{
@@ -130,6 +132,8 @@ pub enum TokenKind {
     FuncArrow,
     /// =>
     ProcArrow,
+    /// ~>
+    Transition,
     /// (
     LParen,
     /// )
@@ -232,8 +236,8 @@ impl TokenKind {
     pub const fn category(&self) -> TokenCategory {
         match self {
             Symbol => TokenCategory::Symbol,
-            NatLit | IntLit | RatioLit | StrLit | BoolLit | NoneLit | EllipsisLit | NoImplLit
-            | InfLit | DocComment => TokenCategory::Literal,
+            NatLit | IntLit | RatioLit | StrLit | BytesLit | BoolLit | NoneLit | EllipsisLit
+            | NoImplLit | InfLit | DocComment => TokenCategory::Literal,
             StrInterpLeft => TokenCategory::StrInterpLeft,
             StrInterpMid => TokenCategory::StrInterpMid,
             StrInterpRight => TokenCategory::StrInterpRight,
@@ -275,7 +279,7 @@ impl TokenKind {
             | IsNotOp => 90, // < > <= >= == != as in notin is isnot
             AndOp => 80,                                              // and
             OrOp => 70,                                               // or
-            FuncArrow | ProcArrow | Inclusion => 60,                  // -> => <-
+            FuncArrow | ProcArrow | Inclusion | Transition => 60,     // -> => <- ~>
             Colon | SupertypeOf | SubtypeOf => 50,                    // : :> <:
             Comma => 40,                                              // ,
             Assign | Walrus => 20,                                    // = :=
@@ -494,3 +498,24 @@ impl Token {
 pub struct TokenStream(VecDeque<Token>);
 
 impl_displayable_deque_stream_for_wrapper!(TokenStream, Token);
+
+impl TokenStream {
+    /// Replaces the tokens in `range` with `tokens` in place.
+    pub fn splice<I: IntoIterator<Item = Token>>(
+        &mut self,
+        range: std::ops::Range<usize>,
+        tokens: I,
+    ) {
+        let tail = self.0.split_off(range.end);
+        self.0.truncate(range.start);
+        self.0.extend(tokens);
+        self.0.extend(tail);
+    }
+
+    /// Shifts the line numbers of the tokens after `idx` (inclusive) by `delta`.
+    pub fn shift_lines(&mut self, idx: usize, delta: i64) {
+        for token in self.0.range_mut(idx..) {
+            token.lineno = (token.lineno as i64 + delta) as u32;
+        }
+    }
+}
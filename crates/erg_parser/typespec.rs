@@ -64,6 +64,13 @@ impl Parser {
             },
             Expr::Dict(dict) => match dict {
                 Dict::Normal(dict) => {
+                    if let Some(spread) = dict.spreads.first() {
+                        return Err(ParseError::feature_error(
+                            line!() as usize,
+                            spread.loc(),
+                            "const spread",
+                        ));
+                    }
                     let mut const_kvs = vec![];
                     for kv in dict.kvs.into_iter() {
                         let key = Self::validate_const_expr(kv.key)?;
@@ -81,7 +88,7 @@ impl Parser {
             },
             Expr::Tuple(tuple) => match tuple {
                 Tuple::Normal(tup) => {
-                    let (elems, _, _, paren) = tup.elems.deconstruct();
+                    let (elems, _, _, _, paren) = tup.elems.deconstruct();
                     let mut const_elems = vec![];
                     for elem in elems.into_iter() {
                         let const_expr = Self::validate_const_expr(elem.expr)?;
@@ -98,6 +105,15 @@ impl Parser {
                 let rhs = Self::validate_const_expr(*args.next().unwrap())?;
                 Ok(ConstExpr::BinOp(ConstBinOp::new(bin.op, lhs, rhs)))
             }
+            Expr::UnaryOp(unary)
+                if matches!(unary.op.kind, TokenKind::PreStar | TokenKind::PreDblStar) =>
+            {
+                Err(ParseError::feature_error(
+                    line!() as usize,
+                    unary.loc(),
+                    "const spread",
+                ))
+            }
             Expr::UnaryOp(unary) => {
                 let mut args = unary.args.into_iter();
                 let arg = Self::validate_const_expr(*args.next().unwrap())?;
@@ -112,7 +128,7 @@ impl Parser {
                         "complex const function call",
                     ));
                 };
-                let (pos_args, _, _, paren) = call.args.deconstruct();
+                let (pos_args, _, _, _, paren) = call.args.deconstruct();
                 let mut const_pos_args = vec![];
                 for elem in pos_args.into_iter() {
                     let const_expr = Self::validate_const_expr(elem.expr)?;
@@ -132,6 +148,13 @@ impl Parser {
                     Record::Normal(rec) => rec,
                     Record::Mixed(mixed) => Desugarer::desugar_shortened_record_inner(mixed),
                 };
+                if let Some(spread) = rec.spreads.first() {
+                    return Err(ParseError::feature_error(
+                        line!() as usize,
+                        spread.loc(),
+                        "const spread",
+                    ));
+                }
                 let mut const_fields = vec![];
                 for attr in rec.attrs.into_iter() {
                     const_fields.push(Self::validate_const_def(attr)?);
@@ -206,7 +229,7 @@ impl Parser {
     pub(crate) fn call_to_predecl_type_spec(call: Call) -> Result<PreDeclTypeSpec, ParseError> {
         match *call.obj {
             Expr::Accessor(Accessor::Ident(ident)) => {
-                let (_pos_args, _var_args, _kw_args, paren) = call.args.deconstruct();
+                let (_pos_args, _var_args, _kw_args, _kw_var_args, paren) = call.args.deconstruct();
                 let mut pos_args = vec![];
                 for arg in _pos_args.into_iter() {
                     let const_expr = Self::validate_const_expr(arg.expr)?;
@@ -281,6 +304,23 @@ impl Parser {
                     }
                     (param, t_spec) => todo!("{param}: {t_spec:?}"),
                 });
+        let kw_var_params = match lambda.sig.params.kw_var_params {
+            Some(kw_var_args) => match (kw_var_args.pat, kw_var_args.t_spec) {
+                (ParamPattern::VarName(name), Some(t_spec_with_op)) => Some(ParamTySpec::new(
+                    Some(name.into_token()),
+                    t_spec_with_op.t_spec,
+                )),
+                (ParamPattern::Discard(_), Some(t_spec_with_op)) => {
+                    Some(ParamTySpec::anonymous(t_spec_with_op.t_spec))
+                }
+                (param, _t_spec) => {
+                    let err =
+                        ParseError::feature_error(line!() as usize, param.loc(), "param pattern");
+                    return Err(err);
+                }
+            },
+            None => None,
+        };
         let mut defaults = vec![];
         for param in lambda.sig.params.defaults.into_iter() {
             let param = match (param.sig.pat, param.sig.t_spec) {
@@ -311,6 +351,7 @@ impl Parser {
             non_defaults,
             var_params,
             defaults,
+            kw_var_params,
             lambda.op,
             return_t,
         ))
@@ -358,6 +399,13 @@ impl Parser {
     fn dict_to_dict_type_spec(dict: Dict) -> Result<Vec<(TypeSpec, TypeSpec)>, ParseError> {
         match dict {
             Dict::Normal(dic) => {
+                if let Some(spread) = dic.spreads.first() {
+                    return Err(ParseError::feature_error(
+                        line!() as usize,
+                        spread.loc(),
+                        "spread in type specification",
+                    ));
+                }
                 let mut kvs = vec![];
                 for kv in dic.kvs.into_iter() {
                     let key = Self::expr_to_type_spec(kv.key)?;
@@ -376,6 +424,17 @@ impl Parser {
     fn record_to_record_type_spec(
         record: Record,
     ) -> Result<Vec<(Identifier, TypeSpec)>, ParseError> {
+        let spread = match &record {
+            Record::Normal(rec) => rec.spreads.first(),
+            Record::Mixed(rec) => rec.spreads.first(),
+        };
+        if let Some(spread) = spread {
+            return Err(ParseError::feature_error(
+                line!() as usize,
+                spread.loc(),
+                "spread in type specification",
+            ));
+        }
         match record {
             Record::Normal(rec) => rec
                 .attrs
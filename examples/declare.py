@@ -5,9 +5,19 @@ def f(x: int) -> int:
     return x + 1
 
 
+def g(x):
+    return x
+
+
 class C:
     def __init__(self, x: int) -> None:
         self.x = x
 
     def f(self, y: int) -> int:
         return self.x + y
+
+
+async def countdown(n: int):
+    while n > 0:
+        yield n
+        n -= 1
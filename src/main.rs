@@ -13,6 +13,7 @@ use erg_parser::ParserRunner;
 use erg_compiler::build_hir::HIRBuilder;
 use erg_compiler::lower::ASTLowerer;
 use erg_compiler::transpile::Transpiler;
+use erg_compiler::ty::asm::Assembler;
 use erg_compiler::ty::deserialize::Deserializer;
 use erg_compiler::Compiler;
 
@@ -30,6 +31,7 @@ fn run() {
         Transpile => Transpiler::run(cfg),
         Execute => DummyVM::run(cfg),
         Read => Deserializer::run(cfg),
+        Assemble => Assembler::run(cfg),
         LanguageServer => {
             #[cfg(feature = "els")]
             {
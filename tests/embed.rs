@@ -1,6 +1,8 @@
 use erg::DummyVM;
-use erg_common::config::ErgConfig;
+use erg_common::config::{ErgConfig, TranspileTarget};
 use erg_common::error::MultiErrorDisplay;
+use erg_common::python_util::PythonVersion;
+use erg_common::traits::Runnable;
 use erg_compiler::artifact::Buildable;
 use erg_compiler::module::SharedCompilerResource;
 use erg_compiler::HIRBuilder;
@@ -29,6 +31,128 @@ fn test_transpiler_embedding() -> Result<(), ()> {
     Ok(())
 }
 
+#[test]
+fn test_native_transpiler_embedding() -> Result<(), ()> {
+    let cfg = ErgConfig {
+        transpile_target: TranspileTarget::Native,
+        ..ErgConfig::default()
+    };
+    let mut trans = Transpiler::new(cfg);
+    let res = trans
+        .transpile("x = 1\nif! x == 1, do!:\n    print! \"\"".into(), "exec")
+        .map_err(|es| {
+            es.errors.fmt_all_stderr();
+        })?;
+    assert!(!res.object.code.contains("_erg"));
+    assert!(res
+        .object
+        .code
+        .ends_with("x__ = 1\nif (x__ == 1):\n    print(\"\")\n"));
+    Ok(())
+}
+
+#[test]
+fn test_transpile_var_params() -> Result<(), ()> {
+    let code = "f a, *args: Int, b := 1, **kw: Int = (a, args, b, kw)\nf(1, 2, b:=3, c:=4)";
+    for target in [TranspileTarget::Compatible, TranspileTarget::Native] {
+        let native = target.is_native();
+        let cfg = ErgConfig {
+            transpile_target: target,
+            ..ErgConfig::default()
+        };
+        let mut trans = Transpiler::new(cfg);
+        let res = trans.transpile(code.into(), "exec").map_err(|es| {
+            es.errors.fmt_all_stderr();
+        })?;
+        let sig = if native {
+            "def f__(a__, *args__, b__ = 1, **kw__):\n"
+        } else {
+            "def f__(a__,*args__,b__ = Nat(1),**kw__,):\n"
+        };
+        assert!(res.object.code.contains(sig), "{}", res.object.code);
+        // the escaped keywords are restored
+        assert!(res
+            .object
+            .code
+            .contains(&format!("{sig}    kw__ = {{(k[:-2]")));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_transpile_non_block_bodies() -> Result<(), ()> {
+    let code = "d = {\"a\": 1}\nprint! d[\"a\"]\np! x = print! x\nfor! [1, 2], p!";
+    for target in [TranspileTarget::Compatible, TranspileTarget::Native] {
+        let native = target.is_native();
+        let cfg = ErgConfig {
+            transpile_target: target,
+            ..ErgConfig::default()
+        };
+        let mut trans = Transpiler::new(cfg);
+        let res = trans.transpile(code.into(), "exec").map_err(|es| {
+            es.errors.fmt_all_stderr();
+        })?;
+        let code = &res.object.code;
+        if native {
+            assert!(code.contains("print(d__[\"a\"])\n"), "{code}");
+            assert!(code.contains("for__([1, 2], p__erg_proc____)\n"), "{code}");
+        } else {
+            // the body is called with the element
+            assert!(
+                code.contains("    p__erg_proc____(for_tmp_0__)\n"),
+                "{code}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_transpile_array_with_length() -> Result<(), ()> {
+    let code = "IntArr = [Int!; 3]\nprint! IntArr";
+    for target in [TranspileTarget::Compatible, TranspileTarget::Native] {
+        let cfg = ErgConfig {
+            transpile_target: target,
+            ..ErgConfig::default()
+        };
+        let mut trans = Transpiler::new(cfg);
+        let res = trans.transpile(code.into(), "exec").map_err(|es| {
+            es.errors.fmt_all_stderr();
+        })?;
+        assert!(res.object.code.contains("(Array([int]) * ("));
+    }
+    Ok(())
+}
+
+fn native_transpile(code: &str, target_version: PythonVersion) -> Result<String, ()> {
+    let cfg = ErgConfig {
+        transpile_target: TranspileTarget::Native,
+        target_version: Some(target_version),
+        ..ErgConfig::default()
+    };
+    let mut trans = Transpiler::new(cfg);
+    let res = trans.transpile(code.into(), "exec").map_err(|es| {
+        es.errors.fmt_all_stderr();
+    })?;
+    Ok(res.object.code)
+}
+
+#[test]
+fn test_native_transpile_match() -> Result<(), ()> {
+    let code = "x = !1\nmatch! x:\n    (i: Int) => print! i\n    _ => print! \"other\"";
+    // mutable scalars are not subclasses of `int`
+    let res = native_transpile(code, PythonVersion::new(3, Some(11), Some(0)))?;
+    assert!(res.contains("match x__:\n    case i__ if isinstance(i__.value, int):\n"));
+    // `match` statements are not available before Python 3.10
+    let res = native_transpile(code, PythonVersion::new(3, Some(9), Some(0)))?;
+    assert!(!res.contains("match x__"));
+    assert!(
+        res.contains("if isinstance(match_subject_0__.value, int):\n    i__ = match_subject_0__\n")
+    );
+    assert!(res.contains("else:\n    print(\"other\")\n"));
+    Ok(())
+}
+
 #[test]
 fn test_builder() -> Result<(), ()> {
     let mods = ["math", "time"];
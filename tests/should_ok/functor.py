@@ -0,0 +1,2 @@
+def fmap(x, f):
+    return type(x)(map(f, x))
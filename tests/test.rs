@@ -16,6 +16,16 @@ fn exec_array() -> Result<(), ()> {
     expect_success("tests/should_ok/array.er", 0)
 }
 
+#[test]
+fn exec_async() -> Result<(), ()> {
+    expect_success("examples/async.er", 0)
+}
+
+#[test]
+fn exec_bytes() -> Result<(), ()> {
+    expect_success("tests/should_ok/bytes.er", 0)
+}
+
 #[test]
 fn exec_class() -> Result<(), ()> {
     expect_success("examples/class.er", 0)
@@ -46,16 +56,36 @@ fn exec_control_expr() -> Result<(), ()> {
     expect_success("tests/should_ok/control_expr.er", 3)
 }
 
+#[test]
+fn exec_dependent_mut() -> Result<(), ()> {
+    expect_success("tests/should_ok/dependent.er", 0)
+}
+
 #[test]
 fn exec_dict() -> Result<(), ()> {
     expect_success("examples/dict.er", 0)
 }
 
+#[test]
+fn exec_enum() -> Result<(), ()> {
+    expect_success("tests/should_ok/enum.er", 0)
+}
+
 #[test]
 fn exec_fib() -> Result<(), ()> {
     expect_success("examples/fib.er", 0)
 }
 
+#[test]
+fn exec_generator() -> Result<(), ()> {
+    expect_success("tests/should_ok/generator.er", 0)
+}
+
+#[test]
+fn exec_glue_patch() -> Result<(), ()> {
+    expect_success("tests/should_ok/glue_patch.er", 0)
+}
+
 #[test]
 fn exec_helloworld() -> Result<(), ()> {
     // HACK: When running the test with pre-commit, the exit code is 1 (the cause is unknown)
@@ -66,6 +96,11 @@ fn exec_helloworld() -> Result<(), ()> {
     }
 }
 
+#[test]
+fn exec_higher_kinded() -> Result<(), ()> {
+    expect_success("tests/should_ok/higher_kinded.er", 0)
+}
+
 #[test]
 fn exec_if() -> Result<(), ()> {
     expect_success("tests/should_ok/if.er", 0)
@@ -107,6 +142,11 @@ fn exec_interpolation() -> Result<(), ()> {
     expect_success("tests/should_ok/interpolation.er", 0)
 }
 
+#[test]
+fn exec_list() -> Result<(), ()> {
+    expect_success("tests/should_ok/list.er", 0)
+}
+
 #[test]
 fn exec_long() -> Result<(), ()> {
     expect_success("tests/should_ok/long.er", 257)
@@ -137,6 +177,11 @@ fn exec_nested() -> Result<(), ()> {
     expect_success("tests/should_ok/nested.er", 3)
 }
 
+#[test]
+fn exec_overload() -> Result<(), ()> {
+    expect_success("tests/should_ok/overload.er", 0)
+}
+
 #[test]
 fn exec_patch() -> Result<(), ()> {
     expect_success("examples/patch.er", 0)
@@ -177,6 +222,11 @@ fn exec_return() -> Result<(), ()> {
     expect_success("tests/should_ok/return.er", 0)
 }
 
+#[test]
+fn exec_spread() -> Result<(), ()> {
+    expect_success("tests/should_ok/spread.er", 0)
+}
+
 #[test]
 fn exec_structural_example() -> Result<(), ()> {
     expect_success("examples/structural.er", 0)
@@ -242,6 +292,11 @@ fn exec_as() -> Result<(), ()> {
     expect_failure("tests/should_err/as.er", 0, 3)
 }
 
+#[test]
+fn exec_async_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/async.er", 0, 3)
+}
+
 #[test]
 fn exec_assert_cast() -> Result<(), ()> {
     expect_failure("examples/assert_cast.er", 0, 3)
@@ -263,6 +318,21 @@ fn exec_tests_impl() -> Result<(), ()> {
     expect_end_with("tests/should_ok/impl.er", 1)
 }
 
+#[test]
+fn exec_enum_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/enum.er", 0, 3)
+}
+
+#[test]
+fn exec_generator_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/generator.er", 0, 2)
+}
+
+#[test]
+fn exec_glue_patch_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/glue_patch.er", 0, 3)
+}
+
 #[test]
 fn exec_impl_err() -> Result<(), ()> {
     expect_failure("tests/should_err/impl.er", 2, 2)
@@ -288,6 +358,11 @@ fn exec_move_check() -> Result<(), ()> {
     expect_failure("examples/move_check.er", 1, 1)
 }
 
+#[test]
+fn exec_overload_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/overload.er", 0, 3)
+}
+
 #[test]
 fn exec_pyimport() -> Result<(), ()> {
     if cfg!(unix) {
@@ -307,6 +382,11 @@ fn exec_side_effect() -> Result<(), ()> {
     expect_failure("examples/side_effect.er", 5, 4)
 }
 
+#[test]
+fn exec_spread_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/spread.er", 0, 11)
+}
+
 #[test]
 fn exec_structural_err() -> Result<(), ()> {
     expect_failure("tests/should_err/structural.er", 1, 9)
@@ -317,11 +397,26 @@ fn exec_subtyping_err() -> Result<(), ()> {
     expect_failure("tests/should_err/subtyping.er", 0, 11)
 }
 
+#[test]
+fn exec_transition_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/transition.er", 0, 5)
+}
+
 #[test]
 fn exec_callable() -> Result<(), ()> {
     expect_failure("tests/should_err/callable.er", 0, 5)
 }
 
+#[test]
+fn exec_higher_kinded_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/higher_kinded.er", 0, 3)
+}
+
+#[test]
+fn exec_list_err() -> Result<(), ()> {
+    expect_failure("tests/should_err/list.er", 0, 3)
+}
+
 #[test]
 fn exec_move() -> Result<(), ()> {
     expect_failure("tests/should_err/move.er", 1, 1)
@@ -339,7 +434,7 @@ fn exec_mut_err() -> Result<(), ()> {
 
 #[test]
 fn exec_mut_array_err() -> Result<(), ()> {
-    expect_failure("tests/should_err/mut_array.er", 0, 1)
+    expect_failure("tests/should_err/mut_array.er", 0, 5)
 }
 
 #[test]
@@ -354,7 +449,7 @@ fn exec_refinement() -> Result<(), ()> {
 
 #[test]
 fn exec_var_args_err() -> Result<(), ()> {
-    expect_failure("tests/should_err/var_args.er", 0, 3)
+    expect_failure("tests/should_err/var_args.er", 0, 5)
 }
 
 #[test]